@@ -0,0 +1,317 @@
+//! Binary search through checkpoint history for the first bad checkpoint
+//!
+//! Candidates are the ancestors of the bad checkpoint that are not ancestors
+//! of any good checkpoint. Each candidate is materialized into a scratch
+//! directory (`.tl/tmp/bisect`) so the working tree is never touched. State
+//! persists in `.tl/state/bisect.json` between `good`/`bad`/`skip` calls.
+
+use crate::data_access;
+use crate::util;
+use anyhow::{anyhow, Context, Result};
+use journal::Checkpoint;
+use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tl_core::Store;
+use ulid::Ulid;
+
+/// Persistent bisect session
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BisectState {
+    /// Newest checkpoint known to be bad
+    bad: Option<Ulid>,
+    /// Checkpoints known to be good (their ancestors are good too)
+    good: Vec<Ulid>,
+    /// Checkpoints that could not be tested
+    skipped: Vec<Ulid>,
+    /// Candidate currently materialized for testing
+    current: Option<Ulid>,
+}
+
+/// How a candidate was judged
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Good,
+    Bad,
+    Skip,
+}
+
+/// Outcome of one bisect step
+enum Step {
+    /// Next candidate to test, with remaining candidate count
+    Test(Checkpoint, usize),
+    /// Search finished: the first bad checkpoint
+    Found(Checkpoint),
+    /// Only skipped checkpoints remain between good and bad
+    Inconclusive(Vec<Ulid>),
+}
+
+fn state_path(tl_dir: &Path) -> PathBuf {
+    tl_dir.join("state/bisect.json")
+}
+
+fn scratch_dir(tl_dir: &Path) -> PathBuf {
+    tl_dir.join("tmp/bisect")
+}
+
+fn load_state(tl_dir: &Path) -> Result<Option<BisectState>> {
+    let path = state_path(tl_dir);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(&path)
+        .context("Failed to read bisect state")?;
+    Ok(Some(serde_json::from_str(&content).context("Corrupt bisect state - run 'tl bisect reset'")?))
+}
+
+fn save_state(tl_dir: &Path, state: &BisectState) -> Result<()> {
+    let path = state_path(tl_dir);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(state)?)
+        .context("Failed to write bisect state")
+}
+
+fn require_state(tl_dir: &Path) -> Result<BisectState> {
+    load_state(tl_dir)?
+        .ok_or_else(|| anyhow!("No bisect in progress. Start one with 'tl bisect start <bad> <good>'"))
+}
+
+async fn resolve(checkpoint: &str, tl_dir: &Path) -> Result<Ulid> {
+    let ids = data_access::resolve_checkpoint_refs(&[checkpoint.to_string()], tl_dir).await?;
+    ids[0].ok_or_else(|| anyhow!("Checkpoint '{}' not found or ambiguous", checkpoint))
+}
+
+/// Start a bisect session
+pub async fn run_start(bad: Option<String>, good: Vec<String>) -> Result<()> {
+    let repo_root = util::find_repo_root()?;
+    let tl_dir = repo_root.join(".tl");
+
+    crate::daemon::ensure_daemon_running().await?;
+
+    if load_state(&tl_dir)?.is_some() {
+        anyhow::bail!("A bisect is already in progress. Run 'tl bisect reset' first");
+    }
+
+    let mut state = BisectState::default();
+    if let Some(bad_ref) = bad {
+        state.bad = Some(resolve(&bad_ref, &tl_dir).await?);
+    }
+    for good_ref in &good {
+        state.good.push(resolve(good_ref, &tl_dir).await?);
+    }
+
+    println!("{}", "Bisect started".bold());
+    advance(&repo_root, &tl_dir, &mut state).await?;
+    save_state(&tl_dir, &state)
+}
+
+/// Mark a checkpoint (default: the current candidate) good, bad, or skipped
+pub async fn run_mark(verdict: Verdict, checkpoint: Option<String>) -> Result<()> {
+    let repo_root = util::find_repo_root()?;
+    let tl_dir = repo_root.join(".tl");
+
+    crate::daemon::ensure_daemon_running().await?;
+
+    let mut state = require_state(&tl_dir)?;
+    let id = match checkpoint {
+        Some(checkpoint_ref) => resolve(&checkpoint_ref, &tl_dir).await?,
+        None => state.current
+            .ok_or_else(|| anyhow!("No candidate under test - specify a checkpoint"))?,
+    };
+
+    mark(&mut state, id, verdict);
+    advance(&repo_root, &tl_dir, &mut state).await?;
+    save_state(&tl_dir, &state)
+}
+
+/// Run a command against each candidate until the first bad checkpoint is found
+///
+/// Exit code 0 marks the candidate good, 125 skips it, 1-127 mark it bad, and
+/// anything else (including death by signal) aborts the bisect. The command
+/// is run directly, not through a shell; wrap it in `sh -c` for pipes.
+pub async fn run_run(command: Vec<String>) -> Result<()> {
+    let repo_root = util::find_repo_root()?;
+    let tl_dir = repo_root.join(".tl");
+
+    crate::daemon::ensure_daemon_running().await?;
+
+    if command.is_empty() {
+        anyhow::bail!("No command given");
+    }
+    let command_line = command.join(" ");
+
+    let mut state = require_state(&tl_dir)?;
+    if state.bad.is_none() || state.good.is_empty() {
+        anyhow::bail!("Mark at least one good and one bad checkpoint before 'tl bisect run'");
+    }
+
+    if state.current.is_none() {
+        advance(&repo_root, &tl_dir, &mut state).await?;
+        save_state(&tl_dir, &state)?;
+    }
+
+    while let Some(current) = state.current {
+        let status = std::process::Command::new(&command[0])
+            .args(&command[1..])
+            .current_dir(scratch_dir(&tl_dir))
+            .env("TL_BISECT_CHECKPOINT", current.to_string())
+            .status()
+            .with_context(|| format!("Failed to run: {}", command_line))?;
+
+        let verdict = match status.code() {
+            Some(0) => Verdict::Good,
+            Some(125) => Verdict::Skip,
+            Some(code) if (1..128).contains(&code) => Verdict::Bad,
+            _ => anyhow::bail!("Bisect aborted: '{}' exited with {}", command_line, status),
+        };
+
+        let current_short = &current.to_string()[..8];
+        println!("  {} {}", current_short.yellow(), match verdict {
+            Verdict::Good => "good".green().to_string(),
+            Verdict::Bad => "bad".red().to_string(),
+            Verdict::Skip => "skip".dimmed().to_string(),
+        });
+
+        mark(&mut state, current, verdict);
+        advance(&repo_root, &tl_dir, &mut state).await?;
+        save_state(&tl_dir, &state)?;
+    }
+
+    Ok(())
+}
+
+/// End the bisect session and remove the scratch directory
+pub async fn run_reset() -> Result<()> {
+    let repo_root = util::find_repo_root()?;
+    let tl_dir = repo_root.join(".tl");
+
+    let path = state_path(&tl_dir);
+    if !path.exists() {
+        println!("{}", "No bisect in progress".dimmed());
+        return Ok(());
+    }
+
+    std::fs::remove_file(&path).context("Failed to remove bisect state")?;
+    let scratch = scratch_dir(&tl_dir);
+    if scratch.exists() {
+        std::fs::remove_dir_all(&scratch).context("Failed to remove bisect scratch directory")?;
+    }
+
+    println!("{} Bisect reset", "✓".green());
+    Ok(())
+}
+
+fn mark(state: &mut BisectState, id: Ulid, verdict: Verdict) {
+    match verdict {
+        Verdict::Good => state.good.push(id),
+        Verdict::Bad => state.bad = Some(id),
+        Verdict::Skip => state.skipped.push(id),
+    }
+    state.current = None;
+}
+
+/// Pick the next candidate (or finish) and materialize it for testing
+async fn advance(repo_root: &Path, tl_dir: &Path, state: &mut BisectState) -> Result<()> {
+    state.current = None;
+
+    let bad = match state.bad {
+        Some(bad) => bad,
+        None => {
+            println!("{}", "Waiting for a bad checkpoint: tl bisect bad [<checkpoint>]".dimmed());
+            return Ok(());
+        }
+    };
+    if state.good.is_empty() {
+        println!("{}", "Waiting for a good checkpoint: tl bisect good <checkpoint>".dimmed());
+        return Ok(());
+    }
+
+    match next_step(tl_dir, bad, &state.good, &state.skipped).await? {
+        Step::Test(candidate, remaining) => {
+            let steps = usize::BITS - remaining.leading_zeros();
+            let scratch = scratch_dir(tl_dir);
+            materialize(repo_root, &candidate, &scratch)?;
+
+            let candidate_short = &candidate.id.to_string()[..8];
+            println!("Bisecting: {} candidate(s) left (roughly {} step(s))", remaining, steps);
+            println!("Testing {} {} in {}",
+                candidate_short.yellow(),
+                util::format_relative_time(candidate.ts_unix_ms).dimmed(),
+                scratch.display()
+            );
+            state.current = Some(candidate.id);
+        }
+        Step::Found(first_bad) => {
+            println!();
+            println!("{} First bad checkpoint: {} {}",
+                "✓".green(),
+                first_bad.id.to_string().red().bold(),
+                util::format_relative_time(first_bad.ts_unix_ms).dimmed()
+            );
+            println!();
+            if let Some(parent) = first_bad.parent {
+                crate::cmd::diff::run(&parent.to_string(), &first_bad.id.to_string(), true, 3, 20).await?;
+            }
+            println!();
+            println!("{}", "Run 'tl bisect reset' to finish".dimmed());
+        }
+        Step::Inconclusive(ids) => {
+            println!();
+            println!("{} Only skipped checkpoints remain; the first bad one is among:", "⚠".yellow());
+            for id in ids {
+                println!("  {}", id.to_string().yellow());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Compute the remaining search space and choose a candidate
+async fn next_step(tl_dir: &Path, bad: Ulid, good: &[Ulid], skipped: &[Ulid]) -> Result<Step> {
+    let mut known_good: HashSet<Ulid> = HashSet::new();
+    for good_id in good {
+        known_good.extend(data_access::get_ancestors(*good_id, tl_dir).await?.iter().map(|cp| cp.id));
+    }
+
+    if known_good.contains(&bad) {
+        anyhow::bail!("Bad checkpoint {} is an ancestor of a good checkpoint", &bad.to_string()[..8]);
+    }
+
+    // Newest first; the bad checkpoint itself is always first
+    let mut candidates: Vec<Checkpoint> = data_access::get_ancestors(bad, tl_dir).await?
+        .into_iter()
+        .filter(|cp| !known_good.contains(&cp.id))
+        .collect();
+
+    let skipped: HashSet<Ulid> = skipped.iter().copied().collect();
+    let testable: Vec<usize> = (1..candidates.len())
+        .filter(|&i| !skipped.contains(&candidates[i].id))
+        .collect();
+
+    if testable.is_empty() {
+        if candidates.len() > 1 {
+            return Ok(Step::Inconclusive(candidates.iter().map(|cp| cp.id).collect()));
+        }
+        return Ok(Step::Found(candidates.swap_remove(0)));
+    }
+
+    let pick = testable[testable.len() / 2];
+    Ok(Step::Test(candidates.swap_remove(pick), testable.len()))
+}
+
+/// Write a checkpoint's tree into a fresh scratch directory
+fn materialize(repo_root: &Path, checkpoint: &Checkpoint, dir: &Path) -> Result<()> {
+    if dir.exists() {
+        std::fs::remove_dir_all(dir)
+            .with_context(|| format!("Failed to clear {}", dir.display()))?;
+    }
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    let store = Store::open(repo_root)?;
+    jj::publish::materialize_checkpoint_to_dir(checkpoint, &store, dir)
+}
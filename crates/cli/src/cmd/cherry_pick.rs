@@ -0,0 +1,183 @@
+//! Cherry-pick and revert individual checkpoints
+//!
+//! Both commands take the change a checkpoint made relative to its parent and
+//! three-way merge it onto HEAD: cherry-pick applies it (base = parent,
+//! theirs = checkpoint), revert undoes it (base = checkpoint, theirs = parent).
+//! Overlapping edits are left in the working directory with conflict markers.
+
+use crate::cmd::merge_checkpoint::{changed_paths, print_conflicts};
+use crate::locks::RestoreLock;
+use crate::util;
+use anyhow::{anyhow, Context, Result};
+use journal::{Checkpoint, CheckpointMeta, CheckpointReason};
+use owo_colors::OwoColorize;
+use tl_core::{Store, Tree};
+
+/// Apply the change made in a checkpoint onto HEAD
+pub async fn run_cherry_pick(checkpoint: &str) -> Result<()> {
+    apply_checkpoint_change(checkpoint, false).await
+}
+
+/// Undo the change made in a checkpoint, keeping everything after it
+pub async fn run_revert(checkpoint: &str) -> Result<()> {
+    apply_checkpoint_change(checkpoint, true).await
+}
+
+async fn apply_checkpoint_change(checkpoint: &str, revert: bool) -> Result<()> {
+    let (title, verb, reason) = if revert {
+        ("Revert Checkpoint", "Reverted", CheckpointReason::Revert)
+    } else {
+        ("Cherry-pick Checkpoint", "Cherry-picked", CheckpointReason::CherryPick)
+    };
+
+    // 1. Find repository root
+    let repo_root = util::find_repo_root()
+        .context("Failed to find repository")?;
+
+    let tl_dir = repo_root.join(".tl");
+
+    // 2. Ensure daemon running (auto-starts if needed)
+    crate::daemon::ensure_daemon_running().await?;
+
+    // 3. Flush pending changes so HEAD matches the working directory
+    // (must happen before taking the restore lock - the daemon skips flushes while it is held)
+    let socket_path = tl_dir.join("state/daemon.sock");
+    crate::ipc::IpcClient::connect(&socket_path)
+        .await
+        .context("Failed to connect to daemon")?
+        .flush_checkpoint()
+        .await?;
+
+    // 4. Acquire restore lock - we're about to rewrite working directory files
+    let _restore_lock = RestoreLock::acquire(&tl_dir)
+        .context("Failed to acquire restore lock - is another restore in progress?")?;
+
+    // 5. Resolve the checkpoint and HEAD
+    let refs = vec![checkpoint.to_string(), "HEAD".to_string()];
+    let ids = crate::data_access::resolve_checkpoint_refs(&refs, &tl_dir).await?;
+
+    let target_id = ids[0].ok_or_else(|| anyhow!("Checkpoint '{}' not found or ambiguous", checkpoint))?;
+    let head_id = ids[1].ok_or_else(|| anyhow!("No checkpoints exist yet"))?;
+
+    if target_id == head_id && !revert {
+        println!("{}", "Checkpoint is HEAD - nothing to cherry-pick".dimmed());
+        return Ok(());
+    }
+
+    let checkpoints = crate::data_access::get_checkpoints(&[target_id, head_id], &tl_dir).await?;
+    let target_cp = checkpoints[0].as_ref().ok_or_else(|| anyhow!("Checkpoint not found: {}", checkpoint))?;
+    let head_cp = checkpoints[1].as_ref().ok_or_else(|| anyhow!("HEAD checkpoint not found"))?;
+
+    if target_cp.is_merge() {
+        println!("{}", "Note: merge checkpoint - using the change relative to its first parent".yellow());
+    }
+
+    // 6. Load trees (a root checkpoint's change is relative to an empty tree)
+    let store = Store::open(&repo_root)?;
+    let parent_tree = match target_cp.parent {
+        Some(parent_id) => {
+            let parents = crate::data_access::get_checkpoints(&[parent_id], &tl_dir).await?;
+            let parent_cp = parents[0].as_ref()
+                .ok_or_else(|| anyhow!("Parent checkpoint {} not found (garbage collected?)", parent_id))?;
+            store.read_tree(parent_cp.root_tree)?
+        }
+        None => Tree::new(),
+    };
+    let target_tree = store.read_tree(target_cp.root_tree)?;
+    let head_tree = store.read_tree(head_cp.root_tree)?;
+
+    let target_short = &target_id.to_string()[..8];
+    let head_short = &head_id.to_string()[..8];
+
+    println!("{}", title.bold());
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    println!();
+    println!("Checkpoint: {} {}", target_short.yellow(), util::format_relative_time(target_cp.ts_unix_ms).dimmed());
+    println!("HEAD:       {} {}", head_short.yellow(), util::format_relative_time(head_cp.ts_unix_ms).dimmed());
+    println!();
+
+    // 7. Three-way merge of the checkpoint's change onto HEAD
+    let (base, theirs, theirs_label) = if revert {
+        (&target_tree, &parent_tree, format!("revert {}", target_short))
+    } else {
+        (&parent_tree, &target_tree, format!("pick {}", target_short))
+    };
+
+    let result = jj::merge_trees(
+        &store,
+        base,
+        &head_tree,
+        theirs,
+        &format!("HEAD ({})", head_short),
+        &theirs_label,
+    )?;
+
+    let merged_hash = store.write_tree(&result.tree)
+        .context("Failed to write merged tree")?;
+
+    if merged_hash == head_cp.root_tree && result.is_clean() {
+        let msg = if revert { "Change is not present in HEAD - nothing to revert" } else { "Change is already in HEAD - nothing to cherry-pick" };
+        println!("{}", msg.dimmed());
+        return Ok(());
+    }
+
+    // 8. Move the working directory from HEAD to the merged tree
+    let apply = crate::cmd::restore::apply_tree_changes(&store, &head_tree, &result.tree, &repo_root)?;
+    if !apply.errors.is_empty() {
+        println!("{} {} error(s) updating working directory:", "⚠".yellow(), apply.errors.len());
+        for error in apply.errors.iter().take(10) {
+            println!("  {}", error.red());
+        }
+        anyhow::bail!("{} aborted before recording checkpoint; working directory may be partially updated", title);
+    }
+
+    // 9. Record the new checkpoint on top of HEAD via the daemon
+    let touched_paths = changed_paths(&head_tree, &result.tree);
+
+    let new_checkpoint = Checkpoint::new(
+        Some(head_id),
+        merged_hash,
+        reason,
+        touched_paths.clone(),
+        CheckpointMeta {
+            files_changed: touched_paths.len() as u32,
+            bytes_added: 0,
+            bytes_removed: 0,
+        },
+    );
+
+    // The daemon serves one request per connection
+    crate::ipc::IpcClient::connect(&socket_path)
+        .await
+        .context("Failed to connect to daemon")?
+        .append_checkpoint(new_checkpoint.clone())
+        .await?;
+
+    // Daemon's pathmap no longer matches the working directory
+    match crate::ipc::IpcClient::connect(&socket_path).await {
+        Ok(mut client) => {
+            if let Err(e) = client.invalidate_pathmap().await {
+                tracing::warn!("Failed to invalidate pathmap: {}", e);
+            }
+        }
+        Err(e) => tracing::warn!("Failed to connect to daemon to invalidate pathmap: {}", e),
+    }
+
+    // 10. Report
+    let new_short = &new_checkpoint.id.to_string()[..8];
+    println!("{} {} {} → {}",
+        "✓".green(),
+        verb,
+        target_short.yellow(),
+        new_short.green()
+    );
+    println!("  {} files changed, {} written, {} deleted",
+        touched_paths.len(),
+        apply.files_restored,
+        apply.files_deleted
+    );
+
+    print_conflicts(&result.conflicts);
+
+    Ok(())
+}
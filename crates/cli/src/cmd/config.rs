@@ -22,6 +22,23 @@ pub async fn run_list() -> Result<()> {
         config.daemon.checkpoint_interval_secs,
         format!("({}s)", config.daemon.checkpoint_interval_secs).dimmed()
     );
+    println!(
+        "  {} = {}",
+        "adaptive_checkpoints".cyan(),
+        config.daemon.adaptive_checkpoints
+    );
+    println!(
+        "  {} = {} {}",
+        "min_checkpoint_interval_ms".cyan(),
+        config.daemon.min_checkpoint_interval_ms,
+        format!("({}ms)", config.daemon.min_checkpoint_interval_ms).dimmed()
+    );
+    println!(
+        "  {} = {} {}",
+        "max_checkpoint_interval_secs".cyan(),
+        config.daemon.max_checkpoint_interval_secs,
+        format!("({}s)", config.daemon.max_checkpoint_interval_secs).dimmed()
+    );
     println!(
         "  {} = {}",
         "auto_gc_enabled".cyan(),
@@ -41,6 +58,32 @@ pub async fn run_list() -> Result<()> {
         "auto_gc_checkpoint_threshold".cyan(),
         config.daemon.auto_gc_checkpoint_threshold
     );
+    println!(
+        "  {} = {}",
+        "quiescence_enabled".cyan(),
+        config.daemon.quiescence_enabled
+    );
+    println!(
+        "  {} = {}",
+        "burst_events_per_sec".cyan(),
+        config.daemon.burst_events_per_sec
+    );
+    println!(
+        "  {} = {} {}",
+        "max_checkpoint_deferral_secs".cyan(),
+        config.daemon.max_checkpoint_deferral_secs,
+        format!("({}s)", config.daemon.max_checkpoint_deferral_secs).dimmed()
+    );
+    println!(
+        "  {} = {} {}",
+        "collapse_oscillation_secs".cyan(),
+        config.daemon.collapse_oscillation_secs,
+        if config.daemon.collapse_oscillation_secs == 0 {
+            "(off)".dimmed().to_string()
+        } else {
+            format!("({}s)", config.daemon.collapse_oscillation_secs).dimmed().to_string()
+        }
+    );
 
     println!("\n{}", "[gc]".yellow());
     println!(
@@ -66,8 +109,13 @@ pub async fn run_list() -> Result<()> {
 
     println!("\n{}", "Valid Ranges:".bold());
     println!("  checkpoint_interval_secs: 1-3600");
+    println!("  min_checkpoint_interval_ms: 100-60000");
+    println!("  max_checkpoint_interval_secs: 1-3600");
     println!("  auto_gc_interval_secs: 60-86400");
     println!("  auto_gc_checkpoint_threshold: 100-100,000");
+    println!("  burst_events_per_sec: 1 or more");
+    println!("  max_checkpoint_deferral_secs: 1-3600");
+    println!("  collapse_oscillation_secs: 0-3600 (0 = off)");
     println!("  retain_count: 10-1,000,000");
     println!("  retain_hours: 0-8760 (0 = no time limit)");
 
@@ -80,9 +128,16 @@ pub async fn run_get(key: &str) -> Result<()> {
 
     let value = match key {
         "daemon.checkpoint_interval_secs" => config.daemon.checkpoint_interval_secs.to_string(),
+        "daemon.adaptive_checkpoints" => config.daemon.adaptive_checkpoints.to_string(),
+        "daemon.min_checkpoint_interval_ms" => config.daemon.min_checkpoint_interval_ms.to_string(),
+        "daemon.max_checkpoint_interval_secs" => config.daemon.max_checkpoint_interval_secs.to_string(),
         "daemon.auto_gc_enabled" => config.daemon.auto_gc_enabled.to_string(),
         "daemon.auto_gc_interval_secs" => config.daemon.auto_gc_interval_secs.to_string(),
         "daemon.auto_gc_checkpoint_threshold" => config.daemon.auto_gc_checkpoint_threshold.to_string(),
+        "daemon.quiescence_enabled" => config.daemon.quiescence_enabled.to_string(),
+        "daemon.burst_events_per_sec" => config.daemon.burst_events_per_sec.to_string(),
+        "daemon.max_checkpoint_deferral_secs" => config.daemon.max_checkpoint_deferral_secs.to_string(),
+        "daemon.collapse_oscillation_secs" => config.daemon.collapse_oscillation_secs.to_string(),
         "gc.retain_count" => config.gc.retain_count.to_string(),
         "gc.retain_hours" => config.gc.retain_hours.to_string(),
         "gc.retain_pins" => config.gc.retain_pins.to_string(),
@@ -106,6 +161,21 @@ pub async fn run_set(key: &str, value: &str) -> Result<()> {
                 .context("Invalid value: must be a positive integer")?;
             config.daemon.checkpoint_interval_secs = val;
         }
+        "daemon.adaptive_checkpoints" => {
+            let val: bool = value.parse()
+                .context("Invalid value: must be 'true' or 'false'")?;
+            config.daemon.adaptive_checkpoints = val;
+        }
+        "daemon.min_checkpoint_interval_ms" => {
+            let val: u64 = value.parse()
+                .context("Invalid value: must be a positive integer")?;
+            config.daemon.min_checkpoint_interval_ms = val;
+        }
+        "daemon.max_checkpoint_interval_secs" => {
+            let val: u64 = value.parse()
+                .context("Invalid value: must be a positive integer")?;
+            config.daemon.max_checkpoint_interval_secs = val;
+        }
         "daemon.auto_gc_enabled" => {
             let val: bool = value.parse()
                 .context("Invalid value: must be 'true' or 'false'")?;
@@ -121,6 +191,26 @@ pub async fn run_set(key: &str, value: &str) -> Result<()> {
                 .context("Invalid value: must be a positive integer")?;
             config.daemon.auto_gc_checkpoint_threshold = val;
         }
+        "daemon.quiescence_enabled" => {
+            let val: bool = value.parse()
+                .context("Invalid value: must be 'true' or 'false'")?;
+            config.daemon.quiescence_enabled = val;
+        }
+        "daemon.burst_events_per_sec" => {
+            let val: u64 = value.parse()
+                .context("Invalid value: must be a positive integer")?;
+            config.daemon.burst_events_per_sec = val;
+        }
+        "daemon.max_checkpoint_deferral_secs" => {
+            let val: u64 = value.parse()
+                .context("Invalid value: must be a positive integer")?;
+            config.daemon.max_checkpoint_deferral_secs = val;
+        }
+        "daemon.collapse_oscillation_secs" => {
+            let val: u64 = value.parse()
+                .context("Invalid value: must be a non-negative integer")?;
+            config.daemon.collapse_oscillation_secs = val;
+        }
         "gc.retain_count" => {
             let val: usize = value.parse()
                 .context("Invalid value: must be a positive integer")?;
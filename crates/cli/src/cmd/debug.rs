@@ -0,0 +1,72 @@
+//! Debugging aids
+//!
+//! `tl debug record-events` runs a watcher in the foreground with the
+//! repository's `[watcher]` settings and writes every raw event it receives
+//! to a trace file, for bug reports about missed or spurious checkpoints.
+//! The daemon can keep running meanwhile; the two watch independently.
+
+use crate::util;
+use anyhow::{Context, Result};
+use owo_colors::OwoColorize;
+use std::path::PathBuf;
+use std::time::Duration;
+use watcher::Watcher;
+
+pub async fn run_record_events(output: Option<PathBuf>, duration: Option<u64>) -> Result<()> {
+    let repo_root = util::find_repo_root()
+        .context("Failed to find repository")?;
+    let tl_dir = repo_root.join(".tl");
+
+    let output = output.unwrap_or_else(|| {
+        PathBuf::from(format!("tl-events-{}.trace", chrono::Local::now().format("%Y%m%d-%H%M%S")))
+    });
+
+    let mut config = crate::daemon::load_watcher_config(&tl_dir);
+    config.record_events = Some(output.clone());
+
+    let mut watcher = Watcher::with_config(&repo_root, config)
+        .context("Failed to create watcher")?;
+    watcher.start().await.context("Failed to start watcher")?;
+
+    println!("{}", "Record Watcher Events".bold());
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    println!();
+    if let Some(diagnostics) = watcher.diagnostics() {
+        println!("  Backend: {}", diagnostics.backend);
+    }
+    println!("  Trace:   {}", output.display());
+    println!();
+    match duration {
+        Some(secs) => println!("{}", format!("Reproduce the problem; recording stops after {}s or on Ctrl-C.", secs).dimmed()),
+        None => println!("{}", "Reproduce the problem, then press Ctrl-C to stop recording.".dimmed()),
+    }
+
+    let deadline = async {
+        match duration {
+            Some(secs) => tokio::time::sleep(Duration::from_secs(secs)).await,
+            None => std::future::pending().await,
+        }
+    };
+    tokio::pin!(deadline);
+
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => break,
+            _ = &mut deadline => break,
+            result = watcher.poll_events() => {
+                result?;
+                // Only the raw events matter here
+                watcher.next_batch();
+            }
+        }
+    }
+
+    let recorded = watcher.recorded_events().unwrap_or(0);
+    watcher.stop().await?;
+
+    println!();
+    println!("{} Recorded {} event(s) to {}", "✓".green(), recorded.to_string().green(), output.display());
+    println!("{}", "The trace lists repository-relative paths - review it before attaching it to a bug report.".dimmed());
+
+    Ok(())
+}
@@ -116,7 +116,7 @@ pub async fn run(checkpoint_a: &str, checkpoint_b: &str, patch: bool, context: u
             let new_content = store.blob_store().read_blob(new_entry.blob_hash)?;
 
             // Check for binary files
-            if crate::diff_utils::is_binary(&old_content) || crate::diff_utils::is_binary(&new_content) {
+            if tl_core::is_binary(&old_content) || tl_core::is_binary(&new_content) {
                 println!("  {} {} (binary file)", "~".yellow(), path_str);
                 println!();
                 shown += 1;
@@ -316,6 +316,7 @@ fn reapply_stash(
             println!("    {}", error.red());
         }
     }
+    crate::cmd::restore::print_not_kept(&result.not_kept);
 
     Ok(())
 }
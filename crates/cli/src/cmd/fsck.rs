@@ -0,0 +1,343 @@
+//! Verify repository integrity end-to-end (and optionally repair)
+//!
+//! Checks, in order:
+//! 1. Journal entries and index (`Journal::verify_integrity`)
+//! 2. Every checkpoint's root tree and referenced blobs exist and re-hash correctly
+//! 3. The daemon's PathMap matches the HEAD tree
+//! 4. JJ mappings point at checkpoints and commits that still exist
+//!
+//! With `--repair`, corrupt objects are moved to `.tl/quarantine/`, the
+//! journal index and PathMap are rebuilt, and dangling mappings are removed.
+//! Exits non-zero if any problem remains (quarantined objects still count,
+//! since their checkpoints stay incomplete), so it can gate CI.
+
+use crate::locks::GcLock;
+use crate::util;
+use anyhow::{Context, Result};
+use journal::{Journal, PathMap};
+use owo_colors::OwoColorize;
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::Duration;
+use tl_core::{EntryKind, Sha1Hash, Store};
+
+/// Problem counters for the final summary
+#[derive(Default)]
+struct FsckTally {
+    problems: usize,
+    repaired: usize,
+}
+
+impl FsckTally {
+    fn problem(&mut self, message: String) {
+        self.problems += 1;
+        println!("  {} {}", "✗".red(), message);
+    }
+
+    fn repaired(&mut self, message: String) {
+        self.repaired += 1;
+        println!("    {} {}", "→".yellow(), message);
+    }
+
+    /// Repair action that contains the damage but leaves the problem open
+    /// (a quarantined object is still missing from its checkpoints)
+    fn contained(&self, message: String) {
+        println!("    {} {}", "→".yellow(), message);
+    }
+}
+
+pub async fn run(repair: bool) -> Result<()> {
+    // 1. Find repository root
+    let repo_root = util::find_repo_root()
+        .context("Failed to find repository")?;
+
+    let tl_dir = repo_root.join(".tl");
+
+    // 2. Hold the GC lock so nothing rewrites the store while we scan it
+    let _gc_lock = GcLock::acquire(&tl_dir)
+        .context("Failed to acquire GC lock - is a GC or restore in progress?")?;
+
+    // 3. Stop daemon for exclusive journal access (same as gc)
+    let socket_path = tl_dir.join("state/daemon.sock");
+    let daemon_was_running = if socket_path.exists() {
+        match crate::ipc::IpcClient::connect(&socket_path).await {
+            Ok(mut client) => {
+                println!("{}", "Stopping daemon for exclusive journal access...".dimmed());
+                client.shutdown().await.ok();
+                tokio::time::sleep(Duration::from_millis(500)).await;
+                true
+            }
+            Err(_) => false,
+        }
+    } else {
+        false
+    };
+
+    // Always bring the daemon back, even when a check fails
+    let result = check_with_daemon_stopped(&repo_root, &tl_dir, repair);
+    let restarted = restart_daemon(daemon_was_running).await;
+    let remaining = result?;
+    restarted?;
+
+    if remaining > 0 {
+        anyhow::bail!("fsck found {} unresolved problem(s)", remaining);
+    }
+
+    Ok(())
+}
+
+/// Run every check with the daemon stopped; returns the unresolved problem count
+fn check_with_daemon_stopped(repo_root: &Path, tl_dir: &Path, repair: bool) -> Result<usize> {
+    let store = Store::open(repo_root)?;
+    let journal = Journal::open_with_cipher(&tl_dir.join("journal"), store.cipher().cloned())
+        .context("Failed to open checkpoint journal")?;
+
+    println!("{}", "Repository Check".bold());
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    println!();
+
+    let mut tally = FsckTally::default();
+
+    check_journal(&journal, repair, &mut tally)?;
+    check_objects(&journal, &store, repair, &mut tally)?;
+    check_pathmap(tl_dir, &journal, &store, repair, &mut tally)?;
+    check_jj_mapping(repo_root, tl_dir, &journal, repair, &mut tally)?;
+
+    println!();
+    let remaining = tally.problems.saturating_sub(tally.repaired);
+    if tally.problems == 0 {
+        println!("{} No problems found", "✓".green());
+    } else if remaining == 0 {
+        println!("{} {} problem(s) found, all repaired", "✓".green(), tally.problems);
+    } else if repair {
+        println!("{} {} problem(s) found, {} could not be repaired", "⚠".yellow(), tally.problems, remaining);
+    } else {
+        println!("{} {} problem(s) found", "⚠".yellow(), tally.problems);
+        println!("{}", "Run 'tl fsck --repair' to quarantine corrupt objects and rebuild indexes".dimmed());
+    }
+
+    Ok(remaining)
+}
+
+async fn restart_daemon(daemon_was_running: bool) -> Result<()> {
+    if daemon_was_running {
+        println!();
+        println!("{}", "Restarting daemon...".dimmed());
+        crate::daemon::ensure_daemon_running_with_timeout(3).await?;
+    }
+    Ok(())
+}
+
+/// Journal entries deserialize and the index matches the database
+fn check_journal(journal: &Journal, repair: bool, tally: &mut FsckTally) -> Result<()> {
+    println!("{}", "Journal".bold());
+
+    let report = journal.verify_integrity()?;
+    if report.is_healthy() {
+        println!("  {} {} entries", "✓".green(), report.valid_entries);
+        return Ok(());
+    }
+
+    let issues = [
+        (report.corrupted_entries.len(), "corrupted entries"),
+        (report.orphaned_entries.len(), "entries missing from index"),
+        (report.index_mismatches.len(), "index sequence mismatches"),
+        (report.index_without_db.len(), "index entries without data"),
+        (report.db_errors.len(), "database errors"),
+    ];
+    for (count, label) in issues {
+        if count > 0 {
+            tally.problem(format!("{} {}", count, label));
+        }
+    }
+
+    if repair {
+        let result = journal.repair(false)?;
+        let healthy = result.final_report.as_ref().map(|r| r.is_healthy()).unwrap_or(false);
+        if healthy {
+            // One repair pass fixes every journal issue category at once
+            tally.repaired += issues.iter().filter(|(count, _)| *count > 0).count();
+            println!("    {} removed {} corrupted entries, index rebuilt", "→".yellow(), result.removed_corrupted);
+        }
+    }
+
+    Ok(())
+}
+
+/// Every checkpoint's tree and blobs exist and re-hash to their IDs
+fn check_objects(journal: &Journal, store: &Store, repair: bool, tally: &mut FsckTally) -> Result<()> {
+    println!("{}", "Objects".bold());
+
+    let mut ids: Vec<_> = journal.all_checkpoint_ids()?.into_iter().collect();
+    ids.sort();
+
+    let mut trees_checked: HashSet<Sha1Hash> = HashSet::new();
+    let mut blobs_checked: HashSet<Sha1Hash> = HashSet::new();
+    let mut not_kept = 0;
+    let problems_before = tally.problems;
+
+    for id in ids {
+        let checkpoint = match journal.get(&id)? {
+            Some(cp) => cp,
+            None => continue,
+        };
+        let id_short = &id.to_string()[..8];
+
+        if !trees_checked.insert(checkpoint.root_tree) {
+            continue;
+        }
+
+        if !store.has_tree(checkpoint.root_tree) {
+            tally.problem(format!("checkpoint {}: tree {} missing", id_short, checkpoint.root_tree.to_hex()));
+            continue;
+        }
+
+        let tree = match store.read_tree(checkpoint.root_tree) {
+            Ok(tree) => tree,
+            Err(e) => {
+                tally.problem(format!("checkpoint {}: tree {} corrupt ({})", id_short, checkpoint.root_tree.to_hex(), e));
+                if repair && store.quarantine_tree(checkpoint.root_tree)? {
+                    tally.contained(format!("tree {} quarantined", checkpoint.root_tree.to_hex()));
+                }
+                continue;
+            }
+        };
+
+        for (path_bytes, entry) in tree.entries_with_paths() {
+            // Each blob is verified (and reported) once, however many trees share it
+            if entry.kind == EntryKind::Tree || !blobs_checked.insert(entry.blob_hash) {
+                continue;
+            }
+
+            // Content dropped on purpose by a path policy is not a problem
+            if store.blob_store().is_omitted(entry.blob_hash) {
+                not_kept += 1;
+                continue;
+            }
+
+            let path = String::from_utf8_lossy(path_bytes);
+            if !store.blob_store().has_blob(entry.blob_hash) {
+                tally.problem(format!("checkpoint {}: blob {} for {} missing", id_short, entry.blob_hash.to_hex(), path));
+                continue;
+            }
+
+            // Streamed and verified chunk by chunk (large files never sit in memory)
+            if let Err(e) = store.blob_store().read_blob_to(entry.blob_hash, &mut std::io::sink()) {
+                tally.problem(format!("checkpoint {}: blob {} for {} corrupt ({})", id_short, entry.blob_hash.to_hex(), path, e));
+                if repair && store.blob_store().quarantine_blob(entry.blob_hash)? {
+                    tally.contained(format!("blob {} quarantined", entry.blob_hash.to_hex()));
+                }
+            }
+        }
+    }
+
+    if tally.problems == problems_before {
+        println!("  {} {} trees, {} blobs verified", "✓".green(), trees_checked.len(), blobs_checked.len() - not_kept);
+        if not_kept > 0 {
+            println!("  {} {} blobs not kept by .tlpolicy", "✓".green(), not_kept);
+        }
+    }
+
+    Ok(())
+}
+
+/// The saved PathMap matches the HEAD tree
+fn check_pathmap(tl_dir: &Path, journal: &Journal, store: &Store, repair: bool, tally: &mut FsckTally) -> Result<()> {
+    println!("{}", "PathMap".bold());
+
+    let pathmap_path = tl_dir.join("state/pathmap.bin");
+    let head = match journal.latest()? {
+        Some(head) => head,
+        None => {
+            println!("  {} no checkpoints yet", "✓".green());
+            return Ok(());
+        }
+    };
+
+    if tl_dir.join("state/pathmap_stale").exists() {
+        println!("  {} marked stale, daemon will rebuild from HEAD", "✓".green());
+        return Ok(());
+    }
+
+    if !pathmap_path.exists() {
+        println!("  {} not created yet", "✓".green());
+        return Ok(());
+    }
+
+    let head_tree = match store.read_tree(head.root_tree) {
+        Ok(tree) => tree,
+        Err(_) => {
+            tally.problem("cannot verify: HEAD tree unreadable".to_string());
+            return Ok(());
+        }
+    };
+
+    match PathMap::load(&pathmap_path) {
+        Ok(pathmap) if pathmap.root_tree == head.root_tree && journal::pathmap_matches_tree(&pathmap, &head_tree) => {
+            println!("  {} matches HEAD ({} paths)", "✓".green(), head_tree.len());
+            return Ok(());
+        }
+        Ok(_) => tally.problem(format!("does not match HEAD {}", &head.id.to_string()[..8])),
+        Err(e) => tally.problem(format!("unreadable ({})", e)),
+    }
+
+    if repair {
+        PathMap::from_tree(&head_tree, head.root_tree).save(&pathmap_path)?;
+        tally.repaired("rebuilt from HEAD".to_string());
+    }
+
+    Ok(())
+}
+
+/// JJ mappings reference existing checkpoints and commits
+fn check_jj_mapping(
+    repo_root: &Path,
+    tl_dir: &Path,
+    journal: &Journal,
+    repair: bool,
+    tally: &mut FsckTally,
+) -> Result<()> {
+    println!("{}", "JJ mapping".bold());
+
+    if jj::detect_jj_workspace(repo_root)?.is_none() || !tl_dir.join("state/jj-mapping").exists() {
+        println!("  {} no JJ workspace", "✓".green());
+        return Ok(());
+    }
+
+    let mapping = jj::JjMapping::open(tl_dir)?;
+    let mappings = mapping.all_mappings()?;
+    let problems_before = tally.problems;
+
+    let commit_ids: Vec<String> = mappings.iter().map(|(_, commit)| commit.clone()).collect();
+    let workspace = jj::load_workspace(repo_root)?;
+    let missing: HashSet<String> = jj::git_ops::missing_commits(&workspace, &commit_ids)?
+        .into_iter()
+        .collect();
+
+    for (checkpoint_id, commit_id) in &mappings {
+        let id_short = &checkpoint_id.to_string()[..8];
+        let problem = if journal.get(checkpoint_id)?.is_none() {
+            format!("checkpoint {} (mapped to {}) no longer exists", id_short, &commit_id[..commit_id.len().min(12)])
+        } else if missing.contains(commit_id) {
+            format!("checkpoint {} maps to missing commit {}", id_short, &commit_id[..commit_id.len().min(12)])
+        } else {
+            continue;
+        };
+
+        tally.problem(problem);
+        if repair {
+            mapping.remove(*checkpoint_id)?;
+            tally.repaired("mapping removed".to_string());
+        }
+    }
+
+    if repair {
+        mapping.flush()?;
+    }
+
+    if tally.problems == problems_before {
+        println!("  {} {} mappings verified", "✓".green(), mappings.len());
+    }
+
+    Ok(())
+}
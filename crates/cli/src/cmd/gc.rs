@@ -14,8 +14,7 @@ use crate::locks::GcLock;
 use crate::system_config;
 use crate::util;
 use anyhow::{Context, Result};
-use std::collections::HashSet;
-use tl_core::Store;
+use tl_core::{PathPolicies, Store};
 use journal::{GarbageCollector, Journal, PinManager};
 use owo_colors::OwoColorize;
 use std::time::Duration;
@@ -51,30 +50,17 @@ pub async fn run() -> Result<()> {
     };
 
     // 3. Now safe to open journal with write access
+    let mut store = Store::open(&repo_root)?;
+
     let journal_path = tl_dir.join("journal");
-    let mut journal = Journal::open(&journal_path)
+    let mut journal = Journal::open_with_cipher(&journal_path, store.cipher().cloned())
         .context("Failed to open checkpoint journal")?;
 
-    let mut store = Store::open(&repo_root)?;
-
     // 4. Create pin manager
     let pin_manager = PinManager::new(&tl_dir);
 
-    // 4. Collect workspace checkpoints (if JJ workspace exists)
-    let workspace_checkpoints = if jj::detect_jj_workspace(&repo_root)?.is_some() {
-        let ws_manager = jj::WorkspaceManager::open(&tl_dir, &repo_root)?;
-        let mut checkpoints = HashSet::new();
-
-        for state in ws_manager.list_states()? {
-            if let Some(cp_id) = state.current_checkpoint {
-                checkpoints.insert(cp_id);
-            }
-        }
-
-        Some(checkpoints)
-    } else {
-        None
-    };
+    // 4. Collect checkpoints referenced by workspaces, stashes and JJ mappings
+    let referenced = util::referenced_checkpoints(&tl_dir, &repo_root)?;
 
     // 5. Load retention policy from system config
     let system_config = system_config::load()
@@ -83,7 +69,11 @@ pub async fn run() -> Result<()> {
             system_config::SystemConfig::default()
         });
     let policy = system_config.gc.to_retention_policy();
-    let gc = GarbageCollector::new(policy.clone());
+    let path_policies = PathPolicies::load(&repo_root).unwrap_or_else(|e| {
+        tracing::warn!("Failed to load .tlpolicy, keeping every version: {}", e);
+        PathPolicies::default()
+    });
+    let gc = GarbageCollector::new(policy.clone()).with_path_policies(path_policies);
 
     // Show retention policy
     println!("Retention policy:");
@@ -95,8 +85,11 @@ pub async fn run() -> Result<()> {
     println!("{}", "Running Garbage Collection...".bold());
     println!();
 
-    // 6. Run GC with workspace checkpoint protection
-    let metrics = gc.collect(&mut journal, &mut store, &pin_manager, workspace_checkpoints.as_ref())?;
+    // 6. Run GC with referenced checkpoints protected
+    let metrics = gc.collect(&mut journal, &mut store, &pin_manager, Some(&referenced))?;
+
+    // Re-measure .tl/ for quota tracking
+    store.usage().rescan()?;
 
     // 7. Display results
     println!("{}", "GC Complete".green().bold());
@@ -106,7 +99,15 @@ pub async fn run() -> Result<()> {
     if metrics.checkpoints_deleted == 0 && metrics.trees_deleted == 0 && metrics.blobs_deleted == 0 {
         println!("{}", "No garbage found - repository is already clean".dimmed());
     } else {
-        println!("Checkpoints deleted: {}", metrics.checkpoints_deleted.to_string().yellow());
+        if metrics.checkpoints_compacted > 0 {
+            println!(
+                "Checkpoints deleted: {} {}",
+                metrics.checkpoints_deleted.to_string().yellow(),
+                format!("(folded into {} compacted checkpoint(s))", metrics.checkpoints_compacted).dimmed()
+            );
+        } else {
+            println!("Checkpoints deleted: {}", metrics.checkpoints_deleted.to_string().yellow());
+        }
         println!("Trees deleted:       {}", metrics.trees_deleted.to_string().yellow());
         println!("Blobs deleted:       {}", metrics.blobs_deleted.to_string().yellow());
         println!();
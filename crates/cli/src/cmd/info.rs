@@ -20,6 +20,9 @@ pub struct RepoInfo {
     pub pin_count: usize,
     pub latest_checkpoint_id: Option<String>,
     pub latest_checkpoint_time: Option<String>,
+    pub checkpoints_last_hour: usize,
+    pub checkpoints_last_day: usize,
+    pub schedule: String,
 }
 
 pub async fn run() -> Result<()> {
@@ -49,7 +52,7 @@ pub async fn run() -> Result<()> {
         .context("Failed to open Timelapse store. Is this a Timelapse repository?")?;
 
     // Gather statistics
-    let info = gather_info(&repo_root, &store, checkpoint_count, latest_checkpoint)?;
+    let info = gather_info(&repo_root, &store, checkpoint_count, &checkpoint_ids, latest_checkpoint)?;
 
     // Display information
     display_info(&info);
@@ -77,6 +80,7 @@ fn gather_info(
     repo_root: &Path,
     _store: &Store,
     checkpoint_count: usize,
+    checkpoint_ids: &[String],
     latest_checkpoint: Option<journal::Checkpoint>,
 ) -> Result<RepoInfo> {
     let tl_dir = repo_root.join(".tl");
@@ -115,6 +119,20 @@ fn gather_info(
     // Total .tl directory size
     let total_size = calculate_dir_size(&tl_dir)?;
 
+    // Checkpoint rate (ULIDs carry their creation time)
+    let now_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    let created_within = |window_ms: u64| {
+        checkpoint_ids.iter()
+            .filter_map(|id| ulid::Ulid::from_string(id).ok())
+            .filter(|id| now_ms.saturating_sub(id.timestamp_ms()) <= window_ms)
+            .count()
+    };
+    let checkpoints_last_hour = created_within(60 * 60 * 1000);
+    let checkpoints_last_day = created_within(24 * 60 * 60 * 1000);
+
     Ok(RepoInfo {
         root: repo_root.to_path_buf(),
         tl_dir,
@@ -128,9 +146,25 @@ fn gather_info(
         pin_count,
         latest_checkpoint_id,
         latest_checkpoint_time,
+        checkpoints_last_hour,
+        checkpoints_last_day,
+        schedule: describe_schedule(),
     })
 }
 
+/// How the daemon schedules checkpoints, from the system config
+fn describe_schedule() -> String {
+    let daemon = crate::system_config::load().unwrap_or_default().daemon;
+    if daemon.adaptive_checkpoints {
+        format!(
+            "adaptive ({}ms after activity settles, at most {}s under load)",
+            daemon.min_checkpoint_interval_ms, daemon.max_checkpoint_interval_secs
+        )
+    } else {
+        format!("every {}s", daemon.checkpoint_interval_secs)
+    }
+}
+
 fn count_objects(dir: &Path) -> Result<(usize, u64)> {
     if !dir.exists() {
         return Ok((0, 0));
@@ -244,6 +278,13 @@ fn display_info(info: &RepoInfo) {
     println!("  Journal size: {}", format_size(info.journal_size));
     println!();
 
+    // Checkpoint rate
+    println!("Checkpoint rate:");
+    println!("  Last hour:    {}", info.checkpoints_last_hour);
+    println!("  Last 24h:     {:.1}/hour", info.checkpoints_last_day as f64 / 24.0);
+    println!("  Schedule:     {}", info.schedule);
+    println!();
+
     // Object storage
     println!("Storage:");
     println!("  Blobs:        {} objects, {}",
@@ -7,8 +7,7 @@ use owo_colors::OwoColorize;
 use std::env;
 use std::path::Path;
 use tl_core::store::{Store, update_vcs_config, update_user_config};
-use tl_core::{Entry, Tree};
-use watcher::ignore::{IgnoreConfig, IgnoreRules};
+use tl_core::{IgnoreConfig, IgnoreRules, SecretScanner};
 
 pub async fn run(skip_git: bool, skip_jj: bool) -> Result<()> {
     let current_dir = env::current_dir()?;
@@ -389,7 +388,7 @@ fn create_initial_checkpoint(repo_root: &Path) -> Result<Option<String>> {
     // Open store and journal
     let store = Store::open(repo_root)
         .context("Failed to open store for initial checkpoint")?;
-    let journal = Journal::open(&tl_dir.join("journal"))
+    let journal = Journal::open_with_cipher(&tl_dir.join("journal"), store.cipher().cloned())
         .context("Failed to open journal for initial checkpoint")?;
 
     // Load ignore rules to respect .gitignore and .tlignore
@@ -397,74 +396,16 @@ fn create_initial_checkpoint(repo_root: &Path) -> Result<Option<String>> {
     let ignore_rules = IgnoreRules::load(repo_root, ignore_config)
         .context("Failed to load ignore rules")?;
 
-    // Build tree from all files in working directory
-    let mut tree = Tree::new();
-    let mut files_count = 0u32;
-    let mut touched_paths = Vec::new();
-
-    for entry in walkdir::WalkDir::new(repo_root)
-        .follow_links(false)
-        .into_iter()
-        .filter_entry(|e| {
-            // Skip the root directory itself from filtering
-            if e.path() == repo_root {
-                return true;
-            }
-            // Use ignore rules to filter
-            let rel_path = e.path().strip_prefix(repo_root).unwrap_or(e.path());
-            !ignore_rules.should_ignore(rel_path)
-        })
-    {
-        let entry = match entry {
-            Ok(e) => e,
-            Err(_) => continue,
-        };
-
-        // Skip directories (we only store files)
-        if !entry.file_type().is_file() {
-            continue;
-        }
-
-        let path = entry.path();
-        let rel_path = match path.strip_prefix(repo_root) {
-            Ok(p) => p,
-            Err(_) => continue,
-        };
-
-        // Skip protected directories (double-check)
-        let rel_str = rel_path.to_string_lossy();
-        if rel_str.starts_with(".tl/") || rel_str.starts_with(".git/") || rel_str.starts_with(".jj/") {
-            continue;
-        }
+    // Screen content for secrets like the daemon does ([secrets] in config.toml)
+    let scanner = SecretScanner::load(&tl_dir)
+        .context("Failed to load [secrets] config")?;
 
-        // Hash file content
-        let blob_hash = tl_core::hash::hash_file(path)
-            .with_context(|| format!("Failed to hash file: {}", path.display()))?;
-
-        // Store blob if not already present
-        if !store.blob_store().has_blob(blob_hash) {
-            let content = std::fs::read(path)
-                .with_context(|| format!("Failed to read file: {}", path.display()))?;
-            store.blob_store().write_blob(blob_hash, &content)
-                .with_context(|| format!("Failed to store blob for: {}", path.display()))?;
-        }
-
-        // Get file mode
-        #[cfg(unix)]
-        let mode = {
-            use std::os::unix::fs::MetadataExt;
-            entry.metadata()
-                .map(|m| m.mode())
-                .unwrap_or(0o644)
-        };
-        #[cfg(not(unix))]
-        let mode = 0o644;
-
-        // Add to tree
-        tree.insert(rel_path, Entry::file(mode, blob_hash));
-        touched_paths.push(rel_path.to_path_buf());
-        files_count += 1;
-    }
+    // Build tree from all files in working directory (hashed in parallel)
+    let snapshot = tl_core::snapshot_worktree(repo_root, &store, &ignore_rules, None, Some(&scanner))
+        .context("Failed to snapshot working directory")?;
+    let tree = snapshot.tree;
+    let touched_paths = snapshot.paths;
+    let files_count = touched_paths.len() as u32;
 
     // If no files found, return None
     if files_count == 0 {
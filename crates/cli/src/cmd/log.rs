@@ -16,11 +16,11 @@ pub struct LogOptions {
     pub grep_filter: Option<String>,
 }
 
-pub async fn run(limit: Option<usize>) -> Result<()> {
+pub async fn run(limit: Option<usize>, graph: bool) -> Result<()> {
     run_with_options(LogOptions {
         limit,
         oneline: false,
-        graph: false,
+        graph,
         author_filter: None,
         grep_filter: None,
     }).await
@@ -43,8 +43,13 @@ pub async fn run_with_options(options: LogOptions) -> Result<()> {
         .context("Failed to connect to daemon")?;
 
     // 4. Get checkpoint count and list in one IPC call
+    // (graph mode follows every parent, so merged-in history is included)
     let limit_val = options.limit.unwrap_or(20);
-    let (checkpoint_count, mut checkpoints) = client.get_log_data(Some(limit_val), None).await?;
+    let (checkpoint_count, mut checkpoints) = if options.graph {
+        client.get_log_graph(Some(limit_val)).await?
+    } else {
+        client.get_log_data(Some(limit_val), None).await?
+    };
 
     if checkpoint_count == 0 {
         println!("{}", "No checkpoints yet".dimmed());
@@ -80,7 +85,7 @@ pub async fn run_with_options(options: LogOptions) -> Result<()> {
     if options.oneline {
         display_oneline(&checkpoints, &pins_by_checkpoint);
     } else if options.graph {
-        display_graph(&checkpoints, &pins_by_checkpoint, &store);
+        display_graph(&checkpoints, &pins_by_checkpoint);
     } else {
         display_full(&checkpoints, &pins_by_checkpoint, &store, limit_val, checkpoint_count)?;
     }
@@ -129,21 +134,26 @@ fn display_oneline(checkpoints: &[Checkpoint], pins_by_checkpoint: &HashMap<Ulid
 fn display_graph(
     checkpoints: &[Checkpoint],
     pins_by_checkpoint: &HashMap<Ulid, Vec<String>>,
-    store: &Store,
 ) {
-    for (idx, checkpoint) in checkpoints.iter().enumerate() {
+    let nodes: Vec<(Ulid, Vec<Ulid>)> = checkpoints.iter()
+        .map(|cp| (cp.id, cp.parents()))
+        .collect();
+
+    for row in graph_rows(&nodes) {
+        let (idx, prefix) = match row {
+            GraphRow::Edge(line) => {
+                println!("{}", line.dimmed());
+                continue;
+            }
+            GraphRow::Node { idx, prefix } => (idx, prefix),
+        };
+
+        let checkpoint = &checkpoints[idx];
         let id_short = checkpoint.id.to_string()[..8].to_string();
         let time_str = util::format_relative_time(checkpoint.ts_unix_ms);
         let reason = format!("{:?}", checkpoint.reason);
 
-        // Draw graph line
-        if checkpoint.parent.is_some() {
-            print!("│ ");
-        } else {
-            print!("* ");  // Root commit
-        }
-
-        print!("{} ", id_short.yellow());
+        print!("{} {} ", prefix, id_short.yellow());
 
         // Display pin names if any
         if let Some(pin_names) = pins_by_checkpoint.get(&checkpoint.id) {
@@ -155,14 +165,135 @@ fn display_graph(
         }
 
         println!("{} - {}", reason.cyan(), time_str.dimmed());
+    }
+}
+
+/// One output line of the log graph
+enum GraphRow {
+    /// Checkpoint line: `prefix` is the lane drawing, `idx` indexes the input
+    Node { idx: usize, prefix: String },
+    /// Connector line between checkpoints (lanes merging or splitting)
+    Edge(String),
+}
+
+/// Lay out a newest-first list of `(id, parents)` as lanes
+///
+/// Each lane tracks the checkpoint it expects next. A checkpoint expected by
+/// several lanes collapses them (`├─┘`); a merge checkpoint opens a lane per
+/// extra parent (`├─╮`), or joins a lane already waiting for that parent.
+fn graph_rows(nodes: &[(Ulid, Vec<Ulid>)]) -> Vec<GraphRow> {
+    let mut lanes: Vec<Option<Ulid>> = Vec::new();
+    let mut rows = Vec::new();
+
+    for (idx, (id, parents)) in nodes.iter().enumerate() {
+        // Column for this node: first lane expecting it, or a free lane
+        let expecting: Vec<usize> = (0..lanes.len()).filter(|&i| lanes[i] == Some(*id)).collect();
+        let col = match expecting.first() {
+            Some(&col) => col,
+            None => allocate_lane(&mut lanes, 0),
+        };
 
-        // Show connector to parent
-        if idx + 1 < checkpoints.len() {
-            println!("│");
+        let prefix = (0..lanes.len())
+            .map(|i| if i == col { "*" } else if lanes[i].is_some() { "│" } else { " " })
+            .collect::<Vec<_>>()
+            .join(" ");
+        rows.push(GraphRow::Node { idx, prefix });
+
+        // Other lanes that were waiting on this node end here
+        if expecting.len() > 1 {
+            let joined: Vec<(usize, bool)> = expecting[1..].iter().map(|&i| (i, false)).collect();
+            for &(i, _) in &joined {
+                lanes[i] = None;
+            }
+            rows.push(GraphRow::Edge(connector_row(&lanes, col, &joined, false)));
+        }
+
+        // First parent continues in this lane; merge parents branch off
+        lanes[col] = parents.first().copied();
+        let mut targets = Vec::new();
+        for parent in parents.iter().skip(1) {
+            match lanes.iter().position(|lane| *lane == Some(*parent)) {
+                Some(i) if i != col => targets.push((i, true)),
+                Some(_) => {}
+                None => {
+                    let i = allocate_lane(&mut lanes, col + 1);
+                    lanes[i] = Some(*parent);
+                    targets.push((i, false));
+                }
+            }
+        }
+        if !targets.is_empty() {
+            rows.push(GraphRow::Edge(connector_row(&lanes, col, &targets, true)));
+        }
+
+        while lanes.last() == Some(&None) {
+            lanes.pop();
+        }
+    }
+
+    rows
+}
+
+/// Reuse the first free lane at or after `from`, or open a new one
+fn allocate_lane(lanes: &mut Vec<Option<Ulid>>, from: usize) -> usize {
+    match (from..lanes.len()).find(|&i| lanes[i].is_none()) {
+        Some(i) => i,
+        None => {
+            lanes.push(None);
+            lanes.len() - 1
         }
     }
 }
 
+/// Draw a horizontal connector from `col` to each `(lane, existing)` target
+///
+/// `down` draws edges leaving the node towards parents (`╮`), otherwise lanes
+/// joining the node from above (`┘`). Existing lanes keep their line (`┤`).
+fn connector_row(lanes: &[Option<Ulid>], col: usize, targets: &[(usize, bool)], down: bool) -> String {
+    let lo = targets.iter().map(|&(i, _)| i).chain([col]).min().unwrap_or(col);
+    let hi = targets.iter().map(|&(i, _)| i).chain([col]).max().unwrap_or(col);
+    let width = lanes.len().max(hi + 1);
+
+    let mut cells = Vec::with_capacity(width * 2);
+    for i in 0..width {
+        let active = lanes.get(i).is_some_and(|lane| lane.is_some());
+        let target = targets.iter().find(|&&(t, _)| t == i);
+
+        let cell = if i == col {
+            match (lo < col, hi > col) {
+                (true, true) => "┼",
+                (true, false) => "┤",
+                _ => "├",
+            }
+        } else if let Some(&(_, existing)) = target {
+            match (i == lo, i == hi, existing, down) {
+                (_, _, true, _) if i == lo => "├",
+                (_, _, true, _) if i == hi => "┤",
+                (_, _, true, _) => "┼",
+                (true, _, false, true) => "╭",
+                (_, true, false, true) => "╮",
+                (_, _, false, true) => "┬",
+                (true, _, false, false) => "└",
+                (_, true, false, false) => "┘",
+                _ => "┴",
+            }
+        } else if i > lo && i < hi {
+            if active { "┼" } else { "─" }
+        } else if active {
+            "│"
+        } else {
+            " "
+        };
+        cells.push(cell);
+
+        if i + 1 < width {
+            cells.push(if i >= lo && i < hi { "─" } else { " " });
+        }
+    }
+
+    cells.concat().trim_end().to_string()
+}
+
 /// Display in full format (default)
 fn display_full(
     checkpoints: &[Checkpoint],
@@ -71,7 +71,7 @@ async fn start_merge(repo_root: &Path, tl_dir: &Path, branch: &str) -> Result<()
     // Open components
     let store = Store::open(repo_root)
         .context("Failed to open Timelapse store")?;
-    let journal = Journal::open(tl_dir)
+    let journal = Journal::open_with_cipher(tl_dir, store.cipher().cloned())
         .context("Failed to open journal")?;
 
     // Get current checkpoint for abort recovery
@@ -161,7 +161,7 @@ async fn handle_abort(repo_root: &Path, tl_dir: &Path, merge_state: Option<Merge
     // Restore pre-merge checkpoint
     let store = Store::open(repo_root)
         .context("Failed to open store")?;
-    let journal = Journal::open(tl_dir)
+    let journal = Journal::open_with_cipher(tl_dir, store.cipher().cloned())
         .context("Failed to open journal")?;
 
     // Parse checkpoint ID
@@ -218,7 +218,7 @@ async fn handle_continue(repo_root: &Path, tl_dir: &Path, merge_state: Option<Me
     // Create a checkpoint with the merged state
     let store = Store::open(repo_root)
         .context("Failed to open store")?;
-    let journal = Journal::open(tl_dir)
+    let journal = Journal::open_with_cipher(tl_dir, store.cipher().cloned())
         .context("Failed to open journal")?;
 
     // Create checkpoint from current working directory
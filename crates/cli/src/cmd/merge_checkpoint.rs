@@ -0,0 +1,201 @@
+//! Three-way merge of two checkpoints (no JJ required)
+//!
+//! Merges `theirs` into `ours` using `base` as the common ancestor, writes the
+//! result to the working directory, and records a merge checkpoint whose
+//! parents are `ours` and `theirs`. `ours` must be HEAD (or have HEAD's
+//! tree), so no working directory changes are overwritten.
+
+use crate::locks::RestoreLock;
+use crate::util;
+use anyhow::{anyhow, Context, Result};
+use journal::{Checkpoint, CheckpointMeta, CheckpointReason};
+use jj::{TreeConflict, TreeConflictKind};
+use owo_colors::OwoColorize;
+use std::path::PathBuf;
+use tl_core::{Store, Tree};
+
+pub async fn run(base: &str, ours: &str, theirs: &str) -> Result<()> {
+    // 1. Find repository root
+    let repo_root = util::find_repo_root()
+        .context("Failed to find repository")?;
+
+    let tl_dir = repo_root.join(".tl");
+
+    // 2. Ensure daemon running (auto-starts if needed)
+    crate::daemon::ensure_daemon_running().await?;
+
+    // 3. Flush pending changes so HEAD matches the working directory
+    // (must happen before taking the restore lock - the daemon skips flushes while it is held)
+    let socket_path = tl_dir.join("state/daemon.sock");
+    crate::ipc::IpcClient::connect(&socket_path)
+        .await
+        .context("Failed to connect to daemon")?
+        .flush_checkpoint()
+        .await?;
+
+    // 4. Acquire restore lock - we're about to rewrite working directory files
+    let _restore_lock = RestoreLock::acquire(&tl_dir)
+        .context("Failed to acquire restore lock - is another restore in progress?")?;
+
+    // 5. Resolve checkpoint references
+    let refs = vec![base.to_string(), ours.to_string(), theirs.to_string(), "HEAD".to_string()];
+    let ids = crate::data_access::resolve_checkpoint_refs(&refs, &tl_dir).await?;
+
+    let base_id = ids[0].ok_or_else(|| anyhow!("Checkpoint '{}' not found or ambiguous", base))?;
+    let ours_id = ids[1].ok_or_else(|| anyhow!("Checkpoint '{}' not found or ambiguous", ours))?;
+    let theirs_id = ids[2].ok_or_else(|| anyhow!("Checkpoint '{}' not found or ambiguous", theirs))?;
+    let head_id = ids[3].ok_or_else(|| anyhow!("No checkpoints exist yet"))?;
+
+    let checkpoints = crate::data_access::get_checkpoints(&[base_id, ours_id, theirs_id, head_id], &tl_dir).await?;
+    let base_cp = checkpoints[0].as_ref().ok_or_else(|| anyhow!("Checkpoint not found: {}", base))?;
+    let ours_cp = checkpoints[1].as_ref().ok_or_else(|| anyhow!("Checkpoint not found: {}", ours))?;
+    let theirs_cp = checkpoints[2].as_ref().ok_or_else(|| anyhow!("Checkpoint not found: {}", theirs))?;
+    let head_cp = checkpoints[3].as_ref().ok_or_else(|| anyhow!("HEAD checkpoint not found"))?;
+
+    // Merging into anything else would overwrite HEAD's files with a result
+    // that doesn't descend from them. A checkpoint with HEAD's tree (just
+    // restored) is as good as HEAD.
+    if ours_id != head_id && ours_cp.root_tree != head_cp.root_tree {
+        anyhow::bail!(
+            "'{}' is not HEAD - restore it first ('tl restore {}') or merge into HEAD",
+            ours,
+            ours
+        );
+    }
+
+    // 6. Load trees
+    let store = Store::open(&repo_root)?;
+    let base_tree = store.read_tree(base_cp.root_tree)?;
+    let ours_tree = store.read_tree(ours_cp.root_tree)?;
+    let theirs_tree = store.read_tree(theirs_cp.root_tree)?;
+    let head_tree = store.read_tree(head_cp.root_tree)?;
+
+    let ours_short = &ours_id.to_string()[..8];
+    let theirs_short = &theirs_id.to_string()[..8];
+    let base_short = &base_id.to_string()[..8];
+
+    println!("{}", "Merge Checkpoints".bold());
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    println!();
+    println!("Base:   {} {}", base_short.yellow(), util::format_relative_time(base_cp.ts_unix_ms).dimmed());
+    println!("Ours:   {} {}", ours_short.yellow(), util::format_relative_time(ours_cp.ts_unix_ms).dimmed());
+    println!("Theirs: {} {}", theirs_short.yellow(), util::format_relative_time(theirs_cp.ts_unix_ms).dimmed());
+    println!();
+
+    // 7. Three-way merge (merged blobs are written to the store)
+    let result = jj::merge_trees(
+        &store,
+        &base_tree,
+        &ours_tree,
+        &theirs_tree,
+        &format!("ours ({})", ours_short),
+        &format!("theirs ({})", theirs_short),
+    )?;
+
+    let merged_hash = store.write_tree(&result.tree)
+        .context("Failed to write merged tree")?;
+
+    if merged_hash == head_cp.root_tree && result.is_clean() {
+        println!("{}", "Already up to date - nothing to merge".dimmed());
+        return Ok(());
+    }
+
+    // 8. Move the working directory from HEAD to the merged tree
+    let apply = crate::cmd::restore::apply_tree_changes(&store, &head_tree, &result.tree, &repo_root)?;
+    if !apply.errors.is_empty() {
+        println!("{} {} error(s) updating working directory:", "⚠".yellow(), apply.errors.len());
+        for error in apply.errors.iter().take(10) {
+            println!("  {}", error.red());
+        }
+        anyhow::bail!("Merge aborted before recording checkpoint; working directory may be partially updated");
+    }
+
+    // 9. Record merge checkpoint via the daemon. HEAD stays the first parent
+    // so first-parent history continues through it; an "ours" other than
+    // HEAD (same tree) is kept as an extra merge parent.
+    let touched_paths = changed_paths(&head_tree, &result.tree);
+
+    let mut merge_parents = Vec::new();
+    if ours_id != head_id {
+        merge_parents.push(ours_id);
+    }
+    merge_parents.push(theirs_id);
+
+    let checkpoint = Checkpoint::new(
+        Some(head_id),
+        merged_hash,
+        CheckpointReason::Merge,
+        touched_paths.clone(),
+        CheckpointMeta {
+            files_changed: touched_paths.len() as u32,
+            bytes_added: 0,
+            bytes_removed: 0,
+        },
+    )
+    .with_merge_parents(merge_parents);
+
+    // The daemon serves one request per connection
+    crate::ipc::IpcClient::connect(&socket_path)
+        .await
+        .context("Failed to connect to daemon")?
+        .append_checkpoint(checkpoint.clone())
+        .await?;
+
+    // Daemon's pathmap no longer matches the working directory
+    match crate::ipc::IpcClient::connect(&socket_path).await {
+        Ok(mut client) => {
+            if let Err(e) = client.invalidate_pathmap().await {
+                tracing::warn!("Failed to invalidate pathmap: {}", e);
+            }
+        }
+        Err(e) => tracing::warn!("Failed to connect to daemon to invalidate pathmap: {}", e),
+    }
+
+    // 10. Report
+    let merge_short = &checkpoint.id.to_string()[..8];
+    println!("{} Merged {} into {} → {}",
+        "✓".green(),
+        theirs_short.yellow(),
+        ours_short.yellow(),
+        merge_short.green()
+    );
+    println!("  {} files changed, {} written, {} deleted",
+        touched_paths.len(),
+        apply.files_restored,
+        apply.files_deleted
+    );
+
+    print_conflicts(&result.conflicts);
+
+    Ok(())
+}
+
+/// Paths that differ between two trees (added, removed, or modified)
+pub fn changed_paths(from: &Tree, to: &Tree) -> Vec<PathBuf> {
+    let diff = tl_core::TreeDiff::diff(from, to);
+    diff.added.iter().map(|(p, _)| p)
+        .chain(diff.removed.iter().map(|(p, _)| p))
+        .chain(diff.modified.iter().map(|(p, _, _)| p))
+        .map(|p| PathBuf::from(String::from_utf8_lossy(p).into_owned()))
+        .collect()
+}
+
+/// Print the conflicts left by a tree merge (no-op when clean)
+pub fn print_conflicts(conflicts: &[TreeConflict]) {
+    if conflicts.is_empty() {
+        return;
+    }
+
+    println!();
+    println!("{} {} conflict(s):", "⚠".yellow(), conflicts.len());
+    for conflict in conflicts {
+        let detail = match conflict.kind {
+            TreeConflictKind::Content { regions } => format!("{} conflicting region(s)", regions),
+            TreeConflictKind::ModifyDelete => "modified on one side, deleted on the other".to_string(),
+            TreeConflictKind::Unmergeable => "binary, special or not-kept file, kept ours".to_string(),
+        };
+        println!("  {} {} {}", "C".red().bold(), conflict.path.display(), format!("({})", detail).dimmed());
+    }
+    println!();
+    println!("{}", "Resolve the conflicts in your editor; the daemon will checkpoint the result.".dimmed());
+}
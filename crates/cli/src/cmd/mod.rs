@@ -6,15 +6,23 @@ pub mod info;
 pub mod log;
 pub mod diff;
 pub mod restore;
+pub mod mount;
 pub mod pin;
 pub mod unpin;
 pub mod gc;
+pub mod fsck;
+pub mod scrub;
+pub mod purge;
+pub mod debug;
 pub mod publish;
 pub mod push;
 pub mod pull;
 pub mod fetch;
 pub mod branch;
 pub mod merge;
+pub mod merge_checkpoint;
+pub mod cherry_pick;
+pub mod bisect;
 pub mod resolve;
 pub mod start;
 pub mod stop;
@@ -0,0 +1,903 @@
+//! Time-travel filesystem view
+//!
+//! `tl mount <dir>` serves every checkpoint as a read-only FUSE filesystem,
+//! straight from the store - nothing is materialized and the working tree is
+//! left alone:
+//!
+//! ```text
+//! <dir>/HEAD/                   latest checkpoint (follows new checkpoints)
+//! <dir>/pins/<name>/            pinned checkpoints
+//! <dir>/by-id/<ulid>/           any checkpoint
+//! <dir>/by-time/<timestamp>/    latest checkpoint as of a time
+//! ```
+//!
+//! `by-time` accepts RFC 3339 (`2026-10-18T14:30:00Z`), local date-times
+//! (`2026-10-18T14:30`) and dates (`2026-10-18`); a name covers its whole
+//! second, minute or day. Listing it shows one UTC timestamp per second
+//! that has checkpoints.
+//!
+//! Checkpoints are resolved through the daemon when it is running; without
+//! it, each lookup opens the journal just for that request, so the mount
+//! never keeps it open between requests. Trees are flat path maps, so each tree
+//! is indexed into directories once, on first use; checkpoint contents never
+//! change, only which checkpoint `HEAD`, a pin or a time names.
+
+use crate::data_access;
+use crate::util;
+use anyhow::{Context, Result};
+use owo_colors::OwoColorize;
+use std::path::PathBuf;
+
+pub async fn run(mountpoint: PathBuf) -> Result<()> {
+    let repo_root = util::find_repo_root()
+        .context("Failed to find repository")?;
+
+    if !mountpoint.is_dir() {
+        anyhow::bail!("Mount point {} is not a directory", mountpoint.display());
+    }
+
+    mount(repo_root, mountpoint).await
+}
+
+#[cfg(target_os = "linux")]
+async fn mount(repo_root: PathBuf, mountpoint: PathBuf) -> Result<()> {
+    use fuser::MountOption;
+
+    let store = tl_core::store::Store::open(&repo_root)
+        .context("Failed to open store")?;
+    let fs = fs::TimeTravelFs::new(repo_root.join(".tl"), store, tokio::runtime::Handle::current());
+
+    let options = [
+        MountOption::RO,
+        MountOption::FSName("timelapse".to_string()),
+        MountOption::Subtype("tl".to_string()),
+        MountOption::DefaultPermissions,
+    ];
+    let session = fuser::spawn_mount2(fs, &mountpoint, &options)
+        .with_context(|| format!("Failed to mount at {} (is FUSE available? fusermount3 must be installed)", mountpoint.display()))?;
+
+    println!("{}", "Time-Travel Mount".bold());
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    println!();
+    println!("  {}/HEAD/               latest checkpoint", mountpoint.display());
+    println!("  {}/pins/<name>/        pinned checkpoints", mountpoint.display());
+    println!("  {}/by-id/<ulid>/       any checkpoint", mountpoint.display());
+    println!("  {}/by-time/<time>/     as of a time (e.g. 2026-10-18T14:30)", mountpoint.display());
+    println!();
+    println!("{}", "Read-only. Press Ctrl-C to unmount.".dimmed());
+
+    tokio::signal::ctrl_c().await?;
+
+    // Dropping the session unmounts
+    drop(session);
+    println!();
+    println!("{} Unmounted {}", "✓".green(), mountpoint.display());
+
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+async fn mount(_repo_root: PathBuf, _mountpoint: PathBuf) -> Result<()> {
+    anyhow::bail!("tl mount is only supported on Linux")
+}
+
+#[cfg(target_os = "linux")]
+mod fs {
+    use super::data_access;
+    use fuser::{
+        FileAttr, FileType, Filesystem, ReplyAttr, ReplyData, ReplyDirectory, ReplyEmpty, ReplyEntry, ReplyOpen,
+        Request,
+    };
+    use journal::PinManager;
+    use std::collections::{BTreeMap, HashMap, VecDeque};
+    use std::ffi::{OsStr, OsString};
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+    use tl_core::store::Store;
+    use tl_core::{Entry, EntryKind, Sha1Hash, Tree};
+    use ulid::Ulid;
+
+    const ROOT_INO: u64 = 1;
+
+    /// Entries whose target moves (HEAD, pins, listings)
+    const MOVING_TTL: Duration = Duration::from_secs(1);
+
+    /// Checkpoint contents, which never change
+    const CONTENT_TTL: Duration = Duration::from_secs(3600);
+
+    /// How long a fetched checkpoint ID list is reused
+    const IDS_TTL: Duration = Duration::from_secs(1);
+
+    /// Bytes of file content loaded per open file at a time
+    const READ_WINDOW: usize = 4 * 1024 * 1024;
+
+    /// Trees kept indexed at once; the least recently used is re-read on demand
+    const MAX_INDEXED_TREES: usize = 16;
+
+    /// Inodes kept before those the kernel no longer references are dropped
+    const MAX_INODES: usize = 100_000;
+
+    /// Names of `by-time` listing entries (UTC, one per second)
+    const TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+    /// A name for a checkpoint, resolved each time it is used
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    enum Ref {
+        Head,
+        Pin(String),
+        Id(Ulid),
+        /// Latest checkpoint at or before this time (Unix ms)
+        Time(u64),
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    enum Node {
+        Root,
+        /// Pin namespace: "" for `/pins`, "stash" for `/pins/stash`
+        Pins(String),
+        ById,
+        ByTime,
+        /// Root directory of a checkpoint
+        Ref(Ref),
+        /// Directory inside the tree a `Ref` inode resolved to
+        ///
+        /// Keyed by that inode too: a directory inode must have a single
+        /// parent, even when several refs share a tree.
+        Dir { root: u64, tree: Sha1Hash, path: PathBuf },
+        /// File or symlink inside the tree a `Ref` inode resolved to
+        Leaf { root: u64, tree: Sha1Hash, path: PathBuf },
+    }
+
+    /// A flat tree arranged into directories
+    struct TreeIndex {
+        entries: HashMap<PathBuf, Entry>,
+        /// Directory -> (name -> is directory)
+        dirs: HashMap<PathBuf, BTreeMap<OsString, bool>>,
+        /// Time of the first checkpoint seen with this tree
+        mtime: SystemTime,
+    }
+
+    impl TreeIndex {
+        /// Entries for which `kept` is false are left out of the view
+        fn build(tree: &Tree, mtime: SystemTime, kept: impl Fn(&Path, &Entry) -> bool) -> Self {
+            let mut entries = HashMap::new();
+            let mut dirs: HashMap<PathBuf, BTreeMap<OsString, bool>> = HashMap::new();
+            dirs.insert(PathBuf::new(), BTreeMap::new());
+
+            for (path_bytes, entry) in tree.entries_with_paths() {
+                // Directories are implied by the paths beneath them
+                if entry.kind == EntryKind::Tree {
+                    continue;
+                }
+                let Ok(path) = std::str::from_utf8(path_bytes).map(PathBuf::from) else { continue };
+                if !kept(&path, entry) {
+                    continue;
+                }
+
+                // Register the path with its parent, and each parent with its own
+                let mut child = path.as_path();
+                let mut is_dir = false;
+                while let (Some(parent), Some(name)) = (child.parent(), child.file_name()) {
+                    let siblings = dirs.entry(parent.to_path_buf()).or_default();
+                    if siblings.insert(name.to_os_string(), is_dir).is_some() && is_dir {
+                        break;
+                    }
+                    child = parent;
+                    is_dir = true;
+                }
+                entries.insert(path, entry.clone());
+            }
+
+            Self { entries, dirs, mtime }
+        }
+    }
+
+    /// An open file: its blob and the window of content last read
+    struct OpenFile {
+        blob: Sha1Hash,
+        window: Option<Window>,
+    }
+
+    /// Loaded slice of a file's content
+    struct Window {
+        start: u64,
+        data: Vec<u8>,
+        /// The slice runs to the end of the file
+        at_eof: bool,
+    }
+
+    impl Window {
+        fn covers(&self, offset: u64, size: usize) -> bool {
+            let end = self.start + self.data.len() as u64;
+            offset >= self.start && (offset + size as u64 <= end || self.at_eof)
+        }
+    }
+
+    /// Read-only view of every checkpoint
+    pub struct TimeTravelFs {
+        tl_dir: PathBuf,
+        store: Store,
+        runtime: tokio::runtime::Handle,
+        uid: u32,
+        gid: u32,
+        mounted_at: SystemTime,
+
+        nodes: HashMap<u64, Node>,
+        inodes: HashMap<Node, u64>,
+        next_ino: u64,
+        /// Kernel lookup count per inode (raised by `lookup`, lowered by `forget`)
+        lookups: HashMap<u64, u64>,
+
+        checkpoint_trees: HashMap<Ulid, Sha1Hash>,
+        /// Time of the first checkpoint seen with each tree
+        tree_mtimes: HashMap<Sha1Hash, SystemTime>,
+        trees: HashMap<Sha1Hash, TreeIndex>,
+        /// Indexed trees, least recently used first
+        tree_order: VecDeque<Sha1Hash>,
+
+        handles: HashMap<u64, OpenFile>,
+        next_handle: u64,
+
+        /// Recently fetched checkpoint IDs, oldest first
+        ids: Option<(Instant, Vec<Ulid>)>,
+    }
+
+    impl TimeTravelFs {
+        pub fn new(tl_dir: PathBuf, store: Store, runtime: tokio::runtime::Handle) -> Self {
+            // SAFETY: getuid/getgid always succeed
+            let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+            Self {
+                tl_dir,
+                store,
+                runtime,
+                uid,
+                gid,
+                mounted_at: SystemTime::now(),
+                nodes: HashMap::from([(ROOT_INO, Node::Root)]),
+                inodes: HashMap::from([(Node::Root, ROOT_INO)]),
+                next_ino: ROOT_INO + 1,
+                lookups: HashMap::new(),
+                checkpoint_trees: HashMap::new(),
+                tree_mtimes: HashMap::new(),
+                trees: HashMap::new(),
+                tree_order: VecDeque::new(),
+                handles: HashMap::new(),
+                next_handle: 1,
+                ids: None,
+            }
+        }
+
+        fn inode(&mut self, node: Node) -> u64 {
+            if let Some(&ino) = self.inodes.get(&node) {
+                return ino;
+            }
+            if self.nodes.len() >= MAX_INODES {
+                self.drop_unreferenced_inodes();
+            }
+            let ino = self.next_ino;
+            self.next_ino += 1;
+            self.nodes.insert(ino, node.clone());
+            self.inodes.insert(node, ino);
+            ino
+        }
+
+        fn node(&self, ino: u64) -> Option<Node> {
+            self.nodes.get(&ino).cloned()
+        }
+
+        /// Forget inodes the kernel holds no lookup on (numbers are not reused)
+        fn drop_unreferenced_inodes(&mut self) {
+            let lookups = &self.lookups;
+            self.nodes.retain(|&ino, _| ino == ROOT_INO || lookups.contains_key(&ino));
+            self.inodes.retain(|_, ino| *ino == ROOT_INO || lookups.contains_key(ino));
+        }
+
+        /// Inode of the directory holding `ino`
+        fn parent(&mut self, ino: u64) -> u64 {
+            let parent = match self.node(ino) {
+                Some(Node::Pins(prefix)) if !prefix.is_empty() => {
+                    let namespace = prefix.rsplit_once('/').map_or("", |(namespace, _)| namespace);
+                    Node::Pins(namespace.to_string())
+                }
+                Some(Node::Ref(Ref::Pin(name))) => {
+                    let namespace = name.rsplit_once('/').map_or("", |(namespace, _)| namespace);
+                    Node::Pins(namespace.to_string())
+                }
+                Some(Node::Ref(Ref::Id(_))) => Node::ById,
+                Some(Node::Ref(Ref::Time(_))) => Node::ByTime,
+                Some(Node::Dir { root, tree, path }) => match path.parent() {
+                    Some(dir) if !dir.as_os_str().is_empty() => Node::Dir { root, tree, path: dir.to_path_buf() },
+                    _ => return root,
+                },
+                _ => return ROOT_INO,
+            };
+            self.inode(parent)
+        }
+
+        /// All checkpoint IDs, oldest first
+        fn checkpoint_ids(&mut self) -> anyhow::Result<Vec<Ulid>> {
+            if let Some((fetched, ids)) = &self.ids {
+                if fetched.elapsed() < IDS_TTL {
+                    return Ok(ids.clone());
+                }
+            }
+            let (_, ids, _) = self.runtime.block_on(data_access::get_info_data(&self.tl_dir))?;
+            let mut ids: Vec<Ulid> = ids.iter().filter_map(|id| Ulid::from_string(id).ok()).collect();
+            ids.sort();
+            self.ids = Some((Instant::now(), ids.clone()));
+            Ok(ids)
+        }
+
+        /// The checkpoint a ref currently names
+        fn resolve(&mut self, reference: &Ref) -> anyhow::Result<Option<Ulid>> {
+            match reference {
+                Ref::Head => {
+                    let resolved = self.runtime.block_on(data_access::resolve_checkpoint_refs(&["HEAD".to_string()], &self.tl_dir))?;
+                    Ok(resolved.into_iter().next().flatten())
+                }
+                Ref::Pin(name) => {
+                    let pins = PinManager::new(&self.tl_dir).list_pins()?;
+                    Ok(pins.into_iter().find(|(pin, _)| pin == name).map(|(_, id)| id))
+                }
+                Ref::Id(id) => Ok(self.checkpoint_ids()?.binary_search(id).ok().map(|_| *id)),
+                Ref::Time(at_ms) => Ok(self.checkpoint_ids()?.into_iter().rev().find(|id| id.timestamp_ms() <= *at_ms)),
+            }
+        }
+
+        /// The tree a ref currently names
+        fn resolve_tree(&mut self, reference: &Ref) -> anyhow::Result<Option<Sha1Hash>> {
+            let Some(id) = self.resolve(reference)? else { return Ok(None) };
+            if let Some(&tree) = self.checkpoint_trees.get(&id) {
+                return Ok(Some(tree));
+            }
+
+            let checkpoints = self.runtime.block_on(data_access::get_checkpoints(&[id], &self.tl_dir))?;
+            let Some(checkpoint) = checkpoints.into_iter().next().flatten() else { return Ok(None) };
+            let tree = checkpoint.root_tree;
+            self.tree_mtimes.entry(tree).or_insert(UNIX_EPOCH + Duration::from_millis(checkpoint.ts_unix_ms));
+            self.checkpoint_trees.insert(id, tree);
+            Ok(Some(tree))
+        }
+
+        /// Index of a tree, built on first use and kept among the most recently used
+        fn tree_index(&mut self, tree: Sha1Hash) -> anyhow::Result<&TreeIndex> {
+            if self.trees.contains_key(&tree) {
+                self.tree_order.retain(|used| *used != tree);
+            } else {
+                while self.trees.len() >= MAX_INDEXED_TREES {
+                    let Some(oldest) = self.tree_order.pop_front() else { break };
+                    self.trees.remove(&oldest);
+                }
+                let mtime = self.tree_mtimes.get(&tree).copied().unwrap_or(self.mounted_at);
+                // Files whose content a path policy dropped cannot be read, so they are hidden
+                let blobs = self.store.blob_store();
+                let index = TreeIndex::build(&self.store.read_tree(tree)?, mtime, |path, entry| {
+                    let omitted = blobs.is_omitted(entry.blob_hash);
+                    if omitted {
+                        tracing::warn!("tl mount: hiding {} (content not kept under its path policy)", path.display());
+                    }
+                    !omitted
+                });
+                self.trees.insert(tree, index);
+            }
+            self.tree_order.push_back(tree);
+            Ok(&self.trees[&tree])
+        }
+
+        /// Inode of `name` in `parent`, if it exists
+        fn lookup_child(&mut self, parent: u64, name: &str) -> anyhow::Result<Option<u64>> {
+            let Some(node) = self.node(parent) else { return Ok(None) };
+            let child = match node {
+                Node::Root => match name {
+                    "HEAD" => self.resolve(&Ref::Head)?.map(|_| Node::Ref(Ref::Head)),
+                    "pins" => Some(Node::Pins(String::new())),
+                    "by-id" => Some(Node::ById),
+                    "by-time" => Some(Node::ByTime),
+                    _ => None,
+                },
+                Node::Pins(prefix) => {
+                    let full = if prefix.is_empty() { name.to_string() } else { format!("{}/{}", prefix, name) };
+                    let namespace = format!("{}/", full);
+                    let pins = PinManager::new(&self.tl_dir).list_pins()?;
+                    if pins.iter().any(|(pin, _)| *pin == full) {
+                        Some(Node::Ref(Ref::Pin(full)))
+                    } else if pins.iter().any(|(pin, _)| pin.starts_with(&namespace)) {
+                        Some(Node::Pins(full))
+                    } else {
+                        None
+                    }
+                }
+                Node::ById => match Ulid::from_string(name) {
+                    Ok(id) if self.resolve(&Ref::Id(id))?.is_some() => Some(Node::Ref(Ref::Id(id))),
+                    _ => None,
+                },
+                Node::ByTime => match parse_time(name) {
+                    Some(at_ms) if self.resolve(&Ref::Time(at_ms))?.is_some() => Some(Node::Ref(Ref::Time(at_ms))),
+                    _ => None,
+                },
+                Node::Ref(reference) => {
+                    let Some(tree) = self.resolve_tree(&reference)? else { return Ok(None) };
+                    return self.tree_child(parent, tree, Path::new(""), name);
+                }
+                Node::Dir { root, tree, path } => return self.tree_child(root, tree, &path, name),
+                Node::Leaf { .. } => None,
+            };
+            Ok(child.map(|node| self.inode(node)))
+        }
+
+        fn tree_child(&mut self, root: u64, tree: Sha1Hash, dir: &Path, name: &str) -> anyhow::Result<Option<u64>> {
+            let index = self.tree_index(tree)?;
+            let Some(&is_dir) = index.dirs.get(dir).and_then(|names| names.get(OsStr::new(name))) else { return Ok(None) };
+            let path = dir.join(name);
+            Ok(Some(self.inode(if is_dir { Node::Dir { root, tree, path } } else { Node::Leaf { root, tree, path } })))
+        }
+
+        fn attr(&mut self, ino: u64) -> anyhow::Result<Option<FileAttr>> {
+            let Some(node) = self.node(ino) else { return Ok(None) };
+
+            let (kind, perm, size, mtime) = match &node {
+                Node::Root | Node::Pins(_) | Node::ById | Node::ByTime => {
+                    (FileType::Directory, 0o555, 0, self.mounted_at)
+                }
+                Node::Ref(reference) => {
+                    let Some(id) = self.resolve(reference)? else { return Ok(None) };
+                    (FileType::Directory, 0o555, 0, UNIX_EPOCH + Duration::from_millis(id.timestamp_ms()))
+                }
+                Node::Dir { tree, .. } => (FileType::Directory, 0o555, 0, self.tree_index(*tree)?.mtime),
+                Node::Leaf { tree, path, .. } => {
+                    let index = self.tree_index(*tree)?;
+                    let mtime = index.mtime;
+                    let Some(entry) = index.entries.get(path).cloned() else { return Ok(None) };
+                    let size = self.store.blob_store().blob_size(entry.blob_hash).unwrap_or(0);
+                    match entry.kind {
+                        EntryKind::Symlink => (FileType::Symlink, 0o777, size, mtime),
+                        EntryKind::ExecutableFile => (FileType::RegularFile, 0o555, size, mtime),
+                        _ => (FileType::RegularFile, 0o444, size, mtime),
+                    }
+                }
+            };
+
+            Ok(Some(FileAttr {
+                ino,
+                size,
+                blocks: size.div_ceil(512),
+                atime: mtime,
+                mtime,
+                ctime: mtime,
+                crtime: mtime,
+                kind,
+                perm,
+                nlink: if kind == FileType::Directory { 2 } else { 1 },
+                uid: self.uid,
+                gid: self.gid,
+                rdev: 0,
+                blksize: 4096,
+                flags: 0,
+            }))
+        }
+
+        /// Only names under `by-id` keep pointing at the same content;
+        /// anything under HEAD, a pin or a time can move to another tree
+        fn ttl(&self, ino: u64) -> Duration {
+            let root = match self.node(ino) {
+                Some(Node::Dir { root, .. } | Node::Leaf { root, .. }) => self.node(root),
+                node => node,
+            };
+            match root {
+                Some(Node::Ref(Ref::Id(_))) => CONTENT_TTL,
+                _ => MOVING_TTL,
+            }
+        }
+
+        fn leaf_entry(&mut self, ino: u64) -> Option<Entry> {
+            match self.node(ino)? {
+                Node::Leaf { tree, path, .. } => self.tree_index(tree).ok()?.entries.get(&path).cloned(),
+                _ => None,
+            }
+        }
+
+        /// (inode, type, name) for each entry of a directory, without `.` and `..`
+        fn list(&mut self, ino: u64) -> anyhow::Result<Option<Vec<(u64, FileType, OsString)>>> {
+            let Some(node) = self.node(ino) else { return Ok(None) };
+
+            let children: Vec<(Node, OsString)> = match node {
+                Node::Root => {
+                    let mut children = vec![
+                        (Node::Pins(String::new()), OsString::from("pins")),
+                        (Node::ById, OsString::from("by-id")),
+                        (Node::ByTime, OsString::from("by-time")),
+                    ];
+                    if self.resolve(&Ref::Head)?.is_some() {
+                        children.insert(0, (Node::Ref(Ref::Head), OsString::from("HEAD")));
+                    }
+                    children
+                }
+                Node::Pins(prefix) => {
+                    let mut names: BTreeMap<String, Node> = BTreeMap::new();
+                    for (pin, _) in PinManager::new(&self.tl_dir).list_pins()? {
+                        let rest = match prefix.as_str() {
+                            "" => pin.as_str(),
+                            prefix => match pin.strip_prefix(prefix).and_then(|rest| rest.strip_prefix('/')) {
+                                Some(rest) => rest,
+                                None => continue,
+                            },
+                        };
+                        let (name, node) = match rest.split_once('/') {
+                            Some((namespace, _)) => (namespace, Node::Pins(pin[..pin.len() - rest.len() + namespace.len()].to_string())),
+                            None => (rest, Node::Ref(Ref::Pin(pin.clone()))),
+                        };
+                        names.insert(name.to_string(), node);
+                    }
+                    names.into_iter().map(|(name, node)| (node, OsString::from(name))).collect()
+                }
+                Node::ById => self.checkpoint_ids()?
+                    .into_iter()
+                    .map(|id| (Node::Ref(Ref::Id(id)), OsString::from(id.to_string())))
+                    .collect(),
+                Node::ByTime => {
+                    // One entry per second with checkpoints, naming the latest of them
+                    let mut children: Vec<(Node, OsString)> = Vec::new();
+                    for id in self.checkpoint_ids()? {
+                        let end_of_second = id.timestamp_ms() / 1000 * 1000 + 999;
+                        if children.last().is_some_and(|(node, _)| *node == Node::Ref(Ref::Time(end_of_second))) {
+                            continue;
+                        }
+                        children.push((Node::Ref(Ref::Time(end_of_second)), OsString::from(format_time(id.timestamp_ms()))));
+                    }
+                    children
+                }
+                Node::Ref(reference) => {
+                    let Some(tree) = self.resolve_tree(&reference)? else { return Ok(None) };
+                    return self.list_tree(ino, tree, Path::new("")).map(Some);
+                }
+                Node::Dir { root, tree, path } => return self.list_tree(root, tree, &path).map(Some),
+                Node::Leaf { .. } => return Ok(None),
+            };
+
+            Ok(Some(children.into_iter()
+                .map(|(node, name)| (self.inode(node), FileType::Directory, name))
+                .collect()))
+        }
+
+        fn list_tree(&mut self, root: u64, tree: Sha1Hash, dir: &Path) -> anyhow::Result<Vec<(u64, FileType, OsString)>> {
+            let index = self.tree_index(tree)?;
+            let children: Vec<(OsString, PathBuf, FileType)> = index.dirs.get(dir)
+                .into_iter()
+                .flatten()
+                .map(|(name, &is_dir)| {
+                    let path = dir.join(name);
+                    let kind = if is_dir {
+                        FileType::Directory
+                    } else {
+                        match index.entries.get(&path).map(|entry| entry.kind) {
+                            Some(EntryKind::Symlink) => FileType::Symlink,
+                            _ => FileType::RegularFile,
+                        }
+                    };
+                    (name.clone(), path, kind)
+                })
+                .collect();
+
+            Ok(children.into_iter()
+                .map(|(name, path, kind)| {
+                    let node = if kind == FileType::Directory { Node::Dir { root, tree, path } } else { Node::Leaf { root, tree, path } };
+                    (self.inode(node), kind, name)
+                })
+                .collect())
+        }
+    }
+
+    impl Filesystem for TimeTravelFs {
+        fn lookup(&mut self, _req: &Request<'_>, parent: u64, name: &OsStr, reply: ReplyEntry) {
+            let Some(name) = name.to_str() else { return reply.error(libc::ENOENT) };
+            let found = self.lookup_child(parent, name)
+                .and_then(|child| match child {
+                    Some(ino) => self.attr(ino),
+                    None => Ok(None),
+                });
+            match found {
+                Ok(Some(attr)) => {
+                    *self.lookups.entry(attr.ino).or_default() += 1;
+                    reply.entry(&self.ttl(attr.ino), &attr, 0);
+                }
+                Ok(None) => reply.error(libc::ENOENT),
+                Err(e) => {
+                    tracing::warn!("tl mount: lookup of {} failed: {:#}", name, e);
+                    reply.error(libc::EIO);
+                }
+            }
+        }
+
+        fn forget(&mut self, _req: &Request<'_>, ino: u64, nlookup: u64) {
+            if let Some(count) = self.lookups.get_mut(&ino) {
+                *count = count.saturating_sub(nlookup);
+                if *count == 0 {
+                    self.lookups.remove(&ino);
+                }
+            }
+        }
+
+        fn getattr(&mut self, _req: &Request<'_>, ino: u64, reply: ReplyAttr) {
+            match self.attr(ino) {
+                Ok(Some(attr)) => reply.attr(&self.ttl(ino), &attr),
+                Ok(None) => reply.error(libc::ENOENT),
+                Err(e) => {
+                    tracing::warn!("tl mount: getattr failed: {:#}", e);
+                    reply.error(libc::EIO);
+                }
+            }
+        }
+
+        fn readlink(&mut self, _req: &Request<'_>, ino: u64, reply: ReplyData) {
+            match self.leaf_entry(ino) {
+                Some(entry) if entry.kind == EntryKind::Symlink => {
+                    match self.store.blob_store().read_blob(entry.blob_hash) {
+                        Ok(target) => reply.data(&target),
+                        Err(_) => reply.error(libc::EIO),
+                    }
+                }
+                Some(_) => reply.error(libc::EINVAL),
+                None => reply.error(libc::ENOENT),
+            }
+        }
+
+        fn open(&mut self, _req: &Request<'_>, ino: u64, flags: i32, reply: ReplyOpen) {
+            if flags & libc::O_ACCMODE != libc::O_RDONLY {
+                return reply.error(libc::EROFS);
+            }
+            let Some(entry) = self.leaf_entry(ino) else { return reply.error(libc::ENOENT) };
+
+            // e.g. content dropped by GC since the tree was indexed
+            if !self.store.blob_store().has_blob(entry.blob_hash) {
+                tracing::warn!("tl mount: content of blob {} is not stored", entry.blob_hash.to_hex());
+                return reply.error(libc::EIO);
+            }
+
+            // Content is loaded a window at a time as it is read
+            let fh = self.next_handle;
+            self.next_handle += 1;
+            self.handles.insert(fh, OpenFile { blob: entry.blob_hash, window: None });
+            reply.opened(fh, fuser::consts::FOPEN_KEEP_CACHE);
+        }
+
+        fn read(
+            &mut self,
+            _req: &Request<'_>,
+            _ino: u64,
+            fh: u64,
+            offset: i64,
+            size: u32,
+            _flags: i32,
+            _lock_owner: Option<u64>,
+            reply: ReplyData,
+        ) {
+            let Some(file) = self.handles.get_mut(&fh) else { return reply.error(libc::EBADF) };
+            let offset = offset.max(0) as u64;
+            let size = size as usize;
+
+            // Load the window holding this range unless it is already loaded
+            if !file.window.as_ref().is_some_and(|window| window.covers(offset, size)) {
+                let start = offset - offset % READ_WINDOW as u64;
+                let len = READ_WINDOW.max((offset - start) as usize + size);
+                match self.store.blob_store().read_blob_range(file.blob, start, len) {
+                    Ok(data) => {
+                        let at_eof = data.len() < len;
+                        file.window = Some(Window { start, data, at_eof });
+                    }
+                    Err(e) => {
+                        tracing::warn!("tl mount: cannot read blob {}: {:#}", file.blob.to_hex(), e);
+                        return reply.error(libc::EIO);
+                    }
+                }
+            }
+
+            let Some(window) = file.window.as_ref() else { return reply.error(libc::EIO) };
+            let from = ((offset - window.start) as usize).min(window.data.len());
+            let to = from.saturating_add(size).min(window.data.len());
+            reply.data(&window.data[from..to]);
+        }
+
+        fn release(
+            &mut self,
+            _req: &Request<'_>,
+            _ino: u64,
+            fh: u64,
+            _flags: i32,
+            _lock_owner: Option<u64>,
+            _flush: bool,
+            reply: ReplyEmpty,
+        ) {
+            self.handles.remove(&fh);
+            reply.ok();
+        }
+
+        fn readdir(&mut self, _req: &Request<'_>, ino: u64, _fh: u64, offset: i64, mut reply: ReplyDirectory) {
+            let entries = match self.list(ino) {
+                Ok(Some(entries)) => entries,
+                Ok(None) => return reply.error(libc::ENOENT),
+                Err(e) => {
+                    tracing::warn!("tl mount: readdir failed: {:#}", e);
+                    return reply.error(libc::EIO);
+                }
+            };
+
+            let dots = [
+                (ino, FileType::Directory, OsString::from(".")),
+                (self.parent(ino), FileType::Directory, OsString::from("..")),
+            ];
+            for (i, (child, kind, name)) in dots.into_iter().chain(entries).enumerate().skip(offset.max(0) as usize) {
+                // Offsets are 1-based: each entry carries the offset of the next
+                if reply.add(child, (i + 1) as i64, kind, &name) {
+                    break;
+                }
+            }
+            reply.ok();
+        }
+    }
+
+    /// Latest moment (Unix ms) a `by-time` name covers
+    ///
+    /// A name covers its whole second, minute or day, so `2026-10-18`
+    /// resolves to the state at the end of that day.
+    fn parse_time(name: &str) -> Option<u64> {
+        use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+
+        let local_ms = |naive: NaiveDateTime| Local.from_local_datetime(&naive).earliest().map(|time| time.timestamp_millis());
+        let naive = |formats: &[&str]| formats.iter().find_map(|format| NaiveDateTime::parse_from_str(name, format).ok());
+
+        let end_ms = if let Ok(time) = DateTime::parse_from_rfc3339(name) {
+            time.timestamp_millis() + if name.contains('.') { 0 } else { 999 }
+        } else if let Some(time) = naive(&["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S"]) {
+            local_ms(time)? + 999
+        } else if let Some(time) = naive(&["%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"]) {
+            local_ms(time)? + 59_999
+        } else {
+            let next_day = NaiveDate::parse_from_str(name, "%Y-%m-%d").ok()?.succ_opt()?;
+            local_ms(next_day.and_hms_opt(0, 0, 0)?)? - 1
+        };
+        u64::try_from(end_ms).ok()
+    }
+
+    fn format_time(ts_ms: u64) -> String {
+        chrono::DateTime::from_timestamp_millis(ts_ms as i64)
+            .map(|time| time.format(TIME_FORMAT).to_string())
+            .unwrap_or_default()
+    }
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use chrono::{Local, NaiveDate, TimeZone};
+
+        fn local_ms(date: (i32, u32, u32), time: (u32, u32, u32)) -> u64 {
+            let naive = NaiveDate::from_ymd_opt(date.0, date.1, date.2).unwrap().and_hms_opt(time.0, time.1, time.2).unwrap();
+            Local.from_local_datetime(&naive).earliest().unwrap().timestamp_millis() as u64
+        }
+
+        #[test]
+        fn test_parse_time() {
+            // RFC 3339 covers its second; with a fraction it is exact
+            assert_eq!(parse_time("2026-10-18T14:30:00Z"), Some(1_792_333_800_999));
+            assert_eq!(parse_time("2026-10-18T16:30:00+02:00"), Some(1_792_333_800_999));
+            assert_eq!(parse_time("2026-10-18T14:30:00.250Z"), Some(1_792_333_800_250));
+
+            // Without an offset, times are local and cover their second or minute
+            assert_eq!(parse_time("2026-10-18T14:30:05"), Some(local_ms((2026, 10, 18), (14, 30, 5)) + 999));
+            assert_eq!(parse_time("2026-10-18 14:30:05"), Some(local_ms((2026, 10, 18), (14, 30, 5)) + 999));
+            assert_eq!(parse_time("2026-10-18T14:30"), Some(local_ms((2026, 10, 18), (14, 30, 0)) + 59_999));
+
+            // A date covers the whole local day
+            assert_eq!(parse_time("2026-10-18"), Some(local_ms((2026, 10, 19), (0, 0, 0)) - 1));
+
+            assert_eq!(parse_time("yesterday"), None);
+            assert_eq!(parse_time("2026-13-01"), None);
+        }
+
+        #[test]
+        fn test_listed_times_name_the_end_of_their_second() {
+            // `by-time` listing names resolve back to the checkpoints they were made from
+            let ts_ms = 1_792_333_805_123;
+            assert_eq!(format_time(ts_ms), "2026-10-18T14:30:05Z");
+            assert_eq!(parse_time(&format_time(ts_ms)), Some(1_792_333_805_999));
+        }
+
+        #[test]
+        fn test_tree_index_build() {
+            let blob = tl_core::hash::git::hash_blob(b"content");
+            let mut tree = Tree::new();
+            tree.insert(Path::new("README.md"), Entry::file(0o100644, blob));
+            tree.insert(Path::new("src/main.rs"), Entry::file(0o100644, blob));
+            tree.insert(Path::new("src/util/mod.rs"), Entry::file(0o100755, blob));
+            tree.insert(Path::new("link"), Entry::symlink(blob));
+
+            let index = TreeIndex::build(&tree, UNIX_EPOCH, |_, _| true);
+            let names = |dir: &str| -> Vec<(String, bool)> {
+                index.dirs[Path::new(dir)].iter().map(|(name, &is_dir)| (name.to_string_lossy().into_owned(), is_dir)).collect()
+            };
+
+            assert_eq!(names(""), vec![("README.md".to_string(), false), ("link".to_string(), false), ("src".to_string(), true)]);
+            assert_eq!(names("src"), vec![("main.rs".to_string(), false), ("util".to_string(), true)]);
+            assert_eq!(names("src/util"), vec![("mod.rs".to_string(), false)]);
+            assert_eq!(index.dirs.len(), 3);
+            assert_eq!(index.entries.len(), 4);
+            assert_eq!(index.entries[Path::new("link")].kind, EntryKind::Symlink);
+        }
+
+        #[test]
+        fn test_pin_namespaces() -> anyhow::Result<()> {
+            let temp_dir = tempfile::tempdir()?;
+            let store = Store::init(temp_dir.path())?;
+            let tl_dir = store.tl_dir().to_path_buf();
+            let runtime = tokio::runtime::Runtime::new()?;
+            let mut fs = TimeTravelFs::new(tl_dir.clone(), store, runtime.handle().clone());
+
+            let pins = PinManager::new(&tl_dir);
+            for name in ["release", "stash/wip", "stash/old/one", "ws-main"] {
+                pins.pin(name, Ulid::new())?;
+            }
+
+            let mut listing = |node: Node| -> anyhow::Result<Vec<(String, Node)>> {
+                let ino = fs.inode(node);
+                let children = fs.list(ino)?.unwrap_or_default();
+                Ok(children.into_iter().map(|(child, _, name)| (name.to_string_lossy().into_owned(), fs.node(child).unwrap())).collect())
+            };
+
+            // Nested names show up as namespace directories
+            assert_eq!(listing(Node::Pins(String::new()))?, vec![
+                ("release".to_string(), Node::Ref(Ref::Pin("release".to_string()))),
+                ("stash".to_string(), Node::Pins("stash".to_string())),
+                ("ws-main".to_string(), Node::Ref(Ref::Pin("ws-main".to_string()))),
+            ]);
+            assert_eq!(listing(Node::Pins("stash".to_string()))?, vec![
+                ("old".to_string(), Node::Pins("stash/old".to_string())),
+                ("wip".to_string(), Node::Ref(Ref::Pin("stash/wip".to_string()))),
+            ]);
+            assert_eq!(listing(Node::Pins("stash/old".to_string()))?, vec![
+                ("one".to_string(), Node::Ref(Ref::Pin("stash/old/one".to_string()))),
+            ]);
+
+            // Lookups agree with the listing
+            let stash = fs.inode(Node::Pins("stash".to_string()));
+            let wip = fs.lookup_child(stash, "wip")?.and_then(|ino| fs.node(ino));
+            assert_eq!(wip, Some(Node::Ref(Ref::Pin("stash/wip".to_string()))));
+            assert_eq!(fs.lookup_child(stash, "missing")?, None);
+
+            Ok(())
+        }
+
+        #[test]
+        fn test_parent_inodes() -> anyhow::Result<()> {
+            let temp_dir = tempfile::tempdir()?;
+            let store = Store::init(temp_dir.path())?;
+            let runtime = tokio::runtime::Runtime::new()?;
+            let mut fs = TimeTravelFs::new(store.tl_dir().to_path_buf(), store, runtime.handle().clone());
+
+            let pins = fs.inode(Node::Pins(String::new()));
+            let stash = fs.inode(Node::Pins("stash".to_string()));
+            let wip = fs.inode(Node::Ref(Ref::Pin("stash/wip".to_string())));
+            let by_id = fs.inode(Node::ById);
+            let checkpoint = fs.inode(Node::Ref(Ref::Id(Ulid::new())));
+
+            assert_eq!(fs.parent(ROOT_INO), ROOT_INO);
+            assert_eq!(fs.parent(pins), ROOT_INO);
+            assert_eq!(fs.parent(stash), pins);
+            assert_eq!(fs.parent(wip), stash);
+            assert_eq!(fs.parent(checkpoint), by_id);
+
+            // Directories inside a checkpoint point at their own parent directory
+            let tree = tl_core::hash::git::hash_blob(b"tree");
+            let src = fs.inode(Node::Dir { root: checkpoint, tree, path: PathBuf::from("src") });
+            let util = fs.inode(Node::Dir { root: checkpoint, tree, path: PathBuf::from("src/util") });
+            assert_eq!(fs.parent(src), checkpoint);
+            assert_eq!(fs.parent(util), src);
+
+            // Only inodes the kernel still holds survive a sweep
+            fs.lookups.insert(util, 1);
+            fs.drop_unreferenced_inodes();
+            assert_eq!(fs.node(util), Some(Node::Dir { root: checkpoint, tree, path: PathBuf::from("src/util") }));
+            assert_eq!(fs.node(src), None);
+            assert_eq!(fs.node(ROOT_INO), Some(Node::Root));
+
+            Ok(())
+        }
+    }
+}
@@ -4,7 +4,9 @@ use anyhow::{anyhow, Context, Result};
 use crate::util;
 use owo_colors::OwoColorize;
 use std::collections::HashSet;
-use tl_core::Store;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tl_core::{Entry, EntryKind, Store, Tree, TreeDiff};
 use journal::{Checkpoint, PinManager};
 use jj::{JjMapping, publish};
 use jj::materialize::{CommitMessageOptions, PublishOptions};
@@ -16,6 +18,7 @@ pub async fn run(
     compact: bool,
     no_pin: bool,
     message_template: Option<String>,
+    select: bool,
 ) -> Result<()> {
     // 1. Find repository root
     let repo_root = util::find_repo_root()
@@ -36,6 +39,14 @@ pub async fn run(
     let pin_manager = PinManager::new(&tl_dir);
     let mapping = JjMapping::open(&tl_dir)?;
 
+    // 4.5 Interactive partial publish (single checkpoint, synthetic tree)
+    if select {
+        if compact || checkpoint_ref.contains("..") {
+            anyhow::bail!("--select only works with a single checkpoint (no ranges or --compact)");
+        }
+        return run_select(checkpoint_ref, bookmark, message_template, &repo_root, &store, &mapping).await;
+    }
+
     // 5. Parse checkpoint reference (support ranges like HEAD~10..HEAD or HEAD~10)
     let checkpoints = if checkpoint_ref.contains("..") {
         // Range syntax (e.g., HEAD~10..HEAD)
@@ -173,6 +184,255 @@ pub async fn run(
     Ok(())
 }
 
+/// Interactive hunk-level publish (`tl publish <ref> --select`)
+///
+/// Starts from the parent checkpoint's tree and applies only the files and
+/// hunks the user picks, then publishes the resulting synthetic tree as a
+/// single JJ commit. The working directory and journal are never modified,
+/// and the checkpoint is not recorded as published (it was only partially
+/// published), so it is neither mapped nor pinned.
+async fn run_select(
+    checkpoint_ref: &str,
+    bookmark: Option<String>,
+    message_template: Option<String>,
+    repo_root: &Path,
+    store: &Store,
+    mapping: &JjMapping,
+) -> Result<()> {
+    let tl_dir = repo_root.join(".tl");
+
+    // 1. Resolve the checkpoint (HEAD~N here means that checkpoint, not a range)
+    let ids = crate::data_access::resolve_checkpoint_refs(&[checkpoint_ref.to_string()], &tl_dir).await?;
+    let checkpoint_id = ids[0].ok_or_else(||
+        anyhow!("Checkpoint '{}' not found or ambiguous", checkpoint_ref))?;
+
+    let checkpoints = crate::data_access::get_checkpoints(&[checkpoint_id], &tl_dir).await?;
+    let cp = checkpoints[0].as_ref().ok_or_else(||
+        anyhow!("Checkpoint not found: {}", checkpoint_ref))?;
+
+    // 2. Load checkpoint tree and its parent's tree (the base for selection)
+    let tree = store.read_tree(cp.root_tree)
+        .context("Failed to read checkpoint tree")?;
+    let base_tree = match cp.parent {
+        Some(parent_id) => {
+            let parents = crate::data_access::get_checkpoints(&[parent_id], &tl_dir).await?;
+            let parent = parents[0].as_ref().ok_or_else(||
+                anyhow!("Parent checkpoint not found: {}", parent_id))?;
+            store.read_tree(parent.root_tree)
+                .context("Failed to read parent checkpoint tree")?
+        }
+        None => Tree::new(),
+    };
+
+    // 3. Collect changed paths (sorted for stable prompting order)
+    let diff = TreeDiff::diff(&base_tree, &tree);
+    let short_id = &cp.id.to_string()[..8];
+    if diff.is_empty() {
+        anyhow::bail!("Checkpoint {} has no changes relative to its parent", short_id);
+    }
+
+    let mut changes: Vec<(String, Option<Entry>, Option<Entry>)> = Vec::new();
+    for (path, entry) in &diff.added {
+        changes.push((String::from_utf8_lossy(path).into_owned(), None, Some(entry.clone())));
+    }
+    for (path, entry) in &diff.removed {
+        changes.push((String::from_utf8_lossy(path).into_owned(), Some(entry.clone()), None));
+    }
+    for (path, old_entry, new_entry) in &diff.modified {
+        changes.push((String::from_utf8_lossy(path).into_owned(), Some(old_entry.clone()), Some(new_entry.clone())));
+    }
+    changes.sort_by(|a, b| a.0.cmp(&b.0));
+
+    println!("{}", "Select changes to publish".bold());
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    println!("Checkpoint: {} {}", short_id.yellow(), util::format_relative_time(cp.ts_unix_ms).dimmed());
+    println!("Files:      {} changed", changes.len());
+    println!();
+    println!("{}", "y = publish, n = skip, a = rest of file, d = skip rest of file, q = stop selecting".dimmed());
+    println!();
+
+    // 4. Build synthetic tree from user selections
+    let mut synthetic = base_tree.clone();
+    let mut selected_paths: Vec<PathBuf> = Vec::new();
+
+    'files: for (path_str, old_entry, new_entry) in &changes {
+        let path = Path::new(path_str);
+
+        // Hunk selection is only possible for text files modified in place
+        if let (Some(old), Some(new)) = (old_entry, new_entry) {
+            // (content dropped by a path policy can only be taken whole)
+            let is_regular = |e: &Entry| matches!(e.kind, EntryKind::File | EntryKind::ExecutableFile)
+                && !store.blob_store().is_omitted(e.blob_hash);
+            if is_regular(old) && is_regular(new) {
+                let old_content = store.blob_store().read_blob(old.blob_hash)?;
+                let new_content = store.blob_store().read_blob(new.blob_hash)?;
+
+                let is_text = |c: &[u8]| !tl_core::is_binary(c) && std::str::from_utf8(c).is_ok();
+                let hunks = if is_text(&old_content) && is_text(&new_content) {
+                    crate::diff_utils::split_hunks(&old_content, &new_content, 3)
+                } else {
+                    Vec::new()
+                };
+
+                if !hunks.is_empty() {
+                    println!("  {} {}", "~".yellow(), path_str);
+                    let mut picks = vec![false; hunks.len()];
+                    let mut stop = false;
+
+                    for (idx, hunk) in hunks.iter().enumerate() {
+                        println!("{}", hunk.rendered);
+                        let question = format!("Publish this hunk ({}/{})?", idx + 1, hunks.len());
+                        match prompt(&question, "y,n,a,d,q")?.as_str() {
+                            "y" => picks[idx] = true,
+                            "a" => {
+                                picks[idx..].fill(true);
+                                break;
+                            }
+                            "d" => break,
+                            "q" => {
+                                stop = true;
+                                break;
+                            }
+                            _ => {}
+                        }
+                    }
+
+                    if picks.iter().all(|&p| p) {
+                        synthetic.insert(path, new.clone());
+                        selected_paths.push(path.to_path_buf());
+                    } else if picks.iter().any(|&p| p) {
+                        let content = crate::diff_utils::apply_selected_hunks(
+                            &old_content,
+                            &new_content,
+                            &hunks,
+                            &picks,
+                        );
+                        let blob_hash = tl_core::hash::git::hash_blob(&content);
+                        if !store.blob_store().has_blob(blob_hash) {
+                            store.blob_store().write_blob(blob_hash, &content)?;
+                        }
+                        synthetic.insert(path, Entry::file(new.mode, blob_hash));
+                        selected_paths.push(path.to_path_buf());
+                    }
+
+                    println!();
+                    if stop {
+                        break 'files;
+                    }
+                    continue;
+                }
+            }
+        }
+
+        // Whole-file selection (added, deleted, binary, symlink, or mode-only change)
+        let (marker, question) = match (old_entry, new_entry) {
+            (None, Some(_)) => ("+".green().to_string(), "Publish new file?"),
+            (Some(_), None) => ("-".red().to_string(), "Publish deletion?"),
+            _ => ("~".yellow().to_string(), "Publish whole-file change?"),
+        };
+        println!("  {} {}", marker, path_str);
+
+        match prompt(question, "y,n,q")?.as_str() {
+            "y" => {
+                match new_entry {
+                    Some(entry) => synthetic.insert(path, entry.clone()),
+                    None => {
+                        synthetic.remove(path);
+                    }
+                }
+                selected_paths.push(path.to_path_buf());
+            }
+            "q" => break 'files,
+            _ => {}
+        }
+        println!();
+    }
+
+    if selected_paths.is_empty() {
+        println!("{}", "Nothing selected, publish cancelled".yellow());
+        return Ok(());
+    }
+
+    // 5. Write synthetic tree and wrap it in a checkpoint copy (never journaled)
+    let synthetic_hash = store.write_tree(&synthetic)
+        .context("Failed to write synthetic tree")?;
+
+    let mut partial = cp.clone();
+    partial.root_tree = synthetic_hash;
+    partial.touched_paths = selected_paths.clone();
+    partial.meta.files_changed = selected_paths.len() as u32;
+
+    // Parent not published: JJ parent falls back to the seed, so pass the
+    // accumulated paths just like a regular publish would
+    let parent_published = match cp.parent {
+        Some(parent_id) => mapping.get_jj_commit(parent_id)?.is_some(),
+        None => true,
+    };
+    let accumulated_paths = if parent_published {
+        None
+    } else {
+        Some(accumulate_touched_paths(cp, mapping, &tl_dir).await?)
+    };
+
+    let mut msg_options = CommitMessageOptions::default();
+    if let Some(template) = message_template {
+        msg_options.template = Some(template);
+    }
+
+    let publish_options = PublishOptions {
+        auto_pin: None,
+        message_options: msg_options,
+        compact_range: false,
+        accumulated_paths,
+    };
+
+    // 6. Publish
+    println!("{}", "Publishing selected changes to JJ...".dimmed());
+    let commit_id = publish::publish_partial_checkpoint(
+        &partial,
+        store,
+        repo_root,
+        mapping,
+        &publish_options,
+    )?;
+
+    // 7. Update bookmark
+    let bookmark_name = bookmark.unwrap_or_else(|| "main".to_string());
+    let mut workspace = jj::load_workspace(repo_root)?;
+    jj::create_bookmark_native(&mut workspace, &bookmark_name, &commit_id)
+        .context("Failed to create JJ bookmark")?;
+
+    println!("{} Updated bookmark: {}", "✓".green(), bookmark_name.yellow());
+    println!();
+    println!("{} Published {} of {} changed file(s) from {}",
+        "✓".green(),
+        selected_paths.len().to_string().green(),
+        changes.len(),
+        short_id.yellow()
+    );
+    let short_commit = &commit_id[..12.min(commit_id.len())];
+    println!("  {} → {}", short_id.yellow(), short_commit.cyan());
+    println!("{}", "Partial publish: checkpoint is not marked as published".dimmed());
+
+    Ok(())
+}
+
+/// Ask a question on stdin, returning the lowercased answer
+///
+/// EOF is treated as "q" so piping /dev/null stops selection cleanly.
+fn prompt(question: &str, choices: &str) -> Result<String> {
+    print!("{} [{}] ", question, choices);
+    std::io::stdout().flush()?;
+
+    let mut input = String::new();
+    if std::io::stdin().read_line(&mut input)? == 0 {
+        println!();
+        return Ok("q".to_string());
+    }
+
+    Ok(input.trim().to_lowercase())
+}
+
 async fn parse_checkpoint_range(
     range: &str,
     tl_dir: &std::path::Path,
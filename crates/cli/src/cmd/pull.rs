@@ -10,7 +10,7 @@
 
 use anyhow::{Context, Result};
 use crate::util;
-use crate::cmd::restore::{restore_tree, RestoreResult};
+use crate::cmd::restore::{restore_tree, write_blob_file, RestoreResult};
 use journal::{Checkpoint, CheckpointMeta, CheckpointReason, Journal, StashEntry, StashManager};
 use owo_colors::OwoColorize;
 use std::path::Path;
@@ -36,7 +36,7 @@ pub async fn run(
     // 3. Open components
     let store = Store::open(&repo_root)
         .context("Failed to open Timelapse store")?;
-    let journal = Journal::open(&tl_dir)
+    let journal = Journal::open_with_cipher(&tl_dir, store.cipher().cloned())
         .context("Failed to open journal")?;
     let stash_manager = StashManager::new(&tl_dir);
     let mapping = jj::JjMapping::open(&tl_dir)
@@ -139,6 +139,7 @@ pub async fn run(
                 println!("  {}", err.dimmed());
             }
         }
+        crate::cmd::restore::print_not_kept(&result.not_kept);
 
         // CRITICAL (Fix 12): Invalidate pathmap after modifying working directory
         // The daemon's in-memory pathmap is now stale and needs to be rebuilt
@@ -506,19 +507,7 @@ async fn reapply_stash(
         }
 
         // Apply stash entry
-        if let Some(parent) = file_path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-
-        let content = store.blob_store().read_blob(stash_entry.blob_hash)?;
-        std::fs::write(&file_path, content)?;
-
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            let permissions = std::fs::Permissions::from_mode(stash_entry.mode);
-            std::fs::set_permissions(&file_path, permissions)?;
-        }
+        write_blob_file(store, stash_entry.blob_hash, stash_entry.mode, &file_path)?;
 
         reapplied += 1;
     }
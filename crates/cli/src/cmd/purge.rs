@@ -0,0 +1,259 @@
+//! Remove paths from history
+//!
+//! `tl purge <path-glob>... [--range A..B]` rewrites every checkpoint whose
+//! tree contains a matching path. Rewritten checkpoints and their
+//! descendants get new IDs; pins, stashes, workspace states and JJ mappings
+//! are moved to the new IDs. The removed content becomes unreachable and is
+//! deleted by the next `tl gc`.
+//!
+//! Checkpoints already published to JJ are left alone unless `--force` is
+//! given; forcing drops their mapping (the JJ commits keep the content).
+
+use crate::data_access;
+use crate::locks::GcLock;
+use crate::util;
+use anyhow::{Context, Result};
+use journal::{purge_paths, Journal, PinManager, StashManager};
+use owo_colors::OwoColorize;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+use tl_core::{PathPatterns, Store};
+use ulid::Ulid;
+
+pub async fn run(
+    patterns: Vec<String>,
+    range: Option<String>,
+    force: bool,
+    dry_run: bool,
+    skip_confirm: bool,
+) -> Result<()> {
+    // 1. Find repository root
+    let repo_root = util::find_repo_root()
+        .context("Failed to find repository")?;
+
+    let tl_dir = repo_root.join(".tl");
+
+    let matcher = PathPatterns::new(&repo_root, &patterns)
+        .context("Invalid path pattern")?;
+
+    // 2. Hold the GC lock so nothing else rewrites the journal meanwhile
+    let _gc_lock = GcLock::acquire(&tl_dir)
+        .context("Failed to acquire GC lock - is a GC or restore in progress?")?;
+
+    // 3. Stop daemon for exclusive journal access (same as gc)
+    let socket_path = tl_dir.join("state/daemon.sock");
+    let daemon_was_running = if socket_path.exists() {
+        match crate::ipc::IpcClient::connect(&socket_path).await {
+            Ok(mut client) => {
+                println!("{}", "Stopping daemon for exclusive journal access...".dimmed());
+                client.shutdown().await.ok();
+                tokio::time::sleep(Duration::from_millis(500)).await;
+                true
+            }
+            Err(_) => false,
+        }
+    } else {
+        false
+    };
+
+    // Always bring the daemon back, even when the purge fails
+    let result = purge_with_daemon_stopped(
+        &repo_root, &tl_dir, &matcher, range, force, dry_run, skip_confirm,
+    ).await;
+    let restarted = restart_daemon(daemon_was_running).await;
+    result.and(restarted)
+}
+
+/// Steps 4-7 of `run`, with the daemon stopped and the GC lock held
+async fn purge_with_daemon_stopped(
+    repo_root: &Path,
+    tl_dir: &Path,
+    matcher: &PathPatterns,
+    range: Option<String>,
+    force: bool,
+    dry_run: bool,
+    skip_confirm: bool,
+) -> Result<()> {
+    // 4. Resolve the range endpoints before taking the journal
+    let range_ids = match &range {
+        Some(range) => Some(resolve_range(range, tl_dir).await?),
+        None => None,
+    };
+
+    let store = Store::open(repo_root)?;
+    let journal = Journal::open_with_cipher(&tl_dir.join("journal"), store.cipher().cloned())
+        .context("Failed to open checkpoint journal")?;
+
+    let scope = match range_ids {
+        Some((from, to)) => Some(range_scope(&journal, from, to)?),
+        None => None,
+    };
+
+    println!("{}", "Purge".bold());
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    println!();
+
+    // 5. Plan the rewrite without writing anything
+    let matches = |path: &Path| matcher.matches(path);
+    let plan = purge_paths(&journal, &store, &matches, scope.as_ref(), true)?;
+
+    if plan.purged.is_empty() {
+        println!("{} No checkpoint contains a matching path", "✓".green());
+        return Ok(());
+    }
+
+    let mut removed_paths: Vec<_> = plan.purged.iter()
+        .flat_map(|(_, paths)| paths.iter().cloned())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    removed_paths.sort();
+
+    for path in &removed_paths {
+        println!("  {} {}", "-".red(), path.display());
+    }
+    println!();
+    println!("Checkpoints with paths removed: {}", plan.purged.len().to_string().yellow());
+    println!("Checkpoints rewritten:          {}", plan.remapped.len().to_string().yellow());
+
+    // 6. Refuse to rewrite published checkpoints unless forced
+    let mapping = jj::JjMapping::open(tl_dir)?;
+    let mut published = Vec::new();
+    for (id, _) in &plan.purged {
+        if let Some(commit) = mapping.get_jj_commit(*id)? {
+            published.push((*id, commit));
+        }
+    }
+    if !published.is_empty() {
+        println!();
+        println!("{} {} affected checkpoint(s) already published to JJ:", "⚠️ ".yellow(), published.len());
+        for (id, commit) in published.iter().take(5) {
+            let short = &id.to_string()[..12];
+            println!("  {} → {}", short.dimmed(), &commit[..commit.len().min(12)]);
+        }
+        if !force {
+            anyhow::bail!("Refusing to rewrite published checkpoints (use --force; the JJ commits keep the content)");
+        }
+    }
+    println!();
+
+    if dry_run {
+        println!("{} Dry run - nothing rewritten", "→".yellow());
+        return Ok(());
+    }
+
+    if !skip_confirm {
+        println!("{}", "⚠️  This rewrites history: affected checkpoints get new IDs.".red().bold());
+        print!("Continue? [y/N] ");
+        std::io::stdout().flush()?;
+
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input)?;
+
+        if !input.trim().eq_ignore_ascii_case("y") {
+            println!("{}", "Purge cancelled".yellow());
+            return Ok(());
+        }
+    }
+
+    // 7. Rewrite the journal, then move every reference to the new IDs
+    let result = purge_paths(&journal, &store, &matches, scope.as_ref(), false)?;
+    drop(journal);
+
+    let pins_moved = PinManager::new(tl_dir).remap(&result.remapped)?;
+    let stashes_moved = StashManager::new(tl_dir).remap(&result.remapped)?;
+    let workspaces_moved = remap_workspaces(tl_dir, repo_root, &result.remapped)?;
+
+    let purged_ids: HashSet<Ulid> = result.purged.iter().map(|(id, _)| *id).collect();
+    let mut mappings_dropped = 0;
+    for (old_id, new_id) in &result.remapped {
+        if let Some(commit) = mapping.get_jj_commit(*old_id)? {
+            mapping.remove(*old_id)?;
+            if purged_ids.contains(old_id) {
+                mappings_dropped += 1;
+            } else {
+                mapping.set(*new_id, &commit)?;
+                mapping.set_reverse(&commit, *new_id)?;
+            }
+        }
+    }
+    mapping.flush()?;
+
+    println!("{} Rewrote {} checkpoint(s)", "✓".green(), result.remapped.len().to_string().green());
+    if pins_moved + stashes_moved + workspaces_moved > 0 {
+        println!("  Moved {} pin(s), {} stash(es), {} workspace(s)", pins_moved, stashes_moved, workspaces_moved);
+    }
+    if mappings_dropped > 0 {
+        println!("  Dropped {} JJ mapping(s) - republish to replace them", mappings_dropped);
+    }
+
+    println!();
+    println!("{}", "Run 'tl gc' to delete the removed content from the object store.".dimmed());
+    if removed_paths.iter().any(|path| repo_root.join(path).exists()) {
+        println!("{}", "Matching files still exist in the working tree - add them to .tlignore to keep them out of new checkpoints.".dimmed());
+    }
+
+    Ok(())
+}
+
+async fn restart_daemon(daemon_was_running: bool) -> Result<()> {
+    if daemon_was_running {
+        println!();
+        println!("{}", "Restarting daemon...".dimmed());
+        crate::daemon::ensure_daemon_running_with_timeout(3).await?;
+    }
+    Ok(())
+}
+
+/// Resolve `A..B` (either side optional: root..HEAD)
+async fn resolve_range(range: &str, tl_dir: &Path) -> Result<(Option<Ulid>, Ulid)> {
+    let (from, to) = range.split_once("..")
+        .with_context(|| format!("Invalid range '{}' - expected <from>..<to>", range))?;
+    let to = if to.is_empty() { "HEAD" } else { to };
+
+    let refs = vec![from.to_string(), to.to_string()];
+    let resolved = data_access::resolve_checkpoint_refs(&refs, tl_dir).await?;
+
+    let from_id = if from.is_empty() {
+        None
+    } else {
+        Some(resolved[0].with_context(|| format!("Checkpoint '{}' not found", from))?)
+    };
+    let to_id = resolved[1].with_context(|| format!("Checkpoint '{}' not found", to))?;
+
+    Ok((from_id, to_id))
+}
+
+/// Checkpoints reachable from `to` but not from `from`
+fn range_scope(journal: &Journal, from: Option<Ulid>, to: Ulid) -> Result<HashSet<Ulid>> {
+    let mut scope: HashSet<Ulid> = journal.ancestors(&to, None)?
+        .into_iter()
+        .map(|cp| cp.id)
+        .collect();
+
+    if let Some(from) = from {
+        for cp in journal.ancestors(&from, None)? {
+            scope.remove(&cp.id);
+        }
+    }
+
+    Ok(scope)
+}
+
+/// Point workspace states at rewritten checkpoints
+fn remap_workspaces(tl_dir: &Path, repo_root: &Path, remapped: &HashMap<Ulid, Ulid>) -> Result<usize> {
+    let ws_manager = jj::WorkspaceManager::open(tl_dir, repo_root)?;
+    let mut updated = 0;
+
+    for mut state in ws_manager.list_states()? {
+        if let Some(new_id) = state.current_checkpoint.and_then(|id| remapped.get(&id)) {
+            state.current_checkpoint = Some(*new_id);
+            ws_manager.set_state(&state)?;
+            updated += 1;
+        }
+    }
+
+    Ok(updated)
+}
@@ -2,11 +2,14 @@
 //!
 //! This module provides both the CLI command and reusable restore utilities
 //! for use by other commands (e.g., pull, stash).
+//!
+//! Paths whose content was not kept (hash-only or superseded latest-only
+//! versions under `.tlpolicy`) are left untouched and reported.
 
 use crate::locks::RestoreLock;
 use crate::util;
 use anyhow::{anyhow, Context, Result};
-use tl_core::{Entry, Store, Tree};
+use tl_core::{Entry, Sha1Hash, Store, Tree};
 use owo_colors::OwoColorize;
 use std::fs;
 use std::io::Write;
@@ -76,6 +79,7 @@ pub async fn run(checkpoint: &str, skip_confirm: bool) -> Result<()> {
     // 7. Restore files
     let mut restored = 0;
     let mut errors = Vec::new();
+    let mut not_kept = Vec::new();
 
     for (path_bytes, entry) in tree.entries_with_paths() {
         let path_str = std::str::from_utf8(path_bytes)
@@ -92,6 +96,11 @@ pub async fn run(checkpoint: &str, skip_confirm: bool) -> Result<()> {
             continue; // Skip protected directories
         }
 
+        if store.blob_store().is_omitted(entry.blob_hash) {
+            not_kept.push(path_str.to_string());
+            continue;
+        }
+
         // Restore file
         match restore_file(&store, &file_path, entry) {
             Ok(()) => {
@@ -125,6 +134,8 @@ pub async fn run(checkpoint: &str, skip_confirm: bool) -> Result<()> {
         }
     }
 
+    print_not_kept(&not_kept);
+
     println!();
 
     // CRITICAL (Fix 12): Invalidate the daemon's pathmap after restore
@@ -158,30 +169,73 @@ pub async fn run(checkpoint: &str, skip_confirm: bool) -> Result<()> {
 // Reusable restore utilities (used by restore, pull, stash)
 // =============================================================================
 
+/// Report paths skipped because their content was not kept
+pub fn print_not_kept(paths: &[String]) {
+    if paths.is_empty() {
+        return;
+    }
+
+    println!();
+    println!("{} {} path(s) not restored - content not kept under .tlpolicy:", "⚠".yellow(), paths.len());
+    for path in paths.iter().take(10) {
+        println!("  {}", path.yellow());
+    }
+    if paths.len() > 10 {
+        println!("  ... and {} more", paths.len() - 10);
+    }
+    println!("{}", "These files were left as they are in the working directory.".dimmed());
+}
+
 /// Restore a single file from a checkpoint entry
 ///
 /// This is a low-level utility used by restore_tree and the restore command.
+/// Fails without touching the file if the entry's content was not kept.
 pub fn restore_file(store: &Store, file_path: &Path, entry: &Entry) -> Result<()> {
-    // Create parent directories
-    if let Some(parent) = file_path.parent() {
-        fs::create_dir_all(parent)?;
+    if store.blob_store().is_omitted(entry.blob_hash) {
+        anyhow::bail!("content not kept (path policy)");
     }
 
-    // Read blob content
-    let content = store.blob_store().read_blob(entry.blob_hash)?;
-
-    // Write file
-    fs::write(file_path, content)?;
+    write_blob_file(store, entry.blob_hash, entry.mode, file_path)
+}
 
-    // Set permissions
-    #[cfg(unix)]
-    {
-        let mode = entry.mode;
-        let permissions = fs::Permissions::from_mode(mode);
-        fs::set_permissions(file_path, permissions)?;
+/// Write a blob to `file_path`, replacing the file only once the blob is verified
+///
+/// Content is streamed into a temporary file next to `file_path` (chunked large
+/// files never sit in memory), synced, and renamed over the target after
+/// `read_blob_to` succeeds. On any error the temporary file is removed and the
+/// existing file is left untouched.
+pub fn write_blob_file(store: &Store, blob_hash: Sha1Hash, mode: u32, file_path: &Path) -> Result<()> {
+    // Create parent directories
+    let parent = file_path.parent().unwrap_or_else(|| Path::new("."));
+    fs::create_dir_all(parent)?;
+
+    let file_name = file_path
+        .file_name()
+        .ok_or_else(|| anyhow!("Invalid file path: {}", file_path.display()))?;
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(format!(".tl-restore.{}", std::process::id()));
+    let temp_path = parent.join(temp_name);
+
+    let result = (|| -> Result<()> {
+        let mut file = std::io::BufWriter::new(fs::File::create(&temp_path)?);
+        store.blob_store().read_blob_to(blob_hash, &mut file)?;
+        let file = file.into_inner().map_err(|e| e.into_error())?;
+        file.sync_all()?;
+        drop(file);
+
+        // Set permissions
+        #[cfg(unix)]
+        fs::set_permissions(&temp_path, fs::Permissions::from_mode(mode))?;
+
+        fs::rename(&temp_path, file_path)?;
+        Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
     }
-
-    Ok(())
+    result
 }
 
 /// Result of a tree restore operation
@@ -190,6 +244,8 @@ pub struct RestoreResult {
     pub files_restored: usize,
     pub files_deleted: usize,
     pub errors: Vec<String>,
+    /// Paths skipped because their content was not kept
+    pub not_kept: Vec<String>,
 }
 
 /// Restore an entire tree to the working directory
@@ -237,6 +293,13 @@ pub fn restore_tree(
             continue;
         }
 
+        if store.blob_store().is_omitted(entry.blob_hash) {
+            // Left as-is, so it must not be deleted as an extra file either
+            result.not_kept.push(path_str.to_string());
+            restored_paths.insert(file_path);
+            continue;
+        }
+
         // Restore the file
         match restore_file(store, &file_path, entry) {
             Ok(()) => {
@@ -267,6 +330,75 @@ pub fn restore_tree(
     Ok(result)
 }
 
+/// Apply the difference between two trees to the working directory
+///
+/// Unlike `restore_tree` with `delete_extra`, only paths that differ between
+/// `from` and `to` are touched, so untracked and ignored files are left alone.
+/// Used by commands that move the working directory from HEAD to a computed
+/// tree (merge-checkpoint, etc.).
+pub fn apply_tree_changes(
+    store: &Store,
+    from: &Tree,
+    to: &Tree,
+    repo_root: &Path,
+) -> Result<RestoreResult> {
+    let mut result = RestoreResult::default();
+    let diff = tl_core::TreeDiff::diff(from, to);
+
+    let writes = diff.added.iter()
+        .map(|(path, entry)| (path, entry))
+        .chain(diff.modified.iter().map(|(path, _old, new)| (path, new)));
+
+    for (path_bytes, entry) in writes {
+        let path_str = match std::str::from_utf8(path_bytes) {
+            Ok(s) => s,
+            Err(e) => {
+                result.errors.push(format!("Invalid UTF-8 in path: {}", e));
+                continue;
+            }
+        };
+
+        if path_str.starts_with(".tl/") || path_str.starts_with(".git/") || path_str.starts_with(".jj/") {
+            continue;
+        }
+
+        let file_path = repo_root.join(path_str);
+        if !file_path.starts_with(repo_root) {
+            result.errors.push(format!("Path escapes repository: {}", path_str));
+            continue;
+        }
+
+        if store.blob_store().is_omitted(entry.blob_hash) {
+            result.not_kept.push(path_str.to_string());
+            continue;
+        }
+
+        match restore_file(store, &file_path, entry) {
+            Ok(()) => result.files_restored += 1,
+            Err(e) => result.errors.push(format!("{}: {}", path_str, e)),
+        }
+    }
+
+    for (path_bytes, _entry) in &diff.removed {
+        let path_str = match std::str::from_utf8(path_bytes) {
+            Ok(s) => s,
+            Err(e) => {
+                result.errors.push(format!("Invalid UTF-8 in path: {}", e));
+                continue;
+            }
+        };
+
+        let file_path = repo_root.join(path_str);
+        match fs::remove_file(&file_path) {
+            Ok(()) => result.files_deleted += 1,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => result.errors.push(format!("Failed to delete {}: {}", path_str, e)),
+        }
+    }
+
+    Ok(result)
+}
+
 /// Result of delete_extra_files operation
 #[derive(Debug, Default)]
 pub struct DeleteResult {
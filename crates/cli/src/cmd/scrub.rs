@@ -0,0 +1,240 @@
+//! Purge stored secrets from every checkpoint
+//!
+//! `tl scrub <blob-or-path>...` removes the content of the given blobs (or of
+//! every stored version of the given paths) from `.tl/objects`. With no
+//! targets, every stored blob is run through the configured secret scanner
+//! and the ones with findings are purged. Dual-written copies in
+//! `.git/objects` belong to Git and are left alone.
+//!
+//! Checkpoints are not rewritten: their trees keep the blob hash, and the
+//! content is recorded as not kept, so `tl restore` reports those paths and
+//! leaves the working copies alone.
+
+use crate::locks::GcLock;
+use crate::util;
+use anyhow::{Context, Result};
+use journal::Journal;
+use owo_colors::OwoColorize;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+use tl_core::{EntryKind, SecretScanner, Sha1Hash, Store};
+
+pub async fn run(targets: Vec<String>, dry_run: bool, skip_confirm: bool) -> Result<()> {
+    // 1. Find repository root
+    let repo_root = util::find_repo_root()
+        .context("Failed to find repository")?;
+
+    let tl_dir = repo_root.join(".tl");
+
+    // 2. Hold the GC lock so nothing rewrites the store while we scrub it
+    let _gc_lock = GcLock::acquire(&tl_dir)
+        .context("Failed to acquire GC lock - is a GC or restore in progress?")?;
+
+    // 3. Stop daemon for exclusive journal access (same as gc)
+    let socket_path = tl_dir.join("state/daemon.sock");
+    let daemon_was_running = if socket_path.exists() {
+        match crate::ipc::IpcClient::connect(&socket_path).await {
+            Ok(mut client) => {
+                println!("{}", "Stopping daemon for exclusive journal access...".dimmed());
+                client.shutdown().await.ok();
+                tokio::time::sleep(Duration::from_millis(500)).await;
+                true
+            }
+            Err(_) => false,
+        }
+    } else {
+        false
+    };
+
+    // Always bring the daemon back, even when the scrub fails
+    let result = scrub_with_daemon_stopped(&repo_root, &tl_dir, targets, dry_run, skip_confirm).await;
+    let restarted = restart_daemon(daemon_was_running).await;
+    result.and(restarted)
+}
+
+/// Steps 4-7 of `run`, with the daemon stopped and the GC lock held
+async fn scrub_with_daemon_stopped(
+    repo_root: &Path,
+    tl_dir: &Path,
+    targets: Vec<String>,
+    dry_run: bool,
+    skip_confirm: bool,
+) -> Result<()> {
+    let store = Store::open(repo_root)?;
+    let journal = Journal::open_with_cipher(&tl_dir.join("journal"), store.cipher().cloned())
+        .context("Failed to open checkpoint journal")?;
+
+    println!("{}", "Scrub".bold());
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    println!();
+
+    // 4. Every stored blob referenced by any checkpoint, with its paths
+    let referenced = referenced_blobs(&journal, &store)?;
+
+    // 5. Pick the blobs to purge (hash -> reason)
+    let doomed = if targets.is_empty() {
+        println!("{}", "Scanning stored content for secrets...".dimmed());
+        let scanner = SecretScanner::load(tl_dir)?;
+        if scanner.is_empty() {
+            anyhow::bail!("Secret scanning is disabled in .tl/config.toml - name blobs or paths to scrub");
+        }
+        scan_blobs(&store, &scanner, &referenced)
+    } else {
+        select_targets(&store, &targets, &referenced)?
+    };
+
+    // 6. Release the journal before purging
+    drop(journal);
+
+    if doomed.is_empty() {
+        println!("{} Nothing to scrub", "✓".green());
+        return Ok(());
+    }
+
+    for (hash, (path, reason)) in &doomed {
+        let hex = hash.to_hex();
+        let short = &hex[..12];
+        println!("  {} {}  {}", short.yellow(), path, reason.dimmed());
+    }
+    println!();
+
+    if dry_run {
+        println!("{} {} blob(s) would be purged (dry run)", "→".yellow(), doomed.len());
+        return Ok(());
+    }
+
+    if !skip_confirm {
+        println!("{}", "⚠️  Purged content cannot be recovered from any checkpoint.".red().bold());
+        print!("Purge {} blob(s)? [y/N] ", doomed.len());
+        std::io::stdout().flush()?;
+
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input)?;
+
+        if !input.trim().eq_ignore_ascii_case("y") {
+            println!("{}", "Scrub cancelled".yellow());
+            return Ok(());
+        }
+    }
+
+    // 7. Purge
+    let mut purged = 0;
+    for hash in doomed.keys() {
+        if store.blob_store().purge_blob(*hash)? {
+            purged += 1;
+        }
+    }
+
+    println!("{} Purged {} blob(s)", "✓".green(), purged.to_string().green());
+    println!("{}", "Checkpoints keep the paths; restores report their content as not kept.".dimmed());
+    if repo_root.join(".git").exists() {
+        println!("{}", "Copies in .git/objects, Git packs and remotes are not affected.".dimmed());
+    }
+
+    Ok(())
+}
+
+async fn restart_daemon(daemon_was_running: bool) -> Result<()> {
+    if daemon_was_running {
+        println!();
+        println!("{}", "Restarting daemon...".dimmed());
+        crate::daemon::ensure_daemon_running_with_timeout(3).await?;
+    }
+    Ok(())
+}
+
+/// Stored blobs referenced by any checkpoint, with every path they appear at
+fn referenced_blobs(journal: &Journal, store: &Store) -> Result<BTreeMap<Sha1Hash, BTreeSet<String>>> {
+    let mut trees_seen: HashSet<Sha1Hash> = HashSet::new();
+    let mut blobs: BTreeMap<Sha1Hash, BTreeSet<String>> = BTreeMap::new();
+
+    for id in journal.all_checkpoint_ids()? {
+        let checkpoint = match journal.get(&id)? {
+            Some(cp) => cp,
+            None => continue,
+        };
+        if !trees_seen.insert(checkpoint.root_tree) {
+            continue;
+        }
+        // Unreadable trees are fsck's business
+        let tree = match store.read_tree(checkpoint.root_tree) {
+            Ok(tree) => tree,
+            Err(_) => continue,
+        };
+
+        for (path_bytes, entry) in tree.entries_with_paths() {
+            if entry.kind == EntryKind::Tree || !store.blob_store().has_blob(entry.blob_hash) {
+                continue;
+            }
+            blobs.entry(entry.blob_hash)
+                .or_default()
+                .insert(String::from_utf8_lossy(path_bytes).into_owned());
+        }
+    }
+
+    Ok(blobs)
+}
+
+/// First path a blob appears at, for display
+fn display_path(paths: Option<&BTreeSet<String>>) -> String {
+    paths.and_then(|paths| paths.iter().next())
+        .cloned()
+        .unwrap_or_else(|| "(unreferenced)".to_string())
+}
+
+/// Run the scanner over every referenced blob (chunked ones as a stream)
+fn scan_blobs(
+    store: &Store,
+    scanner: &SecretScanner,
+    referenced: &BTreeMap<Sha1Hash, BTreeSet<String>>,
+) -> BTreeMap<Sha1Hash, (String, String)> {
+    let mut doomed = BTreeMap::new();
+
+    for (hash, paths) in referenced {
+        let mut stream = scanner.stream();
+        if store.blob_store().read_blob_to(*hash, &mut stream).is_err() {
+            continue;
+        }
+        let report = stream.finish();
+        if !report.is_clean() {
+            doomed.insert(*hash, (display_path(Some(paths)), report.summary()));
+        }
+    }
+
+    doomed
+}
+
+/// Resolve targets: full blob hashes, or paths (every stored version)
+fn select_targets(
+    store: &Store,
+    targets: &[String],
+    referenced: &BTreeMap<Sha1Hash, BTreeSet<String>>,
+) -> Result<BTreeMap<Sha1Hash, (String, String)>> {
+    let mut doomed = BTreeMap::new();
+
+    for target in targets {
+        if let Ok(hash) = Sha1Hash::from_hex(target) {
+            if !store.blob_store().has_blob(hash) {
+                anyhow::bail!("Blob {} is not stored", target);
+            }
+            doomed.insert(hash, (display_path(referenced.get(&hash)), "named blob".to_string()));
+            continue;
+        }
+
+        let path = target.trim_start_matches("./").trim_end_matches('/');
+        let mut found = false;
+        for (hash, paths) in referenced {
+            if paths.contains(path) {
+                doomed.insert(*hash, (path.to_string(), "version of path".to_string()));
+                found = true;
+            }
+        }
+        if !found {
+            anyhow::bail!("No stored content for '{}' in any checkpoint", target);
+        }
+    }
+
+    Ok(doomed)
+}
@@ -4,6 +4,7 @@ use crate::data_access;
 use crate::util;
 use anyhow::{Context, Result};
 use owo_colors::OwoColorize;
+use journal::{ChangeKind, PathChange};
 use std::path::Path;
 use tl_core::store::Store;
 
@@ -37,6 +38,9 @@ pub async fn run(checkpoint_ref: &str, show_diff: bool) -> Result<()> {
     } else {
         println!("{} {}", "Parent:    ".dimmed(), "(none - initial checkpoint)".dimmed());
     }
+    for merge_parent in &checkpoint.merge_parents {
+        println!("{} {}", "Merged:    ".dimmed(), merge_parent.to_string().cyan());
+    }
 
     println!("{} {}", "Tree:      ".dimmed(), checkpoint.root_tree.to_hex().bright_green());
 
@@ -52,8 +56,11 @@ pub async fn run(checkpoint_ref: &str, show_diff: bool) -> Result<()> {
     println!("  Bytes added:    {}", format_bytes(checkpoint.meta.bytes_added));
     println!("  Bytes removed:  {}", format_bytes(checkpoint.meta.bytes_removed));
 
-    // Print touched paths
-    if !checkpoint.touched_paths.is_empty() {
+    // Print per-path changes, or the bare touched paths for checkpoints
+    // recorded without provenance
+    if !checkpoint.changes.is_empty() {
+        print_changes(&checkpoint.changes);
+    } else if !checkpoint.touched_paths.is_empty() {
         println!("\n{} ({} files)", "Changed files:".bold(), checkpoint.touched_paths.len());
         let mut paths = checkpoint.touched_paths.clone();
         paths.sort();
@@ -91,6 +98,46 @@ pub async fn run(checkpoint_ref: &str, show_diff: bool) -> Result<()> {
     Ok(())
 }
 
+/// Print a checkpoint's recorded changes: a summary line, then each path
+fn print_changes(changes: &[PathChange]) {
+    let count = |wanted: fn(&ChangeKind) -> bool| changes.iter().filter(|c| wanted(&c.kind)).count();
+    let summary: Vec<String> = [
+        ("created", count(|k| matches!(k, ChangeKind::Created))),
+        ("modified", count(|k| matches!(k, ChangeKind::Modified))),
+        ("renamed", count(|k| matches!(k, ChangeKind::Renamed { .. }))),
+        ("deleted", count(|k| matches!(k, ChangeKind::Deleted))),
+        ("metadata only", count(|k| matches!(k, ChangeKind::MetadataOnly))),
+    ]
+    .iter()
+    .filter(|(_, n)| *n > 0)
+    .map(|(label, n)| format!("{} {}", label, n))
+    .collect();
+
+    println!("\n{} ({})", "Changes:".bold(), summary.join(", "));
+
+    for change in changes.iter().take(20) {
+        let path = change.path.display().to_string();
+        let line = match &change.kind {
+            ChangeKind::Created => format!("{} {}", "+".green(), path.green()),
+            ChangeKind::Modified => format!("{} {}", "M".yellow(), path.yellow()),
+            ChangeKind::MetadataOnly => format!("{} {}", "m".dimmed(), path),
+            ChangeKind::Deleted => format!("{} {}", "-".red(), path.red()),
+            ChangeKind::Renamed { from } => {
+                format!("{} {} → {}", "R".cyan(), from.display(), path.cyan())
+            }
+        };
+        if change.events > 1 {
+            println!("  {} {}", line, format!("({} events)", change.events).dimmed());
+        } else {
+            println!("  {}", line);
+        }
+    }
+
+    if changes.len() > 20 {
+        println!("  {} ({} more files omitted)", "...".dimmed(), changes.len() - 20);
+    }
+}
+
 /// Format timestamp in absolute format (YYYY-MM-DD HH:MM:SS)
 fn format_absolute_timestamp(ts_ms: u64) -> String {
     use chrono::{DateTime, Local, TimeZone, Utc};
@@ -157,6 +157,49 @@ pub async fn run_push(message: Option<String>, include_untracked: bool) -> Resul
     Ok(())
 }
 
+/// Append a checkpoint for an applied stash (parents: HEAD + stash)
+async fn record_stash_apply(
+    client: &mut crate::ipc::IpcClient,
+    repo_root: &std::path::Path,
+    tl_dir: &std::path::Path,
+    stash_id: Ulid,
+) -> Result<()> {
+    let head_id = data_access::resolve_checkpoint_refs(&["HEAD".to_string()], tl_dir).await?[0];
+    let checkpoints = data_access::get_checkpoints(&[stash_id], tl_dir).await?;
+    let stash_cp = checkpoints[0].as_ref()
+        .ok_or_else(|| anyhow::anyhow!("Stash checkpoint not found: {}", stash_id))?;
+
+    let store = Store::open(repo_root)?;
+    let head_tree = match head_id {
+        Some(id) => match data_access::get_checkpoints(&[id], tl_dir).await?[0].as_ref() {
+            Some(head_cp) => store.read_tree(head_cp.root_tree)?,
+            None => tl_core::Tree::new(),
+        },
+        None => tl_core::Tree::new(),
+    };
+    let stash_tree = store.read_tree(stash_cp.root_tree)?;
+
+    let touched_paths = crate::cmd::merge_checkpoint::changed_paths(&head_tree, &stash_tree);
+
+    let checkpoint = Checkpoint::new(
+        head_id,
+        stash_cp.root_tree,
+        journal::CheckpointReason::Restore,
+        touched_paths.clone(),
+        journal::CheckpointMeta {
+            files_changed: touched_paths.len() as u32,
+            bytes_added: 0,
+            bytes_removed: 0,
+        },
+    )
+    .with_merge_parents(vec![stash_id]);
+
+    client.append_checkpoint(checkpoint).await?;
+    client.invalidate_pathmap().await?;
+
+    Ok(())
+}
+
 /// Apply a stash to the working directory
 pub async fn run_apply(stash_ref: Option<String>, pop: bool) -> Result<()> {
     let repo_root = util::find_repo_root()?;
@@ -228,9 +271,19 @@ pub async fn run_apply(stash_ref: Option<String>, pop: bool) -> Result<()> {
         .map(|(_, id)| *id)
         .ok_or_else(|| anyhow::anyhow!("Stash not found: {}", stash_name))?;
 
+    // Flush pending changes so they are not lost from history
+    let socket_path = tl_dir.join("state/daemon.sock");
+    let mut client = crate::ipc::IpcClient::connect(&socket_path)
+        .await
+        .context("Failed to connect to daemon")?;
+    client.flush_checkpoint().await?;
+
     // Restore the stash (without confirmation since it's explicit)
     crate::cmd::restore::run(&checkpoint_id.to_string(), true).await?;
 
+    // Record the apply as a checkpoint with the stash as a second parent
+    record_stash_apply(&mut client, &repo_root, &tl_dir, checkpoint_id).await?;
+
     if pop {
         // Remove the stash
         pins.unpin(&stash_name)?;
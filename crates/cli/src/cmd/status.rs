@@ -5,6 +5,7 @@ use anyhow::{Context, Result};
 use owo_colors::OwoColorize;
 use std::path::Path;
 use std::time::{SystemTime, UNIX_EPOCH};
+use tl_core::{QuotaConfig, QuotaLevel};
 
 pub async fn run(show_remote: bool) -> Result<()> {
     // 1. Find repository root
@@ -50,10 +51,25 @@ pub async fn run(show_remote: bool) -> Result<()> {
     let uptime_secs = (current_time_ms.saturating_sub(status.start_time_ms)) / 1000;
     println!("  Uptime:      {}", util::format_duration(uptime_secs));
     println!("  Checkpoints: {} created", status.checkpoints_created);
+    if let Some(reason) = &status.deferral_reason {
+        println!("  Deferred:    {} {}", "waiting for the tree to settle".yellow(), format!("({})", reason).dimmed());
+    }
+    if status.checkpoints_deferred > 0 {
+        println!("  {}", format!("{} checkpoint(s) deferred until changes settled", status.checkpoints_deferred).dimmed());
+    }
+    if status.checkpoints_suppressed > 0 {
+        let collapsed = if status.oscillations_collapsed > 0 {
+            format!(", {} A→B→A change(s) collapsed", status.oscillations_collapsed)
+        } else {
+            String::new()
+        };
+        println!("  {}", format!("{} no-op checkpoint(s) skipped{}", status.checkpoints_suppressed, collapsed).dimmed());
+    }
     if let Some(ts) = status.last_checkpoint_time {
         println!("  Last:        {}", util::format_relative_time(ts));
     }
     println!("  Watching:    {} paths", status.watcher_paths);
+    print_watcher(&status);
     println!();
 
     // Latest checkpoint
@@ -86,6 +102,7 @@ pub async fn run(show_remote: bool) -> Result<()> {
     println!("Storage:");
     println!("  Checkpoints: {}", checkpoint_count);
     println!("  Total size:  {}", util::format_size(total_size));
+    print_quota(&tl_dir, &status);
     println!();
 
     // Remote status (if requested)
@@ -96,6 +113,73 @@ pub async fn run(show_remote: bool) -> Result<()> {
     Ok(())
 }
 
+/// Print the watcher backend and any inotify watch-limit shortfall
+fn print_watcher(status: &crate::ipc::DaemonStatus) {
+    if status.watcher_backend.is_empty() {
+        return;
+    }
+    if status.watches > 0 {
+        println!("  Watcher:     {} ({} directory watches)", status.watcher_backend, status.watches);
+    } else {
+        println!("  Watcher:     {}", status.watcher_backend);
+    }
+
+    if status.unwatched_dirs > 0 {
+        let limit = status.watch_limit
+            .map(|limit| limit.to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        println!(
+            "  {} inotify watch limit reached (max_user_watches = {}): {} subtree(s) are polled instead",
+            "⚠️ ".yellow(),
+            limit,
+            status.unwatched_dirs
+        );
+        println!("  {}", "Raise it with: sudo sysctl fs.inotify.max_user_watches=524288".dimmed());
+    }
+}
+
+/// Print usage against the `[quota]` budget, with warnings near the limit
+fn print_quota(tl_dir: &Path, status: &crate::ipc::DaemonStatus) {
+    let config = match QuotaConfig::load(tl_dir) {
+        Ok(Some(config)) => config,
+        Ok(None) => return,
+        Err(e) => {
+            println!("  Quota:       {} {}", "invalid".red(), e.to_string().dimmed());
+            return;
+        }
+    };
+
+    let available = util::available_space(tl_dir).unwrap_or(0);
+    let quota = config.check(status.store_bytes, available);
+    let percent = quota.usage.saturating_mul(100) / quota.budget.max(1);
+    let summary = format!(
+        "{} of {} ({}%)",
+        util::format_size(quota.usage),
+        util::format_size(quota.budget),
+        percent
+    );
+
+    match quota.level {
+        QuotaLevel::Ok => println!("  Quota:       {}", summary),
+        QuotaLevel::Warning => {
+            println!("  Quota:       {}", summary.yellow());
+            println!("  {} Approaching the storage quota", "⚠️ ".yellow());
+        }
+        QuotaLevel::Critical => {
+            println!("  Quota:       {}", summary.yellow().bold());
+            println!("  {} Near the storage quota - emergency GC is tightening retention", "⚠️ ".yellow());
+        }
+        QuotaLevel::Exceeded => {
+            println!("  Quota:       {}", summary.red().bold());
+        }
+    }
+
+    if status.checkpointing_paused || quota.level == QuotaLevel::Exceeded {
+        println!("  {}", "Checkpointing paused: .tl is over its storage quota".red().bold());
+        println!("  {}", "Free space with 'tl gc' or raise [quota] limit in .tl/config.toml".dimmed());
+    }
+}
+
 /// Get the git remote URL for "origin"
 fn get_git_remote_url(repo_root: &Path) -> Option<String> {
     let output = std::process::Command::new("git")
@@ -53,7 +53,7 @@ pub async fn run(
     // 5. Open components
     let ws_manager = jj::WorkspaceManager::open(&tl_dir, &repo_root)?;
     let store = Store::open(&repo_root)?;
-    let journal = Journal::open(&tl_dir.join("journal"))?;
+    let journal = Journal::open_with_cipher(&tl_dir.join("journal"), store.cipher().cloned())?;
     let pin_manager = PinManager::new(&tl_dir);
 
     // 6. Auto-checkpoint current workspace (unless --no-checkpoint)
@@ -61,7 +61,9 @@ pub async fn run(
         println!("{}", "Saving current workspace state...".dimmed());
 
         let current_name = ws_manager.current_workspace_name()?;
-        let checkpoint_id = ws_manager.auto_checkpoint_current(&store, &journal)?;
+        let previous_save = ws_manager.get_state(&current_name)?
+            .and_then(|s| s.current_checkpoint);
+        let checkpoint_id = ws_manager.auto_checkpoint_current(&store, &journal, previous_save)?;
 
         // Update current workspace state
         let current_state = jj::WorkspaceState {
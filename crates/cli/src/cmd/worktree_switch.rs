@@ -25,7 +25,7 @@ pub async fn run(name: &str) -> Result<()> {
     // 3. Open components
     let ws_manager = jj::WorkspaceManager::open(&tl_dir, &repo_root)?;
     let store = Store::open(&repo_root)?;
-    let journal = Journal::open(&tl_dir.join("journal"))?;
+    let journal = Journal::open_with_cipher(&tl_dir.join("journal"), store.cipher().cloned())?;
     let pin_manager = PinManager::new(&tl_dir);
 
     // 4. Get current workspace name
@@ -53,10 +53,12 @@ pub async fn run(name: &str) -> Result<()> {
     // 7. Auto-checkpoint current workspace
     println!("{}", "Saving current workspace state...".dimmed());
 
-    let checkpoint_id = ws_manager.auto_checkpoint_current(&store, &journal)?;
+    let previous_state = ws_manager.get_state(&current_name)?;
+    let previous_save = previous_state.as_ref().and_then(|s| s.current_checkpoint);
+    let checkpoint_id = ws_manager.auto_checkpoint_current(&store, &journal, previous_save)?;
 
     // Update current workspace state
-    let current_state = ws_manager.get_state(&current_name)?
+    let current_state = previous_state
         .unwrap_or_else(|| jj::WorkspaceState {
             name: current_name.clone(),
             path: repo_root.clone(),
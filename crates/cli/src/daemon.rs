@@ -1,32 +1,41 @@
 //! Daemon lifecycle management
 //!
 //! The daemon handles:
-//! - File system watching and checkpoint creation
+//! - File system watching and checkpoint creation (adaptive schedule)
 //! - Auto-GC based on configurable intervals and thresholds
+//! - Storage quota: emergency GC under pressure, paused checkpoints when full
 //! - IPC communication with CLI commands
 
 use crate::ipc::{handle_connection, DaemonStatus, IpcRequest, IpcResponse, IpcServer};
 use crate::locks::{DaemonLock, RestoreLock, GcLock};
+use crate::quiescence::{Quiescence, QuiescenceDetector, QUIESCENCE_RECHECK};
+use crate::schedule::CheckpointScheduler;
 use crate::system_config::{self, SystemConfig};
 use crate::util;
 use anyhow::{Context, Result};
 use tl_core::store::Store;
-use tl_core::EntryKind;
-use journal::{incremental_update, Checkpoint, CheckpointMeta, CheckpointReason, GarbageCollector, Journal, PathMap, PinManager};
-use std::collections::HashSet;
+use tl_core::{EntryKind, PathPolicies, PathPolicy, QuotaConfig, QuotaLevel, QuotaStatus, SecretScanner, Sha1Hash};
+use journal::{incremental_update_with_options, path_changes, Checkpoint, CheckpointMeta, CheckpointReason, GarbageCollector, GcMetrics, Journal, Observed, PathMap, PinManager, RetentionPolicy, UpdateOptions};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::time::{Duration, Instant, SystemTime};
 use tokio::signal::unix::{signal, SignalKind};
 use tokio::sync::{broadcast, mpsc, oneshot, RwLock};
 use ulid::Ulid;
-use watcher::Watcher;
+use watcher::{EventKind, EventProvenance, ModifyKind, Watcher, WatcherBackend, WatcherConfig};
 
 /// Flush checkpoint request with response channel
 type FlushRequest = oneshot::Sender<Result<Option<String>>>;
 
+/// Most retention halvings emergency GC applies before giving up
+const EMERGENCY_GC_MAX_STEPS: u32 = 6;
+
+/// How soon a checkpoint skipped for a restore or GC lock is retried
+const LOCKED_RETRY: Duration = Duration::from_secs(1);
+
 /// Supervisor for daemon process - handles crashes and restarts
 pub struct DaemonSupervisor {
     repo_root: PathBuf,
@@ -93,7 +102,8 @@ pub struct Daemon {
     store: Arc<Store>,
     journal: Arc<Journal>,
     watcher: Watcher,
-    pathmap: PathMap,
+    /// Shared with the watcher as its stat cache (replaced, never mutated)
+    pathmap: Arc<PathMap>,
 
     _lock: DaemonLock,
     ipc_server: IpcServer,
@@ -111,6 +121,23 @@ pub struct Daemon {
 
     // System configuration (for auto-GC)
     system_config: SystemConfig,
+
+    // Per-path checkpoint policies (.tlpolicy)
+    path_policies: PathPolicies,
+    // Changed paths with the `manual` policy, held until the next flush
+    deferred_paths: HashSet<Arc<Path>>,
+    // Watcher event summaries for paths awaiting a checkpoint
+    provenance: HashMap<Arc<Path>, EventProvenance>,
+
+    // Secret scanner for new content ([secrets] in config.toml)
+    secret_scanner: SecretScanner,
+
+    // Storage budget for .tl/ ([quota] in config.toml)
+    quota: Option<QuotaConfig>,
+    emergency_gc_running: Arc<AtomicBool>,
+
+    // Defers checkpoints while the tree is mid-change
+    quiescence: QuiescenceDetector,
 }
 
 impl Daemon {
@@ -125,8 +152,12 @@ impl Daemon {
         // Debouncing state - load any pending paths from previous crash
         let tl_dir = self.store.tl_dir().to_path_buf();
         let mut pending_paths: HashSet<Arc<Path>> = load_pending_paths(&tl_dir);
-        let mut last_checkpoint = Instant::now();
+        self.deferred_paths = load_deferred_paths(&tl_dir);
         let checkpoint_interval = Duration::from_secs(self.system_config.daemon.checkpoint_interval_secs);
+        let mut schedule = CheckpointScheduler::new(&self.system_config.daemon, Instant::now());
+        if !pending_paths.is_empty() {
+            schedule.on_batch(Instant::now());
+        }
         let mut last_pending_save = Instant::now();
         let pending_save_interval = Duration::from_secs(2); // Save pending paths every 2s
 
@@ -136,6 +167,8 @@ impl Daemon {
         let gc_threshold = self.system_config.daemon.auto_gc_checkpoint_threshold;
         let auto_gc_enabled = self.system_config.daemon.auto_gc_enabled;
 
+        self.apply_watcher_diagnostics(&mut *self.status.write().await);
+
         if auto_gc_enabled {
             tracing::info!(
                 "Auto-GC enabled: interval={}s, threshold={} checkpoints",
@@ -145,13 +178,19 @@ impl Daemon {
         }
 
         loop {
+            let next_due = schedule.next_due();
+
             tokio::select! {
                 // Watcher events
                 _ = self.watcher.poll_events() => {
+                    self.quiescence.observe(self.watcher.metrics().events_processed());
+
                     // Collect ready paths
                     let batch = self.watcher.next_batch();
                     if !batch.is_empty() {
+                        self.collect_provenance(&batch);
                         pending_paths.extend(batch);
+                        schedule.on_batch(Instant::now());
 
                         // Update status
                         self.status.write().await.watcher_paths = pending_paths.len();
@@ -166,13 +205,14 @@ impl Daemon {
                     }
                 }
 
-                // Periodic checkpoint creation
-                _ = tokio::time::sleep_until(tokio::time::Instant::from_std(last_checkpoint + checkpoint_interval)), if !pending_paths.is_empty() => {
+                // Scheduled checkpoint creation (no deadline while idle)
+                _ = tokio::time::sleep_until(tokio::time::Instant::from_std(next_due.unwrap_or_else(Instant::now))), if next_due.is_some() => {
                     // CRITICAL: Check if a restore operation is in progress
                     // If so, skip this checkpoint cycle to prevent race conditions
                     if RestoreLock::is_held(&self.store.tl_dir()) {
                         tracing::info!("Skipping checkpoint - restore operation in progress");
                         self.status.write().await.checkpoints_skipped += 1;
+                        schedule.retry_after(Instant::now(), LOCKED_RETRY);
                         continue;
                     }
 
@@ -181,16 +221,51 @@ impl Daemon {
                     if GcLock::is_held(&self.store.tl_dir()) {
                         tracing::info!("Skipping checkpoint - garbage collection in progress");
                         self.status.write().await.checkpoints_skipped += 1;
+                        schedule.retry_after(Instant::now(), LOCKED_RETRY);
                         continue;
                     }
 
+                    // Out of budget: keep the pending paths and retry next interval
+                    if let Some(quota) = self.check_quota().await {
+                        if quota.level == QuotaLevel::Exceeded {
+                            tracing::warn!("{}", quota_paused_message(&quota));
+                            self.status.write().await.checkpoints_skipped += 1;
+                            let _ = save_pending_paths(&tl_dir, &pending_paths);
+                            schedule.retry_after(Instant::now(), checkpoint_interval);
+                            continue;
+                        }
+                    }
+
+                    // Mid-burst (formatter, package install, refactor): wait for the tree to settle
+                    match self.quiescence.check(&pending_paths) {
+                        Quiescence::Defer(activity) => {
+                            tracing::debug!("Deferring checkpoint - {}", activity);
+                            let mut status = self.status.write().await;
+                            status.checkpoints_deferred = self.quiescence.deferred_total();
+                            status.deferral_reason = Some(activity.to_string());
+                            schedule.retry_after(Instant::now(), QUIESCENCE_RECHECK);
+                            continue;
+                        }
+                        Quiescence::Overdue(activity) => {
+                            tracing::info!(
+                                "Checkpointing despite {} - deferred for {}s already",
+                                activity,
+                                self.quiescence.deferred_for().unwrap_or_default().as_secs()
+                            );
+                        }
+                        Quiescence::Quiet => {}
+                    }
+                    self.quiescence.reset();
+                    self.status.write().await.deferral_reason = None;
+
                     // Check if pathmap needs rebuild (after restore operation)
                     let stale_marker = tl_dir.join("state/pathmap_stale");
                     if stale_marker.exists() {
                         tracing::info!("Pathmap marked stale - rebuilding from HEAD");
                         match rebuild_pathmap_from_head(&tl_dir, &self.journal, &self.store) {
                             Ok(new_pathmap) => {
-                                self.pathmap = new_pathmap;
+                                self.pathmap = Arc::new(new_pathmap);
+                                self.watcher.set_stat_cache(self.pathmap.clone());
                                 let _ = std::fs::remove_file(&stale_marker);
                                 tracing::info!("Pathmap rebuilt successfully");
                             }
@@ -202,17 +277,22 @@ impl Daemon {
 
                     tracing::info!("Creating checkpoint for {} paths", pending_paths.len());
 
-                    match self.create_checkpoint(&pending_paths).await {
+                    match self.create_checkpoint(&pending_paths, false).await {
                         Ok(checkpoint_id) => {
-                            tracing::info!("Created checkpoint: {}", checkpoint_id);
+                            if let Some(checkpoint_id) = checkpoint_id {
+                                tracing::info!("Created checkpoint: {}", checkpoint_id);
 
-                            // Update status
-                            let mut status = self.status.write().await;
-                            status.checkpoints_created += 1;
-                            status.last_checkpoint_time = Some(current_timestamp_ms());
+                                // Update status
+                                let mut status = self.status.write().await;
+                                status.checkpoints_created += 1;
+                                status.last_checkpoint_time = Some(current_timestamp_ms());
+                                self.apply_watcher_diagnostics(&mut status);
+                                schedule.checkpointed(Instant::now());
+                            } else {
+                                schedule.cleared();
+                            }
 
                             pending_paths.clear();
-                            last_checkpoint = Instant::now();
 
                             // Clear saved pending paths after successful checkpoint
                             clear_pending_paths(&tl_dir);
@@ -221,6 +301,7 @@ impl Daemon {
                             tracing::error!("Checkpoint creation failed: {}", e);
                             // Save pending paths in case of crash during error recovery
                             let _ = save_pending_paths(&tl_dir, &pending_paths);
+                            schedule.retry_after(Instant::now(), checkpoint_interval);
                         }
                     }
                 }
@@ -245,9 +326,15 @@ impl Daemon {
                         continue;
                     }
 
-                    let result = if !pending_paths.is_empty() {
-                        match self.create_checkpoint(&pending_paths).await {
-                            Ok(checkpoint_id) => {
+                    let over_quota = self.check_quota().await
+                        .filter(|quota| quota.level == QuotaLevel::Exceeded);
+
+                    let result = if let Some(quota) = over_quota {
+                        self.status.write().await.checkpoints_skipped += 1;
+                        Err(anyhow::anyhow!(quota_paused_message(&quota)))
+                    } else if !pending_paths.is_empty() || !self.deferred_paths.is_empty() {
+                        match self.create_checkpoint(&pending_paths, true).await {
+                            Ok(Some(checkpoint_id)) => {
                                 tracing::info!("Flushed checkpoint: {}", checkpoint_id);
 
                                 // Update status
@@ -256,13 +343,22 @@ impl Daemon {
                                 status.last_checkpoint_time = Some(current_timestamp_ms());
 
                                 pending_paths.clear();
-                                last_checkpoint = Instant::now();
+                                schedule.checkpointed(Instant::now());
+                                self.quiescence.reset();
+                                status.deferral_reason = None;
 
                                 // Clear saved pending paths after successful checkpoint
                                 clear_pending_paths(&tl_dir);
 
                                 Ok(Some(checkpoint_id.to_string()))
                             }
+                            Ok(None) => {
+                                // Nothing changed after all
+                                pending_paths.clear();
+                                schedule.cleared();
+                                clear_pending_paths(&tl_dir);
+                                Ok(None)
+                            }
                             Err(e) => {
                                 tracing::error!("Flush checkpoint failed: {}", e);
                                 // Save pending paths in case of crash
@@ -293,12 +389,14 @@ impl Daemon {
 
                         // Run GC in background to avoid blocking the event loop
                         let tl_dir_clone = tl_dir.clone();
+                        let journal_clone = Arc::clone(&self.journal);
                         let store_clone = Arc::clone(&self.store);
                         let system_config_clone = self.system_config.clone();
                         let checkpoint_count_cache = Arc::clone(&self.checkpoint_count_cache);
 
+                        let policy = system_config_clone.gc.to_retention_policy();
                         tokio::spawn(async move {
-                            if let Err(e) = run_auto_gc(&tl_dir_clone, &store_clone, &system_config_clone, checkpoint_count_cache).await {
+                            if let Err(e) = run_auto_gc(&tl_dir_clone, &journal_clone, &store_clone, &policy, checkpoint_count_cache).await {
                                 tracing::error!("Auto-GC failed: {}", e);
                             }
                         });
@@ -512,6 +610,30 @@ impl Daemon {
 
                                     Ok(IpcResponse::LogData { count, checkpoints })
                                 }
+                                IpcRequest::GetAncestors { id, limit } => {
+                                    match Ulid::from_string(&id) {
+                                        Ok(id) => match journal.ancestors(&id, limit) {
+                                            Ok(checkpoints) => Ok(IpcResponse::Checkpoints(checkpoints)),
+                                            Err(e) => Ok(IpcResponse::Error(e.to_string())),
+                                        },
+                                        Err(e) => Ok(IpcResponse::Error(e.to_string())),
+                                    }
+                                }
+                                IpcRequest::GetLogGraph { limit } => {
+                                    let count = checkpoint_count_cache.load(Ordering::Relaxed);
+
+                                    // Walk every parent from HEAD, newest first
+                                    let checkpoints = match journal.latest() {
+                                        Ok(Some(head)) => match journal.ancestors(&head.id, Some(limit.unwrap_or(20))) {
+                                            Ok(checkpoints) => checkpoints,
+                                            Err(e) => return Ok(IpcResponse::Error(e.to_string())),
+                                        },
+                                        Ok(None) => Vec::new(),
+                                        Err(e) => return Ok(IpcResponse::Error(e.to_string())),
+                                    };
+
+                                    Ok(IpcResponse::LogData { count, checkpoints })
+                                }
                                 IpcRequest::ResolveCheckpointRefs(refs) => {
                                     // Resolve each reference (full ULID, short prefix, or pin name)
                                     let tl_dir = store.tl_dir();
@@ -656,6 +778,18 @@ impl Daemon {
 
                                     Ok(IpcResponse::Ok)
                                 }
+                                IpcRequest::AppendCheckpoint(checkpoint) => {
+                                    // Checkpoints built outside the daemon (e.g. merges) must go
+                                    // through here since the daemon holds the journal open
+                                    if let Err(e) = journal.append(&checkpoint) {
+                                        return Ok(IpcResponse::Error(format!("Failed to append checkpoint: {}", e)));
+                                    }
+
+                                    checkpoint_count_cache.fetch_add(1, Ordering::Relaxed);
+
+                                    tracing::info!("Appended {:?} checkpoint: {}", checkpoint.reason, checkpoint.id);
+                                    Ok(IpcResponse::Ok)
+                                }
                             }
                         };
 
@@ -695,23 +829,101 @@ impl Daemon {
     }
 
     /// Create a checkpoint from dirty paths
-    async fn create_checkpoint(&mut self, dirty_paths: &HashSet<Arc<Path>>) -> Result<Ulid> {
+    ///
+    /// `manual` is true for explicit flushes: paths with the `manual` policy
+    /// deferred by earlier automatic checkpoints are included, otherwise they
+    /// are set aside until the next flush.
+    ///
+    /// Returns None if every dirty path was deferred, or if the resulting
+    /// tree is the one HEAD already has (a touch, a save without changes, an
+    /// edit undone within the interval). With `collapse_oscillation_secs`
+    /// set, a tree equal to HEAD's parent (A→B→A) drops B instead.
+    async fn create_checkpoint(&mut self, dirty_paths: &HashSet<Arc<Path>>, manual: bool) -> Result<Option<Ulid>> {
+        // A changed .tlpolicy applies from this checkpoint on
+        if dirty_paths.iter().any(|p| p.as_ref() == Path::new(".tlpolicy")) {
+            self.path_policies = load_path_policies(self.store.root());
+        }
+
+        let mut dirty_paths = dirty_paths.clone();
+        if manual {
+            // Deferred paths stay queued until a checkpoint accounts for them
+            dirty_paths.extend(self.deferred_paths.iter().cloned());
+        } else {
+            let policies = &self.path_policies;
+            let (deferred, rest): (HashSet<_>, HashSet<_>) = dirty_paths.into_iter()
+                .partition(|p| policies.policy_for(p) == PathPolicy::ManualOnly);
+            if !deferred.is_empty() {
+                // Their event summaries aren't kept until the flush
+                for path in &deferred {
+                    self.provenance.remove(path);
+                }
+                self.deferred_paths.extend(deferred);
+                self.save_deferred_paths();
+            }
+            dirty_paths = rest;
+        }
+        if dirty_paths.is_empty() {
+            tracing::info!("All changed paths are manual-only, deferred until flush");
+            return Ok(None);
+        }
+
         // Convert Arc<Path> to &Path
         let paths: Vec<&Path> = dirty_paths.iter().map(|p| p.as_ref()).collect();
+        let touched = paths.clone();
 
         // Calculate bytes statistics before update
         let (bytes_added, bytes_removed) = self.calculate_bytes_statistics(&paths)?;
 
         // Use incremental update algorithm
-        let (new_map, _tree, tree_hash) = incremental_update(
+        let options = UpdateOptions {
+            policies: Some(&self.path_policies),
+            scanner: Some(&self.secret_scanner),
+            manual,
+            ..UpdateOptions::default()
+        };
+        let (new_map, _tree, tree_hash) = incremental_update_with_options(
             &self.pathmap,
             paths,
             self.store.root(),
             &self.store,
+            options,
         )?;
 
         // Get parent checkpoint
-        let parent_id = self.journal.latest()?.map(|cp| cp.id);
+        let head = self.journal.latest()?;
+        let parent_id = head.as_ref().map(|cp| cp.id);
+
+        // Content-identical to HEAD: nothing to record
+        if let Some(head) = head.as_ref().filter(|cp| cp.root_tree == tree_hash) {
+            tracing::debug!("Tree unchanged from {} - no checkpoint needed", head.id);
+            self.status.write().await.checkpoints_suppressed += 1;
+            self.adopt_pathmap(new_map, &dirty_paths)?;
+            return Ok(None);
+        }
+
+        // Back to the tree before HEAD: HEAD was a transient state
+        if let Some(head) = head.as_ref() {
+            if self.is_oscillation(head, tree_hash)? {
+                tracing::info!("Tree reverted to {} - dropping transient checkpoint {}", head.parent.unwrap_or(head.id), head.id);
+                self.journal.delete(&head.id)?;
+                self.checkpoint_count_cache.fetch_sub(1, Ordering::Relaxed);
+                {
+                    let mut status = self.status.write().await;
+                    status.checkpoints_suppressed += 1;
+                    status.oscillations_collapsed += 1;
+                }
+                self.adopt_pathmap(new_map, &dirty_paths)?;
+                return Ok(None);
+            }
+        }
+
+        // What happened to each path, and how many events it took
+        let changes = path_changes(&self.pathmap, &new_map, &touched, |path| {
+            self.provenance.get(path).map_or_else(Observed::default, |p| Observed {
+                events: p.events,
+                metadata: p.kind == EventKind::Modify(ModifyKind::Metadata),
+            })
+        });
 
         // Create checkpoint metadata
         let meta = CheckpointMeta {
@@ -727,24 +939,163 @@ impl Daemon {
             CheckpointReason::FsBatch,
             dirty_paths.iter().map(|p| p.to_path_buf()).collect(),
             meta,
-        );
+        )
+        .with_changes(changes);
 
         // Append to journal
         self.journal.append(&checkpoint)?;
+        if let Err(e) = self.store.usage().persist() {
+            tracing::warn!("Failed to save store usage: {}", e);
+        }
 
         // Update checkpoint count cache atomically
         self.checkpoint_count_cache.fetch_add(1, Ordering::Relaxed);
 
+        self.adopt_pathmap(new_map, &dirty_paths)?;
+
+        Ok(Some(checkpoint.id))
+    }
+
+    /// Make `new_map` the current state once `dirty_paths` are accounted for
+    fn adopt_pathmap(&mut self, new_map: PathMap, dirty_paths: &HashSet<Arc<Path>>) -> Result<()> {
+        let deferred_before = self.deferred_paths.len();
+        for path in dirty_paths {
+            self.provenance.remove(path);
+            self.deferred_paths.remove(path);
+        }
+        if self.deferred_paths.len() != deferred_before {
+            self.save_deferred_paths();
+        }
+
         // Update pathmap (atomic swap)
-        self.pathmap = new_map;
+        self.pathmap = Arc::new(new_map);
 
         // Save pathmap to disk
         save_pathmap(&self.store.tl_dir().join("state/pathmap.bin"), &self.pathmap)?;
 
-        // Mark checkpoint time for watcher overflow recovery
+        // Mark checkpoint time and stat data for watcher overflow recovery
         self.watcher.mark_checkpoint(SystemTime::now());
+        self.watcher.set_stat_cache(self.pathmap.clone());
+
+        Ok(())
+    }
 
-        Ok(checkpoint.id)
+    /// Persist `deferred_paths` so manual-only changes survive a restart
+    fn save_deferred_paths(&self) {
+        if let Err(e) = save_deferred_paths(self.store.tl_dir(), &self.deferred_paths) {
+            tracing::warn!("Failed to save deferred paths: {}", e);
+        }
+    }
+
+    /// Whether `tree_hash` undoes HEAD within the oscillation window
+    ///
+    /// Only an unreferenced, childless automatic checkpoint directly after
+    /// its parent is dropped, so deleting it makes the parent HEAD again.
+    fn is_oscillation(&self, head: &Checkpoint, tree_hash: Sha1Hash) -> Result<bool> {
+        let window_ms = self.system_config.daemon.collapse_oscillation_secs * 1000;
+        if window_ms == 0
+            || head.reason != CheckpointReason::FsBatch
+            || head.is_merge()
+            || current_timestamp_ms().saturating_sub(head.ts_unix_ms) > window_ms
+        {
+            return Ok(false);
+        }
+
+        let Some(parent_id) = head.parent else { return Ok(false) };
+        match self.journal.get(&parent_id)? {
+            Some(parent) if parent.root_tree == tree_hash => {}
+            _ => return Ok(false),
+        }
+
+        let previous = self.journal.last_n(2)?;
+        if previous.len() != 2 || previous[0].id != parent_id || !self.journal.children(&head.id).is_empty() {
+            return Ok(false);
+        }
+
+        Ok(!is_held(self.store.tl_dir(), self.store.root(), head.id))
+    }
+
+    /// Move the watcher's event summaries for a batch into the daemon
+    ///
+    /// Summaries live in memory only: paths restored from
+    /// `pending_paths.json` after a crash, and manual-only paths deferred
+    /// to a flush, are checkpointed without counts.
+    fn collect_provenance(&mut self, batch: &[Arc<Path>]) {
+        for path in batch {
+            let Some(later) = self.watcher.take_provenance(path) else { continue };
+            match self.provenance.remove(path) {
+                Some(earlier) => {
+                    if let Some(merged) = earlier.then(later) {
+                        self.provenance.insert(Arc::clone(path), merged);
+                    }
+                }
+                None => {
+                    self.provenance.insert(Arc::clone(path), later);
+                }
+            }
+        }
+    }
+
+    /// Copy the watcher backend's diagnostics into the daemon status
+    fn apply_watcher_diagnostics(&self, status: &mut DaemonStatus) {
+        if let Some(diagnostics) = self.watcher.diagnostics() {
+            status.watcher_backend = diagnostics.backend;
+            status.watches = diagnostics.watches;
+            status.watch_limit = diagnostics.watch_limit;
+            status.unwatched_dirs = diagnostics.unwatched_dirs;
+        }
+    }
+
+    /// Measure `.tl/` against its quota, starting emergency GC if needed
+    ///
+    /// Updates the quota fields of the daemon status. Returns None when no
+    /// quota is configured, or when free space cannot be measured (a failed
+    /// `statvfs` must not read as a full disk and pause checkpointing).
+    async fn check_quota(&self) -> Option<QuotaStatus> {
+        let config = self.quota.as_ref()?;
+        let tl_dir = self.store.tl_dir();
+        let usage = self.store.usage().bytes();
+        let available = match util::available_space(tl_dir) {
+            Ok(available) => available,
+            Err(e) => {
+                tracing::warn!("Cannot measure free space for the quota check, skipping it: {:#}", e);
+                self.status.write().await.checkpointing_paused = false;
+                return None;
+            }
+        };
+        let quota = config.check(usage, available);
+
+        {
+            let mut status = self.status.write().await;
+            status.store_bytes = usage;
+            status.quota_bytes = Some(quota.budget);
+            status.checkpointing_paused = quota.level == QuotaLevel::Exceeded;
+        }
+
+        if quota.level >= QuotaLevel::Critical && !self.emergency_gc_running.swap(true, Ordering::SeqCst) {
+            tracing::warn!(
+                "Store is at {} of its {} quota - running emergency GC",
+                util::format_size(quota.usage),
+                util::format_size(quota.budget)
+            );
+
+            let tl_dir = tl_dir.to_path_buf();
+            let journal = Arc::clone(&self.journal);
+            let store = Arc::clone(&self.store);
+            let policy = self.system_config.gc.to_retention_policy();
+            let config = config.clone();
+            let checkpoint_count_cache = Arc::clone(&self.checkpoint_count_cache);
+            let running = Arc::clone(&self.emergency_gc_running);
+
+            tokio::spawn(async move {
+                if let Err(e) = run_emergency_gc(&tl_dir, &journal, &store, &policy, &config, checkpoint_count_cache).await {
+                    tracing::error!("Emergency GC failed: {}", e);
+                }
+                running.store(false, Ordering::SeqCst);
+            });
+        }
+
+        Some(quota)
     }
 
     /// Calculate bytes added and removed for changed paths
@@ -802,10 +1153,16 @@ impl Daemon {
         if !pending.is_empty() {
             tracing::info!("Flushing {} pending paths", pending.len());
             let pending_set: HashSet<_> = pending.into_iter().collect();
-            if let Err(e) = self.create_checkpoint(&pending_set).await {
+            let over_quota = self.check_quota().await
+                .filter(|quota| quota.level == QuotaLevel::Exceeded);
+            if let Some(quota) = over_quota {
+                tracing::warn!("{}", quota_paused_message(&quota));
+                let _ = save_pending_paths(self.store.tl_dir(), &pending_set);
+            } else if let Err(e) = self.create_checkpoint(&pending_set, false).await {
                 tracing::error!("Failed to create final checkpoint: {}", e);
             }
         }
+        let _ = self.store.usage().persist();
 
         // 2. Stop watcher
         self.watcher.stop().await?;
@@ -817,6 +1174,74 @@ impl Daemon {
     }
 }
 
+/// Load `.tlpolicy`, falling back to full fidelity for every path
+fn load_path_policies(repo_root: &Path) -> PathPolicies {
+    PathPolicies::load(repo_root).unwrap_or_else(|e| {
+        tracing::warn!("Failed to load .tlpolicy, keeping every version: {}", e);
+        PathPolicies::default()
+    })
+}
+
+/// Load the `[secrets]` scanner, falling back to no scanning
+fn load_secret_scanner(tl_dir: &Path) -> SecretScanner {
+    SecretScanner::load(tl_dir).unwrap_or_else(|e| {
+        tracing::warn!("Failed to load [secrets] config, secret scanning disabled: {}", e);
+        SecretScanner::empty()
+    })
+}
+
+/// Build the watcher configuration from `[watcher]` in config.toml
+///
+/// `backend` is auto | native | poll | fanotify; `poll_interval_ms` and
+/// `max_poll_interval_ms` bound the polling backend's adaptive interval.
+/// `record_events` names a trace file for raw events (relative to `.tl/`).
+pub(crate) fn load_watcher_config(tl_dir: &Path) -> WatcherConfig {
+    let mut config = WatcherConfig::default();
+
+    let section = fs::read_to_string(tl_dir.join("config.toml")).ok()
+        .and_then(|content| content.parse::<toml::Value>().ok())
+        .and_then(|value| value.get("watcher").cloned());
+    let section = match section {
+        Some(section) => section,
+        None => return config,
+    };
+
+    if let Some(backend) = section.get("backend").and_then(|v| v.as_str()) {
+        match backend.parse::<WatcherBackend>() {
+            Ok(backend) => config.backend = backend,
+            Err(e) => tracing::warn!("{} - using auto", e),
+        }
+    }
+    if let Some(ms) = section.get("poll_interval_ms").and_then(|v| v.as_integer()) {
+        config.poll_interval = Duration::from_millis(ms.max(10) as u64);
+    }
+    if let Some(ms) = section.get("max_poll_interval_ms").and_then(|v| v.as_integer()) {
+        config.max_poll_interval = Duration::from_millis(ms.max(10) as u64);
+    }
+    if let Some(trace) = section.get("record_events").and_then(|v| v.as_str()).filter(|s| !s.is_empty()) {
+        config.record_events = Some(tl_dir.join(trace));
+    }
+
+    config
+}
+
+/// Load the `[quota]` budget, falling back to unlimited
+fn load_quota(tl_dir: &Path) -> Option<QuotaConfig> {
+    QuotaConfig::load(tl_dir).unwrap_or_else(|e| {
+        tracing::warn!("Failed to load [quota] config, storage is unlimited: {}", e);
+        None
+    })
+}
+
+/// Message logged and returned to `tl flush` while over quota
+fn quota_paused_message(quota: &QuotaStatus) -> String {
+    format!(
+        "Checkpointing paused: .tl uses {} of its {} quota. Free space with 'tl gc' or raise [quota] limit in .tl/config.toml",
+        util::format_size(quota.usage),
+        util::format_size(quota.budget)
+    )
+}
+
 /// Start daemon in background, returns immediately after spawning
 /// Logs are redirected to .tl/logs/daemon.log
 pub(crate) async fn start_background_internal(repo_root: &Path) -> Result<()> {
@@ -933,17 +1358,25 @@ async fn start_daemon_direct(repo_root: &Path) -> Result<()> {
     let store = Arc::new(
         Store::open(&repo_root).context("Failed to open store")?,
     );
+    // Re-measure .tl/ so quota tracking starts from the real figure
+    if let Err(e) = store.usage().rescan() {
+        tracing::warn!("Failed to measure store usage: {}", e);
+    }
     let journal = Arc::new(
-        Journal::open(&tl_dir.join("journal")).context("Failed to open journal")?,
+        Journal::open_with_cipher(&tl_dir.join("journal"), store.cipher().cloned()).context("Failed to open journal")?,
     );
 
     // Load or create initial pathmap
-    let pathmap = load_or_create_pathmap(&tl_dir, &journal)?;
+    let pathmap = Arc::new(load_or_create_pathmap(&tl_dir, &journal, &store)?);
 
     // 5. Initialize watcher
-    let mut watcher = Watcher::new(&repo_root)
+    let mut watcher = Watcher::with_config(&repo_root, load_watcher_config(&tl_dir))
         .context("Failed to create watcher")?;
+    watcher.set_stat_cache(pathmap.clone());
     watcher.start().await.context("Failed to start watcher")?;
+    if let Some(diagnostics) = watcher.diagnostics() {
+        tracing::info!("Watcher backend: {} ({})", diagnostics.backend, diagnostics.platform_info);
+    }
 
     // 6. Create daemon status and channels
     let (shutdown_tx, shutdown_rx) = broadcast::channel(1);
@@ -962,6 +1395,17 @@ async fn start_daemon_direct(repo_root: &Path) -> Result<()> {
         last_checkpoint_time: None,
         watcher_paths: 0,
         checkpoints_skipped: 0,
+        store_bytes: store.usage().bytes(),
+        quota_bytes: None,
+        checkpointing_paused: false,
+        watcher_backend: String::new(),
+        watches: 0,
+        watch_limit: None,
+        unwatched_dirs: 0,
+        checkpoints_deferred: 0,
+        deferral_reason: None,
+        checkpoints_suppressed: 0,
+        oscillations_collapsed: 0,
     }));
 
     // Initialize checkpoint count cache
@@ -975,6 +1419,7 @@ async fn start_daemon_direct(repo_root: &Path) -> Result<()> {
         .context("Failed to start IPC server")?;
 
     // 8. Create and run daemon
+    let quiescence = QuiescenceDetector::new(repo_root, &system_config.daemon);
     let daemon = Daemon {
         store,
         journal,
@@ -989,6 +1434,13 @@ async fn start_daemon_direct(repo_root: &Path) -> Result<()> {
         status,
         checkpoint_count_cache,
         system_config,
+        path_policies: load_path_policies(repo_root),
+        deferred_paths: HashSet::new(),
+        provenance: HashMap::new(),
+        secret_scanner: load_secret_scanner(&repo_root.join(".tl")),
+        quota: load_quota(&tl_dir),
+        emergency_gc_running: Arc::new(AtomicBool::new(false)),
+        quiescence,
     };
 
     daemon.run().await?;
@@ -1054,36 +1506,33 @@ async fn is_running_impl(tl_dir: &Path) -> bool {
 }
 
 /// Load pathmap from disk or create new one
-fn load_or_create_pathmap(tl_dir: &Path, journal: &Journal) -> Result<PathMap> {
+fn load_or_create_pathmap(tl_dir: &Path, journal: &Journal, store: &Store) -> Result<PathMap> {
     let pathmap_path = tl_dir.join("state/pathmap.bin");
+    let head_tree = journal.latest()?.map(|cp| cp.root_tree);
 
     if pathmap_path.exists() {
         // Load existing pathmap
         match PathMap::load(&pathmap_path) {
-            Ok(map) => {
+            Ok(map) if head_tree.is_none_or(|tree| tree == map.root_tree) => {
                 tracing::info!("Loaded pathmap with {} entries", map.len());
-                Ok(map)
+                return Ok(map);
+            }
+            Ok(_) => {
+                // HEAD moved while the daemon was down (e.g. history rewritten by purge)
+                tracing::info!("Saved pathmap does not match HEAD - rebuilding");
+                return rebuild_pathmap_from_head(tl_dir, journal, store);
             }
             Err(e) => {
                 tracing::warn!("Failed to load pathmap ({}), creating new", e);
-                // Get root tree hash from latest checkpoint, or use zero hash
-                let root_hash = journal
-                    .latest()?
-                    .map(|cp| cp.root_tree)
-                    .unwrap_or_else(|| tl_core::hash::Sha1Hash::from_bytes([0u8; 20]));
-                Ok(PathMap::new(root_hash))
             }
         }
     } else {
-        // Create new pathmap
         tracing::info!("Creating new pathmap");
-        // Get root tree hash from latest checkpoint, or use zero hash
-        let root_hash = journal
-            .latest()?
-            .map(|cp| cp.root_tree)
-            .unwrap_or_else(|| tl_core::hash::Sha1Hash::from_bytes([0u8; 20]));
-        Ok(PathMap::new(root_hash))
     }
+
+    // Get root tree hash from latest checkpoint, or use zero hash
+    let root_hash = head_tree.unwrap_or_else(|| tl_core::hash::Sha1Hash::from_bytes([0u8; 20]));
+    Ok(PathMap::new(root_hash))
 }
 
 /// Save pathmap to disk
@@ -1142,8 +1591,50 @@ fn pending_paths_file(tl_dir: &Path) -> PathBuf {
 /// CRITICAL: This ensures that if the daemon crashes during checkpoint creation,
 /// the pending paths are not lost and can be recovered on restart.
 fn save_pending_paths(tl_dir: &Path, paths: &HashSet<Arc<Path>>) -> Result<()> {
-    let file_path = pending_paths_file(tl_dir);
+    save_path_set(&pending_paths_file(tl_dir), paths)?;
+    tracing::debug!("Saved {} pending paths to disk", paths.len());
+    Ok(())
+}
+
+/// Load pending paths from disk (for crash recovery)
+fn load_pending_paths(tl_dir: &Path) -> HashSet<Arc<Path>> {
+    let paths = load_path_set(&pending_paths_file(tl_dir));
+    if !paths.is_empty() {
+        tracing::info!("Recovered {} pending paths from previous crash", paths.len());
+    }
+    paths
+}
+
+/// Path to the manual-only paths waiting for the next flush
+fn deferred_paths_file(tl_dir: &Path) -> PathBuf {
+    tl_dir.join("state/deferred_paths.json")
+}
+
+/// Save manual-only deferred paths so a restart doesn't drop them
+///
+/// An empty set removes the file.
+fn save_deferred_paths(tl_dir: &Path, paths: &HashSet<Arc<Path>>) -> Result<()> {
+    let file_path = deferred_paths_file(tl_dir);
+    if paths.is_empty() {
+        if file_path.exists() {
+            std::fs::remove_file(&file_path)?;
+        }
+        return Ok(());
+    }
+    save_path_set(&file_path, paths)
+}
+
+/// Load manual-only deferred paths saved by a previous run
+fn load_deferred_paths(tl_dir: &Path) -> HashSet<Arc<Path>> {
+    let paths = load_path_set(&deferred_paths_file(tl_dir));
+    if !paths.is_empty() {
+        tracing::info!("Recovered {} manual-only paths awaiting flush", paths.len());
+    }
+    paths
+}
 
+/// Write a set of paths as a JSON list
+fn save_path_set(file_path: &Path, paths: &HashSet<Arc<Path>>) -> Result<()> {
     // Ensure parent directory exists
     if let Some(parent) = file_path.parent() {
         std::fs::create_dir_all(parent)?;
@@ -1156,42 +1647,31 @@ fn save_pending_paths(tl_dir: &Path, paths: &HashSet<Arc<Path>>) -> Result<()> {
         .collect();
 
     let json = serde_json::to_string(&paths_vec)?;
-    std::fs::write(&file_path, json)?;
-
-    tracing::debug!("Saved {} pending paths to disk", paths.len());
+    std::fs::write(file_path, json)?;
     Ok(())
 }
 
-/// Load pending paths from disk (for crash recovery)
-fn load_pending_paths(tl_dir: &Path) -> HashSet<Arc<Path>> {
-    let file_path = pending_paths_file(tl_dir);
-
+/// Read a set of paths written by `save_path_set`, empty if missing or unreadable
+fn load_path_set(file_path: &Path) -> HashSet<Arc<Path>> {
     if !file_path.exists() {
         return HashSet::new();
     }
 
-    match std::fs::read_to_string(&file_path) {
+    match std::fs::read_to_string(file_path) {
         Ok(json) => {
             match serde_json::from_str::<Vec<String>>(&json) {
-                Ok(paths_vec) => {
-                    let paths: HashSet<Arc<Path>> = paths_vec
-                        .into_iter()
-                        .map(|s| Arc::from(PathBuf::from(s).as_path()))
-                        .collect();
-
-                    if !paths.is_empty() {
-                        tracing::info!("Recovered {} pending paths from previous crash", paths.len());
-                    }
-                    paths
-                }
+                Ok(paths_vec) => paths_vec
+                    .into_iter()
+                    .map(|s| Arc::from(PathBuf::from(s).as_path()))
+                    .collect(),
                 Err(e) => {
-                    tracing::warn!("Failed to parse pending paths: {}", e);
+                    tracing::warn!("Failed to parse {}: {}", file_path.display(), e);
                     HashSet::new()
                 }
             }
         }
         Err(e) => {
-            tracing::warn!("Failed to read pending paths file: {}", e);
+            tracing::warn!("Failed to read {}: {}", file_path.display(), e);
             HashSet::new()
         }
     }
@@ -1256,8 +1736,9 @@ fn rebuild_pathmap_from_head(
 /// SAFETY: Uses proper locking to ensure exclusive access during GC.
 async fn run_auto_gc(
     tl_dir: &Path,
+    journal: &Journal,
     store: &Store,
-    config: &SystemConfig,
+    policy: &RetentionPolicy,
     checkpoint_count_cache: Arc<AtomicUsize>,
 ) -> Result<()> {
     use std::time::Instant;
@@ -1265,26 +1746,125 @@ async fn run_auto_gc(
     let start = Instant::now();
     tracing::info!("Starting auto-GC... (this will pause checkpoint creation)");
 
+    let metrics = match collect_garbage(tl_dir, journal, store, policy, &checkpoint_count_cache)? {
+        Some(metrics) => metrics,
+        None => return Ok(()),
+    };
+
+    let duration = start.elapsed();
+
+    if metrics.checkpoints_deleted > 0 || metrics.blobs_deleted > 0 {
+        tracing::info!(
+            "Auto-GC completed in {:?}: {} checkpoints ({} compacted), {} trees, {} blobs deleted ({:.2} MB freed)",
+            duration,
+            metrics.checkpoints_deleted,
+            metrics.checkpoints_compacted,
+            metrics.trees_deleted,
+            metrics.blobs_deleted,
+            metrics.bytes_freed as f64 / (1024.0 * 1024.0)
+        );
+    } else {
+        tracing::debug!("Auto-GC completed in {:?}: no garbage found", duration);
+    }
+
+    Ok(())
+}
+
+/// Emergency GC when `.tl/` nears its quota
+///
+/// Halves the retention window and count step by step until usage is back
+/// under `gc_at`, or the policy floor is reached. Pins and workspace
+/// checkpoints are always kept.
+async fn run_emergency_gc(
+    tl_dir: &Path,
+    journal: &Journal,
+    store: &Store,
+    base_policy: &RetentionPolicy,
+    quota: &QuotaConfig,
+    checkpoint_count_cache: Arc<AtomicUsize>,
+) -> Result<()> {
+    for step in 1..=EMERGENCY_GC_MAX_STEPS {
+        let policy = base_policy.tightened(step);
+        tracing::warn!(
+            "Emergency GC step {}: keeping {} checkpoints / {} minutes",
+            step,
+            policy.retain_dense_count,
+            policy.retain_dense_window_ms / 60_000
+        );
+
+        let metrics = match collect_garbage(tl_dir, journal, store, &policy, &checkpoint_count_cache)? {
+            Some(metrics) => metrics,
+            None => return Ok(()), // Another GC holds the lock
+        };
+
+        let available = match util::available_space(tl_dir) {
+            Ok(available) => available,
+            Err(e) => {
+                tracing::warn!("Cannot measure free space, stopping emergency GC: {:#}", e);
+                return Ok(());
+            }
+        };
+        let status = quota.check(store.usage().bytes(), available);
+        tracing::info!(
+            "Emergency GC step {} freed {}; store is at {} of {}",
+            step,
+            util::format_size(metrics.bytes_freed),
+            util::format_size(status.usage),
+            util::format_size(status.budget)
+        );
+        if status.level < QuotaLevel::Critical {
+            return Ok(());
+        }
+    }
+
+    tracing::error!(
+        "Emergency GC could not bring .tl under its quota - pinned and workspace checkpoints are kept; \
+         unpin checkpoints, run 'tl purge' or raise [quota] limit"
+    );
+    Ok(())
+}
+
+/// Whether a pin, workspace, stash or JJ mapping points at `id`
+///
+/// Uses the same references GC keeps live. A lookup that fails counts as
+/// held, so an oscillation is never collapsed on a guess.
+fn is_held(tl_dir: &Path, repo_root: &Path, id: Ulid) -> bool {
+    let held = || -> Result<bool> {
+        let pinned = PinManager::new(tl_dir).get_pinned_checkpoints()?;
+        Ok(pinned.contains(&id) || util::referenced_checkpoints(tl_dir, repo_root)?.contains(&id))
+    };
+    held().unwrap_or_else(|e| {
+        tracing::warn!("Keeping checkpoint {} - could not check references: {}", id, e);
+        true
+    })
+}
+
+/// One GC pass under the GC lock, re-measuring store usage afterwards
+///
+/// Runs against the daemon's own journal handle: sled locks the database
+/// exclusively, so a second `Journal::open` here would always fail.
+/// Returns None if another GC holds the lock.
+fn collect_garbage(
+    tl_dir: &Path,
+    journal: &Journal,
+    store: &Store,
+    policy: &RetentionPolicy,
+    checkpoint_count_cache: &AtomicUsize,
+) -> Result<Option<GcMetrics>> {
     // Try to acquire GC lock - if we can't, another GC is running
     let gc_lock = match GcLock::try_acquire(tl_dir) {
         Ok(lock) => lock,
         Err(e) => {
             tracing::debug!("Auto-GC skipped - could not acquire lock: {}", e);
-            return Ok(());
+            return Ok(None);
         }
     };
 
-    // Open journal with write access (we hold the GC lock, so this is safe)
-    let journal_path = tl_dir.join("journal");
-    let journal = Journal::open(&journal_path)
-        .context("Failed to open journal for auto-GC")?;
-
-    // Collect workspace checkpoints for protection
-    let workspace_checkpoints = collect_workspace_checkpoints(tl_dir, store.root())?;
+    // Workspaces, stashes and JJ mappings keep their checkpoints out of compacted runs
+    let referenced = util::referenced_checkpoints(tl_dir, store.root())?;
 
-    // Create GC with configured retention policy
-    let policy = config.gc.to_retention_policy();
-    let gc = GarbageCollector::new(policy);
+    // Create GC with the given retention policy
+    let gc = GarbageCollector::new(policy.clone()).with_path_policies(load_path_policies(store.root()));
 
     // Create pin manager
     let pin_manager = PinManager::new(tl_dir);
@@ -1292,47 +1872,153 @@ async fn run_auto_gc(
     // Run GC
     // Note: GC is a blocking operation. In practice, it should complete in seconds,
     // but we monitor via logging. The checkpoint pause is inherent to the safety model.
-    let metrics = gc.collect(&journal, store, &pin_manager, workspace_checkpoints.as_ref())?;
+    let metrics = gc.collect(journal, store, &pin_manager, Some(&referenced))?;
 
     // Update checkpoint count cache
     let new_count = journal.count();
     checkpoint_count_cache.store(new_count, Ordering::Relaxed);
 
+    // GC deletes trees and journal entries behind the store's back
+    store.usage().rescan()?;
+
     // Drop lock
     drop(gc_lock);
 
-    let duration = start.elapsed();
+    Ok(Some(metrics))
+}
 
-    if metrics.checkpoints_deleted > 0 || metrics.blobs_deleted > 0 {
-        tracing::info!(
-            "Auto-GC completed in {:?}: {} checkpoints, {} trees, {} blobs deleted ({:.2} MB freed)",
-            duration,
-            metrics.checkpoints_deleted,
-            metrics.trees_deleted,
-            metrics.blobs_deleted,
-            metrics.bytes_freed as f64 / (1024.0 * 1024.0)
-        );
-    } else {
-        tracing::debug!("Auto-GC completed in {:?}: no garbage found", duration);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tl_core::{Entry, QuotaLimit, Tree};
+
+    /// Append a first-parent chain of `len` checkpoints, all but the last two expired
+    fn expired_chain(journal: &Journal, store: &Store, len: u64) -> Result<Vec<Checkpoint>> {
+        let now_ms = current_timestamp_ms();
+        let mut chain: Vec<Checkpoint> = Vec::new();
+        for i in 0..len {
+            let content = format!("version {}", i);
+            let blob = tl_core::hash::git::hash_blob(content.as_bytes());
+            store.blob_store().write_blob(blob, content.as_bytes())?;
+            let mut tree = Tree::new();
+            tree.insert(Path::new("a.txt"), Entry::file(0o100644, blob));
+            let root_tree = store.write_tree(&tree)?;
+
+            let meta = CheckpointMeta { files_changed: 1, bytes_added: 10, bytes_removed: 0 };
+            let mut checkpoint = Checkpoint::new(
+                chain.last().map(|cp| cp.id),
+                root_tree,
+                CheckpointReason::FsBatch,
+                vec![PathBuf::from("a.txt")],
+                meta,
+            );
+            let age_ms = if i + 2 < len { (48 - i) * 60 * 60 * 1000 } else { 0 };
+            checkpoint.ts_unix_ms = now_ms - age_ms + i;
+            checkpoint.id = Ulid::from_parts(checkpoint.ts_unix_ms, Ulid::new().random());
+            journal.append(&checkpoint)?;
+            chain.push(checkpoint);
+        }
+        Ok(chain)
     }
 
-    Ok(())
-}
+    #[tokio::test]
+    async fn test_emergency_gc_uses_open_journal() -> Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let store = Store::init(temp_dir.path())?;
+        let tl_dir = store.tl_dir().to_path_buf();
+
+        // Held open for the whole run, as the daemon does
+        let journal = Journal::open(&tl_dir.join("journal"))?;
+        let chain = expired_chain(&journal, &store, 8)?;
+        let checkpoint_count_cache = Arc::new(AtomicUsize::new(journal.count()));
+
+        // A one-byte budget keeps the store over quota, so every step runs
+        let quota = QuotaConfig { limit: QuotaLimit::Bytes(1), warn_at: 80, gc_at: 90 };
+        let policy = RetentionPolicy { retain_dense_count: 2, retain_dense_window_ms: 60 * 60 * 1000, retain_pins: true };
+        run_emergency_gc(&tl_dir, &journal, &store, &policy, &quota, Arc::clone(&checkpoint_count_cache)).await?;
 
-/// Collect workspace checkpoints that should be protected from GC
-fn collect_workspace_checkpoints(tl_dir: &Path, repo_root: &Path) -> Result<Option<HashSet<Ulid>>> {
-    if jj::detect_jj_workspace(repo_root)?.is_none() {
-        return Ok(None);
+        // The expired run was folded, through the same handle
+        assert_eq!(journal.count(), 3);
+        assert_eq!(checkpoint_count_cache.load(Ordering::Relaxed), 3);
+        assert!(journal.get(&chain[7].id)?.is_some());
+        assert!(journal.get(&chain[0].id)?.is_none());
+
+        Ok(())
     }
 
-    let ws_manager = jj::WorkspaceManager::open(tl_dir, repo_root)?;
-    let mut checkpoints = HashSet::new();
+    #[test]
+    fn test_deferred_paths_round_trip() -> Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let tl_dir = temp_dir.path();
 
-    for state in ws_manager.list_states()? {
-        if let Some(cp_id) = state.current_checkpoint {
-            checkpoints.insert(cp_id);
-        }
+        let deferred: HashSet<Arc<Path>> = ["notes/draft.md", "scratch.txt"]
+            .iter()
+            .map(|p| Arc::from(Path::new(p)))
+            .collect();
+        save_deferred_paths(tl_dir, &deferred)?;
+        assert_eq!(load_deferred_paths(tl_dir), deferred);
+
+        // Kept apart from the crash-recovery file
+        assert!(load_pending_paths(tl_dir).is_empty());
+
+        // Flushing every deferred path removes the file
+        save_deferred_paths(tl_dir, &HashSet::new())?;
+        assert!(!deferred_paths_file(tl_dir).exists());
+        assert!(load_deferred_paths(tl_dir).is_empty());
+
+        Ok(())
     }
 
-    Ok(Some(checkpoints))
+    #[test]
+    fn test_is_held_by_references() -> Result<()> {
+        use journal::{StashEntry, StashManager};
+
+        let temp_dir = tempfile::tempdir()?;
+        let repo_root = temp_dir.path();
+        let store = Store::init(repo_root)?;
+        let tl_dir = store.tl_dir().to_path_buf();
+
+        // An unreferenced HEAD may be collapsed
+        assert!(!is_held(&tl_dir, repo_root, Ulid::new()));
+
+        let pinned = Ulid::new();
+        PinManager::new(&tl_dir).pin("keep", pinned)?;
+        assert!(is_held(&tl_dir, repo_root, pinned));
+
+        let (stashed, stash_base) = (Ulid::new(), Ulid::new());
+        StashManager::new(&tl_dir).push(StashEntry {
+            checkpoint_id: stashed,
+            created_at_ms: 0,
+            message: None,
+            base_checkpoint_id: Some(stash_base),
+        })?;
+        assert!(is_held(&tl_dir, repo_root, stashed));
+        assert!(is_held(&tl_dir, repo_root, stash_base));
+
+        let published = Ulid::new();
+        {
+            let mapping = jj::JjMapping::open(&tl_dir)?;
+            mapping.set(published, "abc123")?;
+            mapping.flush()?;
+        }
+        assert!(is_held(&tl_dir, repo_root, published));
+
+        let ws_head = Ulid::new();
+        std::fs::create_dir_all(repo_root.join(".jj/repo"))?;
+        jj::WorkspaceManager::open(&tl_dir, repo_root)?.set_state(&jj::WorkspaceState {
+            name: "default".to_string(),
+            path: repo_root.to_path_buf(),
+            current_checkpoint: Some(ws_head),
+            last_switched_ms: 0,
+            created_ms: 0,
+            auto_pin: None,
+        })?;
+        assert!(is_held(&tl_dir, repo_root, ws_head));
+
+        // A mapping database locked by another handle counts as held
+        let _locked = jj::JjMapping::open(&tl_dir)?;
+        assert!(is_held(&tl_dir, repo_root, Ulid::new()));
+
+        Ok(())
+    }
 }
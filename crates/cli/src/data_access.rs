@@ -3,6 +3,7 @@
 
 use anyhow::{Context, Result};
 use journal::{Checkpoint, Journal, PinManager};
+use tl_core::Store;
 use std::path::Path;
 use ulid::Ulid;
 
@@ -41,6 +42,21 @@ pub async fn get_checkpoints(
     get_via_journal(ids, tl_dir)
 }
 
+/// Get a checkpoint and its ancestors (all parents, newest first) - uses IPC first, falls back to direct access
+pub async fn get_ancestors(
+    id: Ulid,
+    tl_dir: &Path,
+) -> Result<Vec<Checkpoint>> {
+    // Try IPC first
+    if let Ok(Some(checkpoints)) = try_get_ancestors_via_ipc(id, tl_dir).await {
+        return Ok(checkpoints);
+    }
+
+    // Fallback: direct journal access
+    let journal = open_journal(tl_dir)?;
+    journal.ancestors(&id, None)
+}
+
 /// Get repository info - uses IPC first, falls back to direct access
 ///
 /// Returns (total_checkpoints, checkpoint_ids, store_size_bytes)
@@ -108,6 +124,26 @@ async fn try_get_via_ipc(
     }
 }
 
+/// Try to get ancestors via IPC
+async fn try_get_ancestors_via_ipc(
+    id: Ulid,
+    tl_dir: &Path,
+) -> Result<Option<Vec<Checkpoint>>> {
+    let socket_path = tl_dir.join("state/daemon.sock");
+
+    if !socket_path.exists() {
+        return Ok(None);
+    }
+
+    match crate::ipc::IpcClient::connect(&socket_path).await {
+        Ok(mut client) => {
+            let checkpoints = client.get_ancestors(id.to_string(), None).await?;
+            Ok(Some(checkpoints))
+        }
+        Err(_) => Ok(None),
+    }
+}
+
 /// Try to get info data via IPC
 async fn try_get_info_via_ipc(
     tl_dir: &Path,
@@ -131,14 +167,20 @@ async fn try_get_info_via_ipc(
 // Private helper functions - Direct journal access
 // ============================================================================
 
+/// Open the journal for direct access, with the store's cipher
+fn open_journal(tl_dir: &Path) -> Result<Journal> {
+    let repo_root = tl_dir.parent().context("Invalid .tl directory")?;
+    let store = Store::open(repo_root).context("Failed to open store")?;
+    Journal::open_with_cipher(&tl_dir.join("journal"), store.cipher().cloned())
+        .context("Failed to open checkpoint journal")
+}
+
 /// Resolve checkpoint references via direct journal access
 fn resolve_via_journal(
     refs: &[String],
     tl_dir: &Path,
 ) -> Result<Vec<Option<Ulid>>> {
-    let journal_path = tl_dir.join("journal");
-    let journal = Journal::open(&journal_path)
-        .context("Failed to open checkpoint journal")?;
+    let journal = open_journal(tl_dir)?;
 
     let pin_manager = PinManager::new(tl_dir);
     let mut results = Vec::new();
@@ -231,9 +273,7 @@ fn get_via_journal(
     ids: &[Ulid],
     tl_dir: &Path,
 ) -> Result<Vec<Option<Checkpoint>>> {
-    let journal_path = tl_dir.join("journal");
-    let journal = Journal::open(&journal_path)
-        .context("Failed to open checkpoint journal")?;
+    let journal = open_journal(tl_dir)?;
 
     let mut results = Vec::new();
     for id in ids {
@@ -248,9 +288,7 @@ fn get_via_journal(
 fn get_info_via_journal(
     tl_dir: &Path,
 ) -> Result<(usize, Vec<String>, u64)> {
-    let journal_path = tl_dir.join("journal");
-    let journal = Journal::open(&journal_path)
-        .context("Failed to open checkpoint journal")?;
+    let journal = open_journal(tl_dir)?;
 
     // Get total checkpoints
     let total_checkpoints = journal.count();
@@ -309,9 +347,9 @@ mod tests {
     #[test]
     fn test_head_alias_resolves_to_latest() {
         let temp_dir = TempDir::new().unwrap();
-        let tl_dir = temp_dir.path().to_path_buf();
+        Store::init(temp_dir.path()).unwrap();
+        let tl_dir = temp_dir.path().join(".tl");
         let journal_path = tl_dir.join("journal");
-        std::fs::create_dir_all(&journal_path).unwrap();
 
         // Create checkpoints and store IDs before dropping journal
         let cp3_id;
@@ -338,9 +376,9 @@ mod tests {
     #[test]
     fn test_head_alias_empty_journal() {
         let temp_dir = TempDir::new().unwrap();
-        let tl_dir = temp_dir.path().to_path_buf();
+        Store::init(temp_dir.path()).unwrap();
+        let tl_dir = temp_dir.path().join(".tl");
         let journal_path = tl_dir.join("journal");
-        std::fs::create_dir_all(&journal_path).unwrap();
 
         {
             let _journal = Journal::open(&journal_path).unwrap();
@@ -355,9 +393,9 @@ mod tests {
     #[test]
     fn test_head_case_sensitive() {
         let temp_dir = TempDir::new().unwrap();
-        let tl_dir = temp_dir.path().to_path_buf();
+        Store::init(temp_dir.path()).unwrap();
+        let tl_dir = temp_dir.path().join(".tl");
         let journal_path = tl_dir.join("journal");
-        std::fs::create_dir_all(&journal_path).unwrap();
 
         let cp1_id;
         {
@@ -380,9 +418,9 @@ mod tests {
     #[test]
     fn test_mixed_refs_with_head() {
         let temp_dir = TempDir::new().unwrap();
-        let tl_dir = temp_dir.path().to_path_buf();
+        Store::init(temp_dir.path()).unwrap();
+        let tl_dir = temp_dir.path().join(".tl");
         let journal_path = tl_dir.join("journal");
-        std::fs::create_dir_all(&journal_path).unwrap();
 
         let (cp1_id, cp2_id);
         {
@@ -1,12 +1,7 @@
 //! Utilities for generating line-by-line diffs
 
 use owo_colors::OwoColorize;
-use similar::{ChangeTag, TextDiff};
-
-/// Check if content is binary (contains null bytes in first 8KB)
-pub fn is_binary(content: &[u8]) -> bool {
-    content.iter().take(8192).any(|&b| b == 0)
-}
+use similar::{ChangeTag, DiffOp, TextDiff};
 
 /// Generate a unified diff with colored output
 ///
@@ -17,6 +12,27 @@ pub fn generate_unified_diff(
     path: &str,
     context_lines: usize,
 ) -> String {
+    split_hunks(old_content, new_content, context_lines)
+        .into_iter()
+        .map(|hunk| hunk.rendered)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A single hunk of a line diff, as shown by `generate_unified_diff`
+#[derive(Debug, Clone)]
+pub struct DiffHunk {
+    /// Colored rendering of the hunk (header plus changed and context lines)
+    pub rendered: String,
+    /// Line operations covered by this hunk (indices into old/new lines)
+    ops: Vec<DiffOp>,
+}
+
+/// Split a line diff into hunks that can be selected individually
+///
+/// Hunks are identical to the ones printed by `generate_unified_diff` with the
+/// same `context_lines`, so a user can pick from exactly what they were shown.
+pub fn split_hunks(old_content: &[u8], new_content: &[u8], context_lines: usize) -> Vec<DiffHunk> {
     // Convert bytes to UTF-8 (with replacement chars for invalid UTF-8)
     let old_text = String::from_utf8_lossy(old_content);
     let new_text = String::from_utf8_lossy(new_content);
@@ -24,17 +40,13 @@ pub fn generate_unified_diff(
     // Create text diff with unified output
     let diff = TextDiff::from_lines(&old_text, &new_text);
 
-    let mut output = String::new();
+    let mut hunks = Vec::new();
 
-    // Iterate through hunks
-    for (hunk_idx, hunk) in diff.unified_diff().context_radius(context_lines).iter_hunks().enumerate() {
-        if hunk_idx > 0 {
-            output.push('\n');
-        }
+    for hunk in diff.unified_diff().context_radius(context_lines).iter_hunks() {
+        let mut rendered = String::new();
 
         // Hunk header (e.g., @@ -12,7 +12,8 @@)
-        let header = format!("{}", hunk.header());
-        output.push_str(&format!("    {}\n", header.cyan()));
+        rendered.push_str(&format!("    {}\n", hunk.header().to_string().cyan()));
 
         // Iterate through changes in the hunk
         for change in hunk.iter_changes() {
@@ -43,40 +55,90 @@ pub fn generate_unified_diff(
             match change.tag() {
                 ChangeTag::Delete => {
                     // Red for deletions
-                    output.push_str(&format!("    {}", format!("-{}", line).red()));
+                    rendered.push_str(&format!("    {}", format!("-{}", line).red()));
                 }
                 ChangeTag::Insert => {
                     // Green for additions
-                    output.push_str(&format!("    {}", format!("+{}", line).green()));
+                    rendered.push_str(&format!("    {}", format!("+{}", line).green()));
                 }
                 ChangeTag::Equal => {
                     // Dimmed for context
-                    output.push_str(&format!("    {}", format!(" {}", line).dimmed()));
+                    rendered.push_str(&format!("    {}", format!(" {}", line).dimmed()));
                 }
             }
 
             // Add newline if the line doesn't end with one
             if !line.ends_with('\n') {
-                output.push('\n');
+                rendered.push('\n');
+            }
+        }
+
+        hunks.push(DiffHunk {
+            rendered,
+            ops: hunk.ops().to_vec(),
+        });
+    }
+
+    hunks
+}
+
+/// Build new file content that applies only the selected hunks
+///
+/// `hunks` must come from `split_hunks(old_content, new_content, _)` and
+/// `selected` holds one flag per hunk. Lines outside selected hunks keep their
+/// old content, so selecting every hunk yields `new_content` and selecting
+/// none yields `old_content`.
+pub fn apply_selected_hunks(
+    old_content: &[u8],
+    new_content: &[u8],
+    hunks: &[DiffHunk],
+    selected: &[bool],
+) -> Vec<u8> {
+    let old_text = String::from_utf8_lossy(old_content);
+    let new_text = String::from_utf8_lossy(new_content);
+
+    // Same line splitting as TextDiff::from_lines (newlines stay attached)
+    let old_lines: Vec<&str> = old_text.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new_text.split_inclusive('\n').collect();
+
+    let mut output = String::with_capacity(old_text.len().max(new_text.len()));
+    let mut old_pos = 0;
+
+    for (hunk, &take) in hunks.iter().zip(selected) {
+        for op in &hunk.ops {
+            let old_range = op.old_range();
+
+            // Unchanged lines between hunks (or context inside a hunk)
+            for line in &old_lines[old_pos..old_range.start] {
+                output.push_str(line);
             }
+
+            if take {
+                for line in &new_lines[op.new_range()] {
+                    output.push_str(line);
+                }
+            } else {
+                for line in &old_lines[old_range.clone()] {
+                    output.push_str(line);
+                }
+            }
+
+            old_pos = old_range.end;
         }
     }
 
-    output
+    // Remainder of the file after the last hunk
+    for line in &old_lines[old_pos..] {
+        output.push_str(line);
+    }
+
+    output.into_bytes()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_is_binary() {
-        assert!(!is_binary(b"Hello, world!"));
-        assert!(!is_binary(b"Line 1\nLine 2\nLine 3"));
-        assert!(is_binary(b"Hello\x00world"));
-        assert!(is_binary(&[0u8; 100]));
-    }
-
     #[test]
     fn test_generate_unified_diff_simple() {
         let old = b"line 1\nline 2\nline 3\n";
@@ -98,4 +160,37 @@ mod tests {
 
         assert!(diff.contains("line 1.5"));
     }
+
+    #[test]
+    fn test_split_hunks_matches_unified_diff() {
+        let old = b"a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+        let new = b"a\nB\nc\nd\ne\nf\ng\nh\nI\nj\n";
+
+        let hunks = split_hunks(old, new, 1);
+        assert_eq!(hunks.len(), 2);
+        assert!(hunks[0].rendered.contains("@@ -1,3 +1,3 @@"));
+
+        let joined: Vec<_> = hunks.iter().map(|h| h.rendered.clone()).collect();
+        assert_eq!(joined.join("\n"), generate_unified_diff(old, new, "test.txt", 1));
+    }
+
+    #[test]
+    fn test_apply_selected_hunks() {
+        let old = b"a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+        let new = b"a\nB\nc\nd\ne\nf\ng\nh\nI\nj\nk\n";
+
+        let hunks = split_hunks(old, new, 1);
+        assert_eq!(hunks.len(), 2);
+
+        assert_eq!(apply_selected_hunks(old, new, &hunks, &[true, true]), new.to_vec());
+        assert_eq!(apply_selected_hunks(old, new, &hunks, &[false, false]), old.to_vec());
+        assert_eq!(
+            apply_selected_hunks(old, new, &hunks, &[true, false]),
+            b"a\nB\nc\nd\ne\nf\ng\nh\ni\nj\n".to_vec()
+        );
+        assert_eq!(
+            apply_selected_hunks(old, new, &hunks, &[false, true]),
+            b"a\nb\nc\nd\ne\nf\ng\nh\nI\nj\nk\n".to_vec()
+        );
+    }
 }
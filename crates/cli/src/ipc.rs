@@ -43,6 +43,15 @@ pub enum IpcRequest {
         limit: Option<usize>,
         offset: Option<usize>,
     },
+    /// Get checkpoint count and HEAD's ancestry following all parents (for log --graph)
+    GetLogGraph {
+        limit: Option<usize>,
+    },
+    /// Get a checkpoint and its ancestors following all parents, newest first
+    GetAncestors {
+        id: String,
+        limit: Option<usize>,
+    },
     /// Resolve checkpoint references and return checkpoints (supports short IDs, full IDs, pin names)
     ResolveCheckpointRefs(Vec<String>),
     /// Get repository info (checkpoint IDs, storage stats)
@@ -50,6 +59,8 @@ pub enum IpcRequest {
     /// Invalidate pathmap (after restore operation modifies working directory)
     /// Daemon will rebuild pathmap from HEAD checkpoint on next checkpoint cycle
     InvalidatePathmap,
+    /// Append a checkpoint built by the CLI (merge, etc.) to the journal
+    AppendCheckpoint(Checkpoint),
 }
 
 /// IPC response from daemon to CLI
@@ -109,8 +120,30 @@ pub struct DaemonStatus {
     pub last_checkpoint_time: Option<u64>,
     /// Number of paths currently being watched
     pub watcher_paths: usize,
-    /// Number of checkpoints skipped due to GC/restore locks
+    /// Number of checkpoints skipped due to GC/restore locks or the quota
     pub checkpoints_skipped: u64,
+    /// Bytes used by .tl/ (tracked by the store)
+    pub store_bytes: u64,
+    /// Storage budget in bytes, if a quota is configured
+    pub quota_bytes: Option<u64>,
+    /// Checkpointing is paused because .tl/ is over its quota
+    pub checkpointing_paused: bool,
+    /// Watcher backend in use ("inotify", "fanotify", "fsevents" or "poll")
+    pub watcher_backend: String,
+    /// Directory watches registered (inotify)
+    pub watches: u64,
+    /// Per-user inotify watch limit, if known
+    pub watch_limit: Option<u64>,
+    /// Subtrees left unwatched by the watch limit (reconciled by polling)
+    pub unwatched_dirs: u64,
+    /// Checkpoints deferred because the tree was mid-change
+    pub checkpoints_deferred: u64,
+    /// Why the due checkpoint is being deferred right now, if it is
+    pub deferral_reason: Option<String>,
+    /// Checkpoints not recorded because the tree matched HEAD (or HEAD's parent)
+    pub checkpoints_suppressed: u64,
+    /// Transient checkpoints dropped by A→B→A collapsing
+    pub oscillations_collapsed: u64,
 }
 
 /// IPC client for CLI to communicate with daemon
@@ -210,6 +243,15 @@ impl IpcClient {
         }
     }
 
+    /// Append a CLI-built checkpoint to the journal (daemon owns the journal)
+    pub async fn append_checkpoint(&mut self, checkpoint: Checkpoint) -> Result<()> {
+        match self.send_request(&IpcRequest::AppendCheckpoint(checkpoint)).await? {
+            IpcResponse::Ok => Ok(()),
+            IpcResponse::Error(err) => anyhow::bail!("Append checkpoint error: {}", err),
+            _ => anyhow::bail!("Unexpected response to AppendCheckpoint"),
+        }
+    }
+
     /// Get HEAD checkpoint
     pub async fn get_head(&mut self) -> Result<Option<Checkpoint>> {
         match self.send_request(&IpcRequest::GetHead).await? {
@@ -271,6 +313,15 @@ impl IpcClient {
         }
     }
 
+    /// Get a checkpoint and all of its ancestors, newest first
+    pub async fn get_ancestors(&mut self, id: String, limit: Option<usize>) -> Result<Vec<Checkpoint>> {
+        match self.send_request(&IpcRequest::GetAncestors { id, limit }).await? {
+            IpcResponse::Checkpoints(checkpoints) => Ok(checkpoints),
+            IpcResponse::Error(err) => anyhow::bail!("Daemon error: {}", err),
+            _ => anyhow::bail!("Unexpected response to GetAncestors"),
+        }
+    }
+
     /// Get full status information in one IPC call (for status command)
     pub async fn get_status_full(&mut self) -> Result<(DaemonStatus, Option<Checkpoint>, usize)> {
         match self.send_request(&IpcRequest::GetStatusFull).await? {
@@ -292,6 +343,16 @@ impl IpcClient {
         }
     }
 
+    /// Get checkpoint count and HEAD's full ancestry, merge parents included (for log --graph)
+    pub async fn get_log_graph(&mut self, limit: Option<usize>) -> Result<(usize, Vec<Checkpoint>)> {
+        let request = IpcRequest::GetLogGraph { limit };
+        match self.send_request(&request).await? {
+            IpcResponse::LogData { count, checkpoints } => Ok((count, checkpoints)),
+            IpcResponse::Error(err) => anyhow::bail!("Daemon error: {}", err),
+            _ => anyhow::bail!("Unexpected response to GetLogGraph"),
+        }
+    }
+
     /// Resolve checkpoint references (supports full IDs, short prefixes, pin names)
     pub async fn resolve_checkpoint_refs(&mut self, refs: Vec<String>) -> Result<Vec<Option<Checkpoint>>> {
         let request = IpcRequest::ResolveCheckpointRefs(refs);
@@ -508,6 +569,17 @@ mod tests {
             last_checkpoint_time: Some(1234567890),
             watcher_paths: 100,
             checkpoints_skipped: 0,
+            store_bytes: 4096,
+            quota_bytes: Some(1 << 30),
+            checkpointing_paused: false,
+            watcher_backend: "inotify".to_string(),
+            watches: 12,
+            watch_limit: Some(8192),
+            unwatched_dirs: 0,
+            checkpoints_deferred: 3,
+            deferral_reason: None,
+            checkpoints_suppressed: 5,
+            oscillations_collapsed: 1,
         };
 
         let response = IpcResponse::Status(status.clone());
@@ -6,5 +6,7 @@ pub mod data_access;
 pub mod diff_utils;
 pub mod ipc;
 pub mod locks;
+pub mod quiescence;
+pub mod schedule;
 pub mod system_config;
 pub mod util;
@@ -10,6 +10,8 @@ mod data_access;
 mod diff_utils;
 mod ipc;
 mod locks;
+mod quiescence;
+mod schedule;
 mod system_config;
 mod util;
 
@@ -47,6 +49,9 @@ enum Commands {
         /// Number of checkpoints to show (default: 20)
         #[arg(long)]
         limit: Option<usize>,
+        /// Draw the checkpoint DAG, including merge parents
+        #[arg(long)]
+        graph: bool,
     },
     /// Show detailed checkpoint information
     Show {
@@ -80,6 +85,11 @@ enum Commands {
         #[arg(short = 'y', long)]
         yes: bool,
     },
+    /// Browse every checkpoint as a read-only filesystem (Linux, FUSE)
+    Mount {
+        /// Directory to mount on
+        mountpoint: PathBuf,
+    },
     /// Pin a checkpoint with a name
     Pin {
         /// Checkpoint ID
@@ -94,6 +104,42 @@ enum Commands {
     },
     /// Run garbage collection
     Gc,
+    /// Verify journal, objects, pathmap and JJ mappings (exits non-zero on problems)
+    #[command(alias = "doctor")]
+    Fsck {
+        /// Quarantine corrupt objects and rebuild indexes
+        #[arg(long)]
+        repair: bool,
+    },
+    /// Purge stored content (secrets) from every checkpoint
+    Scrub {
+        /// Blob hashes or paths to purge (default: scan all stored content for secrets)
+        targets: Vec<String>,
+        /// Show what would be purged without purging
+        #[arg(long)]
+        dry_run: bool,
+        /// Skip confirmation prompt
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
+    /// Remove paths from every checkpoint (rewrites history)
+    Purge {
+        /// Path patterns to remove (gitignore syntax)
+        #[arg(required = true)]
+        patterns: Vec<String>,
+        /// Only rewrite checkpoints in <from>..<to> (either side optional)
+        #[arg(long)]
+        range: Option<String>,
+        /// Also rewrite checkpoints already published to JJ
+        #[arg(long)]
+        force: bool,
+        /// Show what would be removed without rewriting
+        #[arg(long)]
+        dry_run: bool,
+        /// Skip confirmation prompt
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
     /// Publish checkpoint(s) to JJ
     Publish {
         /// Checkpoint ID or range (e.g., HEAD or HEAD~10..HEAD)
@@ -110,6 +156,9 @@ enum Commands {
         /// Custom commit message template
         #[arg(long)]
         message_template: Option<String>,
+        /// Interactively choose files and hunks to publish (single checkpoint only)
+        #[arg(long)]
+        select: bool,
     },
     /// Push to Git remote via JJ
     Push {
@@ -170,6 +219,25 @@ enum Commands {
         #[arg(long = "continue")]
         continue_merge: bool,
     },
+    /// Three-way merge two checkpoints into the working directory (no JJ)
+    MergeCheckpoint {
+        /// Common ancestor checkpoint
+        base: String,
+        /// Checkpoint to merge into (HEAD, or one with the same tree)
+        ours: String,
+        /// Checkpoint whose changes are merged in
+        theirs: String,
+    },
+    /// Apply the change made in a checkpoint onto HEAD
+    CherryPick {
+        /// Checkpoint ID or label
+        checkpoint: String,
+    },
+    /// Undo the change made in a checkpoint, keeping later changes
+    Revert {
+        /// Checkpoint ID or label
+        checkpoint: String,
+    },
     /// Check and manage conflict resolution
     Resolve {
         /// List files with resolution status
@@ -230,6 +298,12 @@ enum Commands {
     /// Manage Git remotes
     #[command(subcommand)]
     Remote(RemoteCommands),
+    /// Binary search checkpoints for the first bad one
+    #[command(subcommand)]
+    Bisect(BisectCommands),
+    /// Debugging aids for bug reports
+    #[command(subcommand)]
+    Debug(DebugCommands),
 }
 
 #[derive(Subcommand)]
@@ -306,6 +380,53 @@ enum StashCommands {
     },
 }
 
+#[derive(Subcommand)]
+enum BisectCommands {
+    /// Start bisecting between a bad and one or more good checkpoints
+    Start {
+        /// Known bad checkpoint
+        bad: Option<String>,
+        /// Known good checkpoint(s)
+        good: Vec<String>,
+    },
+    /// Mark a checkpoint as good (default: current candidate)
+    Good {
+        /// Checkpoint ID or label
+        checkpoint: Option<String>,
+    },
+    /// Mark a checkpoint as bad (default: current candidate)
+    Bad {
+        /// Checkpoint ID or label
+        checkpoint: Option<String>,
+    },
+    /// Skip a checkpoint that cannot be tested (default: current candidate)
+    Skip {
+        /// Checkpoint ID or label
+        checkpoint: Option<String>,
+    },
+    /// Run a command in each candidate's scratch directory (exit 0 = good, 125 = skip)
+    Run {
+        /// Command and arguments
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, required = true)]
+        command: Vec<String>,
+    },
+    /// End the bisect session
+    Reset,
+}
+
+#[derive(Subcommand)]
+enum DebugCommands {
+    /// Record raw file watcher events to a trace file
+    RecordEvents {
+        /// Trace file (default: tl-events-<timestamp>.trace)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Stop after this many seconds (default: on Ctrl-C)
+        #[arg(long)]
+        duration: Option<u64>,
+    },
+}
+
 #[derive(Subcommand)]
 enum RemoteCommands {
     /// List all remotes
@@ -411,7 +532,7 @@ async fn main() -> Result<()> {
         Commands::Init { skip_git, skip_jj } => cmd::init::run(skip_git, skip_jj).await,
         Commands::Status { remote } => cmd::status::run(remote).await,
         Commands::Info => cmd::info::run().await,
-        Commands::Log { limit } => cmd::log::run(limit).await,
+        Commands::Log { limit, graph } => cmd::log::run(limit, graph).await,
         Commands::Show { checkpoint, diff } => {
             cmd::show::run(&checkpoint, diff).await
         }
@@ -419,11 +540,17 @@ async fn main() -> Result<()> {
             cmd::diff::run(&checkpoint_a, &checkpoint_b, patch, context, max_files).await
         }
         Commands::Restore { checkpoint, yes } => cmd::restore::run(&checkpoint, yes).await,
+        Commands::Mount { mountpoint } => cmd::mount::run(mountpoint).await,
         Commands::Pin { checkpoint, name } => cmd::pin::run(&checkpoint, &name).await,
         Commands::Unpin { name } => cmd::unpin::run(&name).await,
         Commands::Gc => cmd::gc::run().await,
-        Commands::Publish { checkpoint, bookmark, compact, no_pin, message_template } => {
-            cmd::publish::run(&checkpoint, bookmark, compact, no_pin, message_template).await
+        Commands::Fsck { repair } => cmd::fsck::run(repair).await,
+        Commands::Scrub { targets, dry_run, yes } => cmd::scrub::run(targets, dry_run, yes).await,
+        Commands::Purge { patterns, range, force, dry_run, yes } => {
+            cmd::purge::run(patterns, range, force, dry_run, yes).await
+        }
+        Commands::Publish { checkpoint, bookmark, compact, no_pin, message_template, select } => {
+            cmd::publish::run(&checkpoint, bookmark, compact, no_pin, message_template, select).await
         }
         Commands::Push { bookmark, all, force } => {
             cmd::push::run(bookmark, all, force).await
@@ -446,6 +573,11 @@ async fn main() -> Result<()> {
         Commands::Merge { branch, abort, continue_merge } => {
             cmd::merge::run(branch, abort, continue_merge).await
         }
+        Commands::MergeCheckpoint { base, ours, theirs } => {
+            cmd::merge_checkpoint::run(&base, &ours, &theirs).await
+        }
+        Commands::CherryPick { checkpoint } => cmd::cherry_pick::run_cherry_pick(&checkpoint).await,
+        Commands::Revert { checkpoint } => cmd::cherry_pick::run_revert(&checkpoint).await,
         Commands::Resolve { list, continue_merge, abort } => {
             cmd::resolve::run(list, continue_merge, abort).await
         }
@@ -503,6 +635,20 @@ async fn main() -> Result<()> {
             StashCommands::Drop { stash } => cmd::stash::run_drop(stash).await,
             StashCommands::Clear { yes } => cmd::stash::run_clear(yes).await,
         },
+        Commands::Bisect(bisect_cmd) => match bisect_cmd {
+            BisectCommands::Start { bad, good } => cmd::bisect::run_start(bad, good).await,
+            BisectCommands::Good { checkpoint } => {
+                cmd::bisect::run_mark(cmd::bisect::Verdict::Good, checkpoint).await
+            }
+            BisectCommands::Bad { checkpoint } => {
+                cmd::bisect::run_mark(cmd::bisect::Verdict::Bad, checkpoint).await
+            }
+            BisectCommands::Skip { checkpoint } => {
+                cmd::bisect::run_mark(cmd::bisect::Verdict::Skip, checkpoint).await
+            }
+            BisectCommands::Run { command } => cmd::bisect::run_run(command).await,
+            BisectCommands::Reset => cmd::bisect::run_reset().await,
+        },
         Commands::Remote(remote_cmd) => match remote_cmd {
             RemoteCommands::List { verbose } => cmd::remote::run_list(verbose).await,
             RemoteCommands::Add { name, url, fetch } => {
@@ -519,5 +665,10 @@ async fn main() -> Result<()> {
                 cmd::remote::run_get_url(&name, push).await
             }
         },
+        Commands::Debug(debug_cmd) => match debug_cmd {
+            DebugCommands::RecordEvents { output, duration } => {
+                cmd::debug::run_record_events(output, duration).await
+            }
+        },
     }
 }
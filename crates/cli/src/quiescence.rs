@@ -0,0 +1,313 @@
+//! Quiescence detection before checkpointing
+//!
+//! A checkpoint taken while `cargo fmt`, `npm install` or a multi-file
+//! refactor is mid-flight captures a half-applied change. Before each
+//! periodic checkpoint the daemon asks whether the tree is still moving:
+//!
+//! - the watcher's event rate over the last second is at burst level
+//! - a Git lock file (`.git/index.lock`, ...) is held
+//! - a changed file is still open for writing by some process (Linux, via
+//!   `/proc/<pid>/fd`)
+//!
+//! If so, the checkpoint is deferred and retried shortly after, for at most
+//! `max_checkpoint_deferral_secs`, so a stale lock file or a long-running
+//! writer can't stop checkpointing altogether.
+
+use crate::system_config::DaemonConfig;
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Window the event rate is measured over
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Minimum spacing between event-count samples
+const SAMPLE_SPACING: Duration = Duration::from_millis(50);
+
+/// How soon a deferred checkpoint is retried
+pub const QUIESCENCE_RECHECK: Duration = Duration::from_secs(1);
+
+/// Lock files that mean a tool is rewriting the tree or the index
+const LOCK_FILES: &[&str] = &[".git/index.lock", ".git/HEAD.lock", ".git/packed-refs.lock"];
+
+/// Why the tree is not quiescent
+#[derive(Debug, Clone, PartialEq)]
+pub enum Activity {
+    /// Watcher events per second over the last second
+    Burst(u64),
+    /// A lock file exists (repo-relative)
+    LockHeld(PathBuf),
+    /// A changed file is open for writing (repo-relative)
+    OpenForWrite(PathBuf),
+}
+
+impl fmt::Display for Activity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Activity::Burst(rate) => write!(f, "burst of {} events/s", rate),
+            Activity::LockHeld(path) => write!(f, "{} is held", path.display()),
+            Activity::OpenForWrite(path) => write!(f, "{} is open for writing", path.display()),
+        }
+    }
+}
+
+/// What to do with a due checkpoint
+#[derive(Debug, Clone, PartialEq)]
+pub enum Quiescence {
+    /// The tree is quiet: checkpoint now
+    Quiet,
+    /// Still busy: retry after `QUIESCENCE_RECHECK`
+    Defer(Activity),
+    /// Still busy, but deferred for too long: checkpoint anyway
+    Overdue(Activity),
+}
+
+/// Tracks recent watcher activity and decides when the tree has settled
+pub struct QuiescenceDetector {
+    repo_root: PathBuf,
+    enabled: bool,
+    burst_events_per_sec: u64,
+    max_deferral: Duration,
+
+    /// (time, events processed so far), oldest first
+    samples: VecDeque<(Instant, u64)>,
+
+    /// Start of the current deferral
+    deferring_since: Option<Instant>,
+
+    /// Checkpoints deferred at least once
+    deferred_total: u64,
+}
+
+impl QuiescenceDetector {
+    pub fn new(repo_root: &Path, config: &DaemonConfig) -> Self {
+        Self {
+            // /proc reports canonical paths
+            repo_root: repo_root.canonicalize().unwrap_or_else(|_| repo_root.to_path_buf()),
+            enabled: config.quiescence_enabled,
+            burst_events_per_sec: config.burst_events_per_sec,
+            max_deferral: Duration::from_secs(config.max_checkpoint_deferral_secs),
+            samples: VecDeque::new(),
+            deferring_since: None,
+            deferred_total: 0,
+        }
+    }
+
+    /// Record the watcher's running event count
+    pub fn observe(&mut self, events_processed: u64) {
+        self.observe_at(Instant::now(), events_processed);
+    }
+
+    fn observe_at(&mut self, now: Instant, events_processed: u64) {
+        if let Some(&(last, count)) = self.samples.back() {
+            if count == events_processed && now.duration_since(last) < SAMPLE_SPACING {
+                return;
+            }
+        }
+        self.samples.push_back((now, events_processed));
+
+        // Keep one sample at or before the window start as the baseline
+        while self.samples.len() > 2 && now.duration_since(self.samples[1].0) >= RATE_WINDOW {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Events seen during the last `RATE_WINDOW`
+    fn recent_events(&self, now: Instant) -> u64 {
+        let latest = match self.samples.back() {
+            Some(&(_, count)) => count,
+            None => return 0,
+        };
+        let baseline = self.samples.iter()
+            .rev()
+            .find(|(time, _)| now.duration_since(*time) >= RATE_WINDOW)
+            .or_else(|| self.samples.front())
+            .map(|&(_, count)| count)
+            .unwrap_or(latest);
+        latest.saturating_sub(baseline)
+    }
+
+    /// Decide whether a due checkpoint should go ahead
+    pub fn check(&mut self, pending: &HashSet<Arc<Path>>) -> Quiescence {
+        if !self.enabled {
+            return Quiescence::Quiet;
+        }
+
+        let now = Instant::now();
+        let activity = self.activity(now, pending);
+        match (activity, self.deferring_since) {
+            (None, _) => Quiescence::Quiet,
+            (Some(activity), Some(since)) if now.duration_since(since) >= self.max_deferral => {
+                Quiescence::Overdue(activity)
+            }
+            (Some(activity), since) => {
+                if since.is_none() {
+                    self.deferring_since = Some(now);
+                    self.deferred_total += 1;
+                }
+                Quiescence::Defer(activity)
+            }
+        }
+    }
+
+    /// A checkpoint was created: the next deferral starts afresh
+    pub fn reset(&mut self) {
+        self.deferring_since = None;
+    }
+
+    /// What is keeping the tree busy, if anything
+    fn activity(&self, now: Instant, pending: &HashSet<Arc<Path>>) -> Option<Activity> {
+        let recent = self.recent_events(now);
+        if recent >= self.burst_events_per_sec {
+            return Some(Activity::Burst(recent));
+        }
+
+        if let Some(lock) = LOCK_FILES.iter().find(|lock| self.repo_root.join(lock).exists()) {
+            return Some(Activity::LockHeld(PathBuf::from(lock)));
+        }
+
+        open_for_write(&self.repo_root, pending).map(Activity::OpenForWrite)
+    }
+
+    /// How long the current deferral has lasted
+    pub fn deferred_for(&self) -> Option<Duration> {
+        self.deferring_since.map(|since| since.elapsed())
+    }
+
+    /// Checkpoints deferred at least once since the daemon started
+    pub fn deferred_total(&self) -> u64 {
+        self.deferred_total
+    }
+}
+
+/// A pending path some other process holds open for writing
+#[cfg(target_os = "linux")]
+fn open_for_write(repo_root: &Path, pending: &HashSet<Arc<Path>>) -> Option<PathBuf> {
+    if pending.is_empty() {
+        return None;
+    }
+    let own_pid = std::process::id().to_string();
+
+    for process in std::fs::read_dir("/proc").ok()?.flatten() {
+        let pid = process.file_name();
+        let pid = pid.to_string_lossy();
+        if !pid.bytes().all(|b| b.is_ascii_digit()) || pid == own_pid {
+            continue;
+        }
+
+        // Other users' processes are unreadable; skip them
+        let Ok(fds) = std::fs::read_dir(process.path().join("fd")) else { continue };
+        for fd in fds.flatten() {
+            let Ok(target) = std::fs::read_link(fd.path()) else { continue };
+            let Ok(relative) = target.strip_prefix(repo_root) else { continue };
+            if !pending.contains(relative) {
+                continue;
+            }
+
+            let fdinfo = process.path().join("fdinfo").join(fd.file_name());
+            if std::fs::read_to_string(fdinfo).ok().is_some_and(|info| opened_for_write(&info)) {
+                return Some(relative.to_path_buf());
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(not(target_os = "linux"))]
+fn open_for_write(_repo_root: &Path, _pending: &HashSet<Arc<Path>>) -> Option<PathBuf> {
+    None
+}
+
+/// Whether `/proc/<pid>/fdinfo/<fd>` describes a descriptor open for writing
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn opened_for_write(fdinfo: &str) -> bool {
+    fdinfo.lines()
+        .find_map(|line| line.strip_prefix("flags:"))
+        .and_then(|flags| u32::from_str_radix(flags.trim(), 8).ok())
+        .is_some_and(|flags| flags & 0o3 != 0) // O_WRONLY or O_RDWR
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn detector(root: &Path) -> QuiescenceDetector {
+        let config = DaemonConfig {
+            burst_events_per_sec: 10,
+            max_checkpoint_deferral_secs: 60,
+            ..Default::default()
+        };
+        QuiescenceDetector::new(root, &config)
+    }
+
+    #[test]
+    fn test_event_rate_over_window() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut detector = detector(temp_dir.path());
+        let start = Instant::now();
+
+        // 5 events long ago, then 20 within the last second
+        detector.observe_at(start, 0);
+        detector.observe_at(start + Duration::from_millis(100), 5);
+        detector.observe_at(start + Duration::from_millis(2500), 5);
+        detector.observe_at(start + Duration::from_millis(3000), 25);
+        assert_eq!(detector.recent_events(start + Duration::from_millis(3100)), 20);
+        assert_eq!(
+            detector.activity(start + Duration::from_millis(3100), &HashSet::new()),
+            Some(Activity::Burst(20))
+        );
+
+        // Quiet for a second: the rate drops back to zero
+        detector.observe_at(start + Duration::from_millis(4200), 25);
+        assert_eq!(detector.recent_events(start + Duration::from_millis(4200)), 0);
+        assert!(detector.samples.len() <= 3);
+    }
+
+    #[test]
+    fn test_lock_file_defers_until_overdue() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp_dir.path().join(".git")).unwrap();
+        let mut detector = detector(temp_dir.path());
+        let pending = HashSet::new();
+
+        assert_eq!(detector.check(&pending), Quiescence::Quiet);
+
+        std::fs::write(temp_dir.path().join(".git/index.lock"), b"").unwrap();
+        let busy = Activity::LockHeld(PathBuf::from(".git/index.lock"));
+        assert_eq!(detector.check(&pending), Quiescence::Defer(busy.clone()));
+        assert_eq!(detector.check(&pending), Quiescence::Defer(busy.clone()));
+        assert_eq!(detector.deferred_total(), 1);
+
+        // Past the bound the checkpoint goes ahead regardless
+        detector.deferring_since = Some(Instant::now() - Duration::from_secs(61));
+        assert_eq!(detector.check(&pending), Quiescence::Overdue(busy));
+
+        detector.reset();
+        std::fs::remove_file(temp_dir.path().join(".git/index.lock")).unwrap();
+        assert_eq!(detector.check(&pending), Quiescence::Quiet);
+        assert!(detector.deferred_for().is_none());
+    }
+
+    #[test]
+    fn test_fdinfo_write_flags() {
+        assert!(opened_for_write("pos:\t0\nflags:\t0100001\nmnt_id:\t25\n"));
+        assert!(opened_for_write("pos:\t0\nflags:\t02100002\n"));
+        assert!(!opened_for_write("pos:\t0\nflags:\t0100000\n"));
+        assert!(!opened_for_write("pos:\t0\n"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_own_writes_are_not_counted() {
+        let temp_dir = TempDir::new().unwrap();
+        let _file = std::fs::File::create(temp_dir.path().join("a.txt")).unwrap();
+        let pending: HashSet<Arc<Path>> = [Arc::from(Path::new("a.txt"))].into_iter().collect();
+
+        // Only this process has it open, and the daemon ignores itself
+        assert_eq!(open_for_write(temp_dir.path(), &pending), None);
+    }
+}
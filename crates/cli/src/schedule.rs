@@ -0,0 +1,174 @@
+//! Checkpoint scheduling
+//!
+//! With `adaptive_checkpoints` the daemon checkpoints from watcher activity
+//! rather than a fixed timer:
+//!
+//! - a batch is checkpointed once no further batch has arrived for
+//!   `min_checkpoint_interval_ms`, so an agent's burst of edits lands in
+//!   checkpoints sub-second after each pause
+//! - under continuous activity a checkpoint is due `max_checkpoint_interval_secs`
+//!   after the activity began; the quiescence gate then takes it at the next
+//!   quiet moment
+//! - checkpoints are never closer together than `min_checkpoint_interval_ms`
+//! - with nothing pending there is no deadline, so an idle daemon sets no
+//!   checkpoint timer at all
+//!
+//! Without it, a checkpoint is due `checkpoint_interval_secs` after the last.
+
+use crate::system_config::DaemonConfig;
+use std::time::{Duration, Instant};
+
+/// Decides when the next checkpoint is due
+pub struct CheckpointScheduler {
+    adaptive: bool,
+    interval: Duration,
+    min_interval: Duration,
+    max_interval: Duration,
+
+    last_checkpoint: Instant,
+
+    /// First batch since the last checkpoint
+    activity_started: Option<Instant>,
+
+    /// Most recent batch
+    last_batch: Option<Instant>,
+
+    /// Overrides the computed deadline after a skipped or deferred attempt
+    retry_at: Option<Instant>,
+}
+
+impl CheckpointScheduler {
+    pub fn new(config: &DaemonConfig, now: Instant) -> Self {
+        Self {
+            adaptive: config.adaptive_checkpoints,
+            interval: Duration::from_secs(config.checkpoint_interval_secs),
+            min_interval: Duration::from_millis(config.min_checkpoint_interval_ms),
+            max_interval: Duration::from_secs(config.max_checkpoint_interval_secs),
+            last_checkpoint: now,
+            activity_started: None,
+            last_batch: None,
+            retry_at: None,
+        }
+    }
+
+    /// Paths became pending (a watcher batch, or paths restored at startup)
+    pub fn on_batch(&mut self, now: Instant) {
+        self.activity_started.get_or_insert(now);
+        self.last_batch = Some(now);
+    }
+
+    /// When the next checkpoint attempt is due; None while nothing is pending
+    pub fn next_due(&self) -> Option<Instant> {
+        let started = self.activity_started?;
+        if let Some(retry_at) = self.retry_at {
+            return Some(retry_at);
+        }
+
+        if !self.adaptive {
+            return Some(self.last_checkpoint + self.interval);
+        }
+
+        let settled = self.last_batch.unwrap_or(started) + self.min_interval;
+        let capped = started + self.max_interval;
+        Some(settled.min(capped).max(self.last_checkpoint + self.min_interval))
+    }
+
+    /// The due checkpoint was skipped or deferred: try again after `delay`
+    pub fn retry_after(&mut self, now: Instant, delay: Duration) {
+        self.retry_at = Some(now + delay);
+    }
+
+    /// A checkpoint was created and nothing is pending any more
+    pub fn checkpointed(&mut self, now: Instant) {
+        self.last_checkpoint = now;
+        self.cleared();
+    }
+
+    /// Nothing is pending any more (e.g. every path was manual-only)
+    pub fn cleared(&mut self) {
+        self.activity_started = None;
+        self.last_batch = None;
+        self.retry_at = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(adaptive: bool) -> DaemonConfig {
+        DaemonConfig {
+            checkpoint_interval_secs: 5,
+            adaptive_checkpoints: adaptive,
+            min_checkpoint_interval_ms: 500,
+            max_checkpoint_interval_secs: 10,
+            ..Default::default()
+        }
+    }
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn test_idle_has_no_deadline() {
+        let start = Instant::now();
+        let scheduler = CheckpointScheduler::new(&config(true), start);
+        assert_eq!(scheduler.next_due(), None);
+
+        let scheduler = CheckpointScheduler::new(&config(false), start);
+        assert_eq!(scheduler.next_due(), None);
+    }
+
+    #[test]
+    fn test_checkpoint_follows_settled_batch() {
+        let start = Instant::now();
+        let mut scheduler = CheckpointScheduler::new(&config(true), start);
+
+        scheduler.on_batch(start + ms(2000));
+        assert_eq!(scheduler.next_due(), Some(start + ms(2500)));
+
+        // Another batch pushes the deadline back
+        scheduler.on_batch(start + ms(2300));
+        assert_eq!(scheduler.next_due(), Some(start + ms(2800)));
+
+        scheduler.checkpointed(start + ms(2800));
+        assert_eq!(scheduler.next_due(), None);
+
+        // The next burst starts afresh
+        scheduler.on_batch(start + ms(2900));
+        assert_eq!(scheduler.next_due(), Some(start + ms(3400)));
+    }
+
+    #[test]
+    fn test_continuous_activity_is_capped() {
+        let start = Instant::now();
+        let mut scheduler = CheckpointScheduler::new(&config(true), start);
+
+        // A batch every 200ms never settles...
+        for i in 0..60 {
+            scheduler.on_batch(start + ms(1000 + i * 200));
+        }
+        // ...so the checkpoint is due 10s after activity began
+        assert_eq!(scheduler.next_due(), Some(start + ms(11_000)));
+
+        // Deferred by the quiescence gate: retried, not recomputed
+        scheduler.retry_after(start + ms(11_000), ms(1000));
+        scheduler.on_batch(start + ms(11_500));
+        assert_eq!(scheduler.next_due(), Some(start + ms(12_000)));
+    }
+
+    #[test]
+    fn test_fixed_interval() {
+        let start = Instant::now();
+        let mut scheduler = CheckpointScheduler::new(&config(false), start);
+
+        scheduler.on_batch(start + ms(100));
+        assert_eq!(scheduler.next_due(), Some(start + ms(5000)));
+
+        scheduler.checkpointed(start + ms(5000));
+        scheduler.on_batch(start + ms(20_000));
+        // Overdue: due immediately
+        assert_eq!(scheduler.next_due(), Some(start + ms(10_000)));
+    }
+}
@@ -36,8 +36,22 @@ impl Default for SystemConfig {
 #[serde(default)]
 pub struct DaemonConfig {
     /// Checkpoint creation interval in seconds (default: 5)
+    /// Only used when `adaptive_checkpoints` is off
     pub checkpoint_interval_secs: u64,
 
+    /// Schedule checkpoints from watcher activity (default: true)
+    /// Checkpoints follow each batch once it settles, and the timer is not
+    /// armed at all while the tree is idle
+    pub adaptive_checkpoints: bool,
+
+    /// Shortest gap between adaptive checkpoints, in milliseconds (default: 500)
+    /// Also how long a batch must settle before it is checkpointed
+    pub min_checkpoint_interval_ms: u64,
+
+    /// Longest changes wait under continuous activity, in seconds (default: 30)
+    /// After this much activity a checkpoint is taken at the next quiescent moment
+    pub max_checkpoint_interval_secs: u64,
+
     /// Whether to enable auto-GC (default: true)
     pub auto_gc_enabled: bool,
 
@@ -50,15 +64,38 @@ pub struct DaemonConfig {
     /// Auto-GC checkpoint threshold (default: 5000)
     /// Triggers GC when checkpoint count exceeds this
     pub auto_gc_checkpoint_threshold: usize,
+
+    /// Defer checkpoints while the tree is mid-change (default: true)
+    /// A burst of events, a held `.git/index.lock` or a changed file still
+    /// open for writing postpones the checkpoint until things settle
+    pub quiescence_enabled: bool,
+
+    /// Event rate (per second) that counts as a burst (default: 50)
+    pub burst_events_per_sec: u64,
+
+    /// Longest a checkpoint may be deferred, in seconds (default: 60)
+    pub max_checkpoint_deferral_secs: u64,
+
+    /// Collapse A→B→A oscillations within this many seconds (default: 0 = off)
+    /// When the tree returns to HEAD's parent, the transient checkpoint B is
+    /// dropped instead of recording A again
+    pub collapse_oscillation_secs: u64,
 }
 
 impl Default for DaemonConfig {
     fn default() -> Self {
         Self {
             checkpoint_interval_secs: 5,
+            adaptive_checkpoints: true,
+            min_checkpoint_interval_ms: 500,
+            max_checkpoint_interval_secs: 30,
             auto_gc_enabled: true,
             auto_gc_interval_secs: 3600, // 1 hour
             auto_gc_checkpoint_threshold: 5000,
+            quiescence_enabled: true,
+            burst_events_per_sec: 50,
+            max_checkpoint_deferral_secs: 60,
+            collapse_oscillation_secs: 0,
         }
     }
 }
@@ -139,6 +176,29 @@ impl DaemonConfig {
             );
         }
 
+        // min_checkpoint_interval_ms: 100-60000
+        if self.min_checkpoint_interval_ms < 100 || self.min_checkpoint_interval_ms > 60_000 {
+            anyhow::bail!(
+                "min_checkpoint_interval_ms out of range: {} (100-60000)",
+                self.min_checkpoint_interval_ms
+            );
+        }
+
+        // max_checkpoint_interval_secs: 1-3600, not below the minimum
+        if self.max_checkpoint_interval_secs == 0 || self.max_checkpoint_interval_secs > 3600 {
+            anyhow::bail!(
+                "max_checkpoint_interval_secs out of range: {} (1-3600)",
+                self.max_checkpoint_interval_secs
+            );
+        }
+        if self.max_checkpoint_interval_secs * 1000 < self.min_checkpoint_interval_ms {
+            anyhow::bail!(
+                "max_checkpoint_interval_secs ({}s) is below min_checkpoint_interval_ms ({}ms)",
+                self.max_checkpoint_interval_secs,
+                self.min_checkpoint_interval_ms
+            );
+        }
+
         // auto_gc_interval_secs: 60-86400
         if self.auto_gc_interval_secs < 60 {
             anyhow::bail!(
@@ -167,6 +227,27 @@ impl DaemonConfig {
             );
         }
 
+        // burst_events_per_sec: at least 1
+        if self.burst_events_per_sec == 0 {
+            anyhow::bail!("burst_events_per_sec cannot be 0 (minimum: 1)");
+        }
+
+        // max_checkpoint_deferral_secs: 1-3600
+        if self.max_checkpoint_deferral_secs == 0 || self.max_checkpoint_deferral_secs > 3600 {
+            anyhow::bail!(
+                "max_checkpoint_deferral_secs out of range: {} (1-3600)",
+                self.max_checkpoint_deferral_secs
+            );
+        }
+
+        // collapse_oscillation_secs: 0-3600
+        if self.collapse_oscillation_secs > 3600 {
+            anyhow::bail!(
+                "collapse_oscillation_secs too large: {} (maximum: 3600)",
+                self.collapse_oscillation_secs
+            );
+        }
+
         Ok(())
     }
 }
@@ -300,8 +381,13 @@ pub fn example_config() -> String {
     content.push_str("#\n");
     content.push_str("# Valid ranges (values outside these will use defaults):\n");
     content.push_str("#   checkpoint_interval_secs: 1-3600 (seconds)\n");
+    content.push_str("#   min_checkpoint_interval_ms: 100-60000 (milliseconds)\n");
+    content.push_str("#   max_checkpoint_interval_secs: 1-3600 (seconds)\n");
     content.push_str("#   auto_gc_interval_secs: 60-86400 (seconds)\n");
     content.push_str("#   auto_gc_checkpoint_threshold: 100-100,000\n");
+    content.push_str("#   burst_events_per_sec: 1 or more\n");
+    content.push_str("#   max_checkpoint_deferral_secs: 1-3600 (seconds)\n");
+    content.push_str("#   collapse_oscillation_secs: 0-3600 (0 = off)\n");
     content.push_str("#   retain_count: 10-1,000,000\n");
     content.push_str("#   retain_hours: 0-8760 (0 = no time limit)\n\n");
 
@@ -547,6 +633,75 @@ mod tests {
         assert!(max_config.validate().is_ok());
     }
 
+    #[test]
+    fn test_adaptive_interval_bounds() {
+        let config = DaemonConfig {
+            min_checkpoint_interval_ms: 50,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        let config = DaemonConfig {
+            max_checkpoint_interval_secs: 0,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        // The cap can't be shorter than the minimum gap
+        let config = DaemonConfig {
+            min_checkpoint_interval_ms: 5000,
+            max_checkpoint_interval_secs: 2,
+            ..Default::default()
+        };
+        assert!(config.validate().unwrap_err().to_string().contains("below min_checkpoint_interval_ms"));
+
+        let config = DaemonConfig {
+            min_checkpoint_interval_ms: 1000,
+            max_checkpoint_interval_secs: 1,
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_quiescence_bounds() {
+        let config = DaemonConfig {
+            burst_events_per_sec: 0,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        let config = DaemonConfig {
+            max_checkpoint_deferral_secs: 0,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        let config = DaemonConfig {
+            quiescence_enabled: false,
+            max_checkpoint_deferral_secs: 3600,
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_collapse_oscillation_bounds() {
+        assert_eq!(DaemonConfig::default().collapse_oscillation_secs, 0);
+
+        let config = DaemonConfig {
+            collapse_oscillation_secs: 3601,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        let config = DaemonConfig {
+            collapse_oscillation_secs: 30,
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+    }
+
     #[test]
     fn test_system_config_validation_propagates_errors() {
         let config = SystemConfig {
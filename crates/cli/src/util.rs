@@ -1,9 +1,9 @@
 //! Shared utilities for CLI commands
 
 use anyhow::{Context, Result};
-use journal::{Checkpoint, Journal, PinManager};
+use journal::{Checkpoint, Journal, PinManager, StashManager};
 use owo_colors::OwoColorize;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use ulid::Ulid;
 
@@ -196,6 +196,9 @@ pub fn display_checkpoint_compact(cp: &Checkpoint, show_ulid: bool) {
         journal::CheckpointReason::Publish => "publish",
         journal::CheckpointReason::GcCompact => "gc",
         journal::CheckpointReason::WorkspaceSave => "workspace",
+        journal::CheckpointReason::Merge => "merge",
+        journal::CheckpointReason::CherryPick => "pick",
+        journal::CheckpointReason::Revert => "revert",
     };
 
     if show_ulid {
@@ -226,6 +229,33 @@ pub fn build_pin_map(pin_manager: &PinManager) -> Result<HashMap<Ulid, Vec<Strin
     Ok(map)
 }
 
+/// Checkpoints referenced from outside the journal
+///
+/// JJ workspace heads, stashes (and the HEAD they were taken on) and
+/// checkpoints published to JJ. GC keeps these live and never folds them
+/// into a compacted run; the daemon never collapses one as an oscillation.
+pub fn referenced_checkpoints(tl_dir: &Path, repo_root: &Path) -> Result<HashSet<Ulid>> {
+    let mut referenced = HashSet::new();
+
+    if jj::detect_jj_workspace(repo_root)?.is_some() {
+        let ws_manager = jj::WorkspaceManager::open(tl_dir, repo_root)?;
+        referenced.extend(ws_manager.list_states()?.into_iter().filter_map(|state| state.current_checkpoint));
+    }
+
+    for (_, entry) in StashManager::new(tl_dir).list()? {
+        referenced.insert(entry.checkpoint_id);
+        referenced.extend(entry.base_checkpoint_id);
+    }
+
+    // Opening would create the database; nothing was published without it
+    if tl_dir.join("state/jj-mapping").exists() {
+        let mapping = jj::JjMapping::open(tl_dir)?;
+        referenced.extend(mapping.all_mappings()?.into_iter().map(|(id, _)| id));
+    }
+
+    Ok(referenced)
+}
+
 /// Calculate directory size recursively
 pub fn calculate_dir_size(dir: &Path) -> Result<u64> {
     if !dir.exists() {
@@ -248,6 +278,13 @@ pub fn calculate_dir_size(dir: &Path) -> Result<u64> {
     Ok(total)
 }
 
+/// Free space available to unprivileged users on the filesystem holding `path`
+pub fn available_space(path: &Path) -> Result<u64> {
+    let stat = nix::sys::statvfs::statvfs(path)
+        .with_context(|| format!("Failed to stat filesystem of {}", path.display()))?;
+    Ok(stat.blocks_available() as u64 * stat.fragment_size() as u64)
+}
+
 // ============================================================================
 // Git Integration Utilities
 // ============================================================================
@@ -691,4 +728,51 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_referenced_checkpoints() -> Result<()> {
+        use journal::StashEntry;
+        use tempfile::TempDir;
+
+        let temp = TempDir::new()?;
+        let repo_root = temp.path();
+        let tl_dir = repo_root.join(".tl");
+        std::fs::create_dir_all(tl_dir.join("state"))?;
+
+        // Nothing recorded yet, and the JJ mapping database isn't created
+        assert!(referenced_checkpoints(&tl_dir, repo_root)?.is_empty());
+        assert!(!tl_dir.join("state/jj-mapping").exists());
+
+        let (stashed, stash_base, published, ws_head) = (Ulid::new(), Ulid::new(), Ulid::new(), Ulid::new());
+        StashManager::new(&tl_dir).push(StashEntry {
+            checkpoint_id: stashed,
+            created_at_ms: 0,
+            message: None,
+            base_checkpoint_id: Some(stash_base),
+        })?;
+        {
+            let mapping = jj::JjMapping::open(&tl_dir)?;
+            mapping.set_seed("seed")?;
+            mapping.set(published, "abc123")?;
+            mapping.set_reverse("abc123", published)?;
+            mapping.flush()?;
+        }
+
+        // Workspace states only count in a JJ workspace
+        std::fs::create_dir_all(repo_root.join(".jj/repo"))?;
+        jj::WorkspaceManager::open(&tl_dir, repo_root)?.set_state(&jj::WorkspaceState {
+            name: "default".to_string(),
+            path: repo_root.to_path_buf(),
+            current_checkpoint: Some(ws_head),
+            last_switched_ms: 0,
+            created_ms: 0,
+            auto_pin: None,
+        })?;
+
+        let referenced = referenced_checkpoints(&tl_dir, repo_root)?;
+        let expected: HashSet<Ulid> = [stashed, stash_base, published, ws_head].into_iter().collect();
+        assert_eq!(referenced, expected);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,85 @@
+//! Checkpoint helpers for daemon-driven integration tests
+//!
+//! `tl flush` only prints an 8-character short ID, and the adaptive scheduler
+//! often checkpoints a change before the flush runs, so these helpers read
+//! HEAD's full ID from `tl show HEAD` instead of flush output.
+
+use anyhow::Result;
+use std::path::Path;
+use std::time::Duration;
+use super::cli::{TlCommand, extract_ulid};
+use super::fixtures::TestProject;
+
+/// Full ID of HEAD, or None before the first checkpoint
+pub fn head_checkpoint_id(root: &Path) -> Result<Option<String>> {
+    let show = TlCommand::new(root)
+        .args(&["show", "HEAD"])
+        .execute()?;
+
+    if !show.success() {
+        return Ok(None);
+    }
+
+    Ok(show.stdout.lines().next().and_then(extract_ulid))
+}
+
+/// Write a file and flush until a new checkpoint records it
+///
+/// Re-touches the file on each attempt in case the watcher missed the write.
+pub async fn checkpoint_with(
+    root: &Path,
+    project: &mut TestProject,
+    file: &str,
+    content: &str,
+) -> Result<String> {
+    let before = head_checkpoint_id(root)?;
+
+    for attempt in 0..5 {
+        project.modify_files(&[file], content)?;
+
+        let delay = Duration::from_millis(500 + (attempt as u64 * 200));
+        tokio::time::sleep(delay).await;
+
+        TlCommand::new(root).args(&["flush"]).execute()?;
+
+        if let Some(head) = head_checkpoint_id(root)? {
+            if before.as_deref() != Some(head.as_str()) {
+                return Ok(head);
+            }
+        }
+    }
+
+    anyhow::bail!("Failed to create checkpoint after 5 attempts for {}", file)
+}
+
+/// Flush pending changes and return HEAD once it reflects them
+///
+/// Done when `tl flush` records a checkpoint, or when the scheduler already
+/// did and HEAD stays put across two flushes with nothing pending.
+pub async fn create_checkpoint(root: &Path) -> Result<Option<String>> {
+    let mut last_head = None;
+
+    for attempt in 0..3 {
+        let delay = Duration::from_millis(500 + (attempt as u64 * 300));
+        tokio::time::sleep(delay).await;
+
+        let flush_result = TlCommand::new(root)
+            .args(&["flush"])
+            .execute()?;
+
+        if !flush_result.success() {
+            continue;
+        }
+
+        let head = head_checkpoint_id(root)?;
+        if flush_result.contains_stdout("Created checkpoint") {
+            return Ok(head);
+        }
+        if head.is_some() && head == last_head {
+            return Ok(head);
+        }
+        last_head = head;
+    }
+
+    Ok(None)
+}
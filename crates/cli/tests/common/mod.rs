@@ -2,6 +2,7 @@
 
 pub mod fixtures;
 pub mod cli;
+pub mod checkpoint;
 
 // Re-export commonly used items
 pub use fixtures::{ProjectSize, ProjectTemplate, TestProject};
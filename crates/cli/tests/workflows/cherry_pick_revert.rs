@@ -0,0 +1,74 @@
+//! Cherry-pick and revert integration tests
+//!
+//! Tests applying and undoing a single checkpoint's change on top of HEAD,
+//! including the conflict path.
+
+use anyhow::Result;
+use std::fs;
+use std::time::Duration;
+use crate::common::{ProjectSize, ProjectTemplate, TestProject};
+use crate::common::checkpoint::checkpoint_with;
+use crate::common::cli::TlCommand;
+
+/// Revert undoes one checkpoint's change and cherry-pick re-applies it,
+/// leaving later edits to other files alone
+#[tokio::test]
+async fn test_revert_and_cherry_pick_apply() -> Result<()> {
+    let mut project = TestProject::new(
+        ProjectTemplate::rust_project(ProjectSize::Tiny)
+    )?;
+    let root = project.root().to_path_buf();
+
+    TlCommand::new(&root).args(&["init"]).assert_success()?;
+    TlCommand::new(&root).args(&["start"]).assert_success()?;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    fs::write(root.join("a.txt"), "a1\n")?;
+    checkpoint_with(&root, &mut project, "b.txt", "b1\n").await?;
+    let change = checkpoint_with(&root, &mut project, "b.txt", "b2\n").await?;
+    checkpoint_with(&root, &mut project, "a.txt", "a2\n").await?;
+
+    // Revert: b goes back to its parent's content, a keeps the later edit
+    TlCommand::new(&root).args(&["revert", &change]).assert_success()?;
+    assert_eq!(fs::read_to_string(root.join("b.txt"))?, "b1\n");
+    assert_eq!(fs::read_to_string(root.join("a.txt"))?, "a2\n");
+
+    // Cherry-pick the same checkpoint back onto the reverted HEAD
+    TlCommand::new(&root).args(&["cherry-pick", &change]).assert_success()?;
+    assert_eq!(fs::read_to_string(root.join("b.txt"))?, "b2\n");
+    assert_eq!(fs::read_to_string(root.join("a.txt"))?, "a2\n");
+
+    TlCommand::new(&root).args(&["stop"]).assert_success()?;
+
+    Ok(())
+}
+
+/// Overlapping edits are written to the working directory with conflict markers
+#[tokio::test]
+async fn test_revert_conflict_writes_markers() -> Result<()> {
+    let mut project = TestProject::new(
+        ProjectTemplate::rust_project(ProjectSize::Tiny)
+    )?;
+    let root = project.root().to_path_buf();
+
+    TlCommand::new(&root).args(&["init"]).assert_success()?;
+    TlCommand::new(&root).args(&["start"]).assert_success()?;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    checkpoint_with(&root, &mut project, "c.txt", "base\n").await?;
+    let change = checkpoint_with(&root, &mut project, "c.txt", "second\n").await?;
+    checkpoint_with(&root, &mut project, "c.txt", "third\n").await?;
+
+    // HEAD rewrote the same line the reverted checkpoint changed
+    let revert = TlCommand::new(&root).args(&["revert", &change]).assert_success()?;
+    assert!(revert.contains_stdout("conflict"), "Conflict not reported:\n{}", revert.stdout);
+
+    let content = fs::read_to_string(root.join("c.txt"))?;
+    assert!(content.contains("<<<<<<<"), "Missing conflict markers:\n{}", content);
+    assert!(content.contains("third"));
+    assert!(content.contains("base"));
+
+    TlCommand::new(&root).args(&["stop"]).assert_success()?;
+
+    Ok(())
+}
@@ -6,60 +6,9 @@ use anyhow::Result;
 use std::fs;
 use std::time::Duration;
 use crate::common::{ProjectSize, ProjectTemplate, TestProject};
+use crate::common::checkpoint::{checkpoint_with, create_checkpoint};
 use crate::common::cli::TlCommand;
 
-/// Trigger checkpoint creation and return the checkpoint ID
-///
-/// Retries up to 3 times with increasing delays to handle FSEvents latency.
-async fn create_checkpoint(root: &std::path::Path) -> Result<Option<String>> {
-    // FSEvents on macOS can have 100-500ms latency, retry with backoff
-    for attempt in 0..3 {
-        let delay = Duration::from_millis(500 + (attempt as u64 * 300));
-        tokio::time::sleep(delay).await;
-
-        let flush_result = TlCommand::new(root)
-            .args(&["flush"])
-            .execute()?;
-
-        if flush_result.success() {
-            if let Some(ulid) = crate::common::cli::extract_ulid(&flush_result.stdout) {
-                return Ok(Some(ulid));
-            }
-        }
-    }
-
-    Ok(None)
-}
-
-/// Create checkpoint with guaranteed success (retries with file re-touch)
-async fn create_checkpoint_guaranteed(
-    root: &std::path::Path,
-    project: &mut TestProject,
-    file: &str,
-    content: &str,
-) -> Result<String> {
-    // Try up to 5 times with file re-touching
-    for attempt in 0..5 {
-        // Re-touch the file to ensure FSEvents sees it
-        project.modify_files(&[file], content)?;
-
-        let delay = Duration::from_millis(500 + (attempt as u64 * 200));
-        tokio::time::sleep(delay).await;
-
-        let flush_result = TlCommand::new(root)
-            .args(&["flush"])
-            .execute()?;
-
-        if flush_result.success() {
-            if let Some(ulid) = crate::common::cli::extract_ulid(&flush_result.stdout) {
-                return Ok(ulid);
-            }
-        }
-    }
-
-    anyhow::bail!("Failed to create checkpoint after 5 attempts for content: {}", content)
-}
-
 /// Test 100 checkpoints - log query performance
 #[tokio::test]
 async fn test_deep_history_100_checkpoints() -> Result<()> {
@@ -78,7 +27,7 @@ async fn test_deep_history_100_checkpoints() -> Result<()> {
 
     for i in 0..100 {
         let content = format!("// checkpoint {}", i);
-        let cp = create_checkpoint_guaranteed(&root, &mut project, "src/main.rs", &content).await?;
+        let cp = checkpoint_with(&root, &mut project, "src/main.rs", &content).await?;
         checkpoint_ids.push(cp);
 
         if (i + 1) % 20 == 0 {
@@ -6,36 +6,9 @@ use anyhow::Result;
 use std::fs;
 use std::time::Duration;
 use crate::common::{ProjectSize, ProjectTemplate, TestProject};
+use crate::common::checkpoint::{checkpoint_with, create_checkpoint};
 use crate::common::cli::TlCommand;
 
-/// Trigger checkpoint creation and return the checkpoint ID
-///
-/// Retries up to 3 times with increasing delays to handle FSEvents latency.
-async fn create_checkpoint(root: &std::path::Path) -> Result<Option<String>> {
-    // FSEvents on macOS can have 100-500ms latency, retry with backoff
-    for attempt in 0..3 {
-        let delay = Duration::from_millis(500 + (attempt as u64 * 300));
-        tokio::time::sleep(delay).await;
-
-        let flush_result = TlCommand::new(root)
-            .args(&["flush"])
-            .execute()?;
-
-        if flush_result.success() {
-            if let Some(ulid) = crate::common::cli::extract_ulid(&flush_result.stdout) {
-                return Ok(Some(ulid));
-            }
-        }
-
-        // If no checkpoint created, the FSEvents hasn't fired yet - retry
-        if attempt < 2 {
-            tracing::debug!("Checkpoint creation attempt {} failed, retrying...", attempt + 1);
-        }
-    }
-
-    Ok(None)
-}
-
 /// Test flushing with no pending changes
 #[tokio::test]
 async fn test_flush_no_changes() -> Result<()> {
@@ -322,35 +295,6 @@ async fn test_deep_nesting() -> Result<()> {
     Ok(())
 }
 
-/// Create checkpoint with guaranteed success (retries with file re-touch)
-async fn create_checkpoint_guaranteed(
-    root: &std::path::Path,
-    project: &mut TestProject,
-    file: &str,
-    content: &str,
-) -> Result<String> {
-    // Try up to 5 times with file re-touching
-    for attempt in 0..5 {
-        // Re-touch the file to ensure FSEvents sees it
-        project.modify_files(&[file], content)?;
-
-        let delay = Duration::from_millis(500 + (attempt as u64 * 200));
-        tokio::time::sleep(delay).await;
-
-        let flush_result = TlCommand::new(root)
-            .args(&["flush"])
-            .execute()?;
-
-        if flush_result.success() {
-            if let Some(ulid) = crate::common::cli::extract_ulid(&flush_result.stdout) {
-                return Ok(ulid);
-            }
-        }
-    }
-
-    anyhow::bail!("Failed to create checkpoint after 5 attempts for content: {}", content)
-}
-
 /// Test rapid successive checkpoints
 #[tokio::test]
 async fn test_rapid_checkpoints() -> Result<()> {
@@ -369,7 +313,7 @@ async fn test_rapid_checkpoints() -> Result<()> {
 
     for i in 0..10 {
         let content = format!("// version {}", i);
-        let cp = create_checkpoint_guaranteed(&root, &mut project, "src/main.rs", &content).await?;
+        let cp = checkpoint_with(&root, &mut project, "src/main.rs", &content).await?;
         checkpoints.push(cp);
     }
 
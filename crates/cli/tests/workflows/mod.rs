@@ -10,3 +10,4 @@ pub mod pin_unpin_gc;
 pub mod large_files;
 pub mod deep_history;
 pub mod publish_pull;
+pub mod cherry_pick_revert;
@@ -6,24 +6,9 @@ use anyhow::Result;
 use std::fs;
 use std::time::Duration;
 use crate::common::{ProjectSize, ProjectTemplate, TestProject};
+use crate::common::checkpoint::create_checkpoint;
 use crate::common::cli::TlCommand;
 
-/// Trigger checkpoint creation and return the checkpoint ID
-async fn create_checkpoint(root: &std::path::Path) -> Result<Option<String>> {
-    // Wait for FSEvents latency + coalescing window (macOS can have 100-300ms FSEvents delay)
-    tokio::time::sleep(Duration::from_millis(1000)).await;
-
-    let flush_result = TlCommand::new(root)
-        .args(&["flush"])
-        .execute()?;
-
-    if !flush_result.success() {
-        return Ok(None);
-    }
-
-    Ok(crate::common::cli::extract_ulid(&flush_result.stdout))
-}
-
 /// Test basic pin creation
 #[tokio::test]
 async fn test_pin_checkpoint() -> Result<()> {
@@ -7,24 +7,9 @@ use anyhow::Result;
 use std::fs;
 use std::time::Duration;
 use crate::common::{ProjectSize, ProjectTemplate, TestProject};
+use crate::common::checkpoint::create_checkpoint;
 use crate::common::cli::TlCommand;
 
-/// Trigger checkpoint creation and return the checkpoint ID
-async fn create_checkpoint(root: &std::path::Path) -> Result<Option<String>> {
-    // Wait for FSEvents latency + coalescing window (macOS can have 100-300ms FSEvents delay)
-    tokio::time::sleep(Duration::from_millis(1000)).await;
-
-    let flush_result = TlCommand::new(root)
-        .args(&["flush"])
-        .execute()?;
-
-    if !flush_result.success() {
-        return Ok(None);
-    }
-
-    Ok(crate::common::cli::extract_ulid(&flush_result.stdout))
-}
-
 /// Create a JJ commit using native jj-lib APIs
 fn create_jj_commit_native(root: &std::path::Path, message: &str) -> Result<()> {
     use jj_lib::repo::Repo;
@@ -203,6 +188,45 @@ async fn test_publish_with_bookmark() -> Result<()> {
     Ok(())
 }
 
+/// Test publish of a checkpoint holding a hash-only path (content never stored)
+#[tokio::test]
+async fn test_publish_hash_only_path() -> Result<()> {
+    let mut project = TestProject::new(
+        ProjectTemplate::rust_project(ProjectSize::Tiny)
+    )?;
+    let root = project.root().to_path_buf();
+
+    fs::write(root.join(".tlpolicy"), "hash-only data/*.bin\n")?;
+    fs::create_dir_all(root.join("data"))?;
+    fs::write(root.join("data/dump.bin"), vec![0xAB; 64 * 1024])?;
+
+    TlCommand::new(&root).args(&["init"]).assert_success()?;
+
+    TlCommand::new(&root).args(&["start"]).assert_success()?;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    project.modify_files(&["src/main.rs"], "// with hash-only data")?;
+    fs::write(root.join("data/dump.bin"), vec![0xCD; 64 * 1024])?;
+    let checkpoint = create_checkpoint(&root).await?
+        .expect("Should create checkpoint");
+
+    TlCommand::new(&root).args(&["stop"]).assert_success()?;
+
+    // The hash-only file is left out of the commit instead of failing the publish
+    let publish_result = TlCommand::new(&root)
+        .args(&["publish", &checkpoint])
+        .assert_success()?;
+
+    println!("{}", publish_result.stdout);
+    assert!(
+        publish_result.stderr.contains("not kept") && publish_result.stderr.contains("data/dump.bin"),
+        "Should report the skipped hash-only path: {}",
+        publish_result.stderr
+    );
+
+    Ok(())
+}
+
 /// Test publish range of checkpoints
 #[tokio::test]
 async fn test_publish_range() -> Result<()> {
@@ -6,59 +6,8 @@ use anyhow::Result;
 use std::fs;
 use std::time::Duration;
 use crate::common::{ProjectSize, ProjectTemplate, TestProject};
-use crate::common::cli::{TlCommand, extract_ulid};
-
-/// Trigger checkpoint creation and return the checkpoint ID
-///
-/// Retries up to 5 times with increasing delays to handle FSEvents latency.
-async fn create_checkpoint(root: &std::path::Path) -> Result<String> {
-    // FSEvents on macOS can have 100-500ms latency, retry with backoff
-    for attempt in 0..5 {
-        let delay = Duration::from_millis(500 + (attempt as u64 * 300));
-        tokio::time::sleep(delay).await;
-
-        let flush_result = TlCommand::new(root)
-            .args(&["flush"])
-            .execute()?;
-
-        if flush_result.success() {
-            if let Some(ulid) = extract_ulid(&flush_result.stdout) {
-                return Ok(ulid);
-            }
-        }
-    }
-
-    anyhow::bail!("No checkpoint created after 5 attempts (FSEvents latency issue)")
-}
-
-/// Create checkpoint with guaranteed success (retries with file re-touch)
-async fn create_checkpoint_guaranteed(
-    root: &std::path::Path,
-    project: &mut TestProject,
-    file: &str,
-    content: &str,
-) -> Result<String> {
-    // Try up to 5 times with file re-touching
-    for attempt in 0..5 {
-        // Re-touch the file to ensure FSEvents sees it
-        project.modify_files(&[file], content)?;
-
-        let delay = Duration::from_millis(500 + (attempt as u64 * 200));
-        tokio::time::sleep(delay).await;
-
-        let flush_result = TlCommand::new(root)
-            .args(&["flush"])
-            .execute()?;
-
-        if flush_result.success() {
-            if let Some(ulid) = extract_ulid(&flush_result.stdout) {
-                return Ok(ulid);
-            }
-        }
-    }
-
-    anyhow::bail!("Failed to create checkpoint after 5 attempts for content: {}", content)
-}
+use crate::common::checkpoint::{checkpoint_with, create_checkpoint};
+use crate::common::cli::TlCommand;
 
 /// Test basic restore functionality
 #[tokio::test]
@@ -75,7 +24,8 @@ async fn test_basic_restore() -> Result<()> {
 
     // Create initial state
     project.modify_files(&["src/main.rs"], "// version 1\nfn main() {}")?;
-    let checkpoint_id = create_checkpoint(&root).await?;
+    let checkpoint_id = create_checkpoint(&root).await?
+        .expect("Should create checkpoint");
 
     // Stop daemon
     TlCommand::new(&root).args(&["stop"]).assert_success()?;
@@ -118,13 +68,13 @@ async fn test_rewind_to_earlier_state() -> Result<()> {
     tokio::time::sleep(Duration::from_secs(1)).await;
 
     // STATE 1: Initial (use guaranteed checkpoint creation with file re-touch)
-    let checkpoint1 = create_checkpoint_guaranteed(&root, &mut project, "src/main.rs", "// state 1").await?;
+    let checkpoint1 = checkpoint_with(&root, &mut project, "src/main.rs", "// state 1").await?;
 
     // STATE 2: Modified
-    let _checkpoint2 = create_checkpoint_guaranteed(&root, &mut project, "src/main.rs", "// state 2").await?;
+    let _checkpoint2 = checkpoint_with(&root, &mut project, "src/main.rs", "// state 2").await?;
 
     // STATE 3: More changes
-    let _checkpoint3 = create_checkpoint_guaranteed(&root, &mut project, "src/main.rs", "// state 3").await?;
+    let _checkpoint3 = checkpoint_with(&root, &mut project, "src/main.rs", "// state 3").await?;
 
     // Stop daemon to release database locks
     TlCommand::new(&root).args(&["stop"]).assert_success()?;
@@ -167,7 +117,8 @@ async fn bench_restore_performance() -> Result<()> {
 
         // Create a checkpoint
         project.modify_files(&["src/file_0.rs"], "// initial content")?;
-        let checkpoint = create_checkpoint(&root).await?;
+        let checkpoint = create_checkpoint(&root).await?
+            .expect("Should create checkpoint");
 
         // Stop daemon
         TlCommand::new(&root).args(&["stop"]).assert_success()?;
@@ -237,7 +188,8 @@ async fn test_restore_deleted_files() -> Result<()> {
     // Create initial state with known file
     let test_file = root.join("test_file.txt");
     fs::write(&test_file, "test content")?;
-    let checkpoint = create_checkpoint(&root).await?;
+    let checkpoint = create_checkpoint(&root).await?
+        .expect("Should create checkpoint");
 
     // Stop daemon
     TlCommand::new(&root).args(&["stop"]).assert_success()?;
@@ -276,7 +228,8 @@ async fn test_diff_shows_changes() -> Result<()> {
 
     // Create initial state
     project.modify_files(&["src/main.rs"], "// version 1")?;
-    let checkpoint = create_checkpoint(&root).await?;
+    let checkpoint = create_checkpoint(&root).await?
+        .expect("Should create checkpoint");
 
     // Stop daemon
     TlCommand::new(&root).args(&["stop"]).assert_success()?;
@@ -1,15 +1,57 @@
 //! Blob storage with Git-compatible format
-
+//!
+//! Files at or above the large-file threshold are stored chunked instead
+//! (see `chunk`): a manifest keyed by the Git blob SHA-1 plus deduplicated
+//! chunks. All reads go through the same API, so callers never need to know.
+//!
+//! With a repo cipher set (see `encryption`), everything written under
+//! `.tl/objects` is sealed and the dual-write to `.git/objects` is skipped.
+
+use crate::chunk::{chunk_stream, ChunkId, ChunkManifest, ChunkerConfig};
+use crate::encryption::{seal, unseal, RepoCipher};
 use crate::hash::Sha1Hash;
-use anyhow::Result;
+use crate::quota::StoreUsage;
+use crate::secrets::{SecretAction, SecretScanner};
+use anyhow::{Context, Result};
 use dashmap::DashMap;
 use flate2::read::ZlibDecoder;
 use flate2::write::ZlibEncoder;
 use flate2::Compression;
+use std::collections::HashSet;
 use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+/// Default size at which files are stored chunked (64 MiB)
+pub const DEFAULT_LARGE_FILE_THRESHOLD: u64 = 64 * 1024 * 1024;
+
+/// Attempts at reading a file that keeps changing (as in `hash_file_stable`)
+const STABLE_READ_ATTEMPTS: u64 = 3;
+
+/// Content read by one `write_file` attempt
+enum Ingested {
+    /// Chunks already stored; the manifest is written once the read is stable
+    Chunked(Sha1Hash, ChunkManifest),
+    /// Whole content of a small file
+    Whole(Vec<u8>),
+}
+
+/// Outcome of storing content through the secret scanner
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Screened {
+    /// Stored as-is
+    Stored(Sha1Hash),
+    /// A redacted copy was stored (its hash differs from the content's)
+    Redacted(Sha1Hash),
+    /// Not stored (a finding's action is `skip`)
+    Skipped,
+}
+
+/// Check if content is binary (contains null bytes in first 8KB)
+pub fn is_binary(content: &[u8]) -> bool {
+    content.iter().take(8192).any(|&b| b == 0)
+}
+
 /// A blob represents a stored file's contents in Git format
 #[derive(Debug, Clone)]
 pub struct Blob {
@@ -96,6 +138,14 @@ pub struct BlobStore {
     /// Maximum cache size in bytes (default: 50MB)
     #[allow(dead_code)]
     max_cache_size: usize,
+    /// Files of at least this many bytes are stored chunked
+    large_file_threshold: u64,
+    /// Chunk size bounds for large files
+    chunker: ChunkerConfig,
+    /// Seals objects at rest (None: stored as plain zlib/zstd)
+    cipher: Option<Arc<RepoCipher>>,
+    /// Bytes used on disk, updated on every write and delete
+    usage: Arc<StoreUsage>,
 }
 
 impl BlobStore {
@@ -106,9 +156,51 @@ impl BlobStore {
             git_objects_root: None,
             cache: DashMap::new(),
             max_cache_size: 50 * 1024 * 1024, // 50 MB
+            large_file_threshold: DEFAULT_LARGE_FILE_THRESHOLD,
+            chunker: ChunkerConfig::default(),
+            cipher: None,
+            usage: Arc::new(StoreUsage::default()),
         }
     }
 
+    /// Encrypt objects at rest with the repo cipher
+    ///
+    /// Also stops the dual-write to `.git/objects`, which would otherwise
+    /// leave a plaintext copy of every blob.
+    pub fn set_cipher(&mut self, cipher: Option<Arc<RepoCipher>>) {
+        self.cipher = cipher;
+    }
+
+    /// Track disk usage in a shared counter (see `quota`)
+    pub fn set_usage(&mut self, usage: Arc<StoreUsage>) {
+        self.usage = usage;
+    }
+
+    /// Bytes used on disk, as tracked by this store
+    pub fn usage(&self) -> &Arc<StoreUsage> {
+        &self.usage
+    }
+
+    /// True if objects are encrypted at rest
+    pub fn is_encrypted(&self) -> bool {
+        self.cipher.is_some()
+    }
+
+    /// Set the size at which files are stored chunked
+    pub fn set_large_file_threshold(&mut self, threshold: u64) {
+        self.large_file_threshold = threshold.max(1);
+    }
+
+    /// Set the chunk size bounds for large files
+    pub fn set_chunker_config(&mut self, config: ChunkerConfig) {
+        self.chunker = config;
+    }
+
+    /// Size at which files are stored chunked
+    pub fn large_file_threshold(&self) -> u64 {
+        self.large_file_threshold
+    }
+
     /// Enable dual-write to Git objects directory
     ///
     /// When set, blobs will be written to both `.tl/objects/` and
@@ -132,12 +224,26 @@ impl BlobStore {
     pub fn write_blob(&self, hash: Sha1Hash, data: &[u8]) -> Result<()> {
         use std::fs;
 
+        // Large content goes to the chunk store (never to .git/objects)
+        if data.len() as u64 >= self.large_file_threshold {
+            if self.manifest_path(hash).exists() {
+                return Ok(());
+            }
+            let (actual, manifest) = chunk_stream(data, data.len() as u64, self.chunker, |id, chunk| {
+                self.write_chunk(id, chunk)
+            })?;
+            if actual != hash {
+                anyhow::bail!("Hash mismatch: expected {}, got {}", hash.to_hex(), actual.to_hex());
+            }
+            return self.write_manifest(hash, &manifest);
+        }
+
         // Check if blob already exists in TL store
         let blob_path = self.blob_path(hash);
         let tl_exists = blob_path.exists();
 
-        // Check if blob exists in Git store (if configured)
-        let git_path = self.git_blob_path(hash);
+        // Check if blob exists in Git store (if configured, never when encrypting)
+        let git_path = if self.cipher.is_some() { None } else { self.git_blob_path(hash) };
         let git_exists = git_path.as_ref().map(|p| p.exists()).unwrap_or(true);
 
         // If blob exists in both locations, nothing to do
@@ -159,6 +265,8 @@ impl BlobStore {
 
         // Write to TL store if needed
         if !tl_exists {
+            let sealed = seal(self.cipher.as_deref(), &compressed, hash.as_bytes())?;
+
             // Ensure parent directory exists
             if let Some(parent) = blob_path.parent() {
                 fs::create_dir_all(parent)?;
@@ -172,12 +280,13 @@ impl BlobStore {
 
             // Write to temp file
             let mut temp_file = fs::File::create(&temp_path)?;
-            temp_file.write_all(&compressed)?;
+            temp_file.write_all(&sealed)?;
             temp_file.sync_all()?; // fsync file
             drop(temp_file);
 
             // Rename to final location
             fs::rename(&temp_path, &blob_path)?;
+            self.usage.add(sealed.len() as u64);
 
             // Fsync parent directory for durability
             if let Some(parent) = blob_path.parent() {
@@ -221,10 +330,274 @@ impl BlobStore {
         Ok(())
     }
 
+    /// Stream a file into the store, returning its Git blob hash
+    ///
+    /// Files below the large-file threshold are read and stored as a normal
+    /// blob; larger files are chunked without being held in memory. Like
+    /// `hash_file_stable`, a read that races a write (stat differs before and
+    /// after) is retried with backoff; fails if the file never settles.
+    pub fn write_file(&self, path: &Path) -> Result<Sha1Hash> {
+        for attempt in 0..STABLE_READ_ATTEMPTS {
+            let before = crate::stat::FileStat::from_path(path)
+                .with_context(|| format!("Failed to stat {}", path.display()))?;
+
+            // Nothing becomes reachable (no manifest, no blob) until the read is known stable
+            let read = if before.size >= self.large_file_threshold {
+                self.chunk_file(path, before.size)
+                    .map(|(hash, manifest)| Ingested::Chunked(hash, manifest))
+            } else {
+                std::fs::read(path)
+                    .with_context(|| format!("Failed to read {}", path.display()))
+                    .map(Ingested::Whole)
+            };
+
+            if crate::stat::FileStat::from_path(path)? == before {
+                return match read? {
+                    Ingested::Chunked(hash, manifest) => {
+                        if !self.manifest_path(hash).exists() {
+                            self.write_manifest(hash, &manifest)?;
+                        }
+                        Ok(hash)
+                    }
+                    Ingested::Whole(data) => {
+                        let hash = crate::hash::git::hash_blob(&data);
+                        self.write_blob(hash, &data)?;
+                        Ok(hash)
+                    }
+                };
+            }
+
+            // File changed during read - exponential backoff (50ms, 100ms, 200ms)
+            if attempt + 1 < STABLE_READ_ATTEMPTS {
+                std::thread::sleep(std::time::Duration::from_millis(50 << attempt));
+            }
+        }
+
+        anyhow::bail!("File modified during read: {} (will be requeued)", path.display())
+    }
+
+    /// Chunk a file into the store, returning its hash and (unwritten) manifest
+    fn chunk_file(&self, path: &Path, size: u64) -> Result<(Sha1Hash, ChunkManifest)> {
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        let reader = std::io::BufReader::with_capacity(1024 * 1024, file);
+        chunk_stream(reader, size, self.chunker, |id, chunk| self.write_chunk(id, chunk))
+            .with_context(|| format!("Failed to chunk {}", path.display()))
+    }
+
+    /// `write_blob` after screening `data` for secrets
+    ///
+    /// `path` is only used in log messages. With no scanner (or an empty
+    /// one) this is a plain `write_blob`.
+    pub fn write_blob_screened(
+        &self,
+        hash: Sha1Hash,
+        data: &[u8],
+        path: &Path,
+        scanner: Option<&SecretScanner>,
+    ) -> Result<Screened> {
+        let report = match scanner {
+            Some(scanner) if !scanner.is_empty() => scanner.scan(data),
+            _ => {
+                self.write_blob(hash, data)?;
+                return Ok(Screened::Stored(hash));
+            }
+        };
+        report.log(path);
+
+        match report.action() {
+            Some(SecretAction::Skip) => Ok(Screened::Skipped),
+            Some(SecretAction::Redact) => {
+                let redacted = SecretScanner::redact(data, &report);
+                let redacted_hash = crate::hash::git::hash_blob(&redacted);
+                self.write_blob(redacted_hash, &redacted)?;
+                Ok(Screened::Redacted(redacted_hash))
+            }
+            _ => {
+                self.write_blob(hash, data)?;
+                Ok(Screened::Stored(hash))
+            }
+        }
+    }
+
+    /// `write_file` after screening the file for secrets
+    ///
+    /// The file is scanned as a stream, so large files are never held in
+    /// memory; a redacted copy is streamed through a temp file. Fails if the
+    /// file changes between the scan and the store.
+    pub fn write_file_screened(
+        &self,
+        abs_path: &Path,
+        path: &Path,
+        scanner: Option<&SecretScanner>,
+    ) -> Result<Screened> {
+        let scanner = match scanner {
+            Some(scanner) if !scanner.is_empty() => scanner,
+            _ => return Ok(Screened::Stored(self.write_file(abs_path)?)),
+        };
+
+        let before = crate::stat::FileStat::from_path(abs_path)
+            .with_context(|| format!("Failed to stat {}", abs_path.display()))?;
+        let report = scanner.scan_file(abs_path)?;
+        report.log(path);
+
+        let screened = match report.action() {
+            Some(SecretAction::Skip) => return Ok(Screened::Skipped),
+            Some(SecretAction::Redact) => {
+                let tmp_dir = self.root.join("tmp/ingest");
+                std::fs::create_dir_all(&tmp_dir)?;
+                let tmp_path = tmp_dir.join(format!("{}-redacted", uuid::Uuid::new_v4()));
+                let stored = self.write_redacted(abs_path, &tmp_path, &report);
+                let _ = std::fs::remove_file(&tmp_path);
+                Screened::Redacted(stored?)
+            }
+            _ => Screened::Stored(self.write_file(abs_path)?),
+        };
+
+        if crate::stat::FileStat::from_path(abs_path)? != before {
+            anyhow::bail!("File modified during read: {} (will be requeued)", abs_path.display());
+        }
+
+        Ok(screened)
+    }
+
+    /// Stream a redacted copy of `abs_path` into `tmp_path` and store it
+    fn write_redacted(&self, abs_path: &Path, tmp_path: &Path, report: &crate::secrets::ScanReport) -> Result<Sha1Hash> {
+        let mut input = std::io::BufReader::new(std::fs::File::open(abs_path)
+            .with_context(|| format!("Failed to open {}", abs_path.display()))?);
+        let mut output = std::io::BufWriter::new(std::fs::File::create(tmp_path)?);
+        SecretScanner::redact_to(&mut input, &mut output, report)?;
+        output.flush()?;
+        drop(output);
+        self.write_file(tmp_path)
+    }
+
+    /// Record that a blob's content was deliberately not stored
+    ///
+    /// Used for hash-only path policies: trees still reference the blob, and
+    /// readers get a "content not kept" error instead of "not found".
+    pub fn mark_omitted(&self, hash: Sha1Hash, size: u64) -> Result<()> {
+        let path = self.omitted_path(hash);
+        if path.exists() {
+            return Ok(());
+        }
+        crate::store::atomic_write(&self.root.join("tmp/ingest"), &path, size.to_string().as_bytes())
+    }
+
+    /// True if the blob's content was deliberately not stored
+    pub fn is_omitted(&self, hash: Sha1Hash) -> bool {
+        self.omitted_path(hash).exists() && !self.has_blob(hash)
+    }
+
+    /// True if the blob is stored chunked
+    pub fn is_chunked(&self, hash: Sha1Hash) -> bool {
+        self.manifest_path(hash).exists()
+    }
+
+    /// Load the chunk manifest of a chunked blob
+    pub fn read_manifest(&self, hash: Sha1Hash) -> Result<ChunkManifest> {
+        let bytes = std::fs::read(self.manifest_path(hash))
+            .with_context(|| format!("Chunk manifest not found: {}", hash.to_hex()))?;
+        ChunkManifest::deserialize(&unseal(self.cipher.as_deref(), bytes, hash.as_bytes())?)
+    }
+
+    /// Stream a blob's contents into a writer, returning bytes written
+    ///
+    /// Chunked blobs are written chunk by chunk and verified against their
+    /// SHA-1 as they stream, so large files are never held in memory.
+    pub fn read_blob_to(&self, hash: Sha1Hash, writer: &mut dyn Write) -> Result<u64> {
+        if !self.is_chunked(hash) {
+            let data = self.read_blob(hash)?;
+            writer.write_all(&data)?;
+            return Ok(data.len() as u64);
+        }
+
+        let manifest = self.read_manifest(hash)?;
+        let mut hasher = crate::hash::IncrementalHasher::new();
+        hasher.update(format!("blob {}\0", manifest.size).as_bytes());
+        for chunk_ref in &manifest.chunks {
+            let chunk = self.read_chunk(chunk_ref.id)?;
+            hasher.update(&chunk);
+            writer.write_all(&chunk)?;
+        }
+
+        let actual = hasher.finalize();
+        if actual != hash {
+            anyhow::bail!("Hash mismatch: expected {}, got {}", hash.to_hex(), actual.to_hex());
+        }
+        Ok(manifest.size)
+    }
+
+    /// Read up to `len` bytes of a blob's content starting at `offset`
+    ///
+    /// Chunked blobs only load the chunks covering the range (each checked
+    /// against its chunk ID); loose blobs are decompressed as a stream that
+    /// stops at the end of the range. Unlike `read_blob`, the SHA-1 of the
+    /// whole content is not verified.
+    pub fn read_blob_range(&self, hash: Sha1Hash, offset: u64, len: usize) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+
+        if self.is_chunked(hash) {
+            let manifest = self.read_manifest(hash)?;
+            let end = offset.saturating_add(len as u64).min(manifest.size);
+            let mut chunk_start = 0u64;
+            for chunk_ref in &manifest.chunks {
+                if chunk_start >= end {
+                    break;
+                }
+                let chunk_end = chunk_start + chunk_ref.len as u64;
+                if chunk_end > offset {
+                    let chunk = self.read_chunk(chunk_ref.id)?;
+                    let from = offset.saturating_sub(chunk_start) as usize;
+                    let to = ((end - chunk_start) as usize).min(chunk.len());
+                    data.extend_from_slice(&chunk[from..to]);
+                }
+                chunk_start = chunk_end;
+            }
+            return Ok(data);
+        }
+
+        let blob_path = self.blob_path(hash);
+        if !blob_path.exists() {
+            if self.is_omitted(hash) {
+                anyhow::bail!("Content not kept for blob {} (path policy)", hash.to_hex());
+            }
+            anyhow::bail!("Blob not found: {}", hash.to_hex());
+        }
+        let compressed = unseal(self.cipher.as_deref(), std::fs::read(&blob_path)?, hash.as_bytes())?;
+        let mut decoder = ZlibDecoder::new(&compressed[..]);
+
+        // Skip the "blob <size>\0" header
+        let mut byte = [0u8; 1];
+        for _ in 0..32 {
+            decoder.read_exact(&mut byte)?;
+            if byte[0] == 0 {
+                break;
+            }
+        }
+        if byte[0] != 0 {
+            anyhow::bail!("Invalid Git blob format: missing null separator");
+        }
+
+        std::io::copy(&mut (&mut decoder).take(offset), &mut std::io::sink())?;
+        decoder.take(len as u64).read_to_end(&mut data)?;
+        Ok(data)
+    }
+
     /// Read a blob from storage
+    ///
+    /// The whole content is returned in memory, reassembling chunked blobs;
+    /// callers writing content out (restores, exports, verification) should
+    /// stream it with `read_blob_to` or read windows with `read_blob_range`.
     pub fn read_blob(&self, hash: Sha1Hash) -> Result<Vec<u8>> {
         use std::fs;
 
+        if self.is_chunked(hash) {
+            let mut data = Vec::new();
+            self.read_blob_to(hash, &mut data)?;
+            return Ok(data);
+        }
+
         // Check cache first (for metadata)
         if let Some(cached_blob) = self.cache.get(&hash) {
             drop(cached_blob); // Release the lock
@@ -233,10 +606,13 @@ impl BlobStore {
         // Read from disk
         let blob_path = self.blob_path(hash);
         if !blob_path.exists() {
+            if self.is_omitted(hash) {
+                anyhow::bail!("Content not kept for blob {} (path policy)", hash.to_hex());
+            }
             anyhow::bail!("Blob not found: {}", hash.to_hex());
         }
 
-        let compressed = fs::read(&blob_path)?;
+        let compressed = unseal(self.cipher.as_deref(), fs::read(&blob_path)?, hash.as_bytes())?;
 
         // Decompress and parse Git format
         let data = Blob::read_from_bytes(&compressed)?;
@@ -266,13 +642,20 @@ impl BlobStore {
             return Ok(cached_blob.size);
         }
 
+        if self.is_chunked(hash) {
+            return Ok(self.read_manifest(hash)?.size);
+        }
+
         // Read from disk
         let blob_path = self.blob_path(hash);
         if !blob_path.exists() {
+            if let Ok(size) = fs::read_to_string(self.omitted_path(hash)) {
+                return size.trim().parse().context("Corrupt omitted-content marker");
+            }
             anyhow::bail!("Blob not found: {}", hash.to_hex());
         }
 
-        let compressed = fs::read(&blob_path)?;
+        let compressed = unseal(self.cipher.as_deref(), fs::read(&blob_path)?, hash.as_bytes())?;
 
         // Decompress just enough to read the header
         let mut decoder = ZlibDecoder::new(&compressed[..]);
@@ -306,7 +689,180 @@ impl BlobStore {
         }
 
         // Check filesystem
-        self.blob_path(hash).exists()
+        self.blob_path(hash).exists() || self.manifest_path(hash).exists()
+    }
+
+    /// Move a blob out of the object store into `quarantine/blobs/`
+    ///
+    /// Used by fsck repair for objects that fail hash verification. Returns
+    /// false if the blob was not present.
+    pub fn quarantine_blob(&self, hash: Sha1Hash) -> Result<bool> {
+        let blob_path = if self.is_chunked(hash) {
+            self.manifest_path(hash)
+        } else {
+            self.blob_path(hash)
+        };
+        self.cache.remove(&hash);
+        if !blob_path.exists() {
+            return Ok(false);
+        }
+
+        let quarantine_dir = self.root.join("quarantine").join("blobs");
+        std::fs::create_dir_all(&quarantine_dir)?;
+        std::fs::rename(&blob_path, quarantine_dir.join(hash.to_hex()))?;
+        Ok(true)
+    }
+
+    /// List loose (non-chunked) blobs in the store
+    pub fn list_blobs(&self) -> Result<Vec<Sha1Hash>> {
+        Ok(list_fanout(&self.root.join("objects"))?
+            .into_iter()
+            .filter_map(|(hex, _)| Sha1Hash::from_hex(&hex).ok())
+            .collect())
+    }
+
+    /// Delete a loose blob, returning the bytes freed on disk
+    ///
+    /// Only `.tl/objects` is touched; copies in `.git/objects` belong to Git.
+    pub fn delete_blob(&self, hash: Sha1Hash) -> Result<u64> {
+        self.cache.remove(&hash);
+        let blob_path = self.blob_path(hash);
+        let size = match std::fs::metadata(&blob_path) {
+            Ok(metadata) => metadata.len(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+        std::fs::remove_file(&blob_path)?;
+        self.usage.sub(size);
+        Ok(size)
+    }
+
+    /// Permanently remove a blob's content, leaving an omitted-content marker
+    ///
+    /// Deletes the loose object or the chunk manifest and any chunks no
+    /// other manifest uses. Only `.tl/objects` is touched: a dual-written
+    /// copy in `.git/objects` belongs to Git and may be reachable from
+    /// published commits. Trees keep referencing the hash; reads report the
+    /// content as not kept. Returns false if the content was not stored.
+    pub fn purge_blob(&self, hash: Sha1Hash) -> Result<bool> {
+        self.cache.remove(&hash);
+        if !self.has_blob(hash) {
+            return Ok(false);
+        }
+
+        self.mark_omitted(hash, self.blob_size(hash)?)?;
+        self.cache.remove(&hash);
+
+        if self.is_chunked(hash) {
+            let manifest_path = self.manifest_path(hash);
+            self.usage.sub(std::fs::metadata(&manifest_path).map(|m| m.len()).unwrap_or(0));
+            std::fs::remove_file(&manifest_path)?;
+            let remaining: HashSet<Sha1Hash> = list_fanout(&self.root.join("objects/manifests"))?
+                .into_iter()
+                .filter_map(|(hex, _)| Sha1Hash::from_hex(&hex).ok())
+                .collect();
+            self.sweep_chunked(&remaining)?;
+        }
+        self.delete_blob(hash)?;
+
+        Ok(true)
+    }
+
+    /// Delete unreferenced chunked blobs and any chunks no manifest uses
+    ///
+    /// `live` holds every blob hash still referenced. Returns the number of
+    /// manifests removed and the bytes freed (manifests plus chunks).
+    pub fn sweep_chunked(&self, live: &HashSet<Sha1Hash>) -> Result<(usize, u64)> {
+        let mut removed = 0;
+        let mut freed = 0;
+        let mut live_chunks: HashSet<ChunkId> = HashSet::new();
+
+        for (hex, path) in list_fanout(&self.root.join("objects/manifests"))? {
+            let hash = match Sha1Hash::from_hex(&hex) {
+                Ok(hash) => hash,
+                Err(_) => continue,
+            };
+            if live.contains(&hash) {
+                // An unreadable manifest keeps nothing alive; fsck reports it
+                if let Ok(manifest) = self.read_manifest(hash) {
+                    live_chunks.extend(manifest.chunks.iter().map(|c| c.id));
+                }
+                continue;
+            }
+            freed += std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            std::fs::remove_file(&path)?;
+            self.cache.remove(&hash);
+            removed += 1;
+        }
+
+        for (hex, path) in list_fanout(&self.root.join("objects/chunks"))? {
+            let id = match ChunkId::from_hex(&hex) {
+                Ok(id) => id,
+                Err(_) => continue,
+            };
+            if !live_chunks.contains(&id) {
+                freed += std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+                std::fs::remove_file(&path)?;
+            }
+        }
+
+        self.usage.sub(freed);
+        Ok((removed, freed))
+    }
+
+    /// Store one chunk (zstd-compressed) if not already present
+    fn write_chunk(&self, id: ChunkId, data: &[u8]) -> Result<()> {
+        let path = self.chunk_path(id);
+        if path.exists() {
+            return Ok(());
+        }
+        let compressed = zstd::encode_all(data, 3).context("Failed to compress chunk")?;
+        let sealed = seal(self.cipher.as_deref(), &compressed, &id.0)?;
+        crate::store::atomic_write(&self.root.join("tmp/ingest"), &path, &sealed)?;
+        self.usage.add(sealed.len() as u64);
+        Ok(())
+    }
+
+    /// Read and verify one chunk
+    fn read_chunk(&self, id: ChunkId) -> Result<Vec<u8>> {
+        let sealed = std::fs::read(self.chunk_path(id))
+            .with_context(|| format!("Chunk not found: {}", id.to_hex()))?;
+        let compressed = unseal(self.cipher.as_deref(), sealed, &id.0)
+            .with_context(|| format!("Corrupt chunk: {}", id.to_hex()))?;
+        let data = zstd::decode_all(&compressed[..])
+            .with_context(|| format!("Corrupt chunk: {}", id.to_hex()))?;
+        if ChunkId::of(&data) != id {
+            anyhow::bail!("Chunk hash mismatch: {}", id.to_hex());
+        }
+        Ok(data)
+    }
+
+    /// Store a chunk manifest under the file's Git blob hash
+    fn write_manifest(&self, hash: Sha1Hash, manifest: &ChunkManifest) -> Result<()> {
+        let serialized = manifest.serialize()?;
+        let sealed = seal(self.cipher.as_deref(), &serialized, hash.as_bytes())?;
+        crate::store::atomic_write(&self.root.join("tmp/ingest"), &self.manifest_path(hash), &sealed)?;
+        self.usage.add(sealed.len() as u64);
+        self.cache.insert(hash, Arc::new(Blob { hash, size: manifest.size }));
+        Ok(())
+    }
+
+    /// Path of an omitted-content marker: objects/omitted/<2>/<38>
+    fn omitted_path(&self, hash: Sha1Hash) -> PathBuf {
+        let hex = hash.to_hex();
+        self.root.join("objects/omitted").join(&hex[0..2]).join(&hex[2..])
+    }
+
+    /// Path of a chunk manifest: objects/manifests/<2>/<38>
+    fn manifest_path(&self, hash: Sha1Hash) -> PathBuf {
+        let hex = hash.to_hex();
+        self.root.join("objects/manifests").join(&hex[0..2]).join(&hex[2..])
+    }
+
+    /// Path of a chunk: objects/chunks/<2>/<62>
+    fn chunk_path(&self, id: ChunkId) -> PathBuf {
+        let hex = id.to_hex();
+        self.root.join("objects/chunks").join(&hex[0..2]).join(&hex[2..])
     }
 
     /// Get the filesystem path for a blob (Git-compatible structure)
@@ -329,6 +885,36 @@ impl BlobStore {
     }
 }
 
+/// List files in a two-level fan-out directory as (full hex name, path)
+fn list_fanout(dir: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let mut files = Vec::new();
+    if !dir.exists() {
+        return Ok(files);
+    }
+
+    for prefix_entry in std::fs::read_dir(dir)? {
+        let prefix_entry = prefix_entry?;
+        if !prefix_entry.file_type()?.is_dir() {
+            continue;
+        }
+        let prefix = prefix_entry.file_name().to_string_lossy().into_owned();
+        // Only fan-out directories (skips e.g. objects/trees next to loose blobs)
+        if prefix.len() != 2 || !prefix.bytes().all(|b| b.is_ascii_hexdigit()) {
+            continue;
+        }
+        for file_entry in std::fs::read_dir(prefix_entry.path())? {
+            let file_entry = file_entry?;
+            if !file_entry.file_type()?.is_file() {
+                continue;
+            }
+            let name = file_entry.file_name().to_string_lossy().into_owned();
+            files.push((format!("{}{}", prefix, name), file_entry.path()));
+        }
+    }
+
+    Ok(files)
+}
+
 // Re-export for backward compatibility (though we're removing backward compat)
 #[deprecated(note = "Use Git blob format directly")]
 pub struct BlobHeaderV1;
@@ -337,6 +923,14 @@ pub struct BlobHeaderV1;
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_is_binary() {
+        assert!(!is_binary(b"Hello, world!"));
+        assert!(!is_binary(b"Line 1\nLine 2\nLine 3"));
+        assert!(is_binary(b"Hello\x00world"));
+        assert!(is_binary(&[0u8; 100]));
+    }
+
     #[test]
     fn test_blob_git_format() -> Result<()> {
         let data = b"hello world";
@@ -591,4 +1185,265 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_blob_store_chunked_large_files() -> Result<()> {
+        use crate::hash::git::hash_blob;
+
+        let temp_dir = tempfile::tempdir()?;
+        let mut store = BlobStore::new(temp_dir.path().to_path_buf());
+        store.set_large_file_threshold(64 * 1024);
+        store.set_chunker_config(ChunkerConfig { min_size: 1024, avg_size: 4096, max_size: 16384 });
+
+        // Two large files sharing most of their content
+        let base: Vec<u8> = (0..200_000u32).map(|i| (i.wrapping_mul(2654435761) >> 13) as u8).collect();
+        let mut edited = base.clone();
+        edited[100_000] ^= 0xFF;
+
+        let file_a = temp_dir.path().join("a.bin");
+        let file_b = temp_dir.path().join("b.bin");
+        std::fs::write(&file_a, &base)?;
+        std::fs::write(&file_b, &edited)?;
+
+        let hash_a = store.write_file(&file_a)?;
+        let hash_b = store.write_file(&file_b)?;
+        assert_eq!(hash_a, hash_blob(&base));
+        assert!(store.is_chunked(hash_a) && store.has_blob(hash_a));
+        assert_eq!(store.blob_size(hash_a)?, base.len() as u64);
+        assert_eq!(store.read_blob(hash_b)?, edited);
+
+        // Chunks are shared between the two versions
+        let chunks_a = store.read_manifest(hash_a)?.chunks;
+        let chunks_b = store.read_manifest(hash_b)?.chunks;
+        let shared = chunks_b.iter().filter(|c| chunks_a.contains(c)).count();
+        assert!(shared + 3 >= chunks_a.len());
+
+        // Streaming read
+        let mut out = Vec::new();
+        assert_eq!(store.read_blob_to(hash_a, &mut out)?, base.len() as u64);
+        assert_eq!(out, base);
+
+        // Small files stay ordinary blobs
+        let small = temp_dir.path().join("small.txt");
+        std::fs::write(&small, b"small")?;
+        assert!(!store.is_chunked(store.write_file(&small)?));
+
+        // Sweeping with only b live removes a's manifest and its unique chunks
+        let (removed, freed) = store.sweep_chunked(&HashSet::from([hash_b]))?;
+        assert_eq!(removed, 1);
+        assert!(freed > 0);
+        assert!(!store.is_chunked(hash_a));
+        assert_eq!(store.read_blob(hash_b)?, edited);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_blob_store_write_file_retries_while_changing() -> Result<()> {
+        use std::thread;
+        use std::time::{Duration, Instant};
+
+        let temp_dir = tempfile::tempdir()?;
+        let mut store = BlobStore::new(temp_dir.path().to_path_buf());
+        store.set_large_file_threshold(64 * 1024);
+        store.set_chunker_config(ChunkerConfig { min_size: 1024, avg_size: 4096, max_size: 16384 });
+
+        // Whole versions of the file; any stored result must be exactly one of them
+        let versions: Vec<Vec<u8>> = (0..8u32)
+            .map(|v| (0..200_000u32).map(|i| (i.wrapping_mul(2654435761).wrapping_add(v) >> 13) as u8).collect())
+            .collect();
+        let file = temp_dir.path().join("big.bin");
+        std::fs::write(&file, &versions[0])?;
+
+        // Rewrite for 120ms, then leave the last version in place
+        let file_clone = file.clone();
+        let writes = versions.clone();
+        let writer = thread::spawn(move || {
+            let start = Instant::now();
+            let mut i = 1;
+            while start.elapsed() < Duration::from_millis(120) {
+                std::fs::write(&file_clone, &writes[i % writes.len()]).unwrap();
+                i += 1;
+                thread::sleep(Duration::from_millis(5));
+            }
+        });
+
+        thread::sleep(Duration::from_millis(20));
+        let hash = store.write_file(&file)?;
+        writer.join().unwrap();
+
+        assert!(store.is_chunked(hash));
+        assert!(versions.contains(&store.read_blob(hash)?), "stored content is torn");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_blob_store_encrypted_at_rest() -> Result<()> {
+        use crate::hash::git::hash_blob;
+
+        let temp_dir = tempfile::tempdir()?;
+        let git_objects = temp_dir.path().join("git-objects");
+        std::fs::create_dir_all(&git_objects)?;
+
+        let mut store = BlobStore::new(temp_dir.path().to_path_buf()).with_git_objects(git_objects.clone());
+        store.set_large_file_threshold(64 * 1024);
+        store.set_chunker_config(ChunkerConfig { min_size: 1024, avg_size: 4096, max_size: 16384 });
+
+        // A blob written before encryption was enabled is only readable while migrating
+        let old = b"written in the clear";
+        store.write_blob(hash_blob(old), old)?;
+
+        store.set_cipher(Some(Arc::new(RepoCipher::new(&[3u8; 32]))));
+        let data = b"proprietary source";
+        let hash = hash_blob(data);
+        store.write_blob(hash, data)?;
+
+        let on_disk = std::fs::read(store.blob_path(hash))?;
+        assert!(crate::encryption::is_sealed(&on_disk));
+        assert!(store.git_blob_path(hash).is_some_and(|p| !p.exists()));
+        assert_eq!(store.read_blob(hash)?, data);
+        assert_eq!(store.blob_size(hash)?, data.len() as u64);
+        assert!(store.read_blob(hash_blob(old)).is_err());
+        let mut migrating = BlobStore::new(temp_dir.path().to_path_buf());
+        migrating.set_cipher(Some(Arc::new(RepoCipher::new(&[3u8; 32]).with_plaintext_reads(true))));
+        assert_eq!(migrating.read_blob(hash_blob(old))?, old);
+        assert_eq!(migrating.read_blob(hash)?, data);
+
+        // Chunked files: manifest and chunks are sealed too
+        let large: Vec<u8> = (0..100_000u32).map(|i| (i.wrapping_mul(2654435761) >> 11) as u8).collect();
+        let large_hash = hash_blob(&large);
+        store.write_blob(large_hash, &large)?;
+        assert!(crate::encryption::is_sealed(&std::fs::read(store.manifest_path(large_hash))?));
+        assert_eq!(store.read_blob(large_hash)?, large);
+
+        // Without the key the content cannot be read
+        let mut keyless = BlobStore::new(temp_dir.path().to_path_buf());
+        assert!(keyless.read_blob(hash).is_err());
+        keyless.set_cipher(Some(Arc::new(RepoCipher::new(&[4u8; 32]))));
+        assert!(keyless.read_blob(large_hash).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_blob_store_omitted_content() -> Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let store = BlobStore::new(temp_dir.path().to_path_buf());
+
+        let data = b"not kept";
+        let hash = crate::hash::git::hash_blob(data);
+        store.mark_omitted(hash, data.len() as u64)?;
+
+        assert!(store.is_omitted(hash));
+        assert!(!store.has_blob(hash));
+        assert_eq!(store.blob_size(hash)?, data.len() as u64);
+        assert!(store.read_blob(hash).unwrap_err().to_string().contains("not kept"));
+
+        // Storing the content later (e.g. another path with full policy) wins
+        store.write_blob(hash, data)?;
+        assert!(!store.is_omitted(hash));
+        assert_eq!(store.read_blob(hash)?, data);
+
+        // Purging removes it again
+        assert!(store.purge_blob(hash)?);
+        assert!(store.is_omitted(hash));
+        assert!(!store.purge_blob(hash)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_blob_store_read_range() -> Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let mut store = BlobStore::new(temp_dir.path().to_path_buf());
+        store.set_large_file_threshold(64 * 1024);
+        store.set_chunker_config(ChunkerConfig { min_size: 1024, avg_size: 4096, max_size: 16384 });
+
+        let content: Vec<u8> = (0..300_000u32).map(|i| (i % 251) as u8).collect();
+        let large = crate::hash::git::hash_blob(&content);
+        store.write_blob(large, &content)?;
+        assert!(store.is_chunked(large));
+        assert!(store.read_manifest(large)?.chunks.len() > 10);
+
+        let small = crate::hash::git::hash_blob(&content[..1000]);
+        store.write_blob(small, &content[..1000])?;
+
+        for (offset, len) in [(0, 10), (70_000, 200_000), (299_990, 100), (400_000, 10)] {
+            let end = (offset + len).min(content.len());
+            let expected = &content[offset.min(end)..end];
+            assert_eq!(store.read_blob_range(large, offset as u64, len)?, expected);
+        }
+        assert_eq!(store.read_blob_range(small, 990, 100)?, &content[990..1000]);
+        assert_eq!(store.read_blob_range(small, 5, 3)?, &content[5..8]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_purge_blob_leaves_git_objects() -> Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let git_objects = temp_dir.path().join("git-objects");
+        let store = BlobStore::new(temp_dir.path().join("tl")).with_git_objects(git_objects.clone());
+
+        let data = b"published content";
+        let hash = crate::hash::git::hash_blob(data);
+        store.write_blob(hash, data)?;
+
+        let hex = hash.to_hex();
+        let git_copy = git_objects.join(&hex[0..2]).join(&hex[2..]);
+        assert!(git_copy.exists());
+
+        assert!(store.purge_blob(hash)?);
+        assert!(!store.has_blob(hash));
+        assert!(git_copy.exists(), "Git's copy must not be touched");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_blob_store_screened_large_file() -> Result<()> {
+        use crate::secrets::{SecretRuleConfig, SecretsConfig};
+
+        let temp_dir = tempfile::tempdir()?;
+        let mut store = BlobStore::new(temp_dir.path().join("tl"));
+        store.set_large_file_threshold(4096);
+
+        let config = SecretsConfig {
+            enabled: true,
+            rules: vec![SecretRuleConfig {
+                name: "itk".to_string(),
+                pattern: "itk_[a-z0-9]{8}".to_string(),
+                action: SecretAction::Redact,
+            }],
+            ..SecretsConfig::default()
+        };
+        let scanner = SecretScanner::from_config(&config)?;
+
+        // Large enough to be chunked, with the secret near the end
+        let mut content = "filler line\n".repeat(2000).into_bytes();
+        content.extend_from_slice(b"token = itk_abcd1234\n");
+        let file = temp_dir.path().join("big.log");
+        std::fs::write(&file, &content)?;
+
+        let screened = store.write_file_screened(&file, Path::new("big.log"), Some(&scanner))?;
+        let redacted_hash = match screened {
+            Screened::Redacted(hash) => hash,
+            other => panic!("expected a redacted copy, got {:?}", other),
+        };
+        assert!(store.is_chunked(redacted_hash));
+        assert!(!store.has_blob(crate::hash::git::hash_blob(&content)));
+
+        let stored = store.read_blob(redacted_hash)?;
+        assert!(stored.ends_with(b"token = [REDACTED:itk]\n"));
+        assert!(!stored.windows(13).any(|w| w == b"itk_abcd1234\n"));
+
+        // No scanner: stored as-is
+        assert_eq!(
+            store.write_file_screened(&file, Path::new("big.log"), None)?,
+            Screened::Stored(crate::hash::git::hash_blob(&content))
+        );
+
+        Ok(())
+    }
 }
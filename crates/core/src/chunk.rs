@@ -0,0 +1,300 @@
+//! Content-defined chunking for large files
+//!
+//! Files at or above the store's large-file threshold are never held in
+//! memory whole. They are streamed through a gear-hash chunker (FastCDC
+//! style), each chunk is stored once under its BLAKE3 hash in
+//! `objects/chunks/`, and a manifest listing the chunks is stored in
+//! `objects/manifests/` under the file's Git blob SHA-1. The SHA-1 is computed
+//! over the same stream, so trees (and publishing to Git) see an ordinary blob.
+//!
+//! Because boundaries depend on content rather than offsets, an edit in the
+//! middle of a multi-GB file only produces new chunks around the edit.
+
+use crate::hash::{IncrementalHasher, Sha1Hash};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+
+/// Chunk size bounds and target
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkerConfig {
+    /// No boundary is placed before this many bytes
+    pub min_size: usize,
+    /// Target average chunk size (power of two)
+    pub avg_size: usize,
+    /// A boundary is forced at this many bytes
+    pub max_size: usize,
+}
+
+impl Default for ChunkerConfig {
+    fn default() -> Self {
+        Self {
+            min_size: 256 * 1024,
+            avg_size: 1024 * 1024,
+            max_size: 4 * 1024 * 1024,
+        }
+    }
+}
+
+impl ChunkerConfig {
+    /// Mask used before the average size (harder to match: fewer tiny chunks)
+    fn mask_small(&self) -> u64 {
+        (1u64 << (self.avg_size.trailing_zeros() + 1)) - 1
+    }
+
+    /// Mask used after the average size (easier to match: fewer huge chunks)
+    fn mask_large(&self) -> u64 {
+        (1u64 << (self.avg_size.trailing_zeros().saturating_sub(1))) - 1
+    }
+}
+
+/// Gear table: 256 pseudo-random u64s (splitmix64, fixed seed)
+///
+/// Must never change - chunk boundaries (and therefore dedup) depend on it.
+const GEAR: [u64; 256] = {
+    let mut table = [0u64; 256];
+    let mut state: u64 = 0x7469_6d65_6c61_7073; // "timelaps"
+    let mut i = 0;
+    while i < 256 {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+};
+
+/// Find the length of the first chunk in `data`
+///
+/// `data` must hold at least `max_size` bytes unless it is the end of the
+/// stream, so the boundary never depends on how the input was buffered.
+pub fn find_boundary(data: &[u8], config: &ChunkerConfig) -> usize {
+    let len = data.len();
+    if len <= config.min_size {
+        return len;
+    }
+
+    let normal = config.avg_size.min(len);
+    let end = config.max_size.min(len);
+    let mask_small = config.mask_small();
+    let mask_large = config.mask_large();
+    let mut hash: u64 = 0;
+
+    for (i, &byte) in data.iter().enumerate().take(normal).skip(config.min_size) {
+        hash = (hash << 1).wrapping_add(GEAR[byte as usize]);
+        if hash & mask_small == 0 {
+            return i + 1;
+        }
+    }
+    for (i, &byte) in data.iter().enumerate().take(end).skip(normal) {
+        hash = (hash << 1).wrapping_add(GEAR[byte as usize]);
+        if hash & mask_large == 0 {
+            return i + 1;
+        }
+    }
+
+    end
+}
+
+/// Streaming chunker over any reader
+pub struct Chunker<R: Read> {
+    reader: R,
+    config: ChunkerConfig,
+    buffer: Vec<u8>,
+    eof: bool,
+}
+
+impl<R: Read> Chunker<R> {
+    /// Create a chunker with the given size bounds
+    pub fn new(reader: R, config: ChunkerConfig) -> Self {
+        Self {
+            reader,
+            config,
+            buffer: Vec::with_capacity(config.max_size),
+            eof: false,
+        }
+    }
+
+    /// Read the next chunk (None at end of stream)
+    pub fn next_chunk(&mut self) -> Result<Option<Vec<u8>>> {
+        // Top the buffer up to max_size so boundaries are buffering-independent
+        while !self.eof && self.buffer.len() < self.config.max_size {
+            let start = self.buffer.len();
+            self.buffer.resize(self.config.max_size, 0);
+            let read = self.reader.read(&mut self.buffer[start..])?;
+            self.buffer.truncate(start + read);
+            if read == 0 {
+                self.eof = true;
+            }
+        }
+
+        if self.buffer.is_empty() {
+            return Ok(None);
+        }
+
+        let cut = find_boundary(&self.buffer, &self.config);
+        let rest = self.buffer.split_off(cut);
+        let chunk = std::mem::replace(&mut self.buffer, rest);
+        self.buffer.reserve(self.config.max_size.saturating_sub(self.buffer.len()));
+        Ok(Some(chunk))
+    }
+}
+
+/// Identifier of a stored chunk (BLAKE3 of its contents)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ChunkId(pub [u8; 32]);
+
+impl ChunkId {
+    /// Hash chunk contents
+    pub fn of(data: &[u8]) -> Self {
+        Self(*blake3::hash(data).as_bytes())
+    }
+
+    /// Lowercase hex representation
+    pub fn to_hex(&self) -> String {
+        blake3::Hash::from(self.0).to_hex().to_string()
+    }
+
+    /// Parse from hex
+    pub fn from_hex(hex: &str) -> Result<Self> {
+        let hash = blake3::Hash::from_hex(hex)
+            .with_context(|| format!("Invalid chunk id: {}", hex))?;
+        Ok(Self(*hash.as_bytes()))
+    }
+}
+
+/// One chunk of a large file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkRef {
+    /// Chunk identifier
+    pub id: ChunkId,
+    /// Uncompressed length in bytes
+    pub len: u32,
+}
+
+/// Ordered list of chunks that reassemble a large file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkManifest {
+    /// Total file size in bytes
+    pub size: u64,
+    /// Chunks in file order
+    pub chunks: Vec<ChunkRef>,
+}
+
+impl ChunkManifest {
+    /// Serialize for storage
+    pub fn serialize(&self) -> Result<Vec<u8>> {
+        bincode::serialize(self).context("Failed to serialize chunk manifest")
+    }
+
+    /// Deserialize from storage
+    pub fn deserialize(bytes: &[u8]) -> Result<Self> {
+        let manifest: Self = bincode::deserialize(bytes)
+            .context("Failed to deserialize chunk manifest")?;
+        let total: u64 = manifest.chunks.iter().map(|c| c.len as u64).sum();
+        if total != manifest.size {
+            anyhow::bail!("Chunk manifest sizes disagree: {} in chunks, {} declared", total, manifest.size);
+        }
+        Ok(manifest)
+    }
+}
+
+/// Chunk a stream of known size, computing its Git blob SHA-1 on the way
+///
+/// `store_chunk` is called once per chunk in order. Fails if the stream
+/// length does not match `size` (file changed while being read).
+pub fn chunk_stream<R: Read>(
+    reader: R,
+    size: u64,
+    config: ChunkerConfig,
+    mut store_chunk: impl FnMut(ChunkId, &[u8]) -> Result<()>,
+) -> Result<(Sha1Hash, ChunkManifest)> {
+    let mut hasher = IncrementalHasher::new();
+    hasher.update(format!("blob {}\0", size).as_bytes());
+
+    let mut chunker = Chunker::new(reader, config);
+    let mut manifest = ChunkManifest { size: 0, chunks: Vec::new() };
+
+    while let Some(chunk) = chunker.next_chunk()? {
+        hasher.update(&chunk);
+        let id = ChunkId::of(&chunk);
+        store_chunk(id, &chunk)?;
+        manifest.size += chunk.len() as u64;
+        manifest.chunks.push(ChunkRef { id, len: chunk.len() as u32 });
+    }
+
+    if manifest.size != size {
+        anyhow::bail!("File size changed while chunking: expected {} bytes, read {}", size, manifest.size);
+    }
+
+    Ok((hasher.finalize(), manifest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::git::hash_blob;
+
+    fn small_config() -> ChunkerConfig {
+        ChunkerConfig { min_size: 1024, avg_size: 4096, max_size: 16384 }
+    }
+
+    /// Deterministic pseudo-random bytes
+    fn noise(len: usize, seed: u64) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                (state >> 33) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_chunking_roundtrip_and_git_hash() -> Result<()> {
+        let data = noise(200_000, 1);
+        let mut stored = Vec::new();
+
+        let (hash, manifest) = chunk_stream(&data[..], data.len() as u64, small_config(), |id, chunk| {
+            assert_eq!(ChunkId::of(chunk), id);
+            stored.push(chunk.to_vec());
+            Ok(())
+        })?;
+
+        assert_eq!(hash, hash_blob(&data));
+        assert_eq!(stored.concat(), data);
+        assert!(manifest.chunks.len() > 10);
+        assert!(manifest.chunks.iter().all(|c| c.len as usize <= small_config().max_size));
+
+        let decoded = ChunkManifest::deserialize(&manifest.serialize()?)?;
+        assert_eq!(decoded, manifest);
+
+        // A short read is rejected
+        assert!(chunk_stream(&data[..], data.len() as u64 + 1, small_config(), |_, _| Ok(())).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_chunk_boundaries_survive_insertion() -> Result<()> {
+        let original = noise(300_000, 7);
+        let mut edited = original.clone();
+        edited.splice(150_000..150_000, b"inserted bytes".iter().copied());
+
+        let ids = |data: &[u8]| -> Result<Vec<ChunkId>> {
+            let (_, manifest) = chunk_stream(data, data.len() as u64, small_config(), |_, _| Ok(()))?;
+            Ok(manifest.chunks.iter().map(|c| c.id).collect())
+        };
+
+        let before = ids(&original)?;
+        let after = ids(&edited)?;
+        let shared = after.iter().filter(|id| before.contains(id)).count();
+
+        // Only the chunk(s) around the edit differ
+        assert!(shared + 3 >= before.len(), "shared {} of {}", shared, before.len());
+
+        Ok(())
+    }
+}
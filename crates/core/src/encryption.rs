@@ -0,0 +1,262 @@
+//! Encryption at rest for the object store and journal
+//!
+//! When enabled, blobs, chunks, manifests, trees and journal values are
+//! sealed with XChaCha20-Poly1305 under a repo key before they touch disk.
+//! Objects keep their Git SHA-1 identity (file names, dedup and publishing
+//! are unchanged); only the bytes on disk differ. The object's identity is
+//! bound in as associated data, so sealed files cannot be swapped around.
+//!
+//! ```toml
+//! [encryption]
+//! enabled = true
+//! keyfile = "~/.config/tl/keys/myrepo.key"   # 32 bytes, raw or hex
+//! ```
+//!
+//! The `TL_REPO_KEY` environment variable (64 hex characters) takes
+//! precedence over the keyfile. With encryption enabled, unsealed data on
+//! disk is rejected as tampered; objects written before encryption was
+//! enabled are only readable with `allow_plaintext = true`, meant for the
+//! migration period. The dual-write to `.git/objects` is disabled, so
+//! plaintext only reaches Git on publish.
+
+use anyhow::{Context, Result};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Environment variable holding the repo key (hex)
+pub const KEY_ENV_VAR: &str = "TL_REPO_KEY";
+
+/// Prefix marking sealed data (plaintext formats never start with it)
+const MAGIC: &[u8; 8] = b"TLENC\x01\0\0";
+const NONCE_LEN: usize = 24;
+
+/// Seals and opens on-disk data with the repo key
+pub struct RepoCipher {
+    cipher: XChaCha20Poly1305,
+    /// Accept unsealed data written before encryption was enabled
+    allow_plaintext: bool,
+}
+
+impl std::fmt::Debug for RepoCipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("RepoCipher(..)")
+    }
+}
+
+impl RepoCipher {
+    /// Cipher for a 32-byte key
+    pub fn new(key: &[u8; 32]) -> Self {
+        Self { cipher: XChaCha20Poly1305::new(Key::from_slice(key)), allow_plaintext: false }
+    }
+
+    /// Accept unsealed data on read (`allow_plaintext` in `[encryption]`)
+    ///
+    /// Off by default: once encryption is enabled, plaintext on disk is
+    /// either left over from before or planted, and is only trusted while
+    /// migrating an existing repository.
+    pub fn with_plaintext_reads(mut self, allow: bool) -> Self {
+        self.allow_plaintext = allow;
+        self
+    }
+
+    /// Parse a key given as 64 hex characters
+    pub fn from_hex(hex: &str) -> Result<Self> {
+        Ok(Self::new(&parse_hex_key(hex)?))
+    }
+
+    /// Load the cipher configured in `[encryption]` of `.tl/config.toml`
+    ///
+    /// Returns None when encryption is not enabled. Fails if it is enabled
+    /// but no key can be found, so nothing is ever written in the clear by
+    /// mistake.
+    pub fn load(tl_dir: &Path) -> Result<Option<Arc<Self>>> {
+        let content = match std::fs::read_to_string(tl_dir.join("config.toml")) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e).context("Failed to read config.toml"),
+        };
+        let config: toml::Value = content.parse()
+            .context("Failed to parse config.toml as TOML")?;
+
+        let section = match config.get("encryption") {
+            Some(section) => section,
+            None => return Ok(None),
+        };
+        if !section.get("enabled").and_then(|v| v.as_bool()).unwrap_or(false) {
+            return Ok(None);
+        }
+
+        let allow_plaintext = section.get("allow_plaintext").and_then(|v| v.as_bool()).unwrap_or(false);
+
+        if let Ok(hex) = std::env::var(KEY_ENV_VAR) {
+            return Ok(Some(Arc::new(Self::from_hex(&hex)
+                .with_context(|| format!("Invalid key in {}", KEY_ENV_VAR))?
+                .with_plaintext_reads(allow_plaintext))));
+        }
+
+        let keyfile = section.get("keyfile").and_then(|v| v.as_str())
+            .with_context(|| format!("Encryption is enabled but no key is configured: set {} or [encryption] keyfile", KEY_ENV_VAR))?;
+        let keyfile = expand_home(keyfile);
+        let bytes = std::fs::read(&keyfile)
+            .with_context(|| format!("Failed to read keyfile {}", keyfile.display()))?;
+
+        let key = match <[u8; 32]>::try_from(bytes.as_slice()) {
+            Ok(raw) => raw,
+            Err(_) => parse_hex_key(String::from_utf8_lossy(&bytes).trim())
+                .with_context(|| format!("Invalid key in {}", keyfile.display()))?,
+        };
+        Ok(Some(Arc::new(Self::new(&key).with_plaintext_reads(allow_plaintext))))
+    }
+
+    /// Seal `plaintext`, binding it to `context` (the object's identity)
+    pub fn encrypt(&self, plaintext: &[u8], context: &[u8]) -> Result<Vec<u8>> {
+        let mut nonce = [0u8; NONCE_LEN];
+        getrandom::getrandom(&mut nonce)
+            .map_err(|e| anyhow::anyhow!("Failed to generate nonce: {}", e))?;
+
+        let ciphertext = self.cipher
+            .encrypt(XNonce::from_slice(&nonce), Payload { msg: plaintext, aad: context })
+            .map_err(|_| anyhow::anyhow!("Encryption failed"))?;
+
+        let mut sealed = Vec::with_capacity(MAGIC.len() + NONCE_LEN + ciphertext.len());
+        sealed.extend_from_slice(MAGIC);
+        sealed.extend_from_slice(&nonce);
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+
+    /// Open data sealed by `encrypt` with the same `context`
+    pub fn decrypt(&self, sealed: &[u8], context: &[u8]) -> Result<Vec<u8>> {
+        if !is_sealed(sealed) || sealed.len() < MAGIC.len() + NONCE_LEN {
+            anyhow::bail!("Data is not encrypted");
+        }
+        let (nonce, ciphertext) = sealed[MAGIC.len()..].split_at(NONCE_LEN);
+        self.cipher
+            .decrypt(XNonce::from_slice(nonce), Payload { msg: ciphertext, aad: context })
+            .map_err(|_| anyhow::anyhow!("Decryption failed (wrong repo key or corrupt data)"))
+    }
+}
+
+/// True if `data` was sealed by a `RepoCipher`
+pub fn is_sealed(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Seal data for disk if a cipher is configured
+pub fn seal<'a>(cipher: Option<&RepoCipher>, data: &'a [u8], context: &[u8]) -> Result<Cow<'a, [u8]>> {
+    match cipher {
+        Some(cipher) => Ok(Cow::Owned(cipher.encrypt(data, context)?)),
+        None => Ok(Cow::Borrowed(data)),
+    }
+}
+
+/// Open data read from disk
+///
+/// Without a cipher, plaintext passes through. With one, unsealed data is
+/// rejected unless the cipher allows plaintext reads for migration.
+pub fn unseal(cipher: Option<&RepoCipher>, data: Vec<u8>, context: &[u8]) -> Result<Vec<u8>> {
+    match cipher {
+        Some(cipher) if is_sealed(&data) => cipher.decrypt(&data, context),
+        Some(cipher) if cipher.allow_plaintext => Ok(data),
+        Some(_) => anyhow::bail!("Data is not encrypted although encryption is enabled (set [encryption] allow_plaintext = true to read data written before it was)"),
+        None if is_sealed(&data) => anyhow::bail!("Object is encrypted but no repo key is configured (set {} or [encryption] keyfile)", KEY_ENV_VAR),
+        None => Ok(data),
+    }
+}
+
+fn parse_hex_key(hex: &str) -> Result<[u8; 32]> {
+    let hex = hex.trim();
+    if hex.len() != 64 || !hex.is_ascii() {
+        anyhow::bail!("Repo key must be 64 hex characters");
+    }
+    let mut key = [0u8; 32];
+    for (i, byte) in key.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+            .context("Repo key must be 64 hex characters")?;
+    }
+    Ok(key)
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_seal_roundtrip_and_context_binding() -> Result<()> {
+        let cipher = RepoCipher::new(&[7u8; 32]);
+
+        let sealed = cipher.encrypt(b"secret source", b"object-a")?;
+        assert!(is_sealed(&sealed));
+        assert!(!sealed.windows(6).any(|w| w == b"secret"));
+        assert_eq!(cipher.decrypt(&sealed, b"object-a")?, b"secret source");
+
+        // Wrong identity or wrong key fails
+        assert!(cipher.decrypt(&sealed, b"object-b").is_err());
+        assert!(RepoCipher::new(&[8u8; 32]).decrypt(&sealed, b"object-a").is_err());
+
+        // Plaintext passes through; sealed data needs a key
+        assert_eq!(unseal(None, b"plain".to_vec(), b"")?, b"plain");
+        assert!(unseal(None, sealed.clone(), b"object-a").is_err());
+        assert_eq!(unseal(Some(&cipher), sealed, b"object-a")?, b"secret source");
+
+        // With a key, plaintext is only accepted while migrating
+        assert!(unseal(Some(&cipher), b"plain".to_vec(), b"object-a").is_err());
+        let migrating = RepoCipher::new(&[7u8; 32]).with_plaintext_reads(true);
+        assert_eq!(unseal(Some(&migrating), b"plain".to_vec(), b"object-a")?, b"plain");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_from_config() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let tl_dir = temp_dir.path();
+        let keyfile = tl_dir.join("repo.key");
+
+        // Not configured, or disabled
+        assert!(RepoCipher::load(tl_dir)?.is_none());
+        std::fs::write(tl_dir.join("config.toml"), "[encryption]\nenabled = false\n")?;
+        assert!(RepoCipher::load(tl_dir)?.is_none());
+
+        // The rest depends on the key source; an exported key would win
+        if std::env::var_os(KEY_ENV_VAR).is_some() {
+            return Ok(());
+        }
+
+        // Enabled without a key is an error, never a silent fallback
+        std::fs::write(tl_dir.join("config.toml"), "[encryption]\nenabled = true\n")?;
+        assert!(RepoCipher::load(tl_dir).is_err());
+
+        std::fs::write(&keyfile, format!("{}\n", "ab".repeat(32)))?;
+        std::fs::write(
+            tl_dir.join("config.toml"),
+            format!("[encryption]\nenabled = true\nkeyfile = {:?}\n", keyfile.to_string_lossy()),
+        )?;
+        let cipher = RepoCipher::load(tl_dir)?.expect("cipher configured");
+        let sealed = cipher.encrypt(b"data", b"id")?;
+        assert_eq!(RepoCipher::new(&[0xab; 32]).decrypt(&sealed, b"id")?, b"data");
+        assert!(!cipher.allow_plaintext);
+
+        // Plaintext reads are an explicit opt-in
+        std::fs::write(
+            tl_dir.join("config.toml"),
+            format!("[encryption]\nenabled = true\nallow_plaintext = true\nkeyfile = {:?}\n", keyfile.to_string_lossy()),
+        )?;
+        assert!(RepoCipher::load(tl_dir)?.expect("cipher configured").allow_plaintext);
+
+        assert!(RepoCipher::from_hex("abcd").is_err());
+
+        Ok(())
+    }
+}
@@ -3,19 +3,42 @@
 //! This crate provides the foundational storage layer:
 //! - SHA-1 hashing (Git-compatible)
 //! - Blob storage with compression
+//! - Content-defined chunking for large files
 //! - Tree representation and diffing
 //! - On-disk store management
+//! - Stat cache primitives for change detection
+//! - Ignore rules and the shared working-directory snapshot walker
+//! - Per-path checkpoint policies
+//! - Secret scanning before content is stored
+//! - Optional encryption at rest
+//! - Storage quota and usage tracking
 
 pub mod hash;
 pub mod blob;
+pub mod chunk;
 pub mod tree;
 pub mod store;
+pub mod stat;
+pub mod ignore;
+pub mod policy;
+pub mod secrets;
+pub mod encryption;
+pub mod quota;
+pub mod snapshot;
 
 // Re-export main types for convenience
 pub use hash::{Sha1Hash, IncrementalHasher};
-pub use blob::{Blob, BlobStore};
+pub use blob::{is_binary, Blob, BlobStore, Screened};
+pub use chunk::{ChunkManifest, ChunkerConfig};
 pub use tree::{Tree, Entry, EntryKind, TreeDiff};
 pub use store::Store;
+pub use stat::{FileStat, StatCache};
+pub use ignore::{IgnoreConfig, IgnoreRules, PathPatterns};
+pub use policy::{PathPolicies, PathPolicy};
+pub use secrets::{ScanReport, SecretAction, SecretScanner};
+pub use encryption::RepoCipher;
+pub use quota::{QuotaConfig, QuotaLevel, QuotaLimit, QuotaStatus, StoreUsage};
+pub use snapshot::{snapshot_worktree, walk_worktree, Snapshot, WorktreeFile};
 
 /// Common result type used throughout timelapse-core
 pub type Result<T> = anyhow::Result<T>;
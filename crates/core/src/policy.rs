@@ -0,0 +1,172 @@
+//! Per-path checkpoint policies
+//!
+//! Ignore rules decide whether a path is tracked at all. Policies in
+//! `.tlpolicy` keep a path tracked (it stays in every tree, so diffs and
+//! restores see it) but at reduced fidelity:
+//!
+//! ```text
+//! # <policy>        <pattern>   (gitignore pattern syntax, last match wins)
+//! hash-only         *.iso
+//! max-size=50MB     assets/
+//! latest-only       logs/*.log
+//! manual            data/*.sqlite
+//! full              assets/icons/
+//! ```
+//!
+//! - `hash-only`: record the content hash, never store the bytes
+//! - `max-size=N`: store content up to N bytes (K/KB, M/MB, G/GB suffixes), hash-only above
+//! - `latest-only`: store content, but GC keeps only the version in HEAD
+//! - `manual`: only checkpoint changes on an explicit flush
+//! - `full`: normal behaviour (to carve exceptions out of broader rules)
+
+use anyhow::{Context, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::path::Path;
+
+/// How a tracked path is checkpointed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathPolicy {
+    /// Every version is stored
+    Full,
+    /// Content over the given size is hashed but not stored
+    MaxSize(u64),
+    /// Changes are only checkpointed on an explicit flush
+    ManualOnly,
+    /// Only the version in HEAD survives GC
+    LatestOnly,
+    /// Content is hashed but never stored
+    HashOnly,
+}
+
+impl PathPolicy {
+    /// True if content of this size is stored (otherwise hash-only)
+    pub fn stores_content(&self, size: u64) -> bool {
+        match self {
+            PathPolicy::HashOnly => false,
+            PathPolicy::MaxSize(max) => size <= *max,
+            _ => true,
+        }
+    }
+
+    /// Parse a policy keyword (`hash-only`, `max-size=10MB`, ...)
+    pub fn parse(keyword: &str) -> Result<Self> {
+        if let Some(size) = keyword.strip_prefix("max-size=") {
+            return Ok(PathPolicy::MaxSize(parse_size(size)?));
+        }
+        match keyword {
+            "full" => Ok(PathPolicy::Full),
+            "manual" => Ok(PathPolicy::ManualOnly),
+            "latest-only" => Ok(PathPolicy::LatestOnly),
+            "hash-only" => Ok(PathPolicy::HashOnly),
+            _ => anyhow::bail!("Unknown policy '{}'", keyword),
+        }
+    }
+}
+
+/// Parse a size like `512`, `64K`, `10MB` or `2G`
+fn parse_size(text: &str) -> Result<u64> {
+    let upper = text.trim().to_ascii_uppercase();
+    let digits_end = upper.find(|c: char| !c.is_ascii_digit()).unwrap_or(upper.len());
+    let (digits, suffix) = upper.split_at(digits_end);
+    let value: u64 = digits.parse()
+        .with_context(|| format!("Invalid size '{}'", text))?;
+    let multiplier = match suffix {
+        "" | "B" => 1,
+        "K" | "KB" => 1024,
+        "M" | "MB" => 1024 * 1024,
+        "G" | "GB" => 1024 * 1024 * 1024,
+        _ => anyhow::bail!("Invalid size suffix in '{}'", text),
+    };
+    Ok(value * multiplier)
+}
+
+/// Policies loaded from `.tlpolicy`
+#[derive(Debug, Clone, Default)]
+pub struct PathPolicies {
+    /// One matcher per rule, in file order
+    rules: Vec<(Gitignore, PathPolicy)>,
+}
+
+impl PathPolicies {
+    /// Load `.tlpolicy` from the repository root (empty if absent)
+    pub fn load(repo_root: &Path) -> Result<Self> {
+        let path = repo_root.join(".tlpolicy");
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(repo_root, &content)
+    }
+
+    /// Parse policy rules (`<policy> <pattern>` per line, `#` comments)
+    pub fn parse(repo_root: &Path, content: &str) -> Result<Self> {
+        let mut rules = Vec::new();
+
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (keyword, pattern) = line.split_once(char::is_whitespace)
+                .with_context(|| format!(".tlpolicy line {}: expected '<policy> <pattern>'", index + 1))?;
+            let policy = PathPolicy::parse(keyword)
+                .with_context(|| format!(".tlpolicy line {}", index + 1))?;
+
+            let mut builder = GitignoreBuilder::new(repo_root);
+            builder.add_line(None, pattern.trim())
+                .with_context(|| format!(".tlpolicy line {}: invalid pattern", index + 1))?;
+            rules.push((builder.build()?, policy));
+        }
+
+        Ok(Self { rules })
+    }
+
+    /// Policy for a repo-relative file path (last matching rule wins)
+    pub fn policy_for(&self, path: &Path) -> PathPolicy {
+        self.rules.iter()
+            .rev()
+            .find(|(matcher, _)| matcher.matched_path_or_any_parents(path, false).is_ignore())
+            .map(|(_, policy)| *policy)
+            .unwrap_or(PathPolicy::Full)
+    }
+
+    /// True if no rules are configured
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_parsing_and_precedence() -> Result<()> {
+        let policies = PathPolicies::parse(Path::new("/repo"), "\
+# comment
+hash-only      *.iso
+max-size=10MB  assets/
+latest-only    logs/*.log
+manual         data/db.sqlite
+full           assets/icons/
+")?;
+
+        assert_eq!(policies.policy_for(Path::new("dist/image.iso")), PathPolicy::HashOnly);
+        assert_eq!(policies.policy_for(Path::new("assets/video.mp4")), PathPolicy::MaxSize(10 * 1024 * 1024));
+        assert_eq!(policies.policy_for(Path::new("assets/icons/app.png")), PathPolicy::Full);
+        assert_eq!(policies.policy_for(Path::new("logs/app.log")), PathPolicy::LatestOnly);
+        assert_eq!(policies.policy_for(Path::new("data/db.sqlite")), PathPolicy::ManualOnly);
+        assert_eq!(policies.policy_for(Path::new("src/main.rs")), PathPolicy::Full);
+
+        assert!(PathPolicy::MaxSize(100).stores_content(100));
+        assert!(!PathPolicy::MaxSize(100).stores_content(101));
+        assert!(!PathPolicy::HashOnly.stores_content(0));
+
+        assert!(PathPolicies::parse(Path::new("/repo"), "keep-forever *.rs").is_err());
+        assert!(PathPolicies::parse(Path::new("/repo"), "max-size=10XB *.bin").is_err());
+
+        Ok(())
+    }
+}
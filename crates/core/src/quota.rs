@@ -0,0 +1,300 @@
+//! Storage quota for `.tl/`
+//!
+//! The budget is either an absolute size or a share of the disk space that
+//! `.tl/` could use (what it holds now plus what is still free):
+//!
+//! ```toml
+//! [quota]
+//! limit = "10GB"        # or "25%"; unset or "" means unlimited
+//! warn_at = 80          # % of the budget: `tl status` warns
+//! gc_at = 90            # % of the budget: emergency GC tightens retention
+//! ```
+//!
+//! At 100% checkpointing pauses until space is freed. Usage is tracked
+//! incrementally by the store as objects are written and deleted, persisted
+//! in `state/usage`, and re-measured after garbage collection.
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// How the budget is specified
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QuotaLimit {
+    /// Absolute size in bytes
+    Bytes(u64),
+    /// Percent of the space `.tl/` could use (its usage plus free space)
+    PercentOfFree(f64),
+}
+
+impl QuotaLimit {
+    /// Parse "10GB", "512 MiB", "1073741824" or "25%"
+    pub fn parse(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if let Some(percent) = s.strip_suffix('%') {
+            let percent: f64 = percent.trim().parse()
+                .with_context(|| format!("Invalid quota percentage '{}'", s))?;
+            if !(percent > 0.0 && percent <= 100.0) {
+                anyhow::bail!("Quota percentage must be in (0, 100], got {}", s);
+            }
+            return Ok(QuotaLimit::PercentOfFree(percent));
+        }
+
+        let split = s.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(s.len());
+        let (number, unit) = s.split_at(split);
+        let number: f64 = number.parse()
+            .with_context(|| format!("Invalid quota size '{}'", s))?;
+        let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+            "" | "B" => 1,
+            "K" | "KB" | "KIB" => 1 << 10,
+            "M" | "MB" | "MIB" => 1 << 20,
+            "G" | "GB" | "GIB" => 1 << 30,
+            "T" | "TB" | "TIB" => 1 << 40,
+            other => anyhow::bail!("Unknown quota unit '{}' in '{}'", other, s),
+        };
+        let bytes = (number * multiplier as f64) as u64;
+        if bytes == 0 {
+            anyhow::bail!("Quota must be greater than zero");
+        }
+        Ok(QuotaLimit::Bytes(bytes))
+    }
+}
+
+/// `[quota]` section of config.toml
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuotaConfig {
+    pub limit: QuotaLimit,
+    /// Percent of the budget at which status warns (default: 80)
+    pub warn_at: u8,
+    /// Percent of the budget at which emergency GC runs (default: 90)
+    pub gc_at: u8,
+}
+
+#[derive(serde::Deserialize)]
+struct RawQuotaConfig {
+    #[serde(default)]
+    limit: Option<toml::Value>,
+    #[serde(default = "default_warn_at")]
+    warn_at: u8,
+    #[serde(default = "default_gc_at")]
+    gc_at: u8,
+}
+
+fn default_warn_at() -> u8 {
+    80
+}
+
+fn default_gc_at() -> u8 {
+    90
+}
+
+impl QuotaConfig {
+    /// Load from the `[quota]` section of `.tl/config.toml`
+    ///
+    /// Returns None when no limit is configured.
+    pub fn load(tl_dir: &Path) -> Result<Option<Self>> {
+        let content = match std::fs::read_to_string(tl_dir.join("config.toml")) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e).context("Failed to read config.toml"),
+        };
+        let value: toml::Value = content.parse()
+            .context("Failed to parse config.toml as TOML")?;
+        let raw: RawQuotaConfig = match value.get("quota") {
+            Some(section) => section.clone().try_into()
+                .context("Invalid [quota] section in config.toml")?,
+            None => return Ok(None),
+        };
+
+        let limit = match raw.limit {
+            None => return Ok(None),
+            Some(toml::Value::String(s)) if s.trim().is_empty() => return Ok(None),
+            Some(toml::Value::String(s)) => QuotaLimit::parse(&s)?,
+            Some(toml::Value::Integer(n)) if n > 0 => QuotaLimit::Bytes(n as u64),
+            Some(other) => anyhow::bail!("Invalid [quota] limit: {}", other),
+        };
+        if raw.warn_at > raw.gc_at || raw.gc_at > 100 {
+            anyhow::bail!("[quota] needs warn_at <= gc_at <= 100 (got {} and {})", raw.warn_at, raw.gc_at);
+        }
+
+        Ok(Some(Self { limit, warn_at: raw.warn_at, gc_at: raw.gc_at }))
+    }
+
+    /// Budget in bytes, given current usage and the filesystem's free space
+    pub fn budget(&self, usage: u64, available: u64) -> u64 {
+        match self.limit {
+            QuotaLimit::Bytes(bytes) => bytes,
+            QuotaLimit::PercentOfFree(percent) => {
+                ((usage.saturating_add(available)) as f64 * percent / 100.0) as u64
+            }
+        }
+    }
+
+    /// Evaluate usage against the budget
+    pub fn check(&self, usage: u64, available: u64) -> QuotaStatus {
+        let budget = self.budget(usage, available).max(1);
+        let percent = usage.saturating_mul(100) / budget;
+        let level = if usage >= budget {
+            QuotaLevel::Exceeded
+        } else if percent >= self.gc_at as u64 {
+            QuotaLevel::Critical
+        } else if percent >= self.warn_at as u64 {
+            QuotaLevel::Warning
+        } else {
+            QuotaLevel::Ok
+        };
+        QuotaStatus { usage, budget, level }
+    }
+}
+
+/// How close usage is to the budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum QuotaLevel {
+    Ok,
+    /// Past `warn_at`
+    Warning,
+    /// Past `gc_at`: run emergency GC
+    Critical,
+    /// At or over the budget: pause checkpointing
+    Exceeded,
+}
+
+/// Usage measured against the budget
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuotaStatus {
+    pub usage: u64,
+    pub budget: u64,
+    pub level: QuotaLevel,
+}
+
+/// Bytes used by `.tl/`, kept up to date as the store writes and deletes
+#[derive(Debug, Default)]
+pub struct StoreUsage {
+    bytes: AtomicU64,
+    /// `.tl/` directory (None: in-memory only, e.g. a bare BlobStore)
+    tl_dir: Option<PathBuf>,
+}
+
+impl StoreUsage {
+    /// Load the persisted figure from `state/usage`, measuring if missing
+    pub fn load(tl_dir: &Path) -> Result<Self> {
+        let usage = Self { bytes: AtomicU64::new(0), tl_dir: Some(tl_dir.to_path_buf()) };
+        match std::fs::read_to_string(usage_file(tl_dir)).ok().and_then(|s| s.trim().parse().ok()) {
+            Some(bytes) => usage.bytes.store(bytes, Ordering::Relaxed),
+            None => {
+                usage.rescan()?;
+            }
+        }
+        Ok(usage)
+    }
+
+    /// Current usage in bytes
+    pub fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    /// Record bytes written to disk
+    pub fn add(&self, bytes: u64) {
+        self.bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Record bytes removed from disk
+    pub fn sub(&self, bytes: u64) {
+        let _ = self.bytes.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |b| Some(b.saturating_sub(bytes)));
+    }
+
+    /// Measure `.tl/` from scratch and persist the result
+    ///
+    /// Corrects drift from writes made outside the store (journal, state).
+    pub fn rescan(&self) -> Result<u64> {
+        let tl_dir = match &self.tl_dir {
+            Some(tl_dir) => tl_dir,
+            None => return Ok(self.bytes()),
+        };
+        let bytes = walkdir::WalkDir::new(tl_dir)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| entry.metadata().ok())
+            .filter(|metadata| metadata.is_file())
+            .map(|metadata| metadata.len())
+            .sum();
+        self.bytes.store(bytes, Ordering::Relaxed);
+        self.persist()?;
+        Ok(bytes)
+    }
+
+    /// Save the current figure to `state/usage`
+    pub fn persist(&self) -> Result<()> {
+        if let Some(tl_dir) = &self.tl_dir {
+            std::fs::write(usage_file(tl_dir), self.bytes().to_string())
+                .context("Failed to save store usage")?;
+        }
+        Ok(())
+    }
+}
+
+fn usage_file(tl_dir: &Path) -> PathBuf {
+    tl_dir.join("state/usage")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_limits_and_levels() -> Result<()> {
+        assert_eq!(QuotaLimit::parse("10GB")?, QuotaLimit::Bytes(10 << 30));
+        assert_eq!(QuotaLimit::parse("512 MiB")?, QuotaLimit::Bytes(512 << 20));
+        assert_eq!(QuotaLimit::parse("1.5k")?, QuotaLimit::Bytes(1536));
+        assert_eq!(QuotaLimit::parse("4096")?, QuotaLimit::Bytes(4096));
+        assert_eq!(QuotaLimit::parse("25%")?, QuotaLimit::PercentOfFree(25.0));
+        assert!(QuotaLimit::parse("0").is_err());
+        assert!(QuotaLimit::parse("150%").is_err());
+        assert!(QuotaLimit::parse("10 parsecs").is_err());
+
+        let config = QuotaConfig { limit: QuotaLimit::Bytes(1000), warn_at: 80, gc_at: 90 };
+        assert_eq!(config.check(500, 0).level, QuotaLevel::Ok);
+        assert_eq!(config.check(850, 0).level, QuotaLevel::Warning);
+        assert_eq!(config.check(950, 0).level, QuotaLevel::Critical);
+        assert_eq!(config.check(1000, 0).level, QuotaLevel::Exceeded);
+
+        // A share of free space shrinks as the disk fills up
+        let config = QuotaConfig { limit: QuotaLimit::PercentOfFree(50.0), warn_at: 80, gc_at: 90 };
+        assert_eq!(config.budget(100, 900), 500);
+        assert_eq!(config.check(100, 900).level, QuotaLevel::Ok);
+        assert_eq!(config.check(100, 100).level, QuotaLevel::Exceeded);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_config_and_usage() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let tl_dir = temp_dir.path();
+        std::fs::create_dir_all(tl_dir.join("state"))?;
+
+        assert!(QuotaConfig::load(tl_dir)?.is_none());
+        std::fs::write(tl_dir.join("config.toml"), "[quota]\nlimit = \"\"\n")?;
+        assert!(QuotaConfig::load(tl_dir)?.is_none());
+        std::fs::write(tl_dir.join("config.toml"), "[quota]\nlimit = \"2GB\"\nwarn_at = 70\n")?;
+        let config = QuotaConfig::load(tl_dir)?.expect("limit configured");
+        assert_eq!(config, QuotaConfig { limit: QuotaLimit::Bytes(2 << 30), warn_at: 70, gc_at: 90 });
+        std::fs::write(tl_dir.join("config.toml"), "[quota]\nlimit = \"2GB\"\nwarn_at = 95\n")?;
+        assert!(QuotaConfig::load(tl_dir).is_err());
+
+        // First load measures, later loads use the persisted figure
+        let usage = StoreUsage::load(tl_dir)?;
+        let measured = usage.bytes();
+        assert!(measured > 0);
+        usage.add(100);
+        usage.sub(30);
+        usage.persist()?;
+        assert_eq!(StoreUsage::load(tl_dir)?.bytes(), measured + 70);
+
+        std::fs::write(tl_dir.join("big"), vec![0u8; 10_000])?;
+        assert!(usage.rescan()? >= measured + 10_000);
+
+        Ok(())
+    }
+}
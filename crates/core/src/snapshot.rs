@@ -0,0 +1,339 @@
+//! Shared working-directory walker and snapshot builder
+//!
+//! Every full-tree scan (workspace save, `tl init`, overflow recovery,
+//! periodic reconciliation) walks the repository through `walk_worktree`, so
+//! they all honor the same `IgnoreRules`. `snapshot_worktree` turns the walk
+//! into a tree, hashing files in parallel and reusing hashes from a stat cache
+//! when a file's stat data is unchanged. Paths whose `.tlpolicy` policy does
+//! not keep their content are hashed without being stored, and new content
+//! is screened by the secret scanner (if given) before it is stored.
+
+use crate::blob::Screened;
+use crate::hash;
+use crate::ignore::IgnoreRules;
+use crate::secrets::SecretScanner;
+use crate::stat::{FileStat, StatCache};
+use crate::store::Store;
+use crate::tree::{Entry, Tree};
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// A file or symlink found by `walk_worktree`
+#[derive(Debug, Clone)]
+pub struct WorktreeFile {
+    /// Path relative to the repository root
+    pub rel_path: PathBuf,
+    /// Metadata (not following symlinks)
+    pub metadata: Metadata,
+}
+
+impl WorktreeFile {
+    /// True if this entry is a symlink
+    pub fn is_symlink(&self) -> bool {
+        self.metadata.file_type().is_symlink()
+    }
+}
+
+/// Result of `snapshot_worktree`
+#[derive(Debug)]
+pub struct Snapshot {
+    /// Tree of every non-ignored file and symlink
+    pub tree: Tree,
+    /// Repo-relative paths included in the tree
+    pub paths: Vec<PathBuf>,
+    /// Files whose content was hashed
+    pub hashed: usize,
+    /// Files whose hash was reused from the stat cache
+    pub reused: usize,
+}
+
+/// Walk the working directory, skipping ignored paths
+///
+/// Ignored directories are pruned (never descended into). Returns regular
+/// files and symlinks, sorted by path.
+pub fn walk_worktree(root: &Path, rules: &IgnoreRules) -> Result<Vec<WorktreeFile>> {
+    let mut files = Vec::new();
+
+    for entry in WalkDir::new(root)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|e| {
+            match e.path().strip_prefix(root) {
+                Ok(rel) if rel.as_os_str().is_empty() => true,
+                Ok(rel) => !rules.should_ignore(rel),
+                Err(_) => false,
+            }
+        })
+    {
+        let entry = match entry {
+            Ok(e) => e,
+            // Files can vanish mid-walk; skip rather than abort the scan
+            Err(_) => continue,
+        };
+
+        let file_type = entry.file_type();
+        if !file_type.is_file() && !file_type.is_symlink() {
+            continue;
+        }
+
+        let metadata = match entry.metadata() {
+            Ok(m) => m,
+            Err(_) => continue,
+        };
+        let rel_path = entry.path().strip_prefix(root)
+            .context("Walked path outside repository root")?
+            .to_path_buf();
+
+        files.push(WorktreeFile { rel_path, metadata });
+    }
+
+    files.sort_by(|a, b| a.rel_path.cmp(&b.rel_path));
+    Ok(files)
+}
+
+/// Build a tree from the working directory, writing new blobs to the store
+///
+/// Files are hashed in parallel. With a stat cache, a file whose size, mtime,
+/// ctime and inode match the cached values reuses the cached blob hash.
+/// A file the scanner says to skip keeps its cached entry, or is left out
+/// of the tree if it has none.
+pub fn snapshot_worktree(
+    root: &Path,
+    store: &Store,
+    rules: &IgnoreRules,
+    stat_cache: Option<&(dyn StatCache + Sync)>,
+    scanner: Option<&SecretScanner>,
+) -> Result<Snapshot> {
+    let files = walk_worktree(root, rules)?;
+
+    let entries: Vec<Option<(Entry, bool)>> = files
+        .par_iter()
+        .map(|file| snapshot_file(root, store, rules, file, stat_cache, scanner))
+        .collect::<Result<_>>()?;
+
+    let mut snapshot = Snapshot {
+        tree: Tree::new(),
+        paths: Vec::with_capacity(files.len()),
+        hashed: 0,
+        reused: 0,
+    };
+
+    for (file, entry) in files.into_iter().zip(entries) {
+        let Some((entry, reused)) = entry else {
+            continue;
+        };
+        if reused {
+            snapshot.reused += 1;
+        } else {
+            snapshot.hashed += 1;
+        }
+        snapshot.tree.insert(&file.rel_path, entry);
+        snapshot.paths.push(file.rel_path);
+    }
+
+    Ok(snapshot)
+}
+
+/// Hash (or reuse) a single file; returns the entry and whether it was reused
+/// (None: skipped by the secret scanner)
+fn snapshot_file(
+    root: &Path,
+    store: &Store,
+    rules: &IgnoreRules,
+    file: &WorktreeFile,
+    stat_cache: Option<&(dyn StatCache + Sync)>,
+    scanner: Option<&SecretScanner>,
+) -> Result<Option<(Entry, bool)>> {
+    let abs_path = root.join(&file.rel_path);
+
+    if file.is_symlink() {
+        let target = std::fs::read_link(&abs_path)
+            .with_context(|| format!("Failed to read symlink: {}", abs_path.display()))?;
+        let target_bytes = target.to_string_lossy();
+        let blob_hash = hash::git::hash_blob(target_bytes.as_bytes());
+
+        if !store.blob_store().has_blob(blob_hash) {
+            store.blob_store().write_blob(blob_hash, target_bytes.as_bytes())?;
+        }
+        return Ok(Some((Entry::symlink(blob_hash), false)));
+    }
+
+    #[cfg(unix)]
+    let mode = {
+        use std::os::unix::fs::MetadataExt;
+        file.metadata.mode()
+    };
+    #[cfg(not(unix))]
+    let mode = if file.metadata.permissions().readonly() {
+        0o444
+    } else {
+        0o644
+    };
+
+    // Stat cache hit: same stat, same mode, blob still in the store (or its
+    // content deliberately not kept, as long as the policy still says so)
+    let stores_content = rules.policy_for(&file.rel_path).stores_content(file.metadata.len());
+    if let Some(cache) = stat_cache {
+        if cache.cached_stat(&file.rel_path) == Some(FileStat::from_metadata(&file.metadata)) {
+            if let Some(cached) = cache.cached_entry(&file.rel_path) {
+                let blobs = store.blob_store();
+                let content_ok = blobs.has_blob(cached.blob_hash)
+                    || (!stores_content && blobs.is_omitted(cached.blob_hash));
+                if cached == Entry::file(mode, cached.blob_hash) && content_ok {
+                    return Ok(Some((cached, true)));
+                }
+            }
+        }
+    }
+
+    // Hash-only policy: record the hash, keep the bytes out of the store
+    if !stores_content {
+        let blob_hash = hash::hash_file_stable(&abs_path, 3)
+            .with_context(|| format!("Failed to hash file: {}", abs_path.display()))?;
+        if !store.blob_store().has_blob(blob_hash) {
+            store.blob_store().mark_omitted(blob_hash, file.metadata.len())?;
+        }
+        return Ok(Some((Entry::file(mode, blob_hash), false)));
+    }
+
+    // Large files are scanned and chunked as streams, never read whole
+    if file.metadata.len() >= store.blob_store().large_file_threshold() {
+        let screened = store.blob_store().write_file_screened(&abs_path, &file.rel_path, scanner)
+            .with_context(|| format!("Failed to store large file: {}", abs_path.display()))?;
+        return Ok(screened_entry(screened, mode, &file.rel_path, stat_cache));
+    }
+
+    // Read once: the stored bytes are exactly the ones hashed, even if the
+    // file changes underneath
+    let contents = std::fs::read(&abs_path)
+        .with_context(|| format!("Failed to read file: {}", abs_path.display()))?;
+    let blob_hash = hash::git::hash_blob(&contents);
+
+    if !store.blob_store().has_blob(blob_hash) {
+        let screened = store.blob_store().write_blob_screened(blob_hash, &contents, &file.rel_path, scanner)
+            .with_context(|| format!("Failed to store blob for: {}", abs_path.display()))?;
+        return Ok(screened_entry(screened, mode, &file.rel_path, stat_cache));
+    }
+
+    Ok(Some((Entry::file(mode, blob_hash), false)))
+}
+
+/// Entry for screened content; a skipped file falls back to its cached entry
+fn screened_entry(
+    screened: Screened,
+    mode: u32,
+    rel_path: &Path,
+    stat_cache: Option<&(dyn StatCache + Sync)>,
+) -> Option<(Entry, bool)> {
+    match screened {
+        Screened::Stored(blob_hash) | Screened::Redacted(blob_hash) => Some((Entry::file(mode, blob_hash), false)),
+        Screened::Skipped => stat_cache
+            .and_then(|cache| cache.cached_entry(rel_path))
+            .map(|cached| (cached, true)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ignore::IgnoreConfig;
+    use std::collections::HashMap;
+    use std::fs;
+
+    /// In-memory stat cache for tests
+    struct MapCache(HashMap<PathBuf, (FileStat, Entry)>);
+
+    impl StatCache for MapCache {
+        fn cached_stat(&self, path: &Path) -> Option<FileStat> {
+            self.0.get(path).map(|(stat, _)| *stat)
+        }
+
+        fn cached_entry(&self, path: &Path) -> Option<Entry> {
+            self.0.get(path).map(|(_, entry)| entry.clone())
+        }
+
+        fn cached_paths(&self) -> Vec<PathBuf> {
+            self.0.keys().cloned().collect()
+        }
+    }
+
+    #[test]
+    fn test_snapshot_honors_ignore_rules_and_stat_cache() -> Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let root = temp_dir.path();
+        let store = Store::init(root)?;
+
+        fs::create_dir_all(root.join("src"))?;
+        fs::create_dir_all(root.join("node_modules/pkg"))?;
+        fs::create_dir_all(root.join("build"))?;
+        fs::write(root.join("src/main.rs"), b"fn main() {}")?;
+        fs::write(root.join("README.md"), b"readme")?;
+        fs::write(root.join("node_modules/pkg/index.js"), b"module")?;
+        fs::write(root.join("build/out.o"), b"object")?;
+        fs::write(root.join(".gitignore"), b"build/\n")?;
+
+        let rules = IgnoreRules::load(root, IgnoreConfig::default())?;
+        let walked: Vec<PathBuf> = walk_worktree(root, &rules)?.into_iter().map(|f| f.rel_path).collect();
+        assert_eq!(walked, vec![
+            PathBuf::from(".gitignore"),
+            PathBuf::from("README.md"),
+            PathBuf::from("src/main.rs"),
+        ]);
+
+        let first = snapshot_worktree(root, &store, &rules, None, None)?;
+        assert_eq!(first.hashed, 3);
+        assert_eq!(first.reused, 0);
+        let readme = first.tree.get(Path::new("README.md")).unwrap().clone();
+        assert_eq!(readme.blob_hash, hash::git::hash_blob(b"readme"));
+
+        // A matching stat reuses the cached entry (even if it is stale, by design)
+        let stat = FileStat::from_path(&root.join("README.md"))?;
+        let stale = Entry::file(0o644, hash::git::hash_blob(b"cached"));
+        store.blob_store().write_blob(stale.blob_hash, b"cached")?;
+        let cache = MapCache(HashMap::from([(PathBuf::from("README.md"), (stat, stale.clone()))]));
+
+        let second = snapshot_worktree(root, &store, &rules, Some(&cache), None)?;
+        assert_eq!(second.reused, 1);
+        assert_eq!(second.hashed, 2);
+        assert_eq!(second.tree.get(Path::new("README.md")), Some(&stale));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_snapshot_screens_secrets() -> Result<()> {
+        use crate::secrets::{SecretAction, SecretRuleConfig, SecretsConfig};
+
+        let temp_dir = tempfile::tempdir()?;
+        let root = temp_dir.path();
+        let store = Store::init(root)?;
+
+        fs::write(root.join("app.cfg"), b"token = itk_abcd1234\n")?;
+        fs::write(root.join("notes.txt"), b"DONTKEEP this\n")?;
+
+        let config = SecretsConfig {
+            rules: vec![
+                SecretRuleConfig { name: "itk".into(), pattern: "itk_[a-z0-9]{8}".into(), action: SecretAction::Redact },
+                SecretRuleConfig { name: "dontkeep".into(), pattern: "DONTKEEP".into(), action: SecretAction::Skip },
+            ],
+            ..SecretsConfig::default()
+        };
+        let scanner = SecretScanner::from_config(&config)?;
+
+        let rules = IgnoreRules::load(root, IgnoreConfig::default())?;
+        let snapshot = snapshot_worktree(root, &store, &rules, None, Some(&scanner))?;
+
+        let cfg = snapshot.tree.get(Path::new("app.cfg")).unwrap().blob_hash;
+        assert_eq!(store.blob_store().read_blob(cfg)?, b"token = [REDACTED:itk]\n");
+        assert!(!store.blob_store().has_blob(hash::git::hash_blob(b"token = itk_abcd1234\n")));
+
+        assert!(snapshot.tree.get(Path::new("notes.txt")).is_none());
+        assert!(!snapshot.paths.contains(&PathBuf::from("notes.txt")));
+        assert!(!store.blob_store().has_blob(hash::git::hash_blob(b"DONTKEEP this\n")));
+
+        Ok(())
+    }
+}
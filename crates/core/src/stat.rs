@@ -0,0 +1,143 @@
+//! Cached file stat data for change detection without re-hashing
+//!
+//! Mirrors Git's index: a file whose size, mtime, ctime and inode all match
+//! the values recorded when it was last hashed is assumed unchanged. Entries
+//! recorded while the file's mtime was still "hot" (within the filesystem
+//! timestamp granularity of the recording time) are racy - a write in the
+//! same tick would leave the stat data identical - so they are never trusted.
+
+use crate::tree::Entry;
+use std::fs::Metadata;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Window within which an mtime is considered racy (covers 1s-granularity filesystems)
+pub const RACY_WINDOW_NS: i64 = 1_000_000_000;
+
+/// Stat fields used to detect file changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileStat {
+    /// File size in bytes
+    pub size: u64,
+    /// Modification time (ns since epoch)
+    pub mtime_ns: i64,
+    /// Status change time (ns since epoch, 0 where unavailable)
+    pub ctime_ns: i64,
+    /// Inode number (0 where unavailable)
+    pub ino: u64,
+}
+
+impl FileStat {
+    /// Serialized size in bytes (see `to_bytes`)
+    pub const ENCODED_LEN: usize = 32;
+
+    /// Capture stat data from file metadata
+    pub fn from_metadata(metadata: &Metadata) -> Self {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            Self {
+                size: metadata.size(),
+                mtime_ns: metadata.mtime() * 1_000_000_000 + metadata.mtime_nsec(),
+                ctime_ns: metadata.ctime() * 1_000_000_000 + metadata.ctime_nsec(),
+                ino: metadata.ino(),
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            Self {
+                size: metadata.len(),
+                mtime_ns: metadata.modified().map(system_time_ns).unwrap_or(0),
+                ctime_ns: 0,
+                ino: 0,
+            }
+        }
+    }
+
+    /// Stat a path without following symlinks
+    pub fn from_path(path: &Path) -> std::io::Result<Self> {
+        std::fs::symlink_metadata(path).map(|m| Self::from_metadata(&m))
+    }
+
+    /// True if the file may still change without its stat data changing
+    pub fn is_racy(&self, recorded_at_ns: i64) -> bool {
+        self.mtime_ns > recorded_at_ns - RACY_WINDOW_NS
+    }
+
+    /// Encode as little-endian bytes (size, mtime, ctime, inode)
+    pub fn to_bytes(&self) -> [u8; Self::ENCODED_LEN] {
+        let mut bytes = [0u8; Self::ENCODED_LEN];
+        bytes[0..8].copy_from_slice(&self.size.to_le_bytes());
+        bytes[8..16].copy_from_slice(&self.mtime_ns.to_le_bytes());
+        bytes[16..24].copy_from_slice(&self.ctime_ns.to_le_bytes());
+        bytes[24..32].copy_from_slice(&self.ino.to_le_bytes());
+        bytes
+    }
+
+    /// Decode from `to_bytes` output
+    pub fn from_bytes(bytes: &[u8; Self::ENCODED_LEN]) -> Self {
+        let field = |i: usize| {
+            let mut b = [0u8; 8];
+            b.copy_from_slice(&bytes[i..i + 8]);
+            b
+        };
+        Self {
+            size: u64::from_le_bytes(field(0)),
+            mtime_ns: i64::from_le_bytes(field(8)),
+            ctime_ns: i64::from_le_bytes(field(16)),
+            ino: u64::from_le_bytes(field(24)),
+        }
+    }
+}
+
+/// Current time in ns since epoch (for racy checks)
+pub fn now_ns() -> i64 {
+    system_time_ns(SystemTime::now())
+}
+
+fn system_time_ns(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as i64)
+        .unwrap_or(0)
+}
+
+/// Lookup of stat data recorded the last time each path was hashed
+///
+/// Implemented by the journal's `PathMap`; used by rescans (overflow
+/// recovery, reconciliation) and worktree snapshots to skip files whose
+/// stat data is unchanged.
+pub trait StatCache {
+    /// Stat recorded for a repo-relative path, if trusted
+    fn cached_stat(&self, path: &Path) -> Option<FileStat>;
+
+    /// Entry (mode and blob hash) recorded alongside the stat
+    fn cached_entry(&self, path: &Path) -> Option<Entry>;
+
+    /// All repo-relative paths with recorded content (to detect deletions)
+    fn cached_paths(&self) -> Vec<std::path::PathBuf>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_stat_roundtrip_and_change_detection() -> anyhow::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let path = temp_dir.path().join("file.txt");
+        std::fs::write(&path, b"hello")?;
+
+        let stat = FileStat::from_path(&path)?;
+        assert_eq!(stat.size, 5);
+        assert_eq!(FileStat::from_bytes(&stat.to_bytes()), stat);
+
+        // Just written: racy now, trusted once the window has passed
+        assert!(stat.is_racy(now_ns()));
+        assert!(!stat.is_racy(stat.mtime_ns + RACY_WINDOW_NS + 1));
+
+        std::fs::write(&path, b"hello world")?;
+        assert_ne!(FileStat::from_path(&path)?, stat);
+
+        Ok(())
+    }
+}
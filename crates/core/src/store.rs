@@ -1,7 +1,9 @@
 //! On-disk store management for blobs and trees
 
 use crate::blob::BlobStore;
+use crate::encryption::{seal, unseal, RepoCipher};
 use crate::hash::Sha1Hash;
+use crate::quota::StoreUsage;
 use crate::tree::Tree;
 use anyhow::{Context, Result};
 use dashmap::DashMap;
@@ -44,6 +46,8 @@ pub struct Store {
     blob_store: BlobStore,
     /// Tree cache (hash -> tree)
     tree_cache: DashMap<Sha1Hash, Arc<Tree>>,
+    /// Seals trees at rest (shared with the blob store)
+    cipher: Option<Arc<RepoCipher>>,
 }
 
 impl Store {
@@ -76,9 +80,31 @@ impl Store {
 version = 1
 blob_compression_threshold = 4096  # 4KB
 max_cache_size = 104857600  # 100MB
+large_file_threshold = 67108864  # 64MB - larger files are stored chunked
 
 [watcher]
 debounce_ms = 100
+backend = "auto"  # auto | native | poll | fanotify (auto polls on NFS, SMB, SSHFS, 9p, ...)
+poll_interval_ms = 1000
+max_poll_interval_ms = 10000
+
+# Encryption at rest: seal objects and journal with a repo key
+# (TL_REPO_KEY env var, 64 hex chars, or a keyfile)
+[encryption]
+enabled = false
+# keyfile = "~/.config/tl/keys/repo.key"
+
+# Disk budget for .tl/ ("10GB", or "25%" of the space it could use).
+# Past gc_at% retention is tightened; at 100% checkpointing pauses.
+[quota]
+limit = ""  # unlimited
+warn_at = 80
+gc_at = 90
+
+# Secret scanning of new content (see `tl scrub` to purge stored secrets)
+[secrets]
+enabled = true
+action = "warn"  # warn | skip | redact
 
 # VCS Integration (auto-populated by tl init)
 [vcs]
@@ -97,13 +123,16 @@ email = ""
         fs::write(tl_dir.join("HEAD"), "")?;
 
         // Initialize blob store with dual-write to .git/objects/ if Git exists
-        let blob_store = Self::create_blob_store(&tl_dir, repo_root);
+        let cipher = RepoCipher::load(&tl_dir)?;
+        let mut blob_store = Self::create_blob_store(&tl_dir, repo_root, cipher.clone());
+        blob_store.set_usage(Arc::new(StoreUsage::load(&tl_dir)?));
 
         Ok(Self {
             root: repo_root.to_path_buf(),
             tl_dir,
             blob_store,
             tree_cache: DashMap::new(),
+            cipher,
         })
     }
 
@@ -141,25 +170,32 @@ email = ""
         }
 
         // Initialize blob store with dual-write to .git/objects/ if Git exists
-        let blob_store = Self::create_blob_store(&tl_dir, repo_root);
+        let cipher = RepoCipher::load(&tl_dir)?;
+        let mut blob_store = Self::create_blob_store(&tl_dir, repo_root, cipher.clone());
+        blob_store.set_usage(Arc::new(StoreUsage::load(&tl_dir)?));
 
         Ok(Self {
             root: repo_root.to_path_buf(),
             tl_dir,
             blob_store,
             tree_cache: DashMap::new(),
+            cipher,
         })
     }
 
     /// Create blob store with optional dual-write to .git/objects/
     ///
     /// If a .git directory exists, blobs will be written to both
-    /// .tl/objects/ and .git/objects/ for fast publish.
-    fn create_blob_store(tl_dir: &Path, repo_root: &Path) -> BlobStore {
+    /// .tl/objects/ and .git/objects/ for fast publish (unless encrypted).
+    fn create_blob_store(tl_dir: &Path, repo_root: &Path, cipher: Option<Arc<RepoCipher>>) -> BlobStore {
         let git_dir = repo_root.join(".git");
         let git_objects = git_dir.join("objects");
 
-        let blob_store = BlobStore::new(tl_dir.to_path_buf());
+        let mut blob_store = BlobStore::new(tl_dir.to_path_buf());
+        if let Some(threshold) = read_large_file_threshold(tl_dir) {
+            blob_store.set_large_file_threshold(threshold);
+        }
+        blob_store.set_cipher(cipher);
 
         // Enable dual-write if .git/objects exists
         if git_objects.exists() {
@@ -185,6 +221,7 @@ email = ""
 
         // Serialize tree
         let serialized = tree.serialize();
+        let serialized = seal(self.cipher.as_deref(), &serialized, hash.as_bytes())?;
 
         // Atomic write pattern: write to temp, fsync, rename
         let tmp_dir = self.tl_dir.join("tmp").join("ingest");
@@ -202,6 +239,7 @@ email = ""
         }
 
         fs::rename(&temp_path, &tree_path)?;
+        self.blob_store.usage().add(serialized.len() as u64);
 
         // Fsync parent directory for durability
         if let Some(parent) = tree_path.parent() {
@@ -231,7 +269,7 @@ email = ""
             anyhow::bail!("Tree not found: {}", hash);
         }
 
-        let serialized = fs::read(&tree_path)?;
+        let serialized = unseal(self.cipher.as_deref(), fs::read(&tree_path)?, hash.as_bytes())?;
         let tree = Tree::deserialize(&serialized)?;
 
         // Verify hash matches
@@ -250,6 +288,28 @@ email = ""
         Ok(tree)
     }
 
+    /// Check if a tree exists in storage (without verifying it)
+    pub fn has_tree(&self, hash: Sha1Hash) -> bool {
+        self.tree_cache.contains_key(&hash) || self.tree_path(hash).exists()
+    }
+
+    /// Move a tree out of the object store into `quarantine/trees/`
+    ///
+    /// Used by fsck repair for trees that fail to parse or re-hash. Returns
+    /// false if the tree was not present.
+    pub fn quarantine_tree(&self, hash: Sha1Hash) -> Result<bool> {
+        let tree_path = self.tree_path(hash);
+        self.tree_cache.remove(&hash);
+        if !tree_path.exists() {
+            return Ok(false);
+        }
+
+        let quarantine_dir = self.tl_dir.join("quarantine/trees");
+        std::fs::create_dir_all(&quarantine_dir)?;
+        std::fs::rename(&tree_path, quarantine_dir.join(hash.to_hex()))?;
+        Ok(true)
+    }
+
     /// Get the tree path for a given hash
     fn tree_path(&self, hash: Sha1Hash) -> PathBuf {
         // Fan-out structure: objects/trees/<hh>/<rest>
@@ -274,6 +334,16 @@ email = ""
         self.blob_store.blob_size(*hash)
     }
 
+    /// Bytes used by `.tl/`, tracked as objects are written and deleted
+    pub fn usage(&self) -> &Arc<StoreUsage> {
+        self.blob_store.usage()
+    }
+
+    /// Repo cipher, if objects are encrypted at rest
+    pub fn cipher(&self) -> Option<&Arc<RepoCipher>> {
+        self.cipher.as_ref()
+    }
+
     /// Get the .tl directory path
     pub fn tl_dir(&self) -> &Path {
         &self.tl_dir
@@ -285,6 +355,16 @@ email = ""
     }
 }
 
+/// Read `[store] large_file_threshold` from config.toml, if set
+fn read_large_file_threshold(tl_dir: &Path) -> Option<u64> {
+    let content = std::fs::read_to_string(tl_dir.join("config.toml")).ok()?;
+    let config: toml::Value = content.parse().ok()?;
+    config.get("store")?
+        .get("large_file_threshold")?
+        .as_integer()
+        .and_then(|v| u64::try_from(v).ok())
+}
+
 /// Atomic write helper
 ///
 /// Writes data to a temporary file, fsyncs it, then renames it to the target path.
@@ -820,4 +900,62 @@ mod tests {
         assert!(!should_ignore(Path::new("README.md")));
         assert!(!should_ignore(Path::new("a/b/c/file.txt")));
     }
+
+    #[test]
+    fn test_store_quarantine_corrupt_objects() -> Result<()> {
+        use crate::hash::git::hash_blob;
+        use crate::tree::{Entry, Tree};
+
+        let temp_dir = tempfile::tempdir()?;
+        let store = Store::init(temp_dir.path())?;
+
+        let blob_hash = hash_blob(b"content");
+        store.blob_store().write_blob(blob_hash, b"content")?;
+        let mut tree = Tree::new();
+        tree.insert(Path::new("file.txt"), Entry::file(0o644, blob_hash));
+        let tree_hash = store.write_tree(&tree)?;
+
+        assert!(store.has_tree(tree_hash));
+        assert!(store.quarantine_tree(tree_hash)?);
+        assert!(!store.has_tree(tree_hash));
+        assert!(store.tl_dir().join("quarantine/trees").join(tree_hash.to_hex()).exists());
+
+        assert!(store.blob_store().quarantine_blob(blob_hash)?);
+        assert!(!store.blob_store().has_blob(blob_hash));
+        assert!(!store.blob_store().quarantine_blob(blob_hash)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_store_tracks_usage() -> Result<()> {
+        use crate::hash::git::hash_blob;
+        use crate::tree::{Entry, Tree};
+
+        let temp_dir = tempfile::tempdir()?;
+        let store = Store::init(temp_dir.path())?;
+        let initial = store.usage().bytes();
+
+        let data = vec![b'x'; 10_000];
+        let blob_hash = hash_blob(&data);
+        store.blob_store().write_blob(blob_hash, &data)?;
+        let mut tree = Tree::new();
+        tree.insert(Path::new("file.txt"), Entry::file(0o644, blob_hash));
+        store.write_tree(&tree)?;
+        let after_writes = store.usage().bytes();
+        assert!(after_writes > initial);
+
+        // Idempotent writes are not counted twice
+        store.blob_store().write_blob(blob_hash, &data)?;
+        assert_eq!(store.usage().bytes(), after_writes);
+
+        let freed = store.blob_store().delete_blob(blob_hash)?;
+        assert_eq!(store.usage().bytes(), after_writes - freed);
+
+        // The figure survives reopening
+        store.usage().persist()?;
+        assert_eq!(Store::open(temp_dir.path())?.usage().bytes(), after_writes - freed);
+
+        Ok(())
+    }
 }
@@ -2,11 +2,12 @@
 //!
 //! This module provides utilities for:
 //! - Writing conflict markers to files
+//! - Line-level three-way merging of text
 //! - Detecting files with conflict markers
 //! - Parsing conflict markers
 
 use anyhow::{Context, Result};
-use std::io::Write;
+use similar::{DiffTag, TextDiff};
 use std::path::Path;
 
 /// Conflict marker strings (Git-compatible)
@@ -46,6 +47,32 @@ pub fn write_conflict_markers(
     ours_label: &str,
     theirs_label: &str,
 ) -> Result<()> {
+    let output = render_conflict_markers(base, ours, theirs, ours_label, theirs_label);
+
+    // Create parent directories if needed
+    if let Some(parent) = file_path.parent() {
+        std::fs::create_dir_all(parent)
+            .context("Failed to create parent directories")?;
+    }
+
+    // Write file
+    std::fs::write(file_path, output)
+        .context("Failed to write conflict file")?;
+
+    Ok(())
+}
+
+/// Render a single conflict region with Git-style markers
+///
+/// Same format as `write_conflict_markers`, returned as bytes so callers can
+/// embed it in a larger file (e.g. line-level merges with several regions).
+pub fn render_conflict_markers(
+    base: Option<&[u8]>,
+    ours: &[u8],
+    theirs: &[u8],
+    ours_label: &str,
+    theirs_label: &str,
+) -> Vec<u8> {
     let mut output = Vec::new();
 
     // Convert to strings for line-by-line processing
@@ -53,7 +80,7 @@ pub fn write_conflict_markers(
     let theirs_str = String::from_utf8_lossy(theirs);
 
     // Write conflict start marker
-    writeln!(output, "{} {}", CONFLICT_MARKER_START, ours_label)?;
+    output.extend_from_slice(format!("{} {}\n", CONFLICT_MARKER_START, ours_label).as_bytes());
 
     // Write "ours" content
     output.extend_from_slice(ours_str.as_bytes());
@@ -64,7 +91,7 @@ pub fn write_conflict_markers(
     // Optionally write base content (diff3 style)
     if let Some(base_content) = base {
         let base_str = String::from_utf8_lossy(base_content);
-        writeln!(output, "{} BASE", CONFLICT_MARKER_BASE)?;
+        output.extend_from_slice(format!("{} BASE\n", CONFLICT_MARKER_BASE).as_bytes());
         output.extend_from_slice(base_str.as_bytes());
         if !base_str.ends_with('\n') && !base_str.is_empty() {
             output.push(b'\n');
@@ -72,7 +99,7 @@ pub fn write_conflict_markers(
     }
 
     // Write separator
-    writeln!(output, "{}", CONFLICT_MARKER_SEPARATOR)?;
+    output.extend_from_slice(format!("{}\n", CONFLICT_MARKER_SEPARATOR).as_bytes());
 
     // Write "theirs" content
     output.extend_from_slice(theirs_str.as_bytes());
@@ -81,19 +108,172 @@ pub fn write_conflict_markers(
     }
 
     // Write conflict end marker
-    writeln!(output, "{} {}", CONFLICT_MARKER_END, theirs_label)?;
+    output.extend_from_slice(format!("{} {}\n", CONFLICT_MARKER_END, theirs_label).as_bytes());
 
-    // Create parent directories if needed
-    if let Some(parent) = file_path.parent() {
-        std::fs::create_dir_all(parent)
-            .context("Failed to create parent directories")?;
+    output
+}
+
+/// Result of a line-level three-way merge
+#[derive(Debug, Clone)]
+pub struct MergedText {
+    /// Merged content (with conflict markers for unresolved regions)
+    pub content: Vec<u8>,
+    /// Number of conflict regions written
+    pub conflicts: usize,
+}
+
+/// A change made by one side, expressed against base line indices
+struct SideChange {
+    base: std::ops::Range<usize>,
+    lines: std::ops::Range<usize>,
+}
+
+/// Collect the non-equal ops of a base→side line diff
+fn side_changes(base: &str, side: &str) -> Vec<SideChange> {
+    TextDiff::from_lines(base, side)
+        .ops()
+        .iter()
+        .filter(|op| op.tag() != DiffTag::Equal)
+        .map(|op| SideChange {
+            base: op.old_range(),
+            lines: op.new_range(),
+        })
+        .collect()
+}
+
+/// Rebuild one side's text for base lines `start..end` from its changes
+fn rebuild_side(
+    base_lines: &[&str],
+    side_lines: &[&str],
+    changes: &[SideChange],
+    start: usize,
+    end: usize,
+) -> String {
+    let mut output = String::new();
+    let mut pos = start;
+
+    for change in changes {
+        for line in &base_lines[pos..change.base.start] {
+            output.push_str(line);
+        }
+        for line in &side_lines[change.lines.clone()] {
+            output.push_str(line);
+        }
+        pos = change.base.end;
     }
 
-    // Write file
-    std::fs::write(file_path, output)
-        .context("Failed to write conflict file")?;
+    for line in &base_lines[pos..end] {
+        output.push_str(line);
+    }
 
-    Ok(())
+    output
+}
+
+/// Line-level three-way merge (diff3)
+///
+/// Changes made by only one side are applied cleanly. Regions changed by both
+/// sides are applied once if identical; otherwise they are written with
+/// conflict markers. Changes that touch (adjacent lines) are treated as
+/// conflicting, matching Git's behavior.
+pub fn merge_text(
+    base: &[u8],
+    ours: &[u8],
+    theirs: &[u8],
+    ours_label: &str,
+    theirs_label: &str,
+) -> MergedText {
+    let base_str = String::from_utf8_lossy(base);
+    let ours_str = String::from_utf8_lossy(ours);
+    let theirs_str = String::from_utf8_lossy(theirs);
+
+    // Same line splitting as TextDiff::from_lines (newlines stay attached)
+    let base_lines: Vec<&str> = base_str.split_inclusive('\n').collect();
+    let ours_lines: Vec<&str> = ours_str.split_inclusive('\n').collect();
+    let theirs_lines: Vec<&str> = theirs_str.split_inclusive('\n').collect();
+
+    let ours_changes = side_changes(&base_str, &ours_str);
+    let theirs_changes = side_changes(&base_str, &theirs_str);
+
+    let mut output: Vec<u8> = Vec::with_capacity(base.len().max(ours.len()).max(theirs.len()));
+    let mut conflicts = 0;
+    let mut pos = 0;
+    let (mut i, mut j) = (0, 0);
+
+    while i < ours_changes.len() || j < theirs_changes.len() {
+        // Start a group at the earliest pending change
+        let start = match (ours_changes.get(i), theirs_changes.get(j)) {
+            (Some(o), Some(t)) => o.base.start.min(t.base.start),
+            (Some(o), None) => o.base.start,
+            (None, Some(t)) => t.base.start,
+            (None, None) => break,
+        };
+
+        // Grow the group while changes from either side overlap or touch it
+        let (group_i, group_j) = (i, j);
+        let mut end = start;
+        loop {
+            let mut extended = false;
+            while i < ours_changes.len() && ours_changes[i].base.start <= end {
+                end = end.max(ours_changes[i].base.end);
+                i += 1;
+                extended = true;
+            }
+            while j < theirs_changes.len() && theirs_changes[j].base.start <= end {
+                end = end.max(theirs_changes[j].base.end);
+                j += 1;
+                extended = true;
+            }
+            if !extended {
+                break;
+            }
+        }
+
+        // Unchanged base lines before the group
+        for line in &base_lines[pos..start] {
+            output.extend_from_slice(line.as_bytes());
+        }
+
+        let ours_group = &ours_changes[group_i..i];
+        let theirs_group = &theirs_changes[group_j..j];
+
+        if theirs_group.is_empty() {
+            let text = rebuild_side(&base_lines, &ours_lines, ours_group, start, end);
+            output.extend_from_slice(text.as_bytes());
+        } else if ours_group.is_empty() {
+            let text = rebuild_side(&base_lines, &theirs_lines, theirs_group, start, end);
+            output.extend_from_slice(text.as_bytes());
+        } else {
+            let ours_text = rebuild_side(&base_lines, &ours_lines, ours_group, start, end);
+            let theirs_text = rebuild_side(&base_lines, &theirs_lines, theirs_group, start, end);
+
+            if ours_text == theirs_text {
+                // Both sides made the same change
+                output.extend_from_slice(ours_text.as_bytes());
+            } else {
+                let base_text: String = base_lines[start..end].concat();
+                output.extend_from_slice(&render_conflict_markers(
+                    Some(base_text.as_bytes()),
+                    ours_text.as_bytes(),
+                    theirs_text.as_bytes(),
+                    ours_label,
+                    theirs_label,
+                ));
+                conflicts += 1;
+            }
+        }
+
+        pos = end;
+    }
+
+    // Remainder of the file after the last group
+    for line in &base_lines[pos..] {
+        output.extend_from_slice(line.as_bytes());
+    }
+
+    MergedText {
+        content: output,
+        conflicts,
+    }
 }
 
 /// Write a file with conflict markers using smart merging
@@ -348,4 +528,41 @@ some code
 
         assert_eq!(count_conflicts(&file_path).unwrap(), 2);
     }
+
+    #[test]
+    fn test_merge_text_non_overlapping() {
+        let base = b"a\nb\nc\nd\ne\nf\n";
+        let ours = b"A\nb\nc\nd\ne\nf\n";
+        let theirs = b"a\nb\nc\nd\ne\nF\n";
+
+        let merged = merge_text(base, ours, theirs, "ours", "theirs");
+        assert_eq!(merged.conflicts, 0);
+        assert_eq!(merged.content, b"A\nb\nc\nd\ne\nF\n".to_vec());
+    }
+
+    #[test]
+    fn test_merge_text_identical_changes() {
+        let base = b"a\nb\nc\n";
+        let ours = b"a\nB\nc\n";
+
+        let merged = merge_text(base, ours, ours, "ours", "theirs");
+        assert_eq!(merged.conflicts, 0);
+        assert_eq!(merged.content, ours.to_vec());
+    }
+
+    #[test]
+    fn test_merge_text_conflict() {
+        let base = b"a\nb\nc\nd\ne\n";
+        let ours = b"a\nours\nc\nd\ne\n";
+        let theirs = b"a\ntheirs\nc\nd\nE\n";
+
+        let merged = merge_text(base, ours, theirs, "ours", "theirs");
+        assert_eq!(merged.conflicts, 1);
+
+        let content = String::from_utf8(merged.content).unwrap();
+        assert_eq!(
+            content,
+            "a\n<<<<<<< ours\nours\n||||||| BASE\nb\n=======\ntheirs\n>>>>>>> theirs\nc\nd\nE\n"
+        );
+    }
 }
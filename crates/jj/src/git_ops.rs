@@ -714,6 +714,31 @@ pub fn export_commit_to_dir(
     Ok(())
 }
 
+/// Return the commit IDs (hex) that do not exist in the JJ store
+///
+/// Used by `tl fsck` to find checkpoint mappings pointing at commits that
+/// were abandoned or garbage collected on the JJ side.
+pub fn missing_commits(
+    workspace: &jj_lib::workspace::Workspace,
+    commit_ids_hex: &[String],
+) -> Result<Vec<String>> {
+    let repo = workspace.repo_loader().load_at_head()
+        .context("Failed to load repository")?;
+
+    let mut missing = Vec::new();
+    for commit_id_hex in commit_ids_hex {
+        let exists = match hex::decode(commit_id_hex) {
+            Ok(bytes) => repo.store().get_commit(&CommitId::new(bytes)).is_ok(),
+            Err(_) => false,
+        };
+        if !exists {
+            missing.push(commit_id_hex.clone());
+        }
+    }
+
+    Ok(missing)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -5,6 +5,7 @@
 //! - `tl publish` (create JJ commit from checkpoint)
 //! - `tl push` / `tl pull` (Git interop via JJ)
 //! - Checkpoint ↔ JJ commit mapping
+//! - Three-way merging of checkpoint trees (`tl merge-checkpoint`)
 //!
 //! All operations are designed to be configurable via CLI flags to give users
 //! maximum control over behavior.
@@ -16,13 +17,15 @@ pub mod mapping;
 pub mod materialize;
 pub mod merge;
 pub mod publish;
+pub mod tree_merge;
 pub mod workspace;
 
 // Re-export public types
 pub use conflicts::{
     has_conflict_markers, write_conflict_markers, count_conflicts,
     parse_conflict_regions, is_resolved, check_resolution_status,
-    ConflictRegion, ResolutionStatus,
+    merge_text, render_conflict_markers,
+    ConflictRegion, MergedText, ResolutionStatus,
     CONFLICT_MARKER_START, CONFLICT_MARKER_END,
 };
 pub use git_ops::{RemoteBranchInfo, BranchPushResult, BranchPushStatus, LocalBranchInfo};
@@ -33,6 +36,7 @@ pub use merge::{
     perform_merge, find_merge_base, get_branch_commit_id, get_current_commit_id,
 };
 pub use publish::{publish_checkpoint, publish_range};
+pub use tree_merge::{merge_trees, TreeConflict, TreeConflictKind, TreeMergeResult};
 pub use workspace::{validate_workspace_name, JjWorkspace, WorkspaceManager, WorkspaceState};
 
 use anyhow::{anyhow, Context, Result};
@@ -127,8 +127,8 @@ impl JjMapping {
             let key_str = String::from_utf8(key.to_vec())
                 .context("Invalid UTF-8 in mapping key")?;
 
-            // Skip reverse mappings (they start with "rev:")
-            if key_str.starts_with("rev:") {
+            // Skip reverse mappings (they start with "rev:") and seed entries
+            if key_str.starts_with("rev:") || key_str.starts_with(SEED_COMMIT_KEY) {
                 continue;
             }
 
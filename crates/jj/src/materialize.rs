@@ -171,6 +171,60 @@ fn file_id_from_hash(hash: &Sha1Hash) -> jj_lib::backend::FileId {
     jj_lib::backend::FileId::new(hash.as_bytes().to_vec())
 }
 
+/// Write a TL blob to the JJ store, returning its FileId
+///
+/// The Git backend's `write_file` buffers the whole file, so chunked (large)
+/// blobs are instead streamed straight into the backing Git repository as a
+/// loose object; its SHA-1 is the TL hash, so the FileId follows directly.
+fn write_file_to_jj(
+    store: &Store,
+    jj_store: &std::sync::Arc<jj_lib::store::Store>,
+    repo_path: &jj_lib::repo_path::RepoPath,
+    blob_hash: &Sha1Hash,
+    path_str: &str,
+) -> Result<jj_lib::backend::FileId> {
+    let git_backend = jj_store.backend_impl::<jj_lib::git_backend::GitBackend>();
+    if let (true, Some(git_backend)) = (store.blob_store().is_chunked(*blob_hash), git_backend) {
+        write_git_loose_object(store, &git_backend.git_repo_path().join("objects"), blob_hash)
+            .with_context(|| format!("Failed to write file to JJ store: {}", path_str))?;
+        return Ok(file_id_from_hash(blob_hash));
+    }
+
+    let content = store.blob_store().read_blob(*blob_hash)
+        .with_context(|| format!("Failed to read blob for {}", path_str))?;
+    let mut cursor = std::io::Cursor::new(&content);
+    jj_store.write_file(repo_path, &mut cursor)
+        .block_on()
+        .with_context(|| format!("Failed to write file to JJ store: {}", path_str))
+}
+
+/// Stream a blob into `objects_dir` as a zlib-compressed Git loose object
+///
+/// Written to a temporary file and renamed into place, so an interrupted or
+/// failed write (e.g. hash mismatch) never leaves a partial object behind.
+fn write_git_loose_object(store: &Store, objects_dir: &Path, blob_hash: &Sha1Hash) -> Result<()> {
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    let hex = blob_hash.to_hex();
+    let object_dir = objects_dir.join(&hex[..2]);
+    let object_path = object_dir.join(&hex[2..]);
+    if object_path.exists() {
+        return Ok(());
+    }
+    std::fs::create_dir_all(&object_dir)?;
+
+    let size = store.blob_store().blob_size(*blob_hash)?;
+    let temp = tempfile::NamedTempFile::new_in(&object_dir)?;
+    let mut encoder = ZlibEncoder::new(std::io::BufWriter::new(temp), Compression::default());
+    encoder.write_all(format!("blob {}\0", size).as_bytes())?;
+    store.blob_store().read_blob_to(*blob_hash, &mut encoder)?;
+    let temp = encoder.finish()?.into_inner().map_err(|e| e.into_error())?;
+    temp.persist(&object_path)?;
+    Ok(())
+}
+
 /// Convert Timelapse tree to JJ tree
 ///
 /// Converts a Timelapse tree representation to a JJ tree using native jj-lib APIs.
@@ -206,6 +260,12 @@ pub fn convert_tree_to_jj(
             continue;
         }
 
+        // Content dropped by a path policy cannot be published
+        if entry.kind != EntryKind::Tree && store.blob_store().is_omitted(entry.blob_hash) {
+            eprintln!("Warning: Skipping path whose content was not kept (path policy): {}", path_str);
+            continue;
+        }
+
         // Convert path to RepoPath (returns Result in 0.36.0)
         let repo_path = RepoPath::from_internal_string(path_str)
             .with_context(|| format!("Invalid repo path: {}", path_str))?;
@@ -220,12 +280,7 @@ pub fn convert_tree_to_jj(
                     file_id_from_hash(&entry.blob_hash)
                 } else {
                     // Slow path: read from TL, write to JJ
-                    let content = store.blob_store().read_blob(entry.blob_hash)
-                        .with_context(|| format!("Failed to read blob for {}", path_str))?;
-                    let mut cursor = std::io::Cursor::new(&content);
-                    jj_store.write_file(repo_path, &mut cursor)
-                        .block_on()
-                        .with_context(|| format!("Failed to write file to JJ store: {}", path_str))?
+                    write_file_to_jj(store, jj_store, repo_path, &entry.blob_hash, path_str)?
                 };
 
                 // Check if executable
@@ -314,8 +369,18 @@ pub fn convert_tree_to_jj_incremental(
             continue;
         }
 
+        // Content dropped by a path policy cannot be published: leave the path
+        // out of the commit rather than carry the parent's stale version
+        let entry = tl_tree.get(path).filter(|entry| {
+            let omitted = entry.kind != EntryKind::Tree && store.blob_store().is_omitted(entry.blob_hash);
+            if omitted {
+                eprintln!("Warning: Skipping path whose content was not kept (path policy): {}", path_str);
+            }
+            !omitted
+        });
+
         // Check if file exists in the new tree
-        if let Some(entry) = tl_tree.get(path) {
+        if let Some(entry) = entry {
             // File exists - add or update it
             // Convert path to RepoPath (returns Result in 0.36.0)
             let repo_path = RepoPath::from_internal_string(&path_str)
@@ -329,12 +394,7 @@ pub fn convert_tree_to_jj_incremental(
                         file_id_from_hash(&entry.blob_hash)
                     } else {
                         // Slow path: read from TL, write to JJ
-                        let content = store.blob_store().read_blob(entry.blob_hash)
-                            .with_context(|| format!("Failed to read blob for {}", path_str))?;
-                        let mut cursor = std::io::Cursor::new(&content);
-                        jj_store.write_file(repo_path, &mut cursor)
-                            .block_on()
-                            .with_context(|| format!("Failed to write file to JJ store: {}", path_str))?
+                        write_file_to_jj(store, jj_store, repo_path, &entry.blob_hash, &path_str)?
                     };
 
                     let executable = matches!(entry.kind, EntryKind::ExecutableFile) || (entry.mode & 0o111 != 0);
@@ -368,7 +428,7 @@ pub fn convert_tree_to_jj_incremental(
                 .with_context(|| format!("Invalid repo path: {}", path_str))?;
             tree_builder.set(repo_path_buf, tree_value);
         } else {
-            // File doesn't exist in new tree - it was deleted
+            // File doesn't exist in new tree (deleted) or its content was not kept
             let repo_path = RepoPathBuf::from_internal_string(&path_str)
                 .with_context(|| format!("Invalid repo path for deletion: {}", path_str))?;
             tree_builder.remove(repo_path);
@@ -400,6 +460,35 @@ pub fn publish_checkpoint(
     mapping: &crate::mapping::JjMapping,
     options: &PublishOptions,
     repo_root: &Path,
+) -> Result<String> {
+    publish_checkpoint_impl(checkpoint, store, workspace, mapping, options, repo_root, true)
+}
+
+/// Publish a synthetic checkpoint (partial selection) to JJ
+///
+/// Same as `publish_checkpoint`, but the checkpoint's `root_tree` is a tree
+/// assembled from a subset of the real checkpoint's changes. The resulting
+/// commit is NOT recorded in the mapping: the checkpoint itself has not been
+/// published in full, so later publishes must not treat it as a JJ parent.
+pub fn publish_partial_checkpoint(
+    checkpoint: &Checkpoint,
+    store: &Store,
+    workspace: &mut jj_lib::workspace::Workspace,
+    mapping: &crate::mapping::JjMapping,
+    options: &PublishOptions,
+    repo_root: &Path,
+) -> Result<String> {
+    publish_checkpoint_impl(checkpoint, store, workspace, mapping, options, repo_root, false)
+}
+
+fn publish_checkpoint_impl(
+    checkpoint: &Checkpoint,
+    store: &Store,
+    workspace: &mut jj_lib::workspace::Workspace,
+    mapping: &crate::mapping::JjMapping,
+    options: &PublishOptions,
+    repo_root: &Path,
+    record_mapping: bool,
 ) -> Result<String> {
     use jj_lib::merged_tree::MergedTree;
     use jj_lib::repo::Repo;  // Import trait for methods
@@ -539,15 +628,17 @@ pub fn publish_checkpoint(
     tx.commit("publish checkpoint")
         .context("Failed to commit transaction")?;
 
-    // Store bidirectional mapping
-    mapping.set(checkpoint.id, &commit_id)
-        .context("Failed to store checkpoint mapping")?;
-    mapping.set_reverse(&commit_id, checkpoint.id)
-        .context("Failed to store reverse mapping")?;
+    if record_mapping {
+        // Store bidirectional mapping
+        mapping.set(checkpoint.id, &commit_id)
+            .context("Failed to store checkpoint mapping")?;
+        mapping.set_reverse(&commit_id, checkpoint.id)
+            .context("Failed to store reverse mapping")?;
 
-    // Flush mapping database to disk (single flush instead of per-write)
-    mapping.flush()
-        .context("Failed to flush mapping database")?;
+        // Flush mapping database to disk (single flush instead of per-write)
+        mapping.flush()
+            .context("Failed to flush mapping database")?;
+    }
 
     // Auto-pin if configured
     if let Some(ref pin_name) = options.auto_pin {
@@ -849,6 +940,8 @@ mod tests {
                 bytes_added: 1024,
                 bytes_removed: 512,
             },
+            merge_parents: vec![],
+            changes: vec![],
         }
     }
 
@@ -916,4 +1009,37 @@ mod tests {
         assert!(!options.compact_range); // Should expand by default
         assert!(options.message_options.include_files);
     }
+
+    #[test]
+    fn test_write_git_loose_object_streams_chunked_blob() -> Result<()> {
+        use std::io::Read;
+
+        let temp_dir = tempfile::tempdir()?;
+        Store::init(temp_dir.path())?;
+        let config_path = temp_dir.path().join(".tl/config.toml");
+        let config = std::fs::read_to_string(&config_path)?
+            .replace("large_file_threshold = 67108864", "large_file_threshold = 65536");
+        std::fs::write(&config_path, config)?;
+        let store = Store::open(temp_dir.path())?;
+
+        let content: Vec<u8> = (0..300_000u32).map(|i| (i.wrapping_mul(2654435761) >> 13) as u8).collect();
+        let file_path = temp_dir.path().join("large.bin");
+        std::fs::write(&file_path, &content)?;
+        let hash = store.blob_store().write_file(&file_path)?;
+        assert!(store.blob_store().is_chunked(hash));
+
+        let objects_dir = temp_dir.path().join("git-objects");
+        write_git_loose_object(&store, &objects_dir, &hash)?;
+
+        let hex = hash.to_hex();
+        let compressed = std::fs::read(objects_dir.join(&hex[..2]).join(&hex[2..]))?;
+        let mut object = Vec::new();
+        flate2::read::ZlibDecoder::new(&compressed[..]).read_to_end(&mut object)?;
+        let mut expected = format!("blob {}\0", content.len()).into_bytes();
+        expected.extend_from_slice(&content);
+        assert!(object == expected, "loose object does not match the blob");
+        assert_eq!(tl_core::hash::git::hash_blob(&content), hash);
+
+        Ok(())
+    }
 }
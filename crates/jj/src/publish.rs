@@ -38,6 +38,12 @@ pub fn materialize_checkpoint_to_dir(
             continue;
         }
 
+        // Content dropped by a path policy cannot be materialized
+        if store.blob_store().is_omitted(entry.blob_hash) {
+            eprintln!("Warning: Skipping path whose content was not kept (path policy): {}", path_str);
+            continue;
+        }
+
         let file_path = target_dir.join(path_str);
 
         // Create parent directories
@@ -46,15 +52,13 @@ pub fn materialize_checkpoint_to_dir(
                 .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
         }
 
-        // Read blob content
-        let content = store.blob_store().read_blob(entry.blob_hash)
-            .with_context(|| format!("Failed to read blob for {}", path_str))?;
-
         // Handle symlinks
         if entry.kind == tl_core::EntryKind::Symlink {
             #[cfg(unix)]
             {
                 use std::os::unix::fs::symlink;
+                let content = store.blob_store().read_blob(entry.blob_hash)
+                    .with_context(|| format!("Failed to read blob for {}", path_str))?;
                 let target = std::str::from_utf8(&content)
                     .with_context(|| format!("Invalid UTF-8 in symlink target: {}", path_str))?;
                 symlink(target, &file_path)
@@ -68,9 +72,14 @@ pub fn materialize_checkpoint_to_dir(
             }
         }
 
-        // Write file
-        fs::write(&file_path, content)
+        // Stream blob content into the file (chunked large files never sit in memory)
+        let mut file = std::io::BufWriter::new(fs::File::create(&file_path)
+            .with_context(|| format!("Failed to write file: {}", file_path.display()))?);
+        store.blob_store().read_blob_to(entry.blob_hash, &mut file)
+            .with_context(|| format!("Failed to read blob for {}", path_str))?;
+        std::io::Write::flush(&mut file)
             .with_context(|| format!("Failed to write file: {}", file_path.display()))?;
+        drop(file);
 
         // Set permissions (Unix)
         #[cfg(unix)]
@@ -128,6 +137,29 @@ pub fn publish_checkpoint(
     )
 }
 
+/// Publish a partial (hunk-selected) checkpoint to JJ
+///
+/// The checkpoint's `root_tree` must point at a synthetic tree that is already
+/// written to the store. The checkpoint mapping is left untouched.
+pub fn publish_partial_checkpoint(
+    checkpoint: &Checkpoint,
+    store: &Store,
+    repo_root: &Path,
+    mapping: &JjMapping,
+    options: &PublishOptions,
+) -> Result<String> {
+    let mut workspace = crate::load_workspace(repo_root)?;
+
+    crate::materialize::publish_partial_checkpoint(
+        checkpoint,
+        store,
+        &mut workspace,
+        mapping,
+        options,
+        repo_root,
+    )
+}
+
 /// Publish a range of checkpoints to JJ
 ///
 /// Behavior depends on options.compact_range:
@@ -0,0 +1,262 @@
+//! Three-way merge of Timelapse trees
+//!
+//! Merges two checkpoint trees against a common base without going through JJ.
+//! Paths changed on only one side are taken as-is; text files changed on both
+//! sides are merged line-by-line via `conflicts::merge_text`, and any
+//! unresolved regions are stored in the merged blob with conflict markers.
+
+use anyhow::{Context, Result};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use tl_core::{Entry, EntryKind, Store, Tree};
+
+use crate::conflicts::merge_text;
+
+/// Why a path could not be merged cleanly
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TreeConflictKind {
+    /// Both sides edited the same lines (markers written into the file)
+    Content { regions: usize },
+    /// One side modified the file, the other deleted it (modified side kept)
+    ModifyDelete,
+    /// Binary file, symlink, file/symlink mismatch, or content not kept
+    /// under a path policy (ours kept)
+    Unmergeable,
+}
+
+/// A path that needs manual resolution after a merge
+#[derive(Debug, Clone)]
+pub struct TreeConflict {
+    pub path: PathBuf,
+    pub kind: TreeConflictKind,
+}
+
+/// Result of a three-way tree merge
+#[derive(Debug, Clone)]
+pub struct TreeMergeResult {
+    /// Merged tree (all new blobs already written to the store)
+    pub tree: Tree,
+    /// Paths whose merged entry differs from "ours"
+    pub changed_paths: Vec<PathBuf>,
+    /// Paths that need manual resolution
+    pub conflicts: Vec<TreeConflict>,
+}
+
+impl TreeMergeResult {
+    /// True if the merge completed without conflicts
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// Three-way merge `ours` and `theirs` against `base`
+///
+/// The result starts from `ours` and applies every change `theirs` made
+/// relative to `base`. Conflicting text files get Git-style conflict markers
+/// labelled with `ours_label` / `theirs_label`.
+pub fn merge_trees(
+    store: &Store,
+    base: &Tree,
+    ours: &Tree,
+    theirs: &Tree,
+    ours_label: &str,
+    theirs_label: &str,
+) -> Result<TreeMergeResult> {
+    // Union of all paths (sorted for deterministic output)
+    let mut all_paths: BTreeSet<&[u8]> = BTreeSet::new();
+    for tree in [base, ours, theirs] {
+        for (path, _) in tree.entries_with_paths() {
+            all_paths.insert(path);
+        }
+    }
+
+    let mut merged = ours.clone();
+    let mut changed_paths = Vec::new();
+    let mut conflicts = Vec::new();
+
+    for path_bytes in all_paths {
+        let path_str = std::str::from_utf8(path_bytes)
+            .context("Invalid UTF-8 in file path")?;
+        let path = Path::new(path_str);
+
+        let base_entry = base.get(path);
+        let ours_entry = ours.get(path);
+        let theirs_entry = theirs.get(path);
+
+        // Trivial cases: same result on both sides, or only one side changed
+        if ours_entry == theirs_entry || base_entry == theirs_entry {
+            continue;
+        }
+        if base_entry == ours_entry {
+            match theirs_entry {
+                Some(entry) => merged.insert(path, entry.clone()),
+                None => {
+                    merged.remove(path);
+                }
+            }
+            changed_paths.push(path.to_path_buf());
+            continue;
+        }
+
+        // Both sides changed the path differently
+        let content_kept = [base_entry, ours_entry, theirs_entry]
+            .into_iter()
+            .flatten()
+            .all(|entry| !store.blob_store().is_omitted(entry.blob_hash));
+        match (ours_entry, theirs_entry) {
+            (Some(o), Some(t)) if is_regular_file(o) && is_regular_file(t) && content_kept => {
+                let base_content = match base_entry {
+                    Some(b) if is_regular_file(b) => store.blob_store().read_blob(b.blob_hash)
+                        .with_context(|| format!("Failed to read base blob for {}", path_str))?,
+                    _ => Vec::new(), // Added on both sides: merge against empty file
+                };
+                let ours_content = store.blob_store().read_blob(o.blob_hash)
+                    .with_context(|| format!("Failed to read blob for {}", path_str))?;
+                let theirs_content = store.blob_store().read_blob(t.blob_hash)
+                    .with_context(|| format!("Failed to read blob for {}", path_str))?;
+
+                if [&base_content, &ours_content, &theirs_content].iter().any(|c| tl_core::is_binary(c)) {
+                    conflicts.push(TreeConflict {
+                        path: path.to_path_buf(),
+                        kind: TreeConflictKind::Unmergeable,
+                    });
+                    continue;
+                }
+
+                let text = merge_text(&base_content, &ours_content, &theirs_content, ours_label, theirs_label);
+
+                // Take theirs' mode only if ours left it unchanged
+                let mode = match base_entry {
+                    Some(b) if b.mode == o.mode => t.mode,
+                    _ => o.mode,
+                };
+
+                let blob_hash = tl_core::hash::git::hash_blob(&text.content);
+                if !store.blob_store().has_blob(blob_hash) {
+                    store.blob_store().write_blob(blob_hash, &text.content)?;
+                }
+
+                let entry = Entry::file(mode, blob_hash);
+                if Some(&entry) != ours_entry {
+                    merged.insert(path, entry);
+                    changed_paths.push(path.to_path_buf());
+                }
+
+                if text.conflicts > 0 {
+                    conflicts.push(TreeConflict {
+                        path: path.to_path_buf(),
+                        kind: TreeConflictKind::Content { regions: text.conflicts },
+                    });
+                }
+            }
+            (None, Some(t)) => {
+                // Deleted in ours, modified in theirs: keep the modification
+                merged.insert(path, t.clone());
+                changed_paths.push(path.to_path_buf());
+                conflicts.push(TreeConflict {
+                    path: path.to_path_buf(),
+                    kind: TreeConflictKind::ModifyDelete,
+                });
+            }
+            (Some(_), None) => {
+                // Modified in ours, deleted in theirs: ours already in place
+                conflicts.push(TreeConflict {
+                    path: path.to_path_buf(),
+                    kind: TreeConflictKind::ModifyDelete,
+                });
+            }
+            _ => {
+                conflicts.push(TreeConflict {
+                    path: path.to_path_buf(),
+                    kind: TreeConflictKind::Unmergeable,
+                });
+            }
+        }
+    }
+
+    Ok(TreeMergeResult {
+        tree: merged,
+        changed_paths,
+        conflicts,
+    })
+}
+
+fn is_regular_file(entry: &Entry) -> bool {
+    matches!(entry.kind, EntryKind::File | EntryKind::ExecutableFile)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn file(store: &Store, content: &[u8]) -> Result<Entry> {
+        let hash = tl_core::hash::git::hash_blob(content);
+        store.blob_store().write_blob(hash, content)?;
+        Ok(Entry::file(0o644, hash))
+    }
+
+    fn content(store: &Store, tree: &Tree, path: &str) -> Result<Vec<u8>> {
+        let entry = tree.get(Path::new(path)).expect("path missing from tree");
+        store.blob_store().read_blob(entry.blob_hash)
+    }
+
+    #[test]
+    fn test_merge_trees_clean() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let store = Store::init(temp_dir.path())?;
+
+        let mut base = Tree::new();
+        base.insert(Path::new("a.txt"), file(&store, b"one\ntwo\nthree\nfour\nfive\n")?);
+        base.insert(Path::new("gone.txt"), file(&store, b"bye\n")?);
+
+        let mut ours = base.clone();
+        ours.insert(Path::new("a.txt"), file(&store, b"ONE\ntwo\nthree\nfour\nfive\n")?);
+
+        let mut theirs = base.clone();
+        theirs.insert(Path::new("a.txt"), file(&store, b"one\ntwo\nthree\nfour\nFIVE\n")?);
+        theirs.insert(Path::new("new.txt"), file(&store, b"hello\n")?);
+        theirs.remove(Path::new("gone.txt"));
+
+        let result = merge_trees(&store, &base, &ours, &theirs, "ours", "theirs")?;
+
+        assert!(result.is_clean());
+        assert_eq!(content(&store, &result.tree, "a.txt")?, b"ONE\ntwo\nthree\nfour\nFIVE\n");
+        assert_eq!(content(&store, &result.tree, "new.txt")?, b"hello\n");
+        assert!(result.tree.get(Path::new("gone.txt")).is_none());
+        assert_eq!(result.changed_paths.len(), 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_trees_conflicts() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let store = Store::init(temp_dir.path())?;
+
+        let mut base = Tree::new();
+        base.insert(Path::new("a.txt"), file(&store, b"same\nline\n")?);
+        base.insert(Path::new("b.txt"), file(&store, b"keep me\n")?);
+
+        let mut ours = base.clone();
+        ours.insert(Path::new("a.txt"), file(&store, b"same\nours\n")?);
+        ours.remove(Path::new("b.txt"));
+
+        let mut theirs = base.clone();
+        theirs.insert(Path::new("a.txt"), file(&store, b"same\ntheirs\n")?);
+        theirs.insert(Path::new("b.txt"), file(&store, b"edited\n")?);
+
+        let result = merge_trees(&store, &base, &ours, &theirs, "ours", "theirs")?;
+
+        assert_eq!(result.conflicts.len(), 2);
+        assert_eq!(result.conflicts[0].kind, TreeConflictKind::Content { regions: 1 });
+        assert_eq!(result.conflicts[1].kind, TreeConflictKind::ModifyDelete);
+
+        let merged = String::from_utf8(content(&store, &result.tree, "a.txt")?)?;
+        assert!(merged.contains("<<<<<<< ours"));
+        assert!(merged.contains(">>>>>>> theirs"));
+        assert_eq!(content(&store, &result.tree, "b.txt")?, b"edited\n");
+
+        Ok(())
+    }
+}
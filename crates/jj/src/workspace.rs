@@ -8,13 +8,12 @@
 
 use anyhow::{anyhow, Context, Result};
 use jj_lib::object_id::ObjectId;
-use journal::{Checkpoint, CheckpointReason, Journal};
+use journal::{Checkpoint, CheckpointReason, Journal, PathMap};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
-use tl_core::{Entry, Store, Tree};
+use tl_core::{IgnoreConfig, IgnoreRules, SecretScanner, StatCache, Store, Tree};
 use ulid::Ulid;
-use walkdir::WalkDir;
 
 /// Workspace state stored in sled database
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -235,10 +234,15 @@ impl WorkspaceManager {
     ///
     /// This builds a full tree from the current working directory and creates a checkpoint.
     /// Uses file locking to prevent concurrent checkpoint conflicts.
+    ///
+    /// `previous_save` is the checkpoint this workspace was last saved at; when it
+    /// is not already the parent it is recorded as a merge parent, so the
+    /// workspace's own lineage stays visible in the checkpoint DAG.
     pub fn auto_checkpoint_current(
         &self,
         store: &Store,
         journal: &Journal,
+        previous_save: Option<Ulid>,
     ) -> Result<Ulid> {
         // Build tree from current working directory
         let tree = self.build_tree_from_workdir(store)?;
@@ -256,13 +260,19 @@ impl WorkspaceManager {
         let parent_id = journal.latest()?.map(|cp| cp.id);
 
         // Create checkpoint with WorkspaceSave reason
+        let merge_parents = match previous_save {
+            Some(prev) if Some(prev) != parent_id && journal.get(&prev)?.is_some() => vec![prev],
+            _ => vec![],
+        };
+
         let checkpoint = Checkpoint::new(
             parent_id,
             tree_hash,
             CheckpointReason::WorkspaceSave,
             vec![], // TODO: Track touched paths via daemon integration
             journal::CheckpointMeta::default(), // TODO: Calculate actual stats
-        );
+        )
+        .with_merge_parents(merge_parents);
 
         journal.append(&checkpoint)?;
 
@@ -271,80 +281,25 @@ impl WorkspaceManager {
 
     /// Build tree from current working directory
     ///
-    /// This performs a full filesystem scan using walkdir and stores all files as blobs.
+    /// This performs a full scan with the shared snapshot walker, honoring
+    /// .gitignore/.tlignore, and reuses hashes from the daemon's PathMap for
+    /// files whose stat data is unchanged.
     fn build_tree_from_workdir(&self, store: &Store) -> Result<Tree> {
-        let mut tree = Tree::new();
-
-        for entry in WalkDir::new(&self.repo_root)
-            .follow_links(false)
-            .into_iter()
-            .filter_entry(|e| {
-                // Skip .tl/, .git/, .jj/ directories
-                let path = e.path();
-                !path.starts_with(self.repo_root.join(".tl"))
-                    && !path.starts_with(self.repo_root.join(".git"))
-                    && !path.starts_with(self.repo_root.join(".jj"))
-            })
-        {
-            let entry = entry?;
-            let path = entry.path();
-
-            // Skip directories (only files and symlinks)
-            if entry.file_type().is_dir() {
-                continue;
-            }
-
-            let rel_path = path.strip_prefix(&self.repo_root)?;
-
-            // Handle symlinks
-            if entry.file_type().is_symlink() {
-                let target = fs::read_link(path)?;
-                let target_bytes = target.to_string_lossy();
-                let blob_hash = tl_core::hash::hash_bytes(target_bytes.as_bytes());
-
-                if !store.blob_store().has_blob(blob_hash) {
-                    store.blob_store().write_blob(blob_hash, target_bytes.as_bytes())?;
-                }
-
-                tree.insert(rel_path, Entry::symlink(blob_hash));
-                continue;
-            }
-
-            // Handle regular files
-            if entry.file_type().is_file() {
-                let metadata = entry.metadata()?;
-
-                // Extract Unix mode
-                #[cfg(unix)]
-                let mode = {
-                    use std::os::unix::fs::MetadataExt;
-                    metadata.mode()
-                };
-                #[cfg(not(unix))]
-                let mode = if metadata.permissions().readonly() {
-                    0o444
-                } else {
-                    0o644
-                };
-
-                // Hash file (use mmap for large files)
-                let blob_hash = if metadata.len() > 4 * 1024 * 1024 {
-                    tl_core::hash::hash_file_mmap(path)?
-                } else {
-                    tl_core::hash::hash_file(path)?
-                };
-
-                // Write blob if not exists
-                if !store.blob_store().has_blob(blob_hash) {
-                    let contents = fs::read(path)?;
-                    store.blob_store().write_blob(blob_hash, &contents)?;
-                }
-
-                tree.insert(rel_path, Entry::file(mode, blob_hash));
-            }
-        }
+        let rules = IgnoreRules::load(&self.repo_root, IgnoreConfig::default())
+            .context("Failed to load ignore rules")?;
+        let pathmap = PathMap::load(&self.repo_root.join(".tl/state/pathmap.bin")).ok();
+        let scanner = SecretScanner::load(&self.repo_root.join(".tl"))
+            .context("Failed to load [secrets] config")?;
+
+        let snapshot = tl_core::snapshot_worktree(
+            &self.repo_root,
+            store,
+            &rules,
+            pathmap.as_ref().map(|map| map as &(dyn StatCache + Sync)),
+            Some(&scanner),
+        )?;
 
-        Ok(tree)
+        Ok(snapshot.tree)
     }
 
     /// Restore checkpoint to a workspace directory
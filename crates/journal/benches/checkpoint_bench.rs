@@ -2,10 +2,10 @@
 //!
 //! Run with: cargo bench --package journal
 
-use criterion::{black_box, criterion_group, criterion_main, Criterion, BenchmarkId};
-use journal::{Checkpoint, CheckpointMeta, CheckpointReason, Journal, PathMap};
-use core::{Sha1Hash, Entry, Tree};
-use std::path::PathBuf;
+use criterion::{black_box, criterion_group, criterion_main, Criterion, BenchmarkId, Throughput};
+use journal::{incremental_update_with_workers, Checkpoint, CheckpointMeta, CheckpointReason, Journal, PathMap};
+use core::{Sha1Hash, Entry, Store, Tree};
+use std::path::{Path, PathBuf};
 use tempfile::TempDir;
 
 fn bench_checkpoint_serialization(c: &mut Criterion) {
@@ -168,12 +168,59 @@ fn bench_tree_operations(c: &mut Criterion) {
     group.finish();
 }
 
+/// Reconcile a large dirty batch (e.g. after `git checkout`) with 1..8 workers
+///
+/// Every iteration uses a fresh store so blobs are actually ingested.
+fn bench_incremental_reconcile(c: &mut Criterion) {
+    const FILES: usize = 2000;
+
+    let repo_dir = TempDir::new().unwrap();
+    let repo_root = repo_dir.path();
+
+    let mut paths = Vec::with_capacity(FILES);
+    for i in 0..FILES {
+        let path = PathBuf::from(format!("src/mod{}/file{}.rs", i % 50, i));
+        std::fs::create_dir_all(repo_root.join(path.parent().unwrap())).unwrap();
+        // ~16 KiB per file so hashing and compression dominate
+        let content = format!("// file {}\n{}", i, "fn f() {}\n".repeat(1600));
+        std::fs::write(repo_root.join(&path), content).unwrap();
+        paths.push(path);
+    }
+
+    let base_map = PathMap::new(Sha1Hash::from_bytes([5u8; 20]));
+    let mut group = c.benchmark_group("incremental_reconcile");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(FILES as u64));
+
+    for workers in [1, 2, 4, 8] {
+        group.bench_with_input(BenchmarkId::new("workers", workers), &workers, |b, &workers| {
+            b.iter_batched(
+                || {
+                    // Fresh store outside the repo so nothing is deduplicated
+                    let store_dir = TempDir::new().unwrap();
+                    let store = Store::init(store_dir.path()).unwrap();
+                    (store_dir, store)
+                },
+                |(_store_dir, store)| {
+                    let dirty: Vec<&Path> = paths.iter().map(|p| p.as_path()).collect();
+                    let result = incremental_update_with_workers(&base_map, dirty, repo_root, &store, workers).unwrap();
+                    black_box(result.2);
+                },
+                criterion::BatchSize::PerIteration,
+            );
+        });
+    }
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_checkpoint_serialization,
     bench_journal_append,
     bench_journal_queries,
     bench_pathmap_operations,
-    bench_tree_operations
+    bench_tree_operations,
+    bench_incremental_reconcile
 );
 criterion_main!(benches);
@@ -0,0 +1,226 @@
+//! Per-path change provenance for checkpoints
+//!
+//! The watcher reports which paths were touched; what actually happened to
+//! each of them is decided by comparing the pathmap before and after the
+//! checkpoint. A deleted file and a created file with the same content in
+//! one checkpoint are recorded as a rename. Metadata events (a touch, a
+//! chmod Git doesn't track) mark an otherwise unchanged path.
+
+use crate::checkpoint::{ChangeKind, PathChange};
+use crate::pathmap::PathMap;
+use core::{Entry, EntryKind};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// What the watcher saw for one touched path
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Observed {
+    /// Raw event count
+    pub events: u32,
+    /// The net event was a metadata change
+    pub metadata: bool,
+}
+
+/// Classify the changes to `touched` paths between two pathmaps
+///
+/// A touched path that is a directory (present in neither map) stands for
+/// every entry beneath it, so removing or moving a directory lists its
+/// files. `observed` supplies the watcher's summary for a path. Paths
+/// whose entry did not change at all are left out, unless the watcher saw
+/// a metadata event for them.
+pub fn path_changes(
+    old: &PathMap,
+    new: &PathMap,
+    touched: &[&Path],
+    observed: impl Fn(&Path) -> Observed,
+) -> Vec<PathChange> {
+    let mut changed: BTreeMap<PathBuf, ChangeKind> = BTreeMap::new();
+    let mut dirs: HashSet<&Path> = HashSet::new();
+
+    for &path in touched {
+        match classify(old.get(path), new.get(path)) {
+            Some(kind) => {
+                changed.insert(path.to_path_buf(), kind);
+            }
+            None if old.get(path).is_none() => {
+                dirs.insert(path);
+            }
+            None if observed(path).metadata => {
+                changed.insert(path.to_path_buf(), ChangeKind::MetadataOnly);
+            }
+            None => {}
+        }
+    }
+
+    // Entries that appeared or vanished under a touched directory
+    if !dirs.is_empty() {
+        let under_dir = |key: &[u8]| {
+            let path = Path::new(std::str::from_utf8(key).unwrap_or_default());
+            path.ancestors().skip(1).any(|dir| dirs.contains(dir))
+        };
+        for (key, _) in old.entries().filter(|(key, _)| under_dir(key)) {
+            let path = PathBuf::from(String::from_utf8_lossy(&key).into_owned());
+            if new.get(&path).is_none() {
+                changed.insert(path, ChangeKind::Deleted);
+            }
+        }
+        for (key, _) in new.entries().filter(|(key, _)| under_dir(key)) {
+            let path = PathBuf::from(String::from_utf8_lossy(&key).into_owned());
+            if old.get(&path).is_none() {
+                changed.insert(path, ChangeKind::Created);
+            }
+        }
+    }
+
+    // Pair deletions with creations of the same content
+    let empty_blob = core::hash::git::hash_blob(b"");
+    let mut deleted: HashMap<(u32, core::Sha1Hash), Vec<PathBuf>> = HashMap::new();
+    for (path, kind) in &changed {
+        if *kind == ChangeKind::Deleted {
+            if let Some(entry) = old.get(path).filter(|e| renamable(e) && e.blob_hash != empty_blob) {
+                deleted.entry((entry.mode, entry.blob_hash)).or_default().push(path.clone());
+            }
+        }
+    }
+    let mut renamed_from: HashSet<PathBuf> = HashSet::new();
+    for (path, kind) in changed.iter_mut() {
+        if *kind != ChangeKind::Created {
+            continue;
+        }
+        let Some(entry) = new.get(path) else { continue };
+        if let Some(from) = deleted.get_mut(&(entry.mode, entry.blob_hash)).and_then(|paths| paths.pop()) {
+            renamed_from.insert(from.clone());
+            *kind = ChangeKind::Renamed { from };
+        }
+    }
+
+    changed
+        .into_iter()
+        .filter(|(path, _)| !renamed_from.contains(path))
+        .map(|(path, kind)| {
+            let source_events = match &kind {
+                ChangeKind::Renamed { from } => observed(from).events,
+                _ => 0,
+            };
+            PathChange {
+                events: observed(&path).events.saturating_add(source_events),
+                path,
+                kind,
+            }
+        })
+        .collect()
+}
+
+fn classify(old: Option<&Entry>, new: Option<&Entry>) -> Option<ChangeKind> {
+    match (old, new) {
+        (None, None) => None,
+        (None, Some(_)) => Some(ChangeKind::Created),
+        (Some(_), None) => Some(ChangeKind::Deleted),
+        (Some(old), Some(new)) if old.blob_hash != new.blob_hash => Some(ChangeKind::Modified),
+        (Some(old), Some(new)) if old.kind != new.kind || old.mode != new.mode => Some(ChangeKind::MetadataOnly),
+        (Some(_), Some(_)) => None,
+    }
+}
+
+fn renamable(entry: &Entry) -> bool {
+    matches!(entry.kind, EntryKind::File | EntryKind::ExecutableFile | EntryKind::Symlink)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::Sha1Hash;
+
+    fn file(content: &[u8]) -> Entry {
+        Entry::file(0o644, core::hash::git::hash_blob(content))
+    }
+
+    fn map(entries: &[(&str, Entry)]) -> PathMap {
+        let mut map = PathMap::new(Sha1Hash::from_bytes([0u8; 20]));
+        for (path, entry) in entries {
+            map.update(Path::new(path), Some(entry.clone()));
+        }
+        map
+    }
+
+    fn change(path: &str, kind: ChangeKind, events: u32) -> PathChange {
+        PathChange { path: PathBuf::from(path), kind, events }
+    }
+
+    fn events(events: u32) -> Observed {
+        Observed { events, metadata: false }
+    }
+
+    #[test]
+    fn test_classifies_touched_paths() {
+        let old = map(&[
+            ("a.rs", file(b"fn a() {}")),
+            ("edit.rs", file(b"old")),
+            ("gone.txt", file(b"bye")),
+            ("run.sh", file(b"#!/bin/sh")),
+            ("same.txt", file(b"same")),
+        ]);
+        let new = map(&[
+            ("b.rs", file(b"fn a() {}")),
+            ("edit.rs", file(b"new")),
+            ("fresh.txt", file(b"hi")),
+            ("run.sh", Entry::file(0o755, core::hash::git::hash_blob(b"#!/bin/sh"))),
+            ("same.txt", file(b"same")),
+        ]);
+        let touched: Vec<&Path> = ["a.rs", "b.rs", "edit.rs", "fresh.txt", "gone.txt", "run.sh", "same.txt"]
+            .iter()
+            .map(Path::new)
+            .collect();
+
+        let changes = path_changes(&old, &new, &touched, |path| events(if path == Path::new("edit.rs") { 4 } else { 1 }));
+        assert_eq!(
+            changes,
+            vec![
+                change("b.rs", ChangeKind::Renamed { from: PathBuf::from("a.rs") }, 2),
+                change("edit.rs", ChangeKind::Modified, 4),
+                change("fresh.txt", ChangeKind::Created, 1),
+                change("gone.txt", ChangeKind::Deleted, 1),
+                change("run.sh", ChangeKind::MetadataOnly, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_directory_events_cover_their_entries() {
+        let old = map(&[("src/old/a.rs", file(b"a")), ("src/old/b.rs", file(b"b")), ("empty.txt", file(b""))]);
+        let new = map(&[("src/new/a.rs", file(b"a")), ("src/new/b.rs", file(b"b")), ("empty2.txt", file(b""))]);
+        let touched: Vec<&Path> = ["src/old", "src/new", "empty.txt", "empty2.txt"].iter().map(Path::new).collect();
+
+        let changes = path_changes(&old, &new, &touched, |_| Observed::default());
+        assert_eq!(
+            changes,
+            vec![
+                // Empty files all look alike: not treated as a rename
+                change("empty.txt", ChangeKind::Deleted, 0),
+                change("empty2.txt", ChangeKind::Created, 0),
+                change("src/new/a.rs", ChangeKind::Renamed { from: PathBuf::from("src/old/a.rs") }, 0),
+                change("src/new/b.rs", ChangeKind::Renamed { from: PathBuf::from("src/old/b.rs") }, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_metadata_events_on_unchanged_paths() {
+        let old = map(&[("touched.txt", file(b"same")), ("quiet.txt", file(b"same")), ("edit.rs", file(b"old"))]);
+        let new = map(&[("touched.txt", file(b"same")), ("quiet.txt", file(b"same")), ("edit.rs", file(b"new"))]);
+        let touched: Vec<&Path> = ["touched.txt", "quiet.txt", "edit.rs"].iter().map(Path::new).collect();
+
+        // A touch leaves the entry alone; only the watcher knows it happened
+        let changes = path_changes(&old, &new, &touched, |path| Observed {
+            events: 1,
+            metadata: path != Path::new("quiet.txt"),
+        });
+        assert_eq!(
+            changes,
+            vec![
+                change("edit.rs", ChangeKind::Modified, 1),
+                change("touched.txt", ChangeKind::MetadataOnly, 1),
+            ]
+        );
+    }
+}
@@ -2,6 +2,7 @@
 
 use core::Sha1Hash;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use ulid::Ulid;
 
 /// A checkpoint represents a snapshot of the repository at a point in time
@@ -9,7 +10,7 @@ use ulid::Ulid;
 pub struct Checkpoint {
     /// Unique ID (ULID for timestamp + uniqueness)
     pub id: Ulid,
-    /// Parent checkpoint ID
+    /// Parent checkpoint ID (first parent; `HEAD~N` walks follow this)
     pub parent: Option<Ulid>,
     /// Root tree hash for this checkpoint
     pub root_tree: Sha1Hash,
@@ -21,6 +22,100 @@ pub struct Checkpoint {
     pub touched_paths: Vec<std::path::PathBuf>,
     /// Checkpoint metadata
     pub meta: CheckpointMeta,
+    /// Additional parents beyond `parent` (e.g. the merged-in side of a merge)
+    ///
+    /// Appended last so the on-disk layout of older fields is unchanged;
+    /// journals written before this field existed decode with it empty.
+    pub merge_parents: Vec<Ulid>,
+    /// Per-path change kind and event counts (daemon checkpoints only)
+    ///
+    /// Empty for checkpoints that weren't built from watcher events, and for
+    /// those written before provenance was recorded.
+    pub changes: Vec<PathChange>,
+}
+
+/// What happened to one path in a checkpoint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathChange {
+    /// Path after the change
+    pub path: PathBuf,
+    /// Kind of change, as seen by comparing with the parent
+    pub kind: ChangeKind,
+    /// Raw watcher events coalesced into this change (0 if unknown, e.g.
+    /// after overflow recovery)
+    pub events: u32,
+}
+
+/// Kind of change to a path
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChangeKind {
+    /// Path did not exist in the parent
+    Created,
+    /// Content changed
+    Modified,
+    /// Content unchanged; mode or other metadata touched
+    MetadataOnly,
+    /// Path no longer exists
+    Deleted,
+    /// Moved from another path with the same content
+    Renamed { from: PathBuf },
+}
+
+/// On-disk layout of checkpoints written before `changes` existed
+#[derive(Deserialize)]
+struct LegacyMergeCheckpoint {
+    id: Ulid,
+    parent: Option<Ulid>,
+    root_tree: Sha1Hash,
+    ts_unix_ms: u64,
+    reason: CheckpointReason,
+    touched_paths: Vec<std::path::PathBuf>,
+    meta: CheckpointMeta,
+    merge_parents: Vec<Ulid>,
+}
+
+impl From<LegacyMergeCheckpoint> for Checkpoint {
+    fn from(legacy: LegacyMergeCheckpoint) -> Self {
+        Self {
+            id: legacy.id,
+            parent: legacy.parent,
+            root_tree: legacy.root_tree,
+            ts_unix_ms: legacy.ts_unix_ms,
+            reason: legacy.reason,
+            touched_paths: legacy.touched_paths,
+            meta: legacy.meta,
+            merge_parents: legacy.merge_parents,
+            changes: Vec::new(),
+        }
+    }
+}
+
+/// On-disk layout of checkpoints written before `merge_parents` existed
+#[derive(Deserialize)]
+struct LegacyCheckpoint {
+    id: Ulid,
+    parent: Option<Ulid>,
+    root_tree: Sha1Hash,
+    ts_unix_ms: u64,
+    reason: CheckpointReason,
+    touched_paths: Vec<std::path::PathBuf>,
+    meta: CheckpointMeta,
+}
+
+impl From<LegacyCheckpoint> for Checkpoint {
+    fn from(legacy: LegacyCheckpoint) -> Self {
+        Self {
+            id: legacy.id,
+            parent: legacy.parent,
+            root_tree: legacy.root_tree,
+            ts_unix_ms: legacy.ts_unix_ms,
+            reason: legacy.reason,
+            touched_paths: legacy.touched_paths,
+            meta: legacy.meta,
+            merge_parents: Vec::new(),
+            changes: Vec::new(),
+        }
+    }
 }
 
 /// Checkpoint metadata
@@ -59,6 +154,12 @@ pub enum CheckpointReason {
     GcCompact,
     /// Workspace save (auto-checkpoint on workspace switch)
     WorkspaceSave,
+    /// Three-way merge of two checkpoints
+    Merge,
+    /// Change from another checkpoint applied onto HEAD
+    CherryPick,
+    /// Change from an earlier checkpoint undone on HEAD
+    Revert,
 }
 
 impl Checkpoint {
@@ -78,20 +179,64 @@ impl Checkpoint {
             reason,
             touched_paths,
             meta,
+            merge_parents: Vec::new(),
+            changes: Vec::new(),
         }
     }
 
+    /// Record additional parents (for merge checkpoints)
+    pub fn with_merge_parents(mut self, merge_parents: Vec<Ulid>) -> Self {
+        self.merge_parents = merge_parents;
+        self
+    }
+
+    /// Record per-path change provenance
+    pub fn with_changes(mut self, changes: Vec<PathChange>) -> Self {
+        self.changes = changes;
+        self
+    }
+
+    /// All parents: the first parent followed by any merge parents
+    pub fn parents(&self) -> Vec<Ulid> {
+        self.parent.iter().chain(self.merge_parents.iter()).copied().collect()
+    }
+
+    /// True if this checkpoint has more than one parent
+    pub fn is_merge(&self) -> bool {
+        !self.merge_parents.is_empty()
+    }
+
     /// Serialize checkpoint to bytes
     pub fn serialize(&self) -> anyhow::Result<Vec<u8>> {
         Ok(bincode::serialize(self)?)
     }
 
     /// Deserialize checkpoint from bytes
+    ///
+    /// Falls back to the legacy layouts (no `changes`, no `merge_parents`)
+    /// for journals written by older versions. Each layout must consume the
+    /// record exactly, so a damaged current record is reported as an error
+    /// instead of decoding as a legacy one with its newer fields dropped.
     pub fn deserialize(bytes: &[u8]) -> anyhow::Result<Self> {
-        Ok(bincode::deserialize(bytes)?)
+        if let Ok(checkpoint) = decode_exact::<Self>(bytes) {
+            return Ok(checkpoint);
+        }
+        match decode_exact::<LegacyMergeCheckpoint>(bytes) {
+            Ok(legacy) => Ok(legacy.into()),
+            Err(_) => Ok(decode_exact::<LegacyCheckpoint>(bytes)?.into()),
+        }
     }
 }
 
+/// Decode with `bincode::deserialize`'s encoding, but reject trailing bytes
+fn decode_exact<'a, T: Deserialize<'a>>(bytes: &'a [u8]) -> bincode::Result<T> {
+    use bincode::Options;
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .reject_trailing_bytes()
+        .deserialize(bytes)
+}
+
 fn current_timestamp_ms() -> u64 {
     use std::time::{SystemTime, UNIX_EPOCH};
     SystemTime::now()
@@ -193,6 +338,9 @@ mod tests {
             CheckpointReason::Restore,
             CheckpointReason::Publish,
             CheckpointReason::GcCompact,
+            CheckpointReason::Merge,
+            CheckpointReason::CherryPick,
+            CheckpointReason::Revert,
         ];
 
         for reason in reasons {
@@ -241,4 +389,128 @@ mod tests {
         assert_eq!(checkpoint.touched_paths, deserialized.touched_paths);
         assert_eq!(checkpoint.touched_paths.len(), 3);
     }
+
+    #[test]
+    fn test_checkpoint_merge_parents_roundtrip() {
+        let ours = Ulid::new();
+        let theirs = Ulid::new();
+
+        let checkpoint = Checkpoint::new(
+            Some(ours),
+            Sha1Hash::from_bytes([5u8; 20]),
+            CheckpointReason::Merge,
+            vec![],
+            CheckpointMeta::default(),
+        )
+        .with_merge_parents(vec![theirs]);
+
+        let bytes = checkpoint.serialize().unwrap();
+        let deserialized = Checkpoint::deserialize(&bytes).unwrap();
+
+        assert_eq!(deserialized.parent, Some(ours));
+        assert_eq!(deserialized.merge_parents, vec![theirs]);
+        assert_eq!(deserialized.parents(), vec![ours, theirs]);
+        assert!(deserialized.is_merge());
+    }
+
+    #[test]
+    fn test_checkpoint_legacy_deserialization() {
+        #[derive(Serialize)]
+        struct OldCheckpoint {
+            id: Ulid,
+            parent: Option<Ulid>,
+            root_tree: Sha1Hash,
+            ts_unix_ms: u64,
+            reason: CheckpointReason,
+            touched_paths: Vec<PathBuf>,
+            meta: CheckpointMeta,
+        }
+
+        let old = OldCheckpoint {
+            id: Ulid::new(),
+            parent: Some(Ulid::new()),
+            root_tree: Sha1Hash::from_bytes([6u8; 20]),
+            ts_unix_ms: 1704067200000,
+            reason: CheckpointReason::FsBatch,
+            touched_paths: vec![PathBuf::from("src/lib.rs")],
+            meta: CheckpointMeta::default(),
+        };
+
+        let bytes = bincode::serialize(&old).unwrap();
+        let checkpoint = Checkpoint::deserialize(&bytes).unwrap();
+
+        assert_eq!(checkpoint.id, old.id);
+        assert_eq!(checkpoint.parent, old.parent);
+        assert_eq!(checkpoint.touched_paths, old.touched_paths);
+        assert!(checkpoint.merge_parents.is_empty());
+    }
+
+    #[test]
+    fn test_checkpoint_changes_roundtrip() {
+        let changes = vec![
+            PathChange { path: PathBuf::from("src/new.rs"), kind: ChangeKind::Created, events: 3 },
+            PathChange { path: PathBuf::from("run.sh"), kind: ChangeKind::MetadataOnly, events: 1 },
+            PathChange {
+                path: PathBuf::from("b.rs"),
+                kind: ChangeKind::Renamed { from: PathBuf::from("a.rs") },
+                events: 2,
+            },
+        ];
+        let checkpoint = create_test_checkpoint().with_changes(changes.clone());
+
+        let bytes = checkpoint.serialize().unwrap();
+        let deserialized = Checkpoint::deserialize(&bytes).unwrap();
+        assert_eq!(deserialized.changes, changes);
+    }
+
+    #[test]
+    fn test_checkpoint_damaged_record_is_not_legacy() {
+        let changes = vec![PathChange { path: PathBuf::from("src/new.rs"), kind: ChangeKind::Created, events: 3 }];
+        let checkpoint = create_test_checkpoint()
+            .with_merge_parents(vec![Ulid::new()])
+            .with_changes(changes);
+        let bytes = checkpoint.serialize().unwrap();
+
+        // Cut short inside `changes`: the older layouts would match a prefix
+        assert!(Checkpoint::deserialize(&bytes[..bytes.len() - 1]).is_err());
+
+        // Extra bytes after a complete record are damage too
+        let mut padded = bytes.clone();
+        padded.push(0);
+        assert!(Checkpoint::deserialize(&padded).is_err());
+    }
+
+    #[test]
+    fn test_checkpoint_pre_provenance_deserialization() {
+        #[derive(Serialize)]
+        struct MergeCheckpoint {
+            id: Ulid,
+            parent: Option<Ulid>,
+            root_tree: Sha1Hash,
+            ts_unix_ms: u64,
+            reason: CheckpointReason,
+            touched_paths: Vec<PathBuf>,
+            meta: CheckpointMeta,
+            merge_parents: Vec<Ulid>,
+        }
+
+        let old = MergeCheckpoint {
+            id: Ulid::new(),
+            parent: Some(Ulid::new()),
+            root_tree: Sha1Hash::from_bytes([7u8; 20]),
+            ts_unix_ms: 1704067200000,
+            reason: CheckpointReason::Merge,
+            touched_paths: vec![PathBuf::from("src/lib.rs")],
+            meta: CheckpointMeta::default(),
+            merge_parents: vec![Ulid::new()],
+        };
+
+        let bytes = bincode::serialize(&old).unwrap();
+        let checkpoint = Checkpoint::deserialize(&bytes).unwrap();
+
+        assert_eq!(checkpoint.id, old.id);
+        assert_eq!(checkpoint.merge_parents, old.merge_parents);
+        assert_eq!(checkpoint.touched_paths, old.touched_paths);
+        assert!(checkpoint.changes.is_empty());
+    }
 }
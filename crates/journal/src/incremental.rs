@@ -1,12 +1,70 @@
 //! Incremental tree update algorithm
 //!
 //! The performance linchpin: update tree from dirty paths without full rescan
+//!
+//! Reconciliation runs in two phases: dirty paths are stat'ed, hashed and
+//! ingested in parallel against the unchanged base map, then the resulting
+//! updates are applied to the new map in sorted path order so the outcome
+//! does not depend on worker scheduling.
+//!
+//! Per-path policies (`.tlpolicy`) are applied while reconciling: hash-only
+//! content is recorded without being stored, and manual-only paths are left
+//! untouched except on an explicit flush.
+//!
+//! New content is screened by the secret scanner (when one is configured)
+//! before it is written to the object store; see `core::secrets`.
 
 use anyhow::Result;
-use core::{hash, Sha1Hash, Entry, Store, Tree};
+use core::{hash, stat, FileStat, PathPolicies, PathPolicy, Screened, SecretScanner, Sha1Hash, Entry, Store, Tree};
 use crate::PathMap;
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Batches smaller than this are reconciled on the calling thread
+/// (spinning up workers costs more than it saves)
+const PARALLEL_THRESHOLD: usize = 64;
+
+/// Upper bound on hashing workers (ingestion is largely I/O bound)
+const MAX_WORKERS: usize = 8;
+
+/// Outcome of reconciling one dirty path against the base map
+enum PathUpdate {
+    /// Nothing to change
+    Unchanged,
+    /// Content unchanged, but refresh the recorded stat
+    RecordStat(FileStat),
+    /// Path deleted
+    Remove,
+    /// New or changed entry (with the stat it was hashed from, for files)
+    Set(Entry, Option<FileStat>),
+}
+
+/// Options for `incremental_update_with_options`
+#[derive(Debug, Clone, Copy)]
+pub struct UpdateOptions<'a> {
+    /// Hashing workers (`<= 1` reconciles sequentially, capped at `MAX_WORKERS`)
+    pub workers: usize,
+    /// Per-path checkpoint policies (None: every path is stored in full)
+    pub policies: Option<&'a PathPolicies>,
+    /// Explicit flush: also reconcile paths with the `manual` policy
+    pub manual: bool,
+    /// Secret scanner run on content before it is stored (None: no scanning)
+    pub scanner: Option<&'a SecretScanner>,
+}
+
+impl Default for UpdateOptions<'_> {
+    fn default() -> Self {
+        Self {
+            workers: default_workers(),
+            policies: None,
+            manual: false,
+            scanner: None,
+        }
+    }
+}
 
 /// Update a tree incrementally from a set of dirty paths
 ///
@@ -17,15 +75,64 @@ pub fn incremental_update(
     repo_root: &Path,
     store: &Store,
 ) -> Result<(PathMap, Tree, Sha1Hash)> {
-    // Step 1: Normalize and deduplicate dirty paths
-    let normalized = normalize_dirty_paths(dirty_paths, repo_root)?;
+    incremental_update_with_options(base_map, dirty_paths, repo_root, store, UpdateOptions::default())
+}
 
-    // Step 2: Clone base map for modifications
-    let mut new_map = base_map.clone();
+/// `incremental_update` with an explicit number of hashing workers
+///
+/// `workers <= 1` reconciles sequentially. Results are identical for any
+/// worker count.
+pub fn incremental_update_with_workers(
+    base_map: &PathMap,
+    dirty_paths: Vec<&Path>,
+    repo_root: &Path,
+    store: &Store,
+    workers: usize,
+) -> Result<(PathMap, Tree, Sha1Hash)> {
+    let options = UpdateOptions { workers, ..UpdateOptions::default() };
+    incremental_update_with_options(base_map, dirty_paths, repo_root, store, options)
+}
+
+/// `incremental_update` with explicit workers and per-path policies
+pub fn incremental_update_with_options(
+    base_map: &PathMap,
+    dirty_paths: Vec<&Path>,
+    repo_root: &Path,
+    store: &Store,
+    options: UpdateOptions<'_>,
+) -> Result<(PathMap, Tree, Sha1Hash)> {
+    // Step 1: Normalize and deduplicate dirty paths (sorted for deterministic apply order)
+    let mut normalized = normalize_dirty_paths(dirty_paths, repo_root)?;
+    normalized.sort();
+
+    // Step 2: Hash and ingest dirty paths (read-only against the base map)
+    let updates: Vec<PathUpdate> = if options.workers <= 1 || normalized.len() < PARALLEL_THRESHOLD {
+        normalized.iter()
+            .map(|path| reconcile_path(base_map, path, repo_root, store, &options))
+            .collect::<Result<_>>()?
+    } else {
+        hashing_pool(options.workers)?.install(|| {
+            normalized.par_iter()
+                .map(|path| reconcile_path(base_map, path, repo_root, store, &options))
+                .collect::<Result<_>>()
+        })?
+    };
 
-    // Step 3: Reconcile each dirty path
-    for path in normalized {
-        reconcile_path(&mut new_map, &path, repo_root, store)?;
+    // Step 3: Clone base map and apply updates in path order
+    let mut new_map = base_map.clone();
+    let recorded_at = stat::now_ns();
+    for (path, update) in normalized.iter().zip(updates) {
+        match update {
+            PathUpdate::Unchanged => {}
+            PathUpdate::RecordStat(stat) => new_map.record_stat(path, stat, recorded_at),
+            PathUpdate::Remove => new_map.update(path, None),
+            PathUpdate::Set(entry, stat) => {
+                new_map.update(path, Some(entry));
+                if let Some(stat) = stat {
+                    new_map.record_stat(path, stat, recorded_at);
+                }
+            }
+        }
     }
 
     // Step 4: Build tree from updated map
@@ -33,6 +140,7 @@ pub fn incremental_update(
 
     // Step 5: Compute tree hash
     let tree_hash = tree.hash();
+    new_map.root_tree = tree_hash;
 
     // Step 6: Store tree in content-addressed storage
     store.write_tree(&tree)?;
@@ -40,6 +148,30 @@ pub fn incremental_update(
     Ok((new_map, tree, tree_hash))
 }
 
+/// Default worker count: available cores, capped at `MAX_WORKERS`
+fn default_workers() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(MAX_WORKERS)
+}
+
+/// Hashing pools, one per worker count, built on first use and kept for the process
+static HASHING_POOLS: [OnceLock<ThreadPool>; MAX_WORKERS] = [const { OnceLock::new() }; MAX_WORKERS];
+
+/// Shared hashing pool for `workers` threads (capped at `MAX_WORKERS`)
+fn hashing_pool(workers: usize) -> Result<&'static ThreadPool> {
+    let slot = &HASHING_POOLS[workers.clamp(1, MAX_WORKERS) - 1];
+    if let Some(pool) = slot.get() {
+        return Ok(pool);
+    }
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(workers.clamp(1, MAX_WORKERS))
+        .thread_name(|i| format!("tl-hash-{}", i))
+        .build()?;
+    Ok(slot.get_or_init(|| pool))
+}
+
 /// Normalize and deduplicate dirty paths
 fn normalize_dirty_paths(paths: Vec<&Path>, _repo_root: &Path) -> Result<Vec<PathBuf>> {
     let mut normalized = HashSet::new();
@@ -64,11 +196,21 @@ fn normalize_dirty_paths(paths: Vec<&Path>, _repo_root: &Path) -> Result<Vec<Pat
 
 /// Reconcile a single path (update or remove from map)
 fn reconcile_path(
-    map: &mut PathMap,
+    map: &PathMap,
     path: &Path,
     repo_root: &Path,
     store: &Store,
-) -> Result<()> {
+    options: &UpdateOptions<'_>,
+) -> Result<PathUpdate> {
+    let policy = options.policies
+        .map(|policies| policies.policy_for(path))
+        .unwrap_or(PathPolicy::Full);
+
+    // Manual-only paths keep their last checkpointed state until a flush
+    if policy == PathPolicy::ManualOnly && !options.manual {
+        return Ok(PathUpdate::Unchanged);
+    }
+
     let abs_path = repo_root.join(path);
 
     // Attempt to get file metadata
@@ -76,13 +218,12 @@ fn reconcile_path(
         Ok(m) => m,
         Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
             // Case 2: File deleted - remove from map
-            map.update(path, None);
-            return Ok(());
+            return Ok(PathUpdate::Remove);
         }
         Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
             // Permission issue - skip this file with warning
             eprintln!("Warning: Permission denied for {}: {}", path.display(), e);
-            return Ok(());
+            return Ok(PathUpdate::Unchanged);
         }
         Err(e) => {
             return Err(anyhow::anyhow!("Failed to stat {}: {}", path.display(), e));
@@ -96,11 +237,11 @@ fn reconcile_path(
 
     // Case 1: Regular file
     if metadata.is_file() {
-        return reconcile_file(map, path, &abs_path, metadata, store);
+        return reconcile_file(map, path, &abs_path, metadata, store, policy, options.scanner);
     }
 
     // Ignore directories (implicit in flat tree structure)
-    Ok(())
+    Ok(PathUpdate::Unchanged)
 }
 
 /// Verify that a file is stable during read (double-stat verification)
@@ -153,12 +294,14 @@ fn verify_stable_read(path: &Path) -> Result<Vec<u8>> {
 
 /// Reconcile a regular file
 fn reconcile_file(
-    map: &mut PathMap,
+    map: &PathMap,
     path: &Path,
     abs_path: &Path,
     metadata: std::fs::Metadata,
     store: &Store,
-) -> Result<()> {
+    policy: PathPolicy,
+    scanner: Option<&SecretScanner>,
+) -> Result<PathUpdate> {
     // Extract Unix mode bits
     #[cfg(unix)]
     let mode = {
@@ -172,40 +315,74 @@ fn reconcile_file(
         0o644
     };
 
-    // Hash the file with stability verification (double-stat pattern)
-    let blob_hash = hash::hash_file_stable(abs_path, 3)?;
-
-    // Check if we already have this blob
-    if !store.blob_store().has_blob(blob_hash) {
-        // Read file with double-stat verification to ensure stable read
-        let contents = verify_stable_read(abs_path)?;
-        store.blob_store().write_blob(blob_hash, &contents)?;
+    // Stat cache hit: size/mtime/ctime/inode unchanged since last hash
+    let stat = FileStat::from_metadata(&metadata);
+    if map.is_stat_clean(path, &stat)
+        && map.get(path).is_some_and(|e| *e == Entry::file(mode, e.blob_hash))
+    {
+        return Ok(PathUpdate::Unchanged);
     }
 
-    // Check if entry exists and mode has changed (permission-only change detection)
-    if let Some(existing_entry) = map.get(path) {
-        // Only update if hash OR mode changed
-        if existing_entry.blob_hash == blob_hash && existing_entry.mode == mode {
-            // No change - skip update
-            return Ok(());
+    let blob_hash = if !policy.stores_content(stat.size) {
+        // Hash-only policy: record the hash, keep the bytes out of the store
+        let blob_hash = hash::hash_file_stable(abs_path, 3)?;
+        if !store.blob_store().has_blob(blob_hash) {
+            store.blob_store().mark_omitted(blob_hash, stat.size)?;
         }
-        // Otherwise, update with new entry (hash or mode changed)
-    }
+        blob_hash
+    } else if stat.size >= store.blob_store().large_file_threshold() {
+        // Large file: scan it, then stream it through the chunker (hash + store)
+        match store.blob_store().write_file_screened(abs_path, path, scanner)? {
+            Screened::Stored(blob_hash) => blob_hash,
+            Screened::Skipped => return Ok(PathUpdate::Unchanged),
+            Screened::Redacted(redacted_hash) => return Ok(redacted_update(map, path, mode, redacted_hash)),
+        }
+    } else {
+        // Hash the file with stability verification (double-stat pattern)
+        let blob_hash = hash::hash_file_stable(abs_path, 3)?;
+
+        // Check if we already have this blob
+        if !store.blob_store().has_blob(blob_hash) {
+            // Read file with double-stat verification to ensure stable read
+            let contents = verify_stable_read(abs_path)?;
+            match store.blob_store().write_blob_screened(blob_hash, &contents, path, scanner)? {
+                Screened::Stored(_) => {}
+                Screened::Skipped => return Ok(PathUpdate::Unchanged),
+                Screened::Redacted(redacted_hash) => return Ok(redacted_update(map, path, mode, redacted_hash)),
+            }
+        }
+        blob_hash
+    };
 
-    // Update map with new entry
+    // Check if entry exists and mode has changed (permission-only change detection)
     let entry = Entry::file(mode, blob_hash);
-    map.update(path, Some(entry));
+    if map.get(path) == Some(&entry) {
+        // No change - skip update, but remember the stat for next time
+        return Ok(PathUpdate::RecordStat(stat));
+    }
+
+    // Update map with new entry (hash or mode changed)
+    Ok(PathUpdate::Set(entry, Some(stat)))
+}
 
-    Ok(())
+/// Update for a file whose stored copy was redacted
+///
+/// The stored copy differs from the file, so no stat is recorded.
+fn redacted_update(map: &PathMap, path: &Path, mode: u32, redacted_hash: Sha1Hash) -> PathUpdate {
+    let entry = Entry::file(mode, redacted_hash);
+    if map.get(path) == Some(&entry) {
+        return PathUpdate::Unchanged;
+    }
+    PathUpdate::Set(entry, None)
 }
 
 /// Reconcile a symlink
 fn reconcile_symlink(
-    map: &mut PathMap,
+    map: &PathMap,
     path: &Path,
     abs_path: &Path,
     store: &Store,
-) -> Result<()> {
+) -> Result<PathUpdate> {
     // Read symlink target
     let target = std::fs::read_link(abs_path)?;
     let target_bytes = target.to_string_lossy();
@@ -217,7 +394,7 @@ fn reconcile_symlink(
     if let Some(existing_entry) = map.get(path) {
         if existing_entry.blob_hash == blob_hash {
             // Symlink target unchanged - skip update
-            return Ok(());
+            return Ok(PathUpdate::Unchanged);
         }
         // Otherwise, target changed - update entry
     }
@@ -230,10 +407,7 @@ fn reconcile_symlink(
     }
 
     // Create symlink entry
-    let entry = Entry::symlink(blob_hash);
-    map.update(path, Some(entry));
-
-    Ok(())
+    Ok(PathUpdate::Set(Entry::symlink(blob_hash), None))
 }
 
 /// Build a tree from PathMap
@@ -1,12 +1,18 @@
 //! Append-only checkpoint journal using sled
+//!
+//! Values are sealed with the repo cipher when one is passed to
+//! `Journal::open_with_cipher` (keys are plain sequence numbers).
 
 use crate::Checkpoint;
 use anyhow::Result;
+use core::encryption::{seal, unseal};
+use core::RepoCipher;
 use parking_lot::RwLock;
 use sled::Db;
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet};
 use std::path::Path;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use ulid::Ulid;
 
 /// Append-only journal for checkpoints
@@ -15,23 +21,39 @@ pub struct Journal {
     db: Db,
     /// In-memory index: checkpoint_id -> sequence_number
     index: RwLock<BTreeMap<Ulid, u64>>,
+    /// In-memory reverse DAG: parent_id -> child checkpoint IDs
+    children: RwLock<HashMap<Ulid, Vec<Ulid>>>,
     /// Monotonic sequence counter
     seq_counter: AtomicU64,
+    /// Seals values at rest (None: plain bincode)
+    cipher: Option<Arc<RepoCipher>>,
 }
 
 impl Journal {
-    /// Open or create a journal at the given path
+    /// Open or create an unencrypted journal at the given path
+    ///
+    /// Repositories that may have encryption at rest enabled must use
+    /// `open_with_cipher` with the cipher from `RepoCipher::load`.
     pub fn open(path: &Path) -> Result<Self> {
+        Self::open_with_cipher(path, None)
+    }
+
+    /// Open or create a journal, sealing values with `cipher` if given
+    pub fn open_with_cipher(path: &Path, cipher: Option<Arc<RepoCipher>>) -> Result<Self> {
         let db = sled::open(path.join("checkpoints.db"))?;
 
         // Build in-memory index on startup
         let mut index = BTreeMap::new();
+        let mut children: HashMap<Ulid, Vec<Ulid>> = HashMap::new();
         let mut max_seq = 0u64;
 
         for item in db.iter() {
             let (key, value) = item?;
             let seq = u64::from_le_bytes(key.as_ref().try_into()?);
-            let checkpoint = Checkpoint::deserialize(&value)?;
+            let checkpoint = decode(cipher.as_deref(), seq, &value)?;
+            for parent in checkpoint.parents() {
+                children.entry(parent).or_default().push(checkpoint.id);
+            }
             index.insert(checkpoint.id, seq);
             max_seq = max_seq.max(seq);
         }
@@ -39,7 +61,9 @@ impl Journal {
         Ok(Self {
             db,
             index: RwLock::new(index),
+            children: RwLock::new(children),
             seq_counter: AtomicU64::new(max_seq + 1),
+            cipher,
         })
     }
 
@@ -47,12 +71,17 @@ impl Journal {
     pub fn append(&self, checkpoint: &Checkpoint) -> Result<u64> {
         let seq = self.seq_counter.fetch_add(1, Ordering::SeqCst);
         let key = seq.to_le_bytes();
-        let value = checkpoint.serialize()?;
+        let value = self.encode(seq, checkpoint)?;
 
         self.db.insert(&key, value)?;
 
         // Update index
         self.index.write().insert(checkpoint.id, seq);
+        let mut children = self.children.write();
+        for parent in checkpoint.parents() {
+            children.entry(parent).or_default().push(checkpoint.id);
+        }
+        drop(children);
 
         // Flush to ensure durability
         self.db.flush()?;
@@ -73,7 +102,7 @@ impl Journal {
             None => return Ok(None),
         };
 
-        Ok(Some(Checkpoint::deserialize(&value)?))
+        Ok(Some(self.decode(seq, &value)?))
     }
 
     /// Get the latest checkpoint
@@ -88,7 +117,7 @@ impl Journal {
 
         let key = max_seq.to_le_bytes();
         let value = self.db.get(&key)?.unwrap();
-        Ok(Some(Checkpoint::deserialize(&value)?))
+        Ok(Some(self.decode(max_seq, &value)?))
     }
 
     /// Get the last N checkpoints
@@ -106,7 +135,7 @@ impl Journal {
         for &seq in recent_seqs {
             let key = seq.to_le_bytes();
             let value = self.db.get(&key)?.unwrap();
-            checkpoints.push(Checkpoint::deserialize(&value)?);
+            checkpoints.push(self.decode(seq, &value)?);
         }
 
         Ok(checkpoints)
@@ -122,7 +151,7 @@ impl Journal {
             if ulid_ts_ms >= timestamp_ms {
                 let key = seq.to_le_bytes();
                 let value = self.db.get(&key)?.unwrap();
-                checkpoints.push(Checkpoint::deserialize(&value)?);
+                checkpoints.push(self.decode(seq, &value)?);
             }
         }
 
@@ -142,10 +171,197 @@ impl Journal {
         };
 
         let key = seq.to_le_bytes();
-        self.db.remove(&key)?;
+        if let Some(value) = self.db.remove(&key)? {
+            // Drop the reverse-DAG edges pointing at this checkpoint
+            if let Ok(checkpoint) = self.decode(seq, &value) {
+                let mut children = self.children.write();
+                for parent in checkpoint.parents() {
+                    if let Some(ids) = children.get_mut(&parent) {
+                        ids.retain(|child| child != id);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace checkpoints in place, keeping each one's position in the journal
+    ///
+    /// Each `(old_id, checkpoint)` pair overwrites the stored checkpoint
+    /// `old_id`; the replacement may carry a new ID. The whole set is written
+    /// in one batch, so a crash leaves either the old or the new history.
+    pub fn rewrite(&self, replacements: &[(Ulid, Checkpoint)]) -> Result<()> {
+        self.rewrite_and_remove(replacements, &[])
+    }
+
+    /// Replace checkpoints in place and delete others, in one batch
+    ///
+    /// Like `rewrite`, but also drops `removed` (e.g. checkpoints GC folded
+    /// into a compacted one), so a crash can't leave the replacements
+    /// pointing past checkpoints that still exist, or the reverse.
+    pub fn rewrite_and_remove(&self, replacements: &[(Ulid, Checkpoint)], removed: &[Ulid]) -> Result<()> {
+        let mut index = self.index.write();
+
+        let mut batch = sled::Batch::default();
+        let mut seqs = Vec::with_capacity(replacements.len());
+        for (old_id, checkpoint) in replacements {
+            let seq = *index.get(old_id)
+                .ok_or_else(|| anyhow::anyhow!("Checkpoint {} is not in the journal", old_id))?;
+            batch.insert(seq.to_le_bytes().to_vec(), self.encode(seq, checkpoint)?);
+            seqs.push(seq);
+        }
+        for id in removed {
+            if let Some(seq) = index.get(id) {
+                batch.remove(seq.to_le_bytes().to_vec());
+            }
+        }
+        self.db.apply_batch(batch)?;
+        self.db.flush()?;
+
+        for id in removed {
+            index.remove(id);
+        }
+        for ((old_id, checkpoint), seq) in replacements.iter().zip(seqs) {
+            index.remove(old_id);
+            index.insert(checkpoint.id, seq);
+        }
+        drop(index);
+
+        // Parents may have changed, so rebuild the reverse DAG from disk
+        let mut children: HashMap<Ulid, Vec<Ulid>> = HashMap::new();
+        for item in self.db.iter() {
+            let (key, value) = item?;
+            let seq = u64::from_le_bytes(key.as_ref().try_into()?);
+            let checkpoint = self.decode(seq, &value)?;
+            for parent in checkpoint.parents() {
+                children.entry(parent).or_default().push(checkpoint.id);
+            }
+        }
+        *self.children.write() = children;
+
         Ok(())
     }
 
+    /// Get the IDs of checkpoints that list `id` as a parent, oldest first
+    pub fn children(&self, id: &Ulid) -> Vec<Ulid> {
+        let mut ids = self.children.read().get(id).cloned().unwrap_or_default();
+        ids.sort_by_key(|child| (self.seq(child), *child));
+        ids
+    }
+
+    /// Walk the DAG from `id` following every parent (like `git rev-list`)
+    ///
+    /// Returns `id` itself followed by its ancestors, newest first. Order is
+    /// by journal position, not ID: parents are always appended before their
+    /// children (rewrites keep positions), so a max-heap on the sequence
+    /// number yields a topological order even for IDs minted in the same
+    /// millisecond or rewritten with an old timestamp. Ancestors that were
+    /// deleted (e.g. by GC) end that branch of the walk.
+    pub fn ancestors(&self, id: &Ulid, limit: Option<usize>) -> Result<Vec<Checkpoint>> {
+        let limit = limit.unwrap_or(usize::MAX);
+        let mut result = Vec::new();
+        let mut seen: HashSet<Ulid> = HashSet::new();
+        let mut queue: BinaryHeap<(u64, Ulid)> = BinaryHeap::new();
+
+        seen.insert(*id);
+        if let Some(seq) = self.seq(id) {
+            queue.push((seq, *id));
+        }
+
+        while let Some((_, current)) = queue.pop() {
+            if result.len() >= limit {
+                break;
+            }
+
+            let checkpoint = match self.get(&current)? {
+                Some(cp) => cp,
+                None => continue,
+            };
+
+            for parent in checkpoint.parents() {
+                if seen.insert(parent) {
+                    if let Some(seq) = self.seq(&parent) {
+                        queue.push((seq, parent));
+                    }
+                }
+            }
+
+            result.push(checkpoint);
+        }
+
+        Ok(result)
+    }
+
+    /// Check whether `ancestor` is reachable from `descendant` (or equal to it)
+    pub fn is_ancestor(&self, ancestor: &Ulid, descendant: &Ulid) -> Result<bool> {
+        if ancestor == descendant {
+            return Ok(true);
+        }
+        let ancestor_seq = match self.seq(ancestor) {
+            Some(seq) => seq,
+            None => return Ok(false),
+        };
+
+        let mut seen: HashSet<Ulid> = HashSet::new();
+        let mut stack = vec![*descendant];
+
+        while let Some(current) = stack.pop() {
+            if current == *ancestor {
+                return Ok(true);
+            }
+            // Ancestors sit earlier in the journal, so anything before the target can't lead to it
+            if self.seq(&current).is_none_or(|seq| seq < ancestor_seq) || !seen.insert(current) {
+                continue;
+            }
+            if let Some(checkpoint) = self.get(&current)? {
+                stack.extend(checkpoint.parents());
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Find the best common ancestor of two checkpoints
+    ///
+    /// Returns the newest checkpoint reachable from both `a` and `b`, which
+    /// is the natural base for a three-way merge.
+    pub fn merge_base(&self, a: &Ulid, b: &Ulid) -> Result<Option<Ulid>> {
+        let a_ancestors: HashSet<Ulid> = self.ancestors(a, None)?
+            .into_iter()
+            .map(|cp| cp.id)
+            .collect();
+
+        // Walk b newest-first (by journal position): the first hit is the newest common ancestor
+        let mut seen: HashSet<Ulid> = HashSet::new();
+        let mut queue: BinaryHeap<(u64, Ulid)> = BinaryHeap::new();
+        seen.insert(*b);
+        if let Some(seq) = self.seq(b) {
+            queue.push((seq, *b));
+        }
+
+        while let Some((_, current)) = queue.pop() {
+            if a_ancestors.contains(&current) {
+                return Ok(Some(current));
+            }
+            if let Some(checkpoint) = self.get(&current)? {
+                for parent in checkpoint.parents() {
+                    if seen.insert(parent) {
+                        if let Some(seq) = self.seq(&parent) {
+                            queue.push((seq, parent));
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Journal position of a checkpoint (None if it is not in the journal)
+    fn seq(&self, id: &Ulid) -> Option<u64> {
+        self.index.read().get(id).copied()
+    }
+
     /// Get the total number of checkpoints
     pub fn count(&self) -> usize {
         self.index.read().len()
@@ -182,7 +398,7 @@ impl Journal {
                     };
 
                     // Try to deserialize checkpoint
-                    match Checkpoint::deserialize(&value) {
+                    match self.decode(seq, &value) {
                         Ok(checkpoint) => {
                             // Check index consistency
                             let index = self.index.read();
@@ -266,13 +482,17 @@ impl Journal {
         // Rebuild index from valid database entries
         if !dry_run && (!report.orphaned_entries.is_empty() || !report.index_without_db.is_empty()) {
             let mut new_index = BTreeMap::new();
+            let mut new_children: HashMap<Ulid, Vec<Ulid>> = HashMap::new();
             let mut max_seq = 0u64;
 
             for item in self.db.iter() {
                 let (key, value) = item?;
                 let seq = u64::from_le_bytes(key.as_ref().try_into()?);
 
-                if let Ok(checkpoint) = Checkpoint::deserialize(&value) {
+                if let Ok(checkpoint) = self.decode(seq, &value) {
+                    for parent in checkpoint.parents() {
+                        new_children.entry(parent).or_default().push(checkpoint.id);
+                    }
                     new_index.insert(checkpoint.id, seq);
                     max_seq = max_seq.max(seq);
                 }
@@ -280,6 +500,7 @@ impl Journal {
 
             // Update index
             *self.index.write() = new_index;
+            *self.children.write() = new_children;
             self.seq_counter.store(max_seq + 1, Ordering::SeqCst);
             result.index_rebuilt = true;
         }
@@ -301,12 +522,27 @@ impl Journal {
         Ok(result)
     }
 
+    /// Serialize a checkpoint for storage at `seq`
+    fn encode(&self, seq: u64, checkpoint: &Checkpoint) -> Result<Vec<u8>> {
+        let serialized = checkpoint.serialize()?;
+        Ok(seal(self.cipher.as_deref(), &serialized, &seq.to_le_bytes())?.into_owned())
+    }
+
+    /// Deserialize the checkpoint stored at `seq`
+    fn decode(&self, seq: u64, value: &[u8]) -> Result<Checkpoint> {
+        decode(self.cipher.as_deref(), seq, value)
+    }
+
     /// Get direct access to the database (for advanced operations)
     pub fn db(&self) -> &Db {
         &self.db
     }
 }
 
+fn decode(cipher: Option<&RepoCipher>, seq: u64, value: &[u8]) -> Result<Checkpoint> {
+    Checkpoint::deserialize(&unseal(cipher, value.to_vec(), &seq.to_le_bytes())?)
+}
+
 /// Journal integrity report
 #[derive(Debug, Default, Clone)]
 pub struct IntegrityReport {
@@ -619,6 +855,71 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_journal_rewrite_keeps_positions() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+
+        let (a, b) = {
+            let journal = Journal::open(temp_dir.path())?;
+            let a = create_test_checkpoint(None);
+            journal.append(&a)?;
+            let b = create_test_checkpoint(Some(a.id));
+            journal.append(&b)?;
+
+            // Give both new IDs; b follows its rewritten parent
+            let mut new_a = a.clone();
+            new_a.id = Ulid::new();
+            let mut new_b = b.clone();
+            new_b.id = Ulid::new();
+            new_b.parent = Some(new_a.id);
+            journal.rewrite(&[(a.id, new_a.clone()), (b.id, new_b.clone())])?;
+
+            assert!(journal.get(&a.id)?.is_none());
+            assert_eq!(journal.latest()?.unwrap().id, new_b.id);
+            assert_eq!(journal.children(&new_a.id), vec![new_b.id]);
+            assert!(journal.children(&a.id).is_empty());
+            (new_a, new_b)
+        };
+
+        // Survives reopening
+        let journal = Journal::open(temp_dir.path())?;
+        assert_eq!(journal.count(), 2);
+        assert_eq!(journal.get(&b.id)?.unwrap().parent, Some(a.id));
+        assert!(journal.rewrite(&[(Ulid::new(), a)]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_journal_encrypted_values() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let cipher = Some(Arc::new(RepoCipher::new(&[9u8; 32])));
+
+        let checkpoint = create_test_checkpoint(None);
+        {
+            let journal = Journal::open_with_cipher(temp_dir.path(), cipher.clone())?;
+            journal.append(&checkpoint)?;
+            let (_, raw) = journal.db().iter().next().unwrap()?;
+            assert!(core::encryption::is_sealed(&raw));
+        }
+
+        // Reopening needs the key
+        assert!(Journal::open_with_cipher(temp_dir.path(), None).is_err());
+        let journal = Journal::open_with_cipher(temp_dir.path(), cipher)?;
+        assert_eq!(journal.get(&checkpoint.id)?.unwrap().root_tree, checkpoint.root_tree);
+        assert!(journal.verify_integrity()?.is_healthy());
+        drop(journal);
+
+        // A plaintext journal opened with the key is rejected, not trusted
+        let plain_dir = TempDir::new()?;
+        Journal::open(plain_dir.path())?.append(&create_test_checkpoint(None))?;
+        assert!(Journal::open_with_cipher(plain_dir.path(), Some(Arc::new(RepoCipher::new(&[9u8; 32])))).is_err());
+        let migrating = Some(Arc::new(RepoCipher::new(&[9u8; 32]).with_plaintext_reads(true)));
+        assert_eq!(Journal::open_with_cipher(plain_dir.path(), migrating)?.count(), 1);
+
+        Ok(())
+    }
+
     #[test]
     fn test_journal_multiple_appends() -> Result<()> {
         let temp_dir = TempDir::new()?;
@@ -635,4 +936,90 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_journal_dag_queries() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let journal = Journal::open(temp_dir.path())?;
+
+        // root -> a -> b (ours)
+        //      \-> c (theirs)
+        // merge(b, c)
+        let root = create_test_checkpoint(None);
+        journal.append(&root)?;
+        let a = create_test_checkpoint(Some(root.id));
+        journal.append(&a)?;
+        let c = create_test_checkpoint(Some(root.id));
+        journal.append(&c)?;
+        let b = create_test_checkpoint(Some(a.id));
+        journal.append(&b)?;
+        let merge = create_test_checkpoint(Some(b.id)).with_merge_parents(vec![c.id]);
+        journal.append(&merge)?;
+
+        // Children
+        assert_eq!(journal.children(&root.id), vec![a.id, c.id]);
+        assert_eq!(journal.children(&c.id), vec![merge.id]);
+        assert!(journal.children(&merge.id).is_empty());
+
+        // Ancestors follow every parent, newest first
+        let ancestors: Vec<Ulid> = journal.ancestors(&merge.id, None)?.iter().map(|cp| cp.id).collect();
+        assert_eq!(ancestors, vec![merge.id, b.id, c.id, a.id, root.id]);
+        assert_eq!(journal.ancestors(&merge.id, Some(2))?.len(), 2);
+
+        // Ancestry checks
+        assert!(journal.is_ancestor(&c.id, &merge.id)?);
+        assert!(!journal.is_ancestor(&c.id, &b.id)?);
+
+        // Merge base
+        assert_eq!(journal.merge_base(&b.id, &c.id)?, Some(root.id));
+        assert_eq!(journal.merge_base(&merge.id, &c.id)?, Some(c.id));
+
+        // Children survive reopen and are updated on delete
+        drop(journal);
+        let journal = Journal::open(temp_dir.path())?;
+        assert_eq!(journal.children(&root.id), vec![a.id, c.id]);
+        journal.delete(&c.id)?;
+        assert_eq!(journal.children(&root.id), vec![a.id]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_journal_dag_queries_ignore_id_order() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let journal = Journal::open(temp_dir.path())?;
+
+        // Same shape as above, all in one millisecond, with every child's
+        // ID sorting before its parent's (as after a rewrite)
+        let ts = Ulid::new().timestamp_ms();
+        let with_id = |mut cp: Checkpoint, random: u128| {
+            cp.id = Ulid::from_parts(ts, random);
+            cp
+        };
+        let root = with_id(create_test_checkpoint(None), 500);
+        journal.append(&root)?;
+        let a = with_id(create_test_checkpoint(Some(root.id)), 400);
+        journal.append(&a)?;
+        let c = with_id(create_test_checkpoint(Some(root.id)), 300);
+        journal.append(&c)?;
+        let b = with_id(create_test_checkpoint(Some(a.id)), 200);
+        journal.append(&b)?;
+        let merge = with_id(create_test_checkpoint(Some(b.id)).with_merge_parents(vec![c.id]), 100);
+        journal.append(&merge)?;
+
+        assert_eq!(journal.children(&root.id), vec![a.id, c.id]);
+
+        let ancestors: Vec<Ulid> = journal.ancestors(&merge.id, None)?.iter().map(|cp| cp.id).collect();
+        assert_eq!(ancestors, vec![merge.id, b.id, c.id, a.id, root.id]);
+
+        assert!(journal.is_ancestor(&root.id, &merge.id)?);
+        assert!(journal.is_ancestor(&a.id, &b.id)?);
+        assert!(!journal.is_ancestor(&b.id, &a.id)?);
+        assert!(!journal.is_ancestor(&c.id, &b.id)?);
+
+        assert_eq!(journal.merge_base(&b.id, &c.id)?, Some(root.id));
+        assert_eq!(journal.merge_base(&merge.id, &a.id)?, Some(a.id));
+
+        Ok(())
+    }
 }
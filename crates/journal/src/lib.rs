@@ -6,21 +6,27 @@
 //! - PathMap state cache
 //! - Incremental tree update algorithm
 //! - Retention policies & GC
+//! - History rewriting (path purge)
+//! - Per-path change provenance
 
 pub mod checkpoint;
+pub mod changes;
 pub mod journal;
 pub mod pathmap;
 pub mod incremental;
 pub mod retention;
 pub mod recovery;
+pub mod rewrite;
 
 // Re-exports
-pub use checkpoint::{Checkpoint, CheckpointMeta, CheckpointReason};
+pub use changes::{path_changes, Observed};
+pub use checkpoint::{ChangeKind, Checkpoint, CheckpointMeta, CheckpointReason, PathChange};
 pub use journal::{Journal, IntegrityReport, RepairResult};
 pub use pathmap::PathMap;
-pub use incremental::incremental_update;
+pub use incremental::{incremental_update, incremental_update_with_options, incremental_update_with_workers, UpdateOptions};
 pub use retention::{GarbageCollector, GcMetrics, PinManager, RetentionPolicy, StashEntry, StashManager};
-pub use recovery::recover_on_startup;
+pub use recovery::{pathmap_matches_tree, recover_on_startup};
+pub use rewrite::{purge_paths, PurgeResult};
 
 /// Result type for journal operations
 pub type Result<T> = anyhow::Result<T>;
@@ -2,7 +2,7 @@
 
 use ahash::AHashMap;
 use anyhow::Result;
-use core::{Sha1Hash, Entry, EntryKind, Tree};
+use core::{Sha1Hash, Entry, EntryKind, FileStat, StatCache, Tree};
 use smallvec::SmallVec;
 use std::fs;
 use std::path::Path;
@@ -17,6 +17,9 @@ pub struct PathMap {
     /// Mapping from path to entry
     /// Uses AHashMap (faster for small keys) and SmallVec (stack allocation for short paths)
     entries: AHashMap<SmallVec<[u8; 64]>, Entry>,
+    /// Stat data recorded when each file was last hashed (Git index style)
+    /// Only non-racy stats are kept; a missing stat forces a re-hash.
+    stats: AHashMap<SmallVec<[u8; 64]>, FileStat>,
 }
 
 /// Convert a Path to SmallVec<[u8; 64]> for use as map key
@@ -32,12 +35,17 @@ impl PathMap {
         Self {
             root_tree,
             entries: AHashMap::new(),
+            stats: AHashMap::new(),
         }
     }
 
     /// Update an entry in the map (None = remove)
+    ///
+    /// Clears any recorded stat for the path; call `record_stat` afterwards
+    /// if the new entry was hashed from a file on disk.
     pub fn update(&mut self, path: &Path, entry: Option<Entry>) {
         let key = path_to_key(path);
+        self.stats.remove(&key);
         match entry {
             Some(e) => {
                 self.entries.insert(key, e);
@@ -60,6 +68,30 @@ impl PathMap {
         self.entries.get(&key)
     }
 
+    /// Record the stat data a path's entry was hashed from
+    ///
+    /// Racy stats (mtime too close to `recorded_at_ns`) are dropped, so the
+    /// file is re-hashed on the next rescan.
+    pub fn record_stat(&mut self, path: &Path, stat: FileStat, recorded_at_ns: i64) {
+        let key = path_to_key(path);
+        if stat.is_racy(recorded_at_ns) || !self.entries.contains_key(&key) {
+            self.stats.remove(&key);
+        } else {
+            self.stats.insert(key, stat);
+        }
+    }
+
+    /// Get the recorded stat for a path
+    pub fn stat(&self, path: &Path) -> Option<&FileStat> {
+        self.stats.get(&path_to_key(path))
+    }
+
+    /// True if the path has an entry whose recorded stat matches `current`
+    /// (the file can be assumed unchanged without re-hashing)
+    pub fn is_stat_clean(&self, path: &Path, current: &FileStat) -> bool {
+        self.stat(path) == Some(current)
+    }
+
     /// Get the number of entries
     pub fn len(&self) -> usize {
         self.entries.len()
@@ -88,10 +120,10 @@ impl PathMap {
         pathmap
     }
 
-    /// Save PathMap to disk (PMV2 format - Git-compatible)
+    /// Save PathMap to disk (PMV3 format - Git-compatible, with stat cache)
     ///
     /// Format:
-    /// - magic: "PMV2" (4 bytes)
+    /// - magic: "PMV3" (4 bytes)
     /// - root_tree: Sha1Hash (20 bytes)
     /// - entry_count: u32 (4 bytes)
     /// - entries (sorted by path):
@@ -100,8 +132,9 @@ impl PathMap {
     ///   - kind: u8 (0=file, 1=executable, 2=symlink, 3=tree)
     ///   - mode: u32
     ///   - blob_hash: [u8; 20]
+    ///   - has_stat: u8 (0/1), then if 1: size u64, mtime_ns i64, ctime_ns i64, ino u64
     pub fn save(&self, path: &Path) -> Result<()> {
-        const MAGIC: &[u8] = b"PMV2";
+        const MAGIC: &[u8] = b"PMV3";
 
         let mut bytes = Vec::new();
 
@@ -141,6 +174,15 @@ impl PathMap {
 
             // Blob hash (20 bytes for SHA-1)
             bytes.extend_from_slice(entry.blob_hash.as_bytes());
+
+            // Stat cache (optional)
+            match self.stats.get(path_bytes) {
+                Some(stat) => {
+                    bytes.push(1);
+                    bytes.extend_from_slice(&stat.to_bytes());
+                }
+                None => bytes.push(0),
+            }
         }
 
         // Atomic write using core::store::atomic_write pattern
@@ -158,7 +200,7 @@ impl PathMap {
         Ok(())
     }
 
-    /// Load PathMap from disk (supports PMV1 and PMV2 formats)
+    /// Load PathMap from disk (supports PMV2 and PMV3 formats)
     pub fn load(path: &Path) -> Result<Self> {
         let bytes = fs::read(path).map_err(|e| {
             anyhow::anyhow!("Failed to read PathMap from {}: {}", path.display(), e)
@@ -170,14 +212,16 @@ impl PathMap {
 
         // Check magic and dispatch to appropriate loader
         match &bytes[0..4] {
-            b"PMV2" => Self::load_v2(&bytes),
+            b"PMV3" => Self::load_v2(&bytes, true),
+            b"PMV2" => Self::load_v2(&bytes, false),
             b"PMV1" => anyhow::bail!("PMV1 format no longer supported - please reinitialize repository"),
             _ => anyhow::bail!("Invalid PathMap magic: {:?}", &bytes[0..4]),
         }
     }
 
-    /// Load PMV2 format (Git-compatible with SHA-1)
-    fn load_v2(bytes: &[u8]) -> Result<Self> {
+    /// Load PMV2 format (Git-compatible with SHA-1), or PMV3 when `with_stats`
+    /// (PMV2 plus a stat cache record after each entry)
+    fn load_v2(bytes: &[u8], with_stats: bool) -> Result<Self> {
         if bytes.len() < 28 {
             anyhow::bail!(
                 "Invalid PMV2: file too short (expected at least 28 bytes, got {})",
@@ -194,6 +238,7 @@ impl PathMap {
         let entry_count = u32::from_le_bytes([bytes[24], bytes[25], bytes[26], bytes[27]]) as usize;
 
         let mut entries = AHashMap::new();
+        let mut stats = AHashMap::new();
         let mut offset = 28;
 
         // Parse each entry
@@ -249,16 +294,51 @@ impl PathMap {
                 blob_hash,
             };
 
+            if with_stats {
+                if offset + 1 > bytes.len() {
+                    anyhow::bail!("Invalid PMV3: missing stat flag at offset {}", offset);
+                }
+                let has_stat = bytes[offset] != 0;
+                offset += 1;
+
+                if has_stat {
+                    if offset + FileStat::ENCODED_LEN > bytes.len() {
+                        anyhow::bail!("Invalid PMV3: incomplete stat at offset {}", offset);
+                    }
+                    let mut stat_bytes = [0u8; FileStat::ENCODED_LEN];
+                    stat_bytes.copy_from_slice(&bytes[offset..offset + FileStat::ENCODED_LEN]);
+                    stats.insert(path_bytes.clone(), FileStat::from_bytes(&stat_bytes));
+                    offset += FileStat::ENCODED_LEN;
+                }
+            }
+
             entries.insert(path_bytes, entry);
         }
 
         Ok(Self {
             root_tree,
             entries,
+            stats,
         })
     }
 }
 
+impl StatCache for PathMap {
+    fn cached_stat(&self, path: &Path) -> Option<FileStat> {
+        self.stat(path).copied()
+    }
+
+    fn cached_entry(&self, path: &Path) -> Option<Entry> {
+        self.get(path).cloned()
+    }
+
+    fn cached_paths(&self) -> Vec<std::path::PathBuf> {
+        self.entries.keys()
+            .map(|key| std::path::PathBuf::from(String::from_utf8_lossy(key).into_owned()))
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -427,4 +507,37 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_pathmap_stat_cache_roundtrip() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let save_path = temp_dir.path().join("pathmap.bin");
+
+        let mut pathmap = PathMap::new(Sha1Hash::from_bytes([4u8; 20]));
+        let stable = PathBuf::from("stable.txt");
+        let racy = PathBuf::from("racy.txt");
+        pathmap.update(&stable, Some(create_test_entry(b"stable")));
+        pathmap.update(&racy, Some(create_test_entry(b"racy")));
+
+        let stat = FileStat { size: 6, mtime_ns: 1_000_000_000, ctime_ns: 1_000_000_000, ino: 42 };
+        let hot = FileStat { mtime_ns: 9_500_000_000, ..stat };
+        pathmap.record_stat(&stable, stat, 10_000_000_000);
+        pathmap.record_stat(&racy, hot, 10_000_000_000);
+
+        assert!(pathmap.is_stat_clean(&stable, &stat));
+        assert!(pathmap.stat(&racy).is_none(), "racy stat must not be trusted");
+
+        pathmap.save(&save_path)?;
+        let loaded = PathMap::load(&save_path)?;
+        assert!(loaded.is_stat_clean(&stable, &stat));
+        assert!(!loaded.is_stat_clean(&stable, &FileStat { size: 7, ..stat }));
+        assert!(loaded.stat(&racy).is_none());
+
+        // Replacing the entry invalidates its stat
+        let mut loaded = loaded;
+        loaded.update(&stable, Some(create_test_entry(b"changed")));
+        assert!(loaded.stat(&stable).is_none());
+
+        Ok(())
+    }
 }
@@ -142,7 +142,7 @@ fn verify_pathmap_consistency(
 }
 
 /// Check if PathMap matches a Tree
-fn pathmap_matches_tree(pathmap: &PathMap, tree: &core::Tree) -> bool {
+pub fn pathmap_matches_tree(pathmap: &PathMap, tree: &core::Tree) -> bool {
     // Collect tree entries to check count
     let tree_entries: Vec<_> = tree.entries_with_paths().collect();
 
@@ -1,9 +1,13 @@
 //! Retention policies and garbage collection
+//!
+//! Checkpoints outside the retention policy are not simply deleted: runs of
+//! them are folded into synthetic `GcCompact` checkpoints, so the parent
+//! chain stays intact across the compacted region.
 
 use anyhow::Result;
-use core::{EntryKind, Sha1Hash, Store};
-use crate::Journal;
-use std::collections::HashSet;
+use core::{EntryKind, PathPolicies, PathPolicy, Sha1Hash, Store};
+use crate::{Checkpoint, CheckpointMeta, CheckpointReason, Journal};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
@@ -31,6 +35,19 @@ impl Default for RetentionPolicy {
     }
 }
 
+impl RetentionPolicy {
+    /// Emergency policy for storage pressure: `step` halvings of the
+    /// checkpoint count and time window (down to 10 checkpoints / 10 min)
+    pub fn tightened(&self, step: u32) -> Self {
+        let shift = step.min(31);
+        Self {
+            retain_dense_count: (self.retain_dense_count >> shift).max(10.min(self.retain_dense_count)),
+            retain_dense_window_ms: (self.retain_dense_window_ms >> shift).max((10 * 60 * 1000).min(self.retain_dense_window_ms)),
+            retain_pins: self.retain_pins,
+        }
+    }
+}
+
 /// Pin manager for named checkpoints
 pub struct PinManager {
     pins_dir: PathBuf,
@@ -182,6 +199,32 @@ impl StashManager {
         let stashes = self.list()?;
         Ok(stashes.into_iter().last().map(|(_, entry)| entry))
     }
+
+    /// Point stashes at rewritten checkpoints (old ID -> new ID)
+    ///
+    /// Returns the number of stash entries updated.
+    pub fn remap(&self, remapped: &HashMap<Ulid, Ulid>) -> Result<usize> {
+        let mut updated = 0;
+
+        for (index, mut entry) in self.list()? {
+            let checkpoint_id = remapped.get(&entry.checkpoint_id).copied();
+            let base_id = entry.base_checkpoint_id.and_then(|id| remapped.get(&id).copied());
+            if checkpoint_id.is_none() && base_id.is_none() {
+                continue;
+            }
+
+            entry.checkpoint_id = checkpoint_id.unwrap_or(entry.checkpoint_id);
+            entry.base_checkpoint_id = base_id.or(entry.base_checkpoint_id);
+
+            let stash_path = self.stash_dir.join(format!("stash-{}", index));
+            let mut file = fs::File::create(&stash_path)?;
+            file.write_all(serde_json::to_string_pretty(&entry)?.as_bytes())?;
+            file.sync_all()?;
+            updated += 1;
+        }
+
+        Ok(updated)
+    }
 }
 
 impl PinManager {
@@ -289,23 +332,42 @@ impl PinManager {
         let pins = self.list_pins()?;
         Ok(pins.into_iter().map(|(_, id)| id).collect())
     }
+
+    /// Move pins to rewritten checkpoints (old ID -> new ID)
+    ///
+    /// Returns the number of pins updated.
+    pub fn remap(&self, remapped: &HashMap<Ulid, Ulid>) -> Result<usize> {
+        let mut updated = 0;
+        for (name, id) in self.list_pins()? {
+            if let Some(new_id) = remapped.get(&id) {
+                self.pin(&name, *new_id)?;
+                updated += 1;
+            }
+        }
+        Ok(updated)
+    }
 }
 
 /// GC metrics
 #[derive(Debug, Clone, Default)]
 pub struct GcMetrics {
     pub checkpoints_deleted: usize,
+    /// Synthetic checkpoints written in place of runs of expired ones
+    pub checkpoints_compacted: usize,
     pub trees_deleted: usize,
     pub blobs_deleted: usize,
     pub bytes_freed: u64,
     pub duration_ms: u64,
+    /// Last checkpoint of each compacted run -> the checkpoint that replaced
+    /// it (same tree)
+    pub remapped: HashMap<Ulid, Ulid>,
 }
 
 impl GcMetrics {
     pub fn log_summary(&self) {
         eprintln!(
-            "GC completed: {} checkpoints, {} trees, {} blobs deleted",
-            self.checkpoints_deleted, self.trees_deleted, self.blobs_deleted
+            "GC completed: {} checkpoints ({} compacted), {} trees, {} blobs deleted",
+            self.checkpoints_deleted, self.checkpoints_compacted, self.trees_deleted, self.blobs_deleted
         );
         eprintln!(
             "Space freed: {:.2} MB in {} ms",
@@ -318,35 +380,51 @@ impl GcMetrics {
 /// Garbage collector
 pub struct GarbageCollector {
     policy: RetentionPolicy,
+    /// Per-path policies (`latest-only` paths keep only HEAD's version)
+    path_policies: PathPolicies,
 }
 
 impl GarbageCollector {
     /// Create a new GC with the given policy
     pub fn new(policy: RetentionPolicy) -> Self {
-        Self { policy }
+        Self {
+            policy,
+            path_policies: PathPolicies::default(),
+        }
+    }
+
+    /// Apply per-path checkpoint policies (from `.tlpolicy`)
+    pub fn with_path_policies(mut self, path_policies: PathPolicies) -> Self {
+        self.path_policies = path_policies;
+        self
     }
 
     /// Run garbage collection
     ///
-    /// Optionally accepts workspace checkpoint IDs to protect them from deletion.
+    /// Optionally accepts checkpoint IDs referenced outside the journal
+    /// (workspaces, stashes, JJ mappings) to protect them from deletion and
+    /// compaction.
     pub fn collect(
         &self,
         journal: &Journal,
         store: &Store,
         pin_manager: &PinManager,
-        workspace_checkpoints: Option<&HashSet<Ulid>>,
+        referenced_checkpoints: Option<&HashSet<Ulid>>,
     ) -> Result<GcMetrics> {
         let start_time = SystemTime::now();
         let mut metrics = GcMetrics::default();
 
         // Phase 1: Mark live checkpoints
-        let live_checkpoints = self.mark_live_checkpoints(journal, pin_manager, workspace_checkpoints)?;
+        let live_checkpoints = self.mark_live_checkpoints(journal, pin_manager, referenced_checkpoints)?;
 
-        // Phase 2: Mark live objects (trees and blobs)
+        // Phase 2: Fold the expired ones into compacted checkpoints
+        let live_checkpoints = self.compact_expired(&live_checkpoints, journal, &mut metrics)?;
+
+        // Phase 3: Mark live objects (trees and blobs)
         let (live_trees, live_blobs) =
             self.mark_live_objects(&live_checkpoints, journal, store)?;
 
-        // Phase 3: Sweep dead objects
+        // Phase 4: Sweep dead objects
         self.sweep_dead_objects(
             &live_checkpoints,
             &live_trees,
@@ -367,7 +445,7 @@ impl GarbageCollector {
         &self,
         journal: &Journal,
         pin_manager: &PinManager,
-        workspace_checkpoints: Option<&HashSet<Ulid>>,
+        referenced_checkpoints: Option<&HashSet<Ulid>>,
     ) -> Result<HashSet<Ulid>> {
         let mut live = HashSet::new();
 
@@ -376,9 +454,9 @@ impl GarbageCollector {
             live.extend(pin_manager.get_pinned_checkpoints()?);
         }
 
-        // Criterion 2: Workspace checkpoints (always protected)
-        if let Some(ws_checkpoints) = workspace_checkpoints {
-            live.extend(ws_checkpoints.iter().copied());
+        // Criterion 2: Checkpoints referenced outside the journal (always protected)
+        if let Some(referenced) = referenced_checkpoints {
+            live.extend(referenced.iter().copied());
         }
 
         // Criterion 3: Last N checkpoints
@@ -394,6 +472,107 @@ impl GarbageCollector {
         Ok(live)
     }
 
+    /// Fold runs of expired checkpoints into synthetic `GcCompact` ones
+    ///
+    /// A run is a first-parent chain of expired checkpoints in which every
+    /// member but the last has exactly one child: the next member. It is
+    /// replaced by one checkpoint with the last member's tree and timestamp,
+    /// the first member's parents, the union of the run's touched paths and
+    /// its summed byte counts. Children of the run are re-parented onto the
+    /// replacement but keep their IDs (their snapshots are unchanged), so
+    /// pins, stashes and workspaces pointing at them stay valid. Live
+    /// checkpoints, referenced ones included, are never part of a run.
+    ///
+    /// An expired checkpoint that forms a run on its own is kept as is.
+    /// Returns the checkpoints left in the journal.
+    fn compact_expired(
+        &self,
+        live: &HashSet<Ulid>,
+        journal: &Journal,
+        metrics: &mut GcMetrics,
+    ) -> Result<HashSet<Ulid>> {
+        // Journal order puts parents before children
+        let checkpoints = journal.last_n(journal.count())?;
+
+        let mut runs: Vec<Vec<&Checkpoint>> = Vec::new();
+        // Last member of each run -> its index in `runs`
+        let mut run_tails: HashMap<Ulid, usize> = HashMap::new();
+        for checkpoint in checkpoints.iter().filter(|cp| !live.contains(&cp.id)) {
+            let extends = checkpoint.parent
+                .filter(|parent| !checkpoint.is_merge() && journal.children(parent).len() == 1)
+                .and_then(|parent| run_tails.remove(&parent));
+            let run = match extends {
+                Some(run) => {
+                    runs[run].push(checkpoint);
+                    run
+                }
+                None => {
+                    runs.push(vec![checkpoint]);
+                    runs.len() - 1
+                }
+            };
+            run_tails.insert(checkpoint.id, run);
+        }
+
+        let mut remapped: HashMap<Ulid, Ulid> = HashMap::new();
+        let mut replacements = Vec::new();
+        let mut removed = Vec::new();
+
+        // A run's parent may itself end a compacted run; runs are in journal
+        // order, so that one has been remapped already
+        for run in runs.iter().filter(|run| run.len() > 1) {
+            let (first, last) = (run[0], run[run.len() - 1]);
+            let remap = |id: Ulid| remapped.get(&id).copied().unwrap_or(id);
+
+            let touched: BTreeSet<&std::path::PathBuf> = run.iter().flat_map(|cp| &cp.touched_paths).collect();
+            let compacted = Checkpoint {
+                id: Ulid::from_parts(last.id.timestamp_ms(), Ulid::new().random()),
+                parent: first.parent.map(remap),
+                root_tree: last.root_tree,
+                ts_unix_ms: last.ts_unix_ms,
+                reason: CheckpointReason::GcCompact,
+                meta: CheckpointMeta {
+                    files_changed: touched.len() as u32,
+                    bytes_added: run.iter().map(|cp| cp.meta.bytes_added).sum(),
+                    bytes_removed: run.iter().map(|cp| cp.meta.bytes_removed).sum(),
+                },
+                touched_paths: touched.into_iter().cloned().collect(),
+                merge_parents: first.merge_parents.iter().copied().map(remap).collect(),
+                // Per-path provenance doesn't survive folding
+                changes: Vec::new(),
+            };
+
+            removed.extend(run[..run.len() - 1].iter().map(|cp| cp.id));
+            remapped.insert(last.id, compacted.id);
+            replacements.push((last.id, compacted));
+        }
+
+        // Re-parent whatever was built on top of a compacted run
+        let remap = |id: Ulid| remapped.get(&id).copied().unwrap_or(id);
+        let folded: HashSet<Ulid> = removed.iter().chain(remapped.keys()).copied().collect();
+        for checkpoint in checkpoints.iter().filter(|cp| !folded.contains(&cp.id)) {
+            let parent = checkpoint.parent.map(remap);
+            let merge_parents: Vec<Ulid> = checkpoint.merge_parents.iter().copied().map(remap).collect();
+            if parent == checkpoint.parent && merge_parents == checkpoint.merge_parents {
+                continue;
+            }
+
+            let mut reparented = checkpoint.clone();
+            reparented.parent = parent;
+            reparented.merge_parents = merge_parents;
+            replacements.push((checkpoint.id, reparented));
+        }
+
+        if !replacements.is_empty() {
+            journal.rewrite_and_remove(&replacements, &removed)?;
+        }
+        metrics.checkpoints_deleted += removed.len();
+        metrics.checkpoints_compacted += remapped.len();
+        metrics.remapped = remapped;
+
+        journal.all_checkpoint_ids()
+    }
+
     /// Mark live objects (trees and blobs) referenced by live checkpoints
     ///
     /// CRITICAL: This now recursively walks all subtrees to mark nested blobs.
@@ -408,18 +587,45 @@ impl GarbageCollector {
         let mut live_trees = HashSet::new();
         let mut live_blobs = HashSet::new();
 
+        // HEAD first, in full: it holds the one version latest-only paths keep
+        let head = journal.latest()?;
+        if let Some(head) = &head {
+            Self::mark_tree_recursive(head.root_tree, &mut live_trees, &mut live_blobs, store)?;
+        }
+
+        let mut superseded = HashSet::new();
         for cp_id in live_checkpoints {
+            if head.as_ref().is_some_and(|h| h.id == *cp_id) {
+                continue;
+            }
             if let Some(checkpoint) = journal.get(cp_id)? {
-                // Recursively walk the tree and mark all objects
-                Self::mark_tree_recursive(
-                    checkpoint.root_tree,
-                    &mut live_trees,
-                    &mut live_blobs,
-                    store,
-                )?;
+                if self.path_policies.is_empty() {
+                    // Recursively walk the tree and mark all objects
+                    Self::mark_tree_recursive(
+                        checkpoint.root_tree,
+                        &mut live_trees,
+                        &mut live_blobs,
+                        store,
+                    )?;
+                } else {
+                    self.mark_tree_with_policies(
+                        checkpoint.root_tree,
+                        &mut live_trees,
+                        &mut live_blobs,
+                        &mut superseded,
+                        store,
+                    )?;
+                }
             }
         }
 
+        // Older versions of latest-only paths are dropped; leave a marker so
+        // restores of those checkpoints report the content as not kept
+        for blob_hash in superseded.difference(&live_blobs) {
+            let size = store.blob_store().blob_size(*blob_hash)?;
+            store.blob_store().mark_omitted(*blob_hash, size)?;
+        }
+
         Ok((live_trees, live_blobs))
     }
 
@@ -451,7 +657,7 @@ impl GarbageCollector {
                     // This catches corrupted/missing blobs early
                     if store.blob_store().has_blob(entry.blob_hash) {
                         live_blobs.insert(entry.blob_hash);
-                    } else {
+                    } else if !store.blob_store().is_omitted(entry.blob_hash) {
                         // Log warning but don't fail - blob might have been legitimately deleted
                         tracing::warn!(
                             "Missing blob referenced by tree {}: {}",
@@ -466,6 +672,44 @@ impl GarbageCollector {
         Ok(())
     }
 
+    /// Mark a non-HEAD tree, skipping blobs of `latest-only` paths
+    ///
+    /// Skipped blobs are collected in `superseded`; they stay live only if
+    /// another path (or HEAD) still references them.
+    fn mark_tree_with_policies(
+        &self,
+        tree_hash: Sha1Hash,
+        live_trees: &mut HashSet<Sha1Hash>,
+        live_blobs: &mut HashSet<Sha1Hash>,
+        superseded: &mut HashSet<Sha1Hash>,
+        store: &Store,
+    ) -> Result<()> {
+        if !live_trees.insert(tree_hash) {
+            return Ok(());
+        }
+
+        let tree = store.read_tree(tree_hash)?;
+
+        for (path_bytes, entry) in tree.entries_with_paths() {
+            if entry.kind == EntryKind::Tree {
+                Self::mark_tree_recursive(entry.blob_hash, live_trees, live_blobs, store)?;
+                continue;
+            }
+            if !store.blob_store().has_blob(entry.blob_hash) {
+                continue;
+            }
+
+            let path = Path::new(std::str::from_utf8(path_bytes).unwrap_or_default());
+            if self.path_policies.policy_for(path) == PathPolicy::LatestOnly {
+                superseded.insert(entry.blob_hash);
+            } else {
+                live_blobs.insert(entry.blob_hash);
+            }
+        }
+
+        Ok(())
+    }
+
     /// Sweep dead objects (delete unreferenced checkpoints, trees, blobs)
     fn sweep_dead_objects(
         &self,
@@ -494,15 +738,18 @@ impl GarbageCollector {
         }
 
         // Delete unreferenced blobs (with size tracking)
-        for blob_hash in enumerate_stored_blobs(store)? {
+        for blob_hash in store.blob_store().list_blobs()? {
             if !live_blobs.contains(&blob_hash) {
-                let blob_size = get_blob_size(store, blob_hash)?;
-                delete_blob(store, blob_hash)?;
+                metrics.bytes_freed += store.blob_store().delete_blob(blob_hash)?;
                 metrics.blobs_deleted += 1;
-                metrics.bytes_freed += blob_size;
             }
         }
 
+        // Delete unreferenced chunked (large-file) blobs and orphaned chunks
+        let (chunked_deleted, chunked_bytes) = store.blob_store().sweep_chunked(live_blobs)?;
+        metrics.blobs_deleted += chunked_deleted;
+        metrics.bytes_freed += chunked_bytes;
+
         Ok(())
     }
 }
@@ -543,56 +790,6 @@ fn enumerate_stored_trees(store: &Store) -> Result<Vec<Sha1Hash>> {
     Ok(hashes)
 }
 
-/// Enumerate all stored blobs
-fn enumerate_stored_blobs(store: &Store) -> Result<Vec<Sha1Hash>> {
-    let blobs_dir = store.tl_dir().join("objects/blobs");
-    let mut hashes = Vec::new();
-
-    if !blobs_dir.exists() {
-        return Ok(hashes);
-    }
-
-    // Walk through fan-out structure (objects/blobs/ab/cdef...)
-    for prefix_entry in fs::read_dir(&blobs_dir)? {
-        let prefix_entry = prefix_entry?;
-        let prefix_path = prefix_entry.path();
-
-        if prefix_path.is_dir() {
-            for file_entry in fs::read_dir(&prefix_path)? {
-                let file_entry = file_entry?;
-                let file_path = file_entry.path();
-
-                if file_path.is_file() {
-                    // Reconstruct hash from prefix + filename
-                    let prefix = prefix_entry.file_name().to_string_lossy().to_string();
-                    let filename = file_entry.file_name().to_string_lossy().to_string();
-                    let hex = format!("{}{}", prefix, filename);
-
-                    if let Ok(hash) = Sha1Hash::from_hex(&hex) {
-                        hashes.push(hash);
-                    }
-                }
-            }
-        }
-    }
-
-    Ok(hashes)
-}
-
-/// Get blob size
-fn get_blob_size(store: &Store, hash: Sha1Hash) -> Result<u64> {
-    let hex = hash.to_hex();
-    let (prefix, rest) = hex.split_at(2);
-    let blob_path = store
-        .tl_dir()
-        .join("objects/blobs")
-        .join(prefix)
-        .join(rest);
-
-    let metadata = fs::metadata(blob_path)?;
-    Ok(metadata.len())
-}
-
 /// Delete a tree
 fn delete_tree(store: &Store, hash: Sha1Hash) -> Result<()> {
     let hex = hash.to_hex();
@@ -610,19 +807,3 @@ fn delete_tree(store: &Store, hash: Sha1Hash) -> Result<()> {
     Ok(())
 }
 
-/// Delete a blob
-fn delete_blob(store: &Store, hash: Sha1Hash) -> Result<()> {
-    let hex = hash.to_hex();
-    let (prefix, rest) = hex.split_at(2);
-    let blob_path = store
-        .tl_dir()
-        .join("objects/blobs")
-        .join(prefix)
-        .join(rest);
-
-    if blob_path.exists() {
-        fs::remove_file(blob_path)?;
-    }
-
-    Ok(())
-}
@@ -0,0 +1,127 @@
+//! History rewriting: remove paths from existing checkpoints
+//!
+//! Checkpoint IDs are not content-addressed, but a rewritten checkpoint is a
+//! different snapshot, so it gets a new ID (keeping the original timestamp,
+//! so ID order still follows time). Every descendant of a rewritten
+//! checkpoint is re-parented and gets a new ID too. Each replacement takes
+//! over its original's position in the journal, so HEAD stays HEAD.
+//!
+//! Blobs that only the removed entries referenced become unreachable and
+//! are deleted by the next GC.
+
+use anyhow::Result;
+use core::{Sha1Hash, Store, Tree};
+use crate::{ChangeKind, Journal};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use ulid::Ulid;
+
+/// Outcome of a path purge
+#[derive(Debug, Default)]
+pub struct PurgeResult {
+    /// Checkpoints whose tree lost entries: (original ID, removed paths)
+    pub purged: Vec<(Ulid, Vec<PathBuf>)>,
+    /// Original ID -> new ID for every rewritten checkpoint
+    /// (purged ones and their descendants)
+    pub remapped: HashMap<Ulid, Ulid>,
+}
+
+/// Remove matching paths from every checkpoint's tree
+///
+/// `scope` limits which checkpoints have paths removed (None: all of them);
+/// descendants outside the scope are still re-parented. With `dry_run`
+/// nothing is written and `remapped` holds the IDs that would be assigned.
+pub fn purge_paths(
+    journal: &Journal,
+    store: &Store,
+    matches: &dyn Fn(&Path) -> bool,
+    scope: Option<&HashSet<Ulid>>,
+    dry_run: bool,
+) -> Result<PurgeResult> {
+    let mut result = PurgeResult::default();
+    let mut replacements = Vec::new();
+
+    // Many checkpoints share a tree; filter each tree once
+    let mut filtered: HashMap<Sha1Hash, (Sha1Hash, Vec<PathBuf>)> = HashMap::new();
+
+    // Journal order puts parents before children
+    for checkpoint in journal.last_n(journal.count())? {
+        let in_scope = scope.is_none_or(|ids| ids.contains(&checkpoint.id));
+
+        let (root_tree, removed) = if in_scope {
+            match filtered.get(&checkpoint.root_tree) {
+                Some(cached) => cached.clone(),
+                None => {
+                    let outcome = filter_tree(store, checkpoint.root_tree, matches, dry_run)?;
+                    filtered.insert(checkpoint.root_tree, outcome.clone());
+                    outcome
+                }
+            }
+        } else {
+            (checkpoint.root_tree, Vec::new())
+        };
+
+        let remap = |id: Ulid| result.remapped.get(&id).copied().unwrap_or(id);
+        let parent = checkpoint.parent.map(remap);
+        let merge_parents: Vec<Ulid> = checkpoint.merge_parents.iter().copied().map(remap).collect();
+
+        if removed.is_empty() && parent == checkpoint.parent && merge_parents == checkpoint.merge_parents {
+            continue;
+        }
+
+        let mut rewritten = checkpoint.clone();
+        rewritten.id = Ulid::from_parts(checkpoint.id.timestamp_ms(), Ulid::new().random());
+        rewritten.parent = parent;
+        rewritten.merge_parents = merge_parents;
+        rewritten.root_tree = root_tree;
+        if in_scope {
+            rewritten.touched_paths.retain(|path| !matches(path));
+            rewritten.changes.retain(|change| !matches(&change.path));
+            for change in &mut rewritten.changes {
+                // The rename source is gone from history
+                if let ChangeKind::Renamed { from } = &change.kind {
+                    if matches(from) {
+                        change.kind = ChangeKind::Created;
+                    }
+                }
+            }
+        }
+
+        if !removed.is_empty() {
+            result.purged.push((checkpoint.id, removed));
+        }
+        result.remapped.insert(checkpoint.id, rewritten.id);
+        replacements.push((checkpoint.id, rewritten));
+    }
+
+    if !dry_run && !replacements.is_empty() {
+        journal.rewrite(&replacements)?;
+    }
+
+    Ok(result)
+}
+
+/// Drop matching entries from a tree, returning the new tree hash
+fn filter_tree(
+    store: &Store,
+    tree_hash: Sha1Hash,
+    matches: &dyn Fn(&Path) -> bool,
+    dry_run: bool,
+) -> Result<(Sha1Hash, Vec<PathBuf>)> {
+    let tree = store.read_tree(tree_hash)?;
+
+    let removed: Vec<PathBuf> = tree.entries_with_paths()
+        .filter_map(|(path_bytes, _)| std::str::from_utf8(path_bytes).ok())
+        .map(PathBuf::from)
+        .filter(|path| matches(path))
+        .collect();
+    if removed.is_empty() {
+        return Ok((tree_hash, removed));
+    }
+
+    let changes = removed.iter().map(|path| (path.as_path(), None)).collect();
+    let new_tree = Tree::update_entries(&tree, changes);
+    let new_hash = if dry_run { new_tree.hash() } else { store.write_tree(&new_tree)? };
+
+    Ok((new_hash, removed))
+}
@@ -158,3 +158,395 @@ fn test_pathmap_recovery() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_parallel_reconcile_matches_sequential() -> anyhow::Result<()> {
+    use journal::incremental::incremental_update_with_workers;
+    use std::path::Path;
+
+    let temp_dir = TempDir::new()?;
+    let repo_root = temp_dir.path();
+    let store = Store::init(repo_root)?;
+
+    // Enough files to cross the parallel threshold
+    let mut paths = Vec::new();
+    for i in 0..300 {
+        let path = PathBuf::from(format!("dir{}/file{}.txt", i % 7, i));
+        std::fs::create_dir_all(repo_root.join(path.parent().unwrap()))?;
+        std::fs::write(repo_root.join(&path), format!("content {}", i))?;
+        paths.push(path);
+    }
+    // A dirty path that was deleted before reconcile
+    paths.push(PathBuf::from("gone.txt"));
+
+    let base_map = PathMap::new(hash_bytes(b"initial"));
+    let dirty: Vec<&Path> = paths.iter().map(|p| p.as_path()).collect();
+
+    let (seq_map, _, seq_hash) = incremental_update_with_workers(&base_map, dirty.clone(), repo_root, &store, 1)?;
+    let (par_map, _, par_hash) = incremental_update_with_workers(&base_map, dirty, repo_root, &store, 4)?;
+
+    assert_eq!(seq_hash, par_hash);
+    assert_eq!(seq_map.len(), 300);
+    assert_eq!(par_map.len(), 300);
+    for path in &paths[..300] {
+        assert!(store.blob_store().has_blob(par_map.get(path).unwrap().blob_hash));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_path_policies_reduce_fidelity() -> anyhow::Result<()> {
+    use core::{PathPolicies, hash::git::hash_blob};
+    use journal::{incremental_update_with_options, GarbageCollector, PinManager, RetentionPolicy, UpdateOptions};
+    use std::path::Path;
+
+    let temp_dir = TempDir::new()?;
+    let repo_root = temp_dir.path();
+    let store = Store::init(repo_root)?;
+    let tl_dir = repo_root.join(".tl");
+    let journal = Journal::open(&tl_dir.join("journal"))?;
+
+    let policies = PathPolicies::parse(repo_root, "\
+hash-only    *.iso
+max-size=8   big/
+latest-only  *.log
+manual       db.sqlite
+")?;
+
+    std::fs::create_dir_all(repo_root.join("big"))?;
+    for (path, content) in [
+        ("disk.iso", "iso image"),
+        ("big/small.txt", "small"),
+        ("big/large.txt", "more than eight bytes"),
+        ("app.log", "log v1"),
+        ("db.sqlite", "db v1"),
+    ] {
+        std::fs::write(repo_root.join(path), content)?;
+    }
+    let paths = ["disk.iso", "big/small.txt", "big/large.txt", "app.log", "db.sqlite"];
+    let dirty: Vec<&Path> = paths.iter().map(Path::new).collect();
+
+    // Automatic checkpoint: manual-only path is left out
+    let auto = UpdateOptions { policies: Some(&policies), ..UpdateOptions::default() };
+    let base_map = PathMap::new(hash_bytes(b"initial"));
+    let (map1, _, tree1) = incremental_update_with_options(&base_map, dirty.clone(), repo_root, &store, auto)?;
+
+    let blobs = store.blob_store();
+    let iso = map1.get(Path::new("disk.iso")).unwrap().blob_hash;
+    assert_eq!(iso, hash_blob(b"iso image"));
+    assert!(blobs.is_omitted(iso));
+    assert!(blobs.has_blob(map1.get(Path::new("big/small.txt")).unwrap().blob_hash));
+    assert!(blobs.is_omitted(map1.get(Path::new("big/large.txt")).unwrap().blob_hash));
+    assert!(map1.get(Path::new("db.sqlite")).is_none());
+
+    // Explicit flush picks it up
+    let manual = UpdateOptions { manual: true, ..auto };
+    let (map1, _, tree1_flushed) = incremental_update_with_options(&map1, vec![Path::new("db.sqlite")], repo_root, &store, manual)?;
+    assert!(blobs.has_blob(map1.get(Path::new("db.sqlite")).unwrap().blob_hash));
+    assert_ne!(tree1, tree1_flushed);
+
+    let cp1 = Checkpoint::new(None, tree1_flushed, CheckpointReason::FsBatch, vec![], CheckpointMeta::default());
+    journal.append(&cp1)?;
+    std::thread::sleep(std::time::Duration::from_millis(2));
+
+    // New log version: GC keeps only HEAD's copy of a latest-only path
+    std::fs::write(repo_root.join("app.log"), "log v2")?;
+    let (map2, _, tree2) = incremental_update_with_options(&map1, vec![Path::new("app.log")], repo_root, &store, auto)?;
+    let cp2 = Checkpoint::new(Some(cp1.id), tree2, CheckpointReason::FsBatch, vec![], CheckpointMeta::default());
+    journal.append(&cp2)?;
+
+    let log_v1 = map1.get(Path::new("app.log")).unwrap().blob_hash;
+    let log_v2 = map2.get(Path::new("app.log")).unwrap().blob_hash;
+
+    GarbageCollector::new(RetentionPolicy::default())
+        .with_path_policies(policies)
+        .collect(&journal, &store, &PinManager::new(&tl_dir), None)?;
+
+    assert!(blobs.is_omitted(log_v1));
+    assert!(blobs.has_blob(log_v2));
+    assert!(blobs.has_blob(map1.get(Path::new("db.sqlite")).unwrap().blob_hash));
+
+    Ok(())
+}
+
+#[test]
+fn test_secret_scanning_on_ingest() -> anyhow::Result<()> {
+    use core::{SecretAction, SecretScanner, hash::git::hash_blob};
+    use core::secrets::{SecretRuleConfig, SecretsConfig};
+    use journal::{incremental_update_with_options, UpdateOptions};
+    use std::path::Path;
+
+    let temp_dir = TempDir::new()?;
+    let repo_root = temp_dir.path();
+    let store = Store::init(repo_root)?;
+
+    let config = SecretsConfig {
+        rules: vec![
+            SecretRuleConfig { name: "itk".into(), pattern: "itk_[a-z0-9]{8}".into(), action: SecretAction::Redact },
+            SecretRuleConfig { name: "dontkeep".into(), pattern: "DONTKEEP".into(), action: SecretAction::Skip },
+        ],
+        ..SecretsConfig::default()
+    };
+    let scanner = SecretScanner::from_config(&config)?;
+
+    std::fs::write(repo_root.join("app.cfg"), "token = itk_abcd1234\n")?;
+    std::fs::write(repo_root.join("notes.txt"), "DONTKEEP this\n")?;
+    std::fs::write(repo_root.join("clean.txt"), "nothing here\n")?;
+
+    let options = UpdateOptions { scanner: Some(&scanner), ..UpdateOptions::default() };
+    let base_map = PathMap::new(hash_bytes(b"initial"));
+    let dirty = vec![Path::new("app.cfg"), Path::new("notes.txt"), Path::new("clean.txt")];
+    let (map, _, _) = incremental_update_with_options(&base_map, dirty, repo_root, &store, options)?;
+
+    // Redacted copy is stored in place of the original
+    let cfg = map.get(Path::new("app.cfg")).unwrap().blob_hash;
+    assert_eq!(store.blob_store().read_blob(cfg)?, b"token = [REDACTED:itk]\n");
+    assert!(!store.blob_store().has_blob(hash_blob(b"token = itk_abcd1234\n")));
+
+    // Skipped content never enters the store
+    assert!(map.get(Path::new("notes.txt")).is_none());
+    assert!(!store.blob_store().has_blob(hash_blob(b"DONTKEEP this\n")));
+
+    assert!(store.blob_store().has_blob(map.get(Path::new("clean.txt")).unwrap().blob_hash));
+
+    // Chunked large files are scanned too
+    let config_path = repo_root.join(".tl/config.toml");
+    let config = std::fs::read_to_string(&config_path)?
+        .replace("large_file_threshold = 67108864", "large_file_threshold = 4096");
+    std::fs::write(&config_path, config)?;
+    let store = Store::open(repo_root)?;
+
+    let mut large = "filler line\n".repeat(1000);
+    large.push_str("DONTKEEP this\n");
+    std::fs::write(repo_root.join("dump.log"), &large)?;
+
+    let dirty = vec![Path::new("dump.log")];
+    let (map, _, _) = incremental_update_with_options(&map, dirty, repo_root, &store, options)?;
+    assert!(map.get(Path::new("dump.log")).is_none());
+    assert!(!store.blob_store().has_blob(hash_blob(large.as_bytes())));
+
+    Ok(())
+}
+
+#[test]
+fn test_purge_path_rewrites_history() -> anyhow::Result<()> {
+    use journal::{purge_paths, PinManager, StashEntry, StashManager};
+    use std::collections::HashSet;
+    use std::path::Path;
+
+    let temp_dir = TempDir::new()?;
+    let store = Store::init(temp_dir.path())?;
+    let tl_dir = temp_dir.path().join(".tl");
+    let journal = Journal::open(&tl_dir.join("journal"))?;
+
+    // Three checkpoints; the dump is captured in the first two
+    let keep = core::hash::git::hash_blob(b"fn main() {}");
+    let dump = core::hash::git::hash_blob(b"4 GB of rows");
+    store.blob_store().write_blob(keep, b"fn main() {}")?;
+    store.blob_store().write_blob(dump, b"4 GB of rows")?;
+    let mut with_dump = Tree::new();
+    with_dump.insert(Path::new("src/main.rs"), Entry::file(0o100644, keep));
+    with_dump.insert(Path::new("dump.sql"), Entry::file(0o100644, dump));
+    let mut without_dump = Tree::new();
+    without_dump.insert(Path::new("src/main.rs"), Entry::file(0o100644, keep));
+    let tree_with = store.write_tree(&with_dump)?;
+    let tree_without = store.write_tree(&without_dump)?;
+
+    let cp1 = Checkpoint::new(None, tree_with, CheckpointReason::FsBatch, vec![PathBuf::from("dump.sql")], CheckpointMeta::default());
+    journal.append(&cp1)?;
+    let cp2 = Checkpoint::new(Some(cp1.id), tree_with, CheckpointReason::FsBatch, vec![], CheckpointMeta::default());
+    journal.append(&cp2)?;
+    let cp3 = Checkpoint::new(Some(cp2.id), tree_without, CheckpointReason::FsBatch, vec![], CheckpointMeta::default());
+    journal.append(&cp3)?;
+
+    let pins = PinManager::new(&tl_dir);
+    pins.pin("release", cp2.id)?;
+    let stashes = StashManager::new(&tl_dir);
+    stashes.push(StashEntry { checkpoint_id: cp1.id, created_at_ms: 0, message: None, base_checkpoint_id: Some(cp3.id) })?;
+
+    let matches = |path: &Path| path == Path::new("dump.sql");
+
+    // Dry run changes nothing
+    let plan = purge_paths(&journal, &store, &matches, None, true)?;
+    assert_eq!(plan.purged.len(), 2);
+    assert_eq!(plan.remapped.len(), 3);
+    assert!(journal.get(&cp1.id)?.is_some());
+
+    // Limited to cp2: cp1 keeps the dump, cp3 is only re-parented
+    let scope: HashSet<_> = [cp2.id].into_iter().collect();
+    let scoped = purge_paths(&journal, &store, &matches, Some(&scope), true)?;
+    assert_eq!(scoped.purged, vec![(cp2.id, vec![PathBuf::from("dump.sql")])]);
+    assert!(!scoped.remapped.contains_key(&cp1.id));
+
+    let result = purge_paths(&journal, &store, &matches, None, false)?;
+    pins.remap(&result.remapped)?;
+    stashes.remap(&result.remapped)?;
+
+    let new1 = journal.get(&result.remapped[&cp1.id])?.unwrap();
+    let new2 = journal.get(&result.remapped[&cp2.id])?.unwrap();
+    let new3 = journal.get(&result.remapped[&cp3.id])?.unwrap();
+    assert!(journal.get(&cp1.id)?.is_none());
+    assert_eq!(new1.root_tree, tree_without);
+    assert!(new1.touched_paths.is_empty());
+    assert_eq!(new2.parent, Some(new1.id));
+    assert_eq!(new3.parent, Some(new2.id));
+    assert_eq!(new3.root_tree, tree_without);
+    assert_eq!(journal.latest()?.unwrap().id, new3.id);
+
+    assert_eq!(pins.list_pins()?, vec![("release".to_string(), new2.id)]);
+    let stash = stashes.peek()?.unwrap();
+    assert_eq!(stash.checkpoint_id, new1.id);
+    assert_eq!(stash.base_checkpoint_id, Some(new3.id));
+
+    // Nothing left to purge
+    assert!(purge_paths(&journal, &store, &matches, None, false)?.remapped.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_retention_tightens_under_storage_pressure() {
+    use journal::RetentionPolicy;
+
+    let base = RetentionPolicy::default();
+    let step1 = base.tightened(1);
+    assert_eq!(step1.retain_dense_count, base.retain_dense_count / 2);
+    assert_eq!(step1.retain_dense_window_ms, base.retain_dense_window_ms / 2);
+    assert!(step1.retain_pins);
+
+    // Each step is at least as strict, down to a floor
+    let step3 = base.tightened(3);
+    assert!(step3.retain_dense_count < step1.retain_dense_count);
+    let floor = base.tightened(20);
+    assert_eq!(floor.retain_dense_count, 10);
+    assert_eq!(floor.retain_dense_window_ms, 10 * 60 * 1000);
+}
+
+#[test]
+fn test_gc_compacts_expired_runs() -> anyhow::Result<()> {
+    use journal::{GarbageCollector, PinManager, RetentionPolicy};
+    use std::path::Path;
+    use ulid::Ulid;
+
+    let temp_dir = TempDir::new()?;
+    let store = Store::init(temp_dir.path())?;
+    let tl_dir = temp_dir.path().join(".tl");
+    let journal = Journal::open(&tl_dir.join("journal"))?;
+
+    // A chain of eight checkpoints, one file per version; all but the last two expired
+    let now_ms = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?.as_millis() as u64;
+    let mut chain: Vec<Checkpoint> = Vec::new();
+    for i in 0..8u64 {
+        let content = format!("version {}", i);
+        let blob = core::hash::git::hash_blob(content.as_bytes());
+        store.blob_store().write_blob(blob, content.as_bytes())?;
+        let mut tree = Tree::new();
+        tree.insert(Path::new("a.txt"), Entry::file(0o100644, blob));
+        let root_tree = store.write_tree(&tree)?;
+
+        let touched = vec![PathBuf::from("a.txt"), PathBuf::from(format!("{}.tmp", i))];
+        let meta = CheckpointMeta { files_changed: 2, bytes_added: 10, bytes_removed: 1 };
+        let mut checkpoint = Checkpoint::new(chain.last().map(|cp| cp.id), root_tree, CheckpointReason::FsBatch, touched, meta);
+        let age_ms = if i < 6 { (48 - i) * 60 * 60 * 1000 } else { 0 };
+        checkpoint.ts_unix_ms = now_ms - age_ms + i;
+        checkpoint.id = Ulid::from_parts(checkpoint.ts_unix_ms, Ulid::new().random());
+        journal.append(&checkpoint)?;
+        chain.push(checkpoint);
+    }
+
+    // The pin splits the expired region into two runs
+    let pins = PinManager::new(&tl_dir);
+    pins.pin("keep", chain[3].id)?;
+
+    let policy = RetentionPolicy { retain_dense_count: 2, retain_dense_window_ms: 60 * 60 * 1000, retain_pins: true };
+    let metrics = GarbageCollector::new(policy.clone()).collect(&journal, &store, &pins, None)?;
+    assert_eq!(metrics.checkpoints_compacted, 2);
+    assert_eq!(metrics.checkpoints_deleted, 3);
+    assert_eq!(journal.count(), 5);
+
+    // HEAD~N walks the compacted history: c7, c6, [c4 c5], c3, [c0 c1 c2]
+    let history = journal.ancestors(&chain[7].id, None)?;
+    let ids: Vec<Ulid> = history.iter().map(|cp| cp.id).collect();
+    assert_eq!(ids, vec![chain[7].id, chain[6].id, metrics.remapped[&chain[5].id], chain[3].id, metrics.remapped[&chain[2].id]]);
+    assert_eq!(history[1].parent, Some(metrics.remapped[&chain[5].id]));
+    assert_eq!(history[3].parent, Some(metrics.remapped[&chain[2].id]));
+
+    let first = &history[4];
+    assert_eq!(first.reason, CheckpointReason::GcCompact);
+    assert_eq!(first.parent, None);
+    assert_eq!(first.root_tree, chain[2].root_tree);
+    assert_eq!(first.id.timestamp_ms(), chain[2].id.timestamp_ms());
+    assert_eq!(first.touched_paths.len(), 4);
+    assert_eq!(first.meta.files_changed, 4);
+    assert_eq!(first.meta.bytes_added, 30);
+    assert_eq!(first.meta.bytes_removed, 3);
+
+    // Folded-away versions are swept; compacted ones keep their content
+    let version = |i: usize| core::hash::git::hash_blob(format!("version {}", i).as_bytes());
+    for i in [0, 1, 4] {
+        assert!(!store.blob_store().has_blob(version(i)));
+    }
+    for i in [2, 3, 5, 6, 7] {
+        assert!(store.blob_store().has_blob(version(i)));
+    }
+
+    // Nothing more to fold
+    let again = GarbageCollector::new(policy).collect(&journal, &store, &pins, None)?;
+    assert_eq!(again.checkpoints_compacted, 0);
+    assert_eq!(again.checkpoints_deleted, 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_gc_keeps_referenced_checkpoints_out_of_runs() -> anyhow::Result<()> {
+    use journal::{GarbageCollector, PinManager, RetentionPolicy};
+    use std::collections::HashSet;
+    use std::path::Path;
+    use ulid::Ulid;
+
+    let temp_dir = TempDir::new()?;
+    let store = Store::init(temp_dir.path())?;
+    let tl_dir = temp_dir.path().join(".tl");
+    let journal = Journal::open(&tl_dir.join("journal"))?;
+
+    // A chain of eight checkpoints; all but the last two expired
+    let now_ms = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?.as_millis() as u64;
+    let mut chain: Vec<Checkpoint> = Vec::new();
+    for i in 0..8u64 {
+        let content = format!("version {}", i);
+        let blob = core::hash::git::hash_blob(content.as_bytes());
+        store.blob_store().write_blob(blob, content.as_bytes())?;
+        let mut tree = Tree::new();
+        tree.insert(Path::new("a.txt"), Entry::file(0o100644, blob));
+        let root_tree = store.write_tree(&tree)?;
+
+        let mut checkpoint = Checkpoint::new(chain.last().map(|cp| cp.id), root_tree, CheckpointReason::FsBatch, vec![PathBuf::from("a.txt")], CheckpointMeta::default());
+        let age_ms = if i < 6 { (48 - i) * 60 * 60 * 1000 } else { 0 };
+        checkpoint.ts_unix_ms = now_ms - age_ms + i;
+        checkpoint.id = Ulid::from_parts(checkpoint.ts_unix_ms, Ulid::new().random());
+        journal.append(&checkpoint)?;
+        chain.push(checkpoint);
+    }
+
+    // Mid-run checkpoints held by a stash and a JJ mapping
+    let referenced: HashSet<Ulid> = [chain[1].id, chain[4].id].into_iter().collect();
+
+    let pins = PinManager::new(&tl_dir);
+    let policy = RetentionPolicy { retain_dense_count: 2, retain_dense_window_ms: 60 * 60 * 1000, retain_pins: true };
+    let metrics = GarbageCollector::new(policy).collect(&journal, &store, &pins, Some(&referenced))?;
+
+    // Only [c2 c3] is left to fold; c0 and c5 are runs of one
+    assert_eq!(metrics.checkpoints_compacted, 1);
+    assert_eq!(metrics.checkpoints_deleted, 1);
+    assert!(journal.get(&chain[2].id)?.is_none());
+    for id in &referenced {
+        assert!(journal.get(id)?.is_some());
+    }
+
+    let ids: Vec<Ulid> = journal.ancestors(&chain[7].id, None)?.iter().map(|cp| cp.id).collect();
+    assert_eq!(ids, vec![chain[7].id, chain[6].id, chain[5].id, chain[4].id, metrics.remapped[&chain[3].id], chain[1].id, chain[0].id]);
+
+    Ok(())
+}
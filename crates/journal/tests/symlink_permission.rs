@@ -140,6 +140,7 @@ fn test_no_change_when_content_and_mode_unchanged() -> Result<()> {
     assert_eq!(entry1.blob_hash, entry2.blob_hash);
     assert_eq!(entry1.mode, entry2.mode);
     assert_eq!(hash1, hash2, "Tree hash should be unchanged when nothing changes");
+    assert_eq!(map1.root_tree, hash1, "Map should record the tree it was built into");
 
     Ok(())
 }
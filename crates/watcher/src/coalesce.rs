@@ -10,7 +10,8 @@ use crate::{EventKind, ModifyKind, WatchEvent};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::Duration;
+use tokio::time::Instant;
 
 /// Event coalescer that merges and deduplicates file system events
 pub struct Coalescer {
@@ -111,7 +112,7 @@ impl Coalescer {
     /// Coalesce two event kinds
     ///
     /// Returns None if events cancel out, or Some(kind) for the coalesced event.
-    fn coalesce_kinds(existing: EventKind, new: EventKind) -> Option<EventKind> {
+    pub(crate) fn coalesce_kinds(existing: EventKind, new: EventKind) -> Option<EventKind> {
         use EventKind::*;
 
         match (existing, new) {
@@ -11,7 +11,9 @@
 use dashmap::DashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::Duration;
+// tokio's clock, so a paused runtime (trace replay) drives the timing
+use tokio::time::Instant;
 use tokio::sync::mpsc;
 use tokio::time::sleep;
 
@@ -5,6 +5,8 @@
 //! - Event coalescing and deduplication
 //! - Overflow recovery with targeted rescan
 //! - Path interning for memory optimization
+//! - A polling backend for network and FUSE filesystems
+//! - Event trace recording and deterministic replay
 
 pub mod platform;
 pub mod debounce;
@@ -12,9 +14,11 @@ pub mod coalesce;
 pub mod overflow;
 pub mod reconcile;
 pub mod ignore;
+pub mod record;
 
 use anyhow::Result;
 use dashmap::DashMap;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -40,6 +44,18 @@ pub struct WatcherConfig {
 
     /// Overflow recovery strategy
     pub overflow_strategy: OverflowStrategy,
+
+    /// Watcher backend (default: auto)
+    pub backend: WatcherBackend,
+
+    /// Shortest interval between polling scans (default: 1s)
+    pub poll_interval: Duration,
+
+    /// Longest interval between polling scans while idle (default: 10s)
+    pub max_poll_interval: Duration,
+
+    /// Record raw platform events to this trace file (default: off)
+    pub record_events: Option<PathBuf>,
 }
 
 impl Default for WatcherConfig {
@@ -51,6 +67,42 @@ impl Default for WatcherConfig {
             fsevent_latency: Duration::from_millis(50),
             detect_atomic_saves: true,
             overflow_strategy: OverflowStrategy::PauseAndRescan,
+            backend: WatcherBackend::Auto,
+            poll_interval: Duration::from_secs(1),
+            max_poll_interval: Duration::from_secs(10),
+            record_events: None,
+        }
+    }
+}
+
+/// Which file watching backend to use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatcherBackend {
+    /// fanotify where permitted, else native notifications; polling on
+    /// network/FUSE filesystems
+    Auto,
+
+    /// Always use native notifications (inotify, FSEvents)
+    Native,
+
+    /// Always poll with stat snapshots
+    Poll,
+
+    /// One fanotify mark for the whole filesystem (Linux, needs
+    /// CAP_SYS_ADMIN; falls back to inotify without it)
+    Fanotify,
+}
+
+impl std::str::FromStr for WatcherBackend {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "auto" => Ok(WatcherBackend::Auto),
+            "native" => Ok(WatcherBackend::Native),
+            "poll" => Ok(WatcherBackend::Poll),
+            "fanotify" => Ok(WatcherBackend::Fanotify),
+            other => anyhow::bail!("Unknown watcher backend '{}' (expected auto, native, poll or fanotify)", other),
         }
     }
 }
@@ -129,7 +181,7 @@ impl Default for PathInterner {
 }
 
 /// File system event with interned paths
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct WatchEvent {
     /// Path that changed (interned for memory efficiency)
     pub path: Arc<Path>,
@@ -177,6 +229,32 @@ pub enum ModifyKind {
     Any,
 }
 
+/// The raw events behind one batched path
+///
+/// Batches carry bare paths; the watcher keeps this summary alongside so a
+/// consumer can tell what happened to a path, and how often, once it has
+/// been batched (`Watcher::take_provenance`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventProvenance {
+    /// Net kind after coalescing (Create + Modify → Create, ...)
+    pub kind: EventKind,
+
+    /// Number of raw events seen
+    pub events: u32,
+}
+
+impl EventProvenance {
+    /// Fold later events for the same path into this summary
+    ///
+    /// Returns None when they cancel out (created, then deleted).
+    pub fn then(self, later: EventProvenance) -> Option<Self> {
+        coalesce::Coalescer::coalesce_kinds(self.kind, later.kind).map(|kind| Self {
+            kind,
+            events: self.events.saturating_add(later.events),
+        })
+    }
+}
+
 // =============================================================================
 // Watcher Metrics (Fix 15)
 // =============================================================================
@@ -282,6 +360,12 @@ pub struct Watcher {
 
     /// Watcher metrics (Fix 15)
     metrics: WatcherMetrics,
+
+    /// Trace of raw platform events, when recording
+    recorder: Option<record::EventRecorder>,
+
+    /// Event summaries for paths not yet taken by the consumer
+    provenance: HashMap<Arc<Path>, EventProvenance>,
 }
 
 impl Watcher {
@@ -315,6 +399,8 @@ impl Watcher {
             overflow_recovery,
             is_running: false,
             metrics: WatcherMetrics::new(),
+            recorder: None,
+            provenance: HashMap::new(),
         })
     }
 
@@ -351,7 +437,28 @@ impl Watcher {
         }
 
         // Create platform watcher
-        let mut platform_watcher = platform::create_platform_watcher(&self.root, &self.config)?;
+        let platform_watcher = platform::create_platform_watcher(&self.root, &self.config)?;
+        self.start_with(platform_watcher).await
+    }
+
+    /// Start watching with the given platform backend
+    ///
+    /// Used to replay a recorded trace (`platform::ReplayWatcher`) through
+    /// the same pipeline as live events.
+    pub async fn start_with(&mut self, mut platform_watcher: Box<dyn platform::PlatformWatcher>) -> Result<()> {
+        if self.is_running {
+            return Ok(());
+        }
+
+        if let Some(cache) = self.overflow_recovery.stat_cache() {
+            platform_watcher.set_baseline(Arc::clone(cache));
+        }
+
+        if let Some(trace_path) = &self.config.record_events {
+            let backend = platform_watcher.diagnostics().backend;
+            self.recorder = Some(record::EventRecorder::create(trace_path, &self.config, &backend)?);
+            tracing::info!("Recording watcher events to {}", trace_path.display());
+        }
 
         // Start platform watcher
         platform_watcher.start().await?;
@@ -375,6 +482,10 @@ impl Watcher {
             watcher.stop().await?;
         }
 
+        if let Some(mut recorder) = self.recorder.take() {
+            recorder.flush()?;
+        }
+
         self.is_running = false;
         Ok(())
     }
@@ -431,6 +542,14 @@ impl Watcher {
             // Record event processed metric (Fix 15)
             self.metrics.record_event();
 
+            // A trace that can't be written is dropped, not fatal
+            if let Some(recorder) = self.recorder.as_mut() {
+                if let Err(e) = recorder.record(&event) {
+                    tracing::warn!("Stopped recording watcher events: {}", e);
+                    self.recorder = None;
+                }
+            }
+
             // Handle overflow events
             if matches!(event.kind, EventKind::Overflow) {
                 // Will be handled on next poll
@@ -444,9 +563,13 @@ impl Watcher {
             let interned_event = WatchEvent::new(interned_path, event.kind);
 
             // Push to coalescer
-            if let Some(immediate_event) = self.coalescer.push(interned_event) {
+            if let Some(immediate_event) = self.coalescer.push(interned_event.clone()) {
                 // Atomic save detected or other immediate emission
-                self.debouncer.push(immediate_event.path);
+                let target = self.interner.intern(immediate_event.path.as_ref());
+                Self::note_provenance(&mut self.provenance, target.clone(), immediate_event.kind);
+                self.debouncer.push(target);
+            } else {
+                Self::note_provenance(&mut self.provenance, interned_event.path, interned_event.kind);
             }
         }
 
@@ -457,6 +580,13 @@ impl Watcher {
             processed_any = true;
         }
 
+        if processed_any {
+            if let Some(Err(e)) = self.recorder.as_mut().map(|r| r.flush()) {
+                tracing::warn!("Stopped recording watcher events: {}", e);
+                self.recorder = None;
+            }
+        }
+
         // If no events were processed, sleep briefly to prevent tight loop
         // This ensures poll_events() always awaits at least once
         if !processed_any {
@@ -500,15 +630,55 @@ impl Watcher {
         all_paths
     }
 
+    /// Take the event summary for a batched path
+    ///
+    /// None for paths that reached a batch without events of their own,
+    /// such as those found by overflow recovery.
+    pub fn take_provenance(&mut self, path: &Path) -> Option<EventProvenance> {
+        self.provenance.remove(path)
+    }
+
+    fn note_provenance(provenance: &mut HashMap<Arc<Path>, EventProvenance>, path: Arc<Path>, kind: EventKind) {
+        let event = EventProvenance { kind, events: 1 };
+        match provenance.remove(&path) {
+            Some(earlier) => {
+                if let Some(merged) = earlier.then(event) {
+                    provenance.insert(path, merged);
+                }
+            }
+            None => {
+                provenance.insert(path, event);
+            }
+        }
+    }
+
     /// Check if the watcher is currently running
     pub fn is_running(&self) -> bool {
         self.is_running
     }
 
+    /// Events written to the trace so far (None when not recording)
+    pub fn recorded_events(&self) -> Option<u64> {
+        self.recorder.as_ref().map(|r| r.recorded())
+    }
+
+    /// Diagnostics from the platform backend (None until started)
+    pub fn diagnostics(&self) -> Option<platform::WatcherDiagnostics> {
+        self.platform_watcher.as_ref().map(|w| w.diagnostics())
+    }
+
     /// Update the last checkpoint time for overflow recovery
     pub fn mark_checkpoint(&mut self, time: std::time::SystemTime) {
         self.overflow_recovery.mark_checkpoint(time);
     }
+
+    /// Provide stat data from the last checkpoint for overflow recovery
+    ///
+    /// Turns recovery into an exact stat-based rescan (only files without
+    /// recorded stat data are hashed).
+    pub fn set_stat_cache(&mut self, cache: overflow::SharedStatCache) {
+        self.overflow_recovery.set_stat_cache(cache);
+    }
 }
 
 #[cfg(test)]
@@ -589,6 +759,11 @@ mod tests {
         assert_eq!(config.fsevent_latency, Duration::from_millis(50));
         assert!(config.detect_atomic_saves);
         assert_eq!(config.overflow_strategy, OverflowStrategy::PauseAndRescan);
+        assert_eq!(config.backend, WatcherBackend::Auto);
+        assert!(config.poll_interval <= config.max_poll_interval);
+        assert_eq!("poll".parse::<WatcherBackend>().unwrap(), WatcherBackend::Poll);
+        assert_eq!("fanotify".parse::<WatcherBackend>().unwrap(), WatcherBackend::Fanotify);
+        assert!("kqueue".parse::<WatcherBackend>().is_err());
     }
 
     #[test]
@@ -680,6 +855,98 @@ mod tests {
         watcher.stop().await.unwrap();
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn test_replayed_trace_is_deterministic() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let trace_path = temp_dir.path().join("events.trace");
+        std::fs::write(
+            &trace_path,
+            "# timelapse watcher trace v1\n\
+             # debounce_ms: 300\n\
+             0 create notes.txt\n\
+             1000 delete notes.txt\n\
+             5000 modify-data .main.rs.swp\n\
+             100000 modify-data a.txt\n\
+             150000 modify-data a.txt\n\
+             400000 modify-metadata a.txt\n",
+        )
+        .unwrap();
+        let trace = record::EventTrace::load(&trace_path).unwrap();
+        let mut config = WatcherConfig::default();
+        trace.apply_to(&mut config);
+
+        // Under the paused clock, every run emits the same paths at the same times
+        let mut runs = Vec::new();
+        for _ in 0..2 {
+            let mut watcher = Watcher::with_config(temp_dir.path(), config.clone()).unwrap();
+            let replay = platform::ReplayWatcher::new(trace.events.clone());
+            watcher.start_with(Box::new(replay)).await.unwrap();
+
+            let start = tokio::time::Instant::now();
+            let mut emitted = Vec::new();
+            while start.elapsed() < Duration::from_secs(2) {
+                watcher.poll_events().await.unwrap();
+                for path in watcher.next_batch() {
+                    emitted.push((start.elapsed().as_millis(), path.to_path_buf()));
+                }
+            }
+            watcher.stop().await.unwrap();
+            runs.push(emitted);
+        }
+        assert_eq!(runs[0], runs[1]);
+
+        // The create/delete pair cancelled out, the swap file became main.rs,
+        // and the later metadata change re-armed a.txt's debounce timer
+        let paths: Vec<&Path> = runs[0].iter().map(|(_, path)| path.as_path()).collect();
+        assert_eq!(paths, vec![Path::new("main.rs"), Path::new("a.txt")]);
+        assert!(runs[0][1].0 >= 400 + 300);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_batched_paths_carry_provenance() {
+        use record::RecordedEvent;
+        use tempfile::TempDir;
+
+        let event = |ms: u64, path: &str, kind: EventKind| RecordedEvent {
+            offset: Duration::from_millis(ms),
+            event: WatchEvent::new(Arc::from(Path::new(path)), kind),
+        };
+        let events = vec![
+            event(0, "new.rs", EventKind::Create),
+            event(10, "new.rs", EventKind::Modify(ModifyKind::Data)),
+            event(20, "new.rs", EventKind::Modify(ModifyKind::Data)),
+            event(30, "run.sh", EventKind::Modify(ModifyKind::Metadata)),
+            event(40, "scratch.txt", EventKind::Create),
+            event(50, "scratch.txt", EventKind::Delete),
+        ];
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut watcher = Watcher::new(temp_dir.path()).unwrap();
+        watcher.start_with(Box::new(platform::ReplayWatcher::new(events))).await.unwrap();
+
+        let start = tokio::time::Instant::now();
+        let mut batched = Vec::new();
+        while start.elapsed() < Duration::from_secs(2) {
+            watcher.poll_events().await.unwrap();
+            batched.extend(watcher.next_batch());
+        }
+
+        assert_eq!(batched.len(), 2);
+        assert_eq!(
+            watcher.take_provenance(Path::new("new.rs")),
+            Some(EventProvenance { kind: EventKind::Create, events: 3 })
+        );
+        assert_eq!(
+            watcher.take_provenance(Path::new("run.sh")),
+            Some(EventProvenance { kind: EventKind::Modify(ModifyKind::Metadata), events: 1 })
+        );
+        // Taken once; the cancelled pair left nothing behind
+        assert_eq!(watcher.take_provenance(Path::new("new.rs")), None);
+        assert_eq!(watcher.take_provenance(Path::new("scratch.txt")), None);
+    }
+
     #[test]
     fn test_watcher_flush() {
         use tempfile::TempDir;
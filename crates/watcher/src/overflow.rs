@@ -4,6 +4,8 @@
 //! provides intelligent recovery by:
 //! - Detecting overflow conditions from platform watchers
 //! - Performing targeted rescans based on mtime heuristics (NOT full repo scans)
+//! - Or, when a stat cache is available, a stat-only full walk that compares
+//!   each file against the stat recorded when it was last hashed
 //! - Finding files modified since last checkpoint
 //! - Minimizing performance impact during recovery
 
@@ -12,8 +14,13 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::SystemTime;
+use timelapse_core::hash::{git, hash_file_stable};
+use timelapse_core::{walk_worktree, Entry, FileStat, IgnoreConfig, IgnoreRules, StatCache, WorktreeFile};
 use walkdir::WalkDir;
 
+/// Shared stat cache handle (a snapshot of the daemon's PathMap)
+pub type SharedStatCache = Arc<dyn StatCache + Send + Sync>;
+
 /// Overflow recovery coordinator
 pub struct OverflowRecovery {
     /// Root directory being watched
@@ -22,6 +29,12 @@ pub struct OverflowRecovery {
     /// Last successful checkpoint time
     /// Files modified after this time are candidates for rescan
     last_checkpoint: Option<SystemTime>,
+
+    /// Stat data from the last checkpoint (enables exact stat-based rescans)
+    stat_cache: Option<SharedStatCache>,
+
+    /// Repository ignore rules (.gitignore, .tlignore, built-ins)
+    rules: IgnoreRules,
 }
 
 impl OverflowRecovery {
@@ -30,9 +43,23 @@ impl OverflowRecovery {
         Self {
             root: root.as_ref().to_path_buf(),
             last_checkpoint: None,
+            stat_cache: None,
+            rules: load_ignore_rules(root.as_ref()),
         }
     }
 
+    /// Set the stat cache used for recovery
+    ///
+    /// Call this after each checkpoint with the updated PathMap.
+    pub fn set_stat_cache(&mut self, cache: SharedStatCache) {
+        self.stat_cache = Some(cache);
+    }
+
+    /// Stat cache currently used for recovery, if any
+    pub fn stat_cache(&self) -> Option<&SharedStatCache> {
+        self.stat_cache.as_ref()
+    }
+
     /// Update the last checkpoint time
     ///
     /// Call this after each successful checkpoint to track what's been saved.
@@ -43,8 +70,14 @@ impl OverflowRecovery {
     /// Perform targeted overflow recovery
     ///
     /// Returns paths that were modified since the last checkpoint.
-    /// Uses mtime-based heuristics to avoid full repository scans.
+    /// With a stat cache this is a stat-only walk that also catches deletions;
+    /// otherwise mtime-based heuristics avoid full repository scans.
     pub fn recover(&self) -> Result<Vec<Arc<Path>>> {
+        if let Some(cache) = &self.stat_cache {
+            let changed = scan_with_stat_cache(&self.root, cache.as_ref(), &self.rules)?;
+            return Ok(changed.into_iter().map(Arc::from).collect());
+        }
+
         let checkpoint_time = self
             .last_checkpoint
             .unwrap_or_else(|| SystemTime::UNIX_EPOCH);
@@ -126,6 +159,13 @@ impl OverflowRecovery {
 
     /// Check if a path should be ignored during recovery
     fn should_ignore(&self, path: &Path) -> bool {
+        // Repository ignore rules (.gitignore, .tlignore)
+        if let Ok(relative) = path.strip_prefix(&self.root) {
+            if !relative.as_os_str().is_empty() && self.rules.should_ignore(relative) {
+                return true;
+            }
+        }
+
         let path_str = path.to_string_lossy();
 
         // Ignore .tl directory
@@ -181,24 +221,81 @@ impl OverflowRecovery {
     /// Prefer targeted recovery whenever possible.
     #[allow(dead_code)]
     fn full_scan(&self) -> Result<Vec<Arc<Path>>> {
-        let mut all_files = Vec::new();
-
-        for entry in WalkDir::new(&self.root)
-            .follow_links(false)
+        Ok(walk_worktree(&self.root, &self.rules)?
             .into_iter()
-            .filter_entry(|e| !self.should_ignore(e.path()))
-        {
-            let entry = entry.context("Failed to read directory entry")?;
+            .map(|file| Arc::from(file.rel_path))
+            .collect())
+    }
+}
 
-            if entry.file_type().is_file() {
-                if let Ok(relative) = entry.path().strip_prefix(&self.root) {
-                    all_files.push(Arc::from(relative));
-                }
-            }
+/// Load the repository's ignore rules, falling back to built-ins only
+pub(crate) fn load_ignore_rules(root: &Path) -> IgnoreRules {
+    IgnoreRules::load(root, IgnoreConfig::default()).unwrap_or_else(|e| {
+        tracing::warn!("Failed to load ignore rules ({}), using built-in patterns only", e);
+        IgnoreRules::builtin_only(root)
+    })
+}
+
+/// Walk `root` and compare every file against a stat cache
+///
+/// Returns repo-relative paths that are new, whose stat differs from the
+/// cache, and cached paths that no longer exist on disk. Entries recorded
+/// without a stat (symlinks, racily-clean files, maps built from a tree)
+/// are compared by content hash instead.
+pub(crate) fn scan_with_stat_cache(
+    root: &Path,
+    cache: &dyn StatCache,
+    rules: &IgnoreRules,
+) -> Result<Vec<PathBuf>> {
+    let mut changed = Vec::new();
+
+    for file in walk_worktree(root, rules)? {
+        let unchanged = match cache.cached_stat(&file.rel_path) {
+            Some(stat) => stat == FileStat::from_metadata(&file.metadata),
+            None => matches_cached_entry(root, &file, cache),
+        };
+        if !unchanged {
+            changed.push(file.rel_path);
         }
+    }
 
-        Ok(all_files)
+    // Deletions: cached paths that are gone
+    for path in cache.cached_paths() {
+        if !rules.should_ignore(&path) && fs::symlink_metadata(root.join(&path)).is_err() {
+            changed.push(path);
+        }
     }
+
+    Ok(changed)
+}
+
+/// True if a file without a cached stat still matches its recorded entry
+fn matches_cached_entry(root: &Path, file: &WorktreeFile, cache: &dyn StatCache) -> bool {
+    let Some(cached) = cache.cached_entry(&file.rel_path) else {
+        return false;
+    };
+    let abs_path = root.join(&file.rel_path);
+
+    let current = if file.is_symlink() {
+        match fs::read_link(&abs_path) {
+            Ok(target) => Entry::symlink(git::hash_blob(target.to_string_lossy().as_bytes())),
+            Err(_) => return false,
+        }
+    } else {
+        #[cfg(unix)]
+        let mode = {
+            use std::os::unix::fs::MetadataExt;
+            file.metadata.mode()
+        };
+        #[cfg(not(unix))]
+        let mode = 0o644;
+        match hash_file_stable(&abs_path, 3) {
+            Ok(hash) => Entry::file(mode, hash),
+            Err(_) => return false,
+        }
+    };
+
+    current == cached
 }
 
 #[cfg(test)]
@@ -336,4 +433,100 @@ mod tests {
         assert_eq!(modified.len(), 1);
         assert!(modified[0].to_str().unwrap().contains("included.txt"));
     }
+
+    /// In-memory stat cache for tests
+    #[derive(Default)]
+    struct MapCache {
+        stats: std::collections::HashMap<PathBuf, FileStat>,
+        entries: std::collections::HashMap<PathBuf, Entry>,
+    }
+
+    impl StatCache for MapCache {
+        fn cached_stat(&self, path: &Path) -> Option<FileStat> {
+            self.stats.get(path).copied()
+        }
+
+        fn cached_entry(&self, path: &Path) -> Option<Entry> {
+            self.entries.get(path).cloned()
+        }
+
+        fn cached_paths(&self) -> Vec<PathBuf> {
+            self.stats.keys().chain(self.entries.keys()).cloned().collect()
+        }
+    }
+
+    #[test]
+    fn test_overflow_recovery_with_stat_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("same.txt"), b"content").unwrap();
+        fs::write(temp_dir.path().join("edited.txt"), b"content").unwrap();
+        fs::write(temp_dir.path().join("deleted.txt"), b"content").unwrap();
+
+        let mut stats = std::collections::HashMap::new();
+        for name in ["same.txt", "edited.txt", "deleted.txt"] {
+            let stat = FileStat::from_path(&temp_dir.path().join(name)).unwrap();
+            stats.insert(PathBuf::from(name), stat);
+        }
+
+        let mut recovery = OverflowRecovery::new(temp_dir.path());
+        recovery.set_stat_cache(Arc::new(MapCache { stats, ..Default::default() }));
+
+        fs::write(temp_dir.path().join("edited.txt"), b"changed content").unwrap();
+        fs::remove_file(temp_dir.path().join("deleted.txt")).unwrap();
+        fs::write(temp_dir.path().join("new.txt"), b"content").unwrap();
+
+        let mut modified: Vec<String> = recovery.recover().unwrap()
+            .iter()
+            .map(|p| p.to_string_lossy().into_owned())
+            .collect();
+        modified.sort();
+        assert_eq!(modified, vec!["deleted.txt", "edited.txt", "new.txt"]);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_overflow_recovery_hashes_entries_without_stat() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("same.txt"), b"content").unwrap();
+        fs::write(temp_dir.path().join("edited.txt"), b"content").unwrap();
+        std::os::unix::fs::symlink("same.txt", temp_dir.path().join("link")).unwrap();
+
+        // Entries only, as for racily-clean files, symlinks or tree-built maps
+        let mut cache = MapCache::default();
+        for name in ["same.txt", "edited.txt"] {
+            let mode = {
+                use std::os::unix::fs::MetadataExt;
+                fs::metadata(temp_dir.path().join(name)).unwrap().mode()
+            };
+            let hash = git::hash_blob(b"content");
+            cache.entries.insert(PathBuf::from(name), Entry::file(mode, hash));
+        }
+        cache.entries.insert(PathBuf::from("link"), Entry::symlink(git::hash_blob(b"same.txt")));
+
+        let mut recovery = OverflowRecovery::new(temp_dir.path());
+        recovery.set_stat_cache(Arc::new(cache));
+
+        fs::write(temp_dir.path().join("edited.txt"), b"changed content").unwrap();
+
+        let modified = recovery.recover().unwrap();
+        assert_eq!(modified.len(), 1);
+        assert_eq!(modified[0].to_str().unwrap(), "edited.txt");
+    }
+
+    #[test]
+    fn test_overflow_recovery_respects_gitignore() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join(".gitignore"), b"*.log\n").unwrap();
+
+        let mut recovery = OverflowRecovery::new(temp_dir.path());
+        recovery.mark_checkpoint(SystemTime::now());
+        sleep(Duration::from_millis(10));
+
+        fs::write(temp_dir.path().join("debug.log"), b"noise").unwrap();
+        fs::write(temp_dir.path().join("main.rs"), b"fn main() {}").unwrap();
+
+        let modified = recovery.recover().unwrap();
+        assert_eq!(modified.len(), 1);
+        assert!(modified[0].to_str().unwrap().contains("main.rs"));
+    }
 }
@@ -0,0 +1,532 @@
+//! Linux fanotify implementation
+//!
+//! One filesystem mark (FAN_MARK_FILESYSTEM) replaces inotify's
+//! per-directory watches: nothing is registered up front, and a directory
+//! created while running is covered before its first file is written.
+//! Events carry the parent directory's file handle and the entry name
+//! (FAN_REPORT_DFID_NAME). Handles are resolved to paths with
+//! `open_by_handle_at` and cached per directory, along with whether the
+//! directory is outside the repository or ignored, so the rest of the
+//! filesystem's traffic is dropped cheaply.
+//!
+//! Needs Linux 5.9+, CAP_SYS_ADMIN for the mark and CAP_DAC_READ_SEARCH to
+//! resolve handles. `FanotifyWatcher::new` fails without them and the
+//! caller falls back to inotify.
+
+use super::linux::is_atomic_save_temp;
+use super::{PlatformWatcher, WatcherDiagnostics};
+use crate::overflow::load_ignore_rules;
+use crate::{EventKind, ModifyKind, WatchEvent, WatcherConfig};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use parking_lot::RwLock;
+use std::collections::{HashMap, VecDeque};
+use std::ffi::{CString, OsStr};
+use std::fs::File;
+use std::io;
+use std::mem::size_of;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use timelapse_core::IgnoreRules;
+
+/// Events requested from the kernel
+const EVENT_MASK: u64 = libc::FAN_CREATE
+    | libc::FAN_DELETE
+    | libc::FAN_MODIFY
+    | libc::FAN_ATTRIB
+    | libc::FAN_MOVED_FROM
+    | libc::FAN_MOVED_TO
+    | libc::FAN_ONDIR;
+
+/// Size of the buffer events are read into
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Resolved directories to remember before starting over
+const MAX_CACHED_DIRS: usize = 65_536;
+
+/// Linux-specific watcher using a fanotify filesystem mark
+pub struct FanotifyWatcher {
+    /// Root path being watched (canonical, as the kernel reports paths)
+    root: PathBuf,
+
+    /// fanotify group (None while stopped)
+    fan_fd: Option<OwnedFd>,
+
+    /// Any directory on the watched filesystem, for `open_by_handle_at`
+    mount_fd: File,
+
+    /// Directory handle -> repo-relative path (None: outside the
+    /// repository or ignored)
+    dirs: HashMap<Vec<u8>, Option<PathBuf>>,
+
+    /// Ignore rules
+    rules: IgnoreRules,
+
+    /// Parsed events not yet returned
+    pending: VecDeque<WatchEvent>,
+
+    /// Read buffer
+    buffer: Vec<u8>,
+
+    /// Diagnostics (protected by RwLock for interior mutability)
+    diagnostics: Arc<RwLock<WatcherDiagnostics>>,
+
+    /// Overflow detected flag
+    overflow_detected: bool,
+
+    /// Running state
+    is_running: bool,
+
+    /// Configuration
+    config: WatcherConfig,
+}
+
+impl FanotifyWatcher {
+    /// Create a fanotify watcher, marking the filesystem holding `path`
+    ///
+    /// Fails if the kernel or the process's capabilities do not allow it.
+    pub fn new(path: &Path, config: &WatcherConfig) -> Result<Self> {
+        let root = path.canonicalize()
+            .with_context(|| format!("Failed to resolve {}", path.display()))?;
+        let mount_fd = File::open(&root)
+            .with_context(|| format!("Failed to open {}", root.display()))?;
+        let fan_fd = open_group(&root)?;
+
+        Ok(Self {
+            rules: load_ignore_rules(&root),
+            root,
+            fan_fd: Some(fan_fd),
+            mount_fd,
+            dirs: HashMap::new(),
+            pending: VecDeque::new(),
+            buffer: vec![0; READ_BUFFER_SIZE],
+            diagnostics: Arc::new(RwLock::new(WatcherDiagnostics {
+                backend: "fanotify".to_string(),
+                ..WatcherDiagnostics::default()
+            })),
+            overflow_detected: false,
+            is_running: false,
+            config: config.clone(),
+        })
+    }
+
+    /// Parse a buffer of events into `pending`
+    fn parse_events(&mut self, data: &[u8]) {
+        let metadata_size = size_of::<libc::fanotify_event_metadata>();
+        let mut offset = 0;
+
+        while offset + metadata_size <= data.len() {
+            // SAFETY: bounds checked above; the buffer has no alignment guarantee
+            let metadata: libc::fanotify_event_metadata =
+                unsafe { std::ptr::read_unaligned(data[offset..].as_ptr().cast()) };
+            let len = metadata.event_len as usize;
+            if metadata.vers != libc::FANOTIFY_METADATA_VERSION
+                || len < metadata_size
+                || offset + len > data.len()
+            {
+                tracing::warn!("Malformed fanotify event, skipping rest of buffer");
+                break;
+            }
+            let event = &data[offset..offset + len];
+            offset += len;
+
+            // Never set in FID mode, but don't leak one if it is
+            if metadata.fd >= 0 {
+                // SAFETY: the kernel handed us this descriptor
+                drop(unsafe { OwnedFd::from_raw_fd(metadata.fd) });
+            }
+
+            self.diagnostics.write().events_received += 1;
+
+            if metadata.mask & libc::FAN_Q_OVERFLOW != 0 {
+                self.overflow_detected = true;
+                self.diagnostics.write().overflow_count += 1;
+                self.pending.push_back(WatchEvent::new(
+                    Arc::from(Path::new("<overflow>")),
+                    EventKind::Overflow,
+                ));
+                continue;
+            }
+
+            match parse_dfid_name(&event[metadata.metadata_len as usize..]) {
+                Some((handle, name)) => self.handle_event(metadata.mask, handle, name),
+                None => self.diagnostics.write().events_filtered += 1,
+            }
+        }
+    }
+
+    /// Queue the events for `name` in the directory identified by `handle`
+    fn handle_event(&mut self, mask: u64, handle: &[u8], name: &OsStr) {
+        let Some(dir) = self.resolve_dir(handle) else {
+            self.diagnostics.write().events_filtered += 1;
+            return;
+        };
+        let path = if name == "." { dir } else { dir.join(name) };
+
+        if path.as_os_str().is_empty() || self.should_filter(&path) {
+            self.diagnostics.write().events_filtered += 1;
+            return;
+        }
+
+        // Cached paths below a moved or deleted directory are stale
+        if mask & libc::FAN_ONDIR != 0
+            && mask & (libc::FAN_MOVED_FROM | libc::FAN_MOVED_TO | libc::FAN_DELETE) != 0
+        {
+            self.dirs.clear();
+        }
+
+        let path: Arc<Path> = Arc::from(path.as_path());
+        for kind in event_kinds(mask) {
+            self.pending.push_back(WatchEvent::new(path.clone(), kind));
+        }
+    }
+
+    /// Repo-relative path of a directory handle, or None to drop its events
+    fn resolve_dir(&mut self, handle: &[u8]) -> Option<PathBuf> {
+        if let Some(cached) = self.dirs.get(handle) {
+            return cached.clone();
+        }
+
+        // A directory that is already gone: its own deletion is reported
+        // through its parent, which still exists
+        let absolute = handle_path(&self.mount_fd, handle).ok()?;
+        let relative = absolute.strip_prefix(&self.root).ok()
+            .map(Path::to_path_buf)
+            .filter(|rel| !rel.ancestors().any(|dir| {
+                !dir.as_os_str().is_empty() && (is_internal(dir) || self.rules.should_ignore(dir))
+            }));
+
+        if self.dirs.len() >= MAX_CACHED_DIRS {
+            self.dirs.clear();
+        }
+        self.dirs.insert(handle.to_vec(), relative.clone());
+        relative
+    }
+
+    /// Check if a repo-relative path should be filtered
+    fn should_filter(&self, path: &Path) -> bool {
+        if is_internal(path) || self.rules.should_ignore(path) {
+            return true;
+        }
+
+        // Detect atomic save patterns (Vim, Emacs, etc.)
+        if self.config.detect_atomic_saves && is_atomic_save_temp(path) {
+            self.diagnostics.write().atomic_saves_detected += 1;
+            return true;
+        }
+
+        false
+    }
+}
+
+#[async_trait]
+impl PlatformWatcher for FanotifyWatcher {
+    async fn start(&mut self) -> Result<()> {
+        if self.is_running {
+            return Ok(());
+        }
+
+        if self.fan_fd.is_none() {
+            self.fan_fd = Some(open_group(&self.root)?);
+        }
+        self.is_running = true;
+
+        {
+            let mut diag = self.diagnostics.write();
+            diag.platform_info = format!("fanotify filesystem mark: {}", self.root.display());
+        }
+
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> Result<()> {
+        if !self.is_running {
+            return Ok(());
+        }
+
+        // Closing the group removes the mark
+        self.fan_fd = None;
+        self.dirs.clear();
+
+        self.is_running = false;
+        Ok(())
+    }
+
+    async fn poll_event(&mut self) -> Result<Option<WatchEvent>> {
+        if let Some(event) = self.pending.pop_front() {
+            return Ok(Some(event));
+        }
+
+        let Some(fan_fd) = self.fan_fd.as_ref().filter(|_| self.is_running) else {
+            return Ok(None);
+        };
+
+        // Non-blocking read
+        let mut buffer = std::mem::take(&mut self.buffer);
+        // SAFETY: the buffer is valid for writes of its length
+        let n = unsafe { libc::read(fan_fd.as_raw_fd(), buffer.as_mut_ptr().cast(), buffer.len()) };
+        let result = if n < 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::WouldBlock {
+                Ok(None)
+            } else {
+                Err(err).context("Failed to read fanotify events")
+            }
+        } else {
+            self.parse_events(&buffer[..n as usize]);
+            Ok(self.pending.pop_front())
+        };
+        self.buffer = buffer;
+
+        result
+    }
+
+    fn has_overflow(&self) -> bool {
+        self.overflow_detected
+    }
+
+    fn reset_overflow(&mut self) {
+        self.overflow_detected = false;
+    }
+
+    fn diagnostics(&self) -> WatcherDiagnostics {
+        self.diagnostics.read().clone()
+    }
+
+    fn is_running(&self) -> bool {
+        self.is_running
+    }
+}
+
+/// Create a fanotify group with a filesystem mark covering `root`
+fn open_group(root: &Path) -> Result<OwnedFd> {
+    // SAFETY: plain syscall, result checked
+    let fd = unsafe {
+        libc::fanotify_init(
+            libc::FAN_CLASS_NOTIF | libc::FAN_CLOEXEC | libc::FAN_NONBLOCK | libc::FAN_REPORT_DFID_NAME,
+            (libc::O_RDONLY | libc::O_LARGEFILE) as libc::c_uint,
+        )
+    };
+    if fd < 0 {
+        return Err(io::Error::last_os_error()).context("fanotify_init failed");
+    }
+    // SAFETY: fd is a freshly created descriptor we own
+    let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+    let path = CString::new(root.as_os_str().as_bytes())
+        .context("Repository path contains a NUL byte")?;
+    // SAFETY: path is a valid C string; result checked
+    let marked = unsafe {
+        libc::fanotify_mark(
+            fd.as_raw_fd(),
+            libc::FAN_MARK_ADD | libc::FAN_MARK_FILESYSTEM,
+            EVENT_MASK,
+            libc::AT_FDCWD,
+            path.as_ptr(),
+        )
+    };
+    if marked < 0 {
+        return Err(io::Error::last_os_error())
+            .with_context(|| format!("fanotify_mark failed for {}", root.display()));
+    }
+
+    Ok(fd)
+}
+
+/// Extract the directory file handle and entry name from an event's
+/// info records
+fn parse_dfid_name(info: &[u8]) -> Option<(&[u8], &OsStr)> {
+    let header_size = size_of::<libc::fanotify_event_info_header>();
+    let mut offset = 0;
+
+    while offset + header_size <= info.len() {
+        // SAFETY: bounds checked above
+        let header: libc::fanotify_event_info_header =
+            unsafe { std::ptr::read_unaligned(info[offset..].as_ptr().cast()) };
+        let len = header.len as usize;
+        if len < header_size || offset + len > info.len() {
+            return None;
+        }
+        let record = &info[offset..offset + len];
+        offset += len;
+
+        if header.info_type != libc::FAN_EVENT_INFO_TYPE_DFID_NAME {
+            continue;
+        }
+
+        // Header and fsid, then a `struct file_handle`, then the name
+        let handle_start = size_of::<libc::fanotify_event_info_fid>();
+        let handle_bytes = u32::from_ne_bytes(record.get(handle_start..handle_start + 4)?.try_into().ok()?);
+        let handle_end = handle_start + size_of::<libc::file_handle>() + handle_bytes as usize;
+        let handle = record.get(handle_start..handle_end)?;
+
+        let name = record.get(handle_end..)?;
+        let name = &name[..name.iter().position(|&b| b == 0)?];
+        return Some((handle, OsStr::from_bytes(name)));
+    }
+
+    None
+}
+
+/// Resolve a `struct file_handle` (as raw bytes) to an absolute path
+fn handle_path(mount_fd: &File, handle: &[u8]) -> io::Result<PathBuf> {
+    // open_by_handle_at takes a mutable, aligned `struct file_handle`
+    let mut aligned = vec![0u64; handle.len().div_ceil(8)];
+    // SAFETY: `aligned` holds at least handle.len() bytes
+    unsafe { std::ptr::copy_nonoverlapping(handle.as_ptr(), aligned.as_mut_ptr().cast::<u8>(), handle.len()) };
+
+    // SAFETY: the handle was produced by the kernel; result checked
+    let fd = unsafe {
+        libc::open_by_handle_at(
+            mount_fd.as_raw_fd(),
+            aligned.as_mut_ptr().cast(),
+            libc::O_PATH | libc::O_CLOEXEC,
+        )
+    };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: fd is a freshly opened descriptor we own
+    let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+    let path = std::fs::read_link(format!("/proc/self/fd/{}", fd.as_raw_fd()))?;
+    if path.as_os_str().as_bytes().ends_with(b" (deleted)") {
+        return Err(io::Error::from(io::ErrorKind::NotFound));
+    }
+    Ok(path)
+}
+
+/// Paths inside `.tl/` or `.git/`
+fn is_internal(path: &Path) -> bool {
+    path.components().any(|c| c.as_os_str() == ".tl" || c.as_os_str() == ".git")
+}
+
+/// Watch events for a fanotify mask, in the order they most likely happened
+fn event_kinds(mask: u64) -> Vec<EventKind> {
+    let mut kinds = Vec::new();
+    if mask & (libc::FAN_CREATE | libc::FAN_MOVED_TO) != 0 {
+        kinds.push(EventKind::Create);
+    }
+    if mask & libc::FAN_MODIFY != 0 {
+        kinds.push(EventKind::Modify(ModifyKind::Data));
+    }
+    if mask & libc::FAN_ATTRIB != 0 {
+        kinds.push(EventKind::Modify(ModifyKind::Metadata));
+    }
+    if mask & (libc::FAN_DELETE | libc::FAN_MOVED_FROM) != 0 {
+        kinds.push(EventKind::Delete);
+    }
+    kinds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    /// A watcher, or None where fanotify is unavailable (no CAP_SYS_ADMIN,
+    /// old kernel, unsupported filesystem)
+    fn try_watcher(path: &Path) -> Option<FanotifyWatcher> {
+        match FanotifyWatcher::new(path, &WatcherConfig::default()) {
+            Ok(watcher) => Some(watcher),
+            Err(e) => {
+                eprintln!("Skipping fanotify test: {:#}", e);
+                None
+            }
+        }
+    }
+
+    async fn collect_events(watcher: &mut FanotifyWatcher) -> Vec<WatchEvent> {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let mut events = Vec::new();
+        for _ in 0..20 {
+            match watcher.poll_event().await.unwrap() {
+                Some(event) => events.push(event),
+                None => tokio::time::sleep(Duration::from_millis(10)).await,
+            }
+        }
+        events
+    }
+
+    #[tokio::test]
+    async fn test_fanotify_watcher_reports_repo_changes_only() {
+        let outside = TempDir::new().unwrap();
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("node_modules/pkg")).unwrap();
+        fs::write(temp_dir.path().join(".gitignore"), "node_modules/\n").unwrap();
+
+        let Some(mut watcher) = try_watcher(temp_dir.path()) else { return };
+        watcher.start().await.unwrap();
+        assert!(watcher.is_running());
+        assert_eq!(watcher.diagnostics().backend, "fanotify");
+
+        // A directory created while running is covered immediately
+        fs::create_dir_all(temp_dir.path().join("src/fresh")).unwrap();
+        fs::write(temp_dir.path().join("src/fresh/new.txt"), b"hi").unwrap();
+        fs::write(temp_dir.path().join("node_modules/pkg/index.js"), b"x").unwrap();
+        fs::write(temp_dir.path().join(".git"), b"gitdir: elsewhere").unwrap();
+        fs::write(outside.path().join("elsewhere.txt"), b"x").unwrap();
+
+        let events = collect_events(&mut watcher).await;
+        let paths: Vec<&Path> = events.iter().map(|e| e.path.as_ref()).collect();
+        assert!(events.iter().any(|e| e.path.as_ref() == Path::new("src/fresh/new.txt") && e.kind == EventKind::Create));
+        assert!(paths.contains(&Path::new("src/fresh")));
+        assert!(!paths.iter().any(|p| p.starts_with("node_modules") || p.starts_with(".git")));
+        assert!(!paths.iter().any(|p| p.ends_with("elsewhere.txt")));
+
+        // Renames are a delete and a create, deletes are reported
+        fs::rename(temp_dir.path().join("src/fresh"), temp_dir.path().join("src/moved")).unwrap();
+        fs::remove_file(temp_dir.path().join("src/moved/new.txt")).unwrap();
+
+        let events = collect_events(&mut watcher).await;
+        assert!(events.iter().any(|e| e.path.as_ref() == Path::new("src/fresh") && e.kind == EventKind::Delete));
+        assert!(events.iter().any(|e| e.path.as_ref() == Path::new("src/moved") && e.kind == EventKind::Create));
+        assert!(events.iter().any(|e| e.path.as_ref() == Path::new("src/moved/new.txt") && e.kind == EventKind::Delete));
+
+        watcher.stop().await.unwrap();
+        assert!(!watcher.is_running());
+    }
+
+    #[test]
+    fn test_event_kinds_and_info_parsing() {
+        assert_eq!(event_kinds(libc::FAN_CREATE | libc::FAN_MODIFY), vec![EventKind::Create, EventKind::Modify(ModifyKind::Data)]);
+        assert_eq!(event_kinds(libc::FAN_MOVED_FROM | libc::FAN_ONDIR), vec![EventKind::Delete]);
+        assert_eq!(event_kinds(libc::FAN_ATTRIB), vec![EventKind::Modify(ModifyKind::Metadata)]);
+
+        // hdr (type, pad, len), fsid, file_handle (bytes, type, handle), name
+        let mut record = vec![libc::FAN_EVENT_INFO_TYPE_DFID_NAME, 0, 0, 0];
+        record.extend_from_slice(&[0u8; 8]);
+        record.extend_from_slice(&4u32.to_ne_bytes());
+        record.extend_from_slice(&1i32.to_ne_bytes());
+        record.extend_from_slice(&[1, 2, 3, 4]);
+        record.extend_from_slice(b"file.txt\0\0\0");
+        let len = record.len() as u16;
+        record[2..4].copy_from_slice(&len.to_ne_bytes());
+
+        let (handle, name) = parse_dfid_name(&record).unwrap();
+        assert_eq!(handle.len(), 12);
+        assert_eq!(&handle[8..], &[1, 2, 3, 4]);
+        assert_eq!(name, OsStr::new("file.txt"));
+
+        // Truncated records are rejected
+        assert!(parse_dfid_name(&record[..10]).is_none());
+        assert!(is_internal(Path::new("sub/.git/config")));
+        assert!(!is_internal(Path::new("src/git.rs")));
+    }
+
+    #[test]
+    fn test_auto_backend_prefers_fanotify() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = WatcherConfig::default();
+        assert_eq!(config.backend, crate::WatcherBackend::Auto);
+
+        // fanotify where it can be set up, inotify otherwise
+        let expected = if try_watcher(temp_dir.path()).is_some() { "fanotify" } else { "inotify" };
+        let watcher = crate::platform::create_platform_watcher(temp_dir.path(), &config).unwrap();
+        assert_eq!(watcher.diagnostics().backend, expected);
+    }
+}
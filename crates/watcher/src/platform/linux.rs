@@ -3,17 +3,32 @@
 //! Uses inotify (via notify crate) for file system monitoring on Linux.
 //! inotify provides efficient, kernel-level file system monitoring with
 //! fine-grained, file-level events.
-
+//!
+//! inotify needs one watch per directory, capped per user by
+//! `fs.inotify.max_user_watches`. Directories are registered one at a time,
+//! skipping ignored ones (`target/`, `node_modules/`, ...) so they never use
+//! up the budget. When the limit is reached, the remaining subtrees are
+//! reconciled periodically by stat comparison instead, and the shortfall is
+//! reported in the diagnostics.
+
+use super::poll::SubtreePoller;
 use super::{PlatformWatcher, WatcherDiagnostics};
+use crate::overflow::load_ignore_rules;
 use crate::{EventKind, ModifyKind, WatchEvent, WatcherConfig};
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use crossbeam_channel::{Receiver, Sender};
+use notify::event::RenameMode;
 use notify::{Config, Event, EventKind as NotifyEventKind, RecommendedWatcher, Watcher as NotifyWatcher, RecursiveMode};
 use parking_lot::RwLock;
+use std::collections::{HashSet, VecDeque};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
+use timelapse_core::IgnoreRules;
+
+/// Per-user inotify watch limit
+const MAX_USER_WATCHES: &str = "/proc/sys/fs/inotify/max_user_watches";
 
 /// Linux-specific watcher using inotify
 pub struct LinuxWatcher {
@@ -40,6 +55,21 @@ pub struct LinuxWatcher {
 
     /// Configuration
     config: WatcherConfig,
+
+    /// Ignore rules (ignored directories are never watched)
+    rules: IgnoreRules,
+
+    /// Directories with a registered watch
+    watched: HashSet<PathBuf>,
+
+    /// Set once registration failed with ENOSPC
+    limit_reached: bool,
+
+    /// Reconciles the subtrees left unwatched
+    fallback: SubtreePoller,
+
+    /// Events produced locally (new directories, fallback scans)
+    pending: VecDeque<WatchEvent>,
 }
 
 impl LinuxWatcher {
@@ -52,13 +82,154 @@ impl LinuxWatcher {
             watcher: None,
             event_rx: rx,
             event_tx: tx,
-            diagnostics: Arc::new(RwLock::new(WatcherDiagnostics::default())),
+            diagnostics: Arc::new(RwLock::new(WatcherDiagnostics {
+                backend: "inotify".to_string(),
+                ..WatcherDiagnostics::default()
+            })),
             overflow_detected: false,
             is_running: false,
             config: config.clone(),
+            rules: load_ignore_rules(path),
+            watched: HashSet::new(),
+            limit_reached: false,
+            fallback: SubtreePoller::new(config),
+            pending: VecDeque::new(),
         })
     }
 
+    /// Watch `dir` and every non-ignored directory below it
+    ///
+    /// Once the watch limit is hit, the remaining subtrees go to the
+    /// fallback poller. With `report_existing` (a directory that appeared
+    /// while running), files already inside are reported as created, since
+    /// they may have been written before the watch existed.
+    fn register_tree(&mut self, dir: &Path, report_existing: bool) {
+        let Some(watcher) = self.watcher.as_mut() else {
+            return;
+        };
+
+        let root = &self.root;
+        let rules = &self.rules;
+        let mut walk = walkdir::WalkDir::new(dir)
+            .follow_links(false)
+            .into_iter()
+            .filter_entry(|e| match e.path().strip_prefix(root) {
+                Ok(rel) => rel.as_os_str().is_empty() || !rules.should_ignore(rel),
+                Err(_) => false,
+            });
+
+        let mut unwatched = Vec::new();
+        while let Some(entry) = walk.next() {
+            let Ok(entry) = entry else { continue };
+            let path = entry.path();
+
+            if !entry.file_type().is_dir() {
+                if report_existing {
+                    if let Ok(rel) = path.strip_prefix(root) {
+                        self.pending.push_back(WatchEvent::new(Arc::from(rel), EventKind::Create));
+                    }
+                }
+                continue;
+            }
+            if self.watched.contains(path) {
+                continue;
+            }
+
+            if self.limit_reached {
+                unwatched.push(path.to_path_buf());
+                walk.skip_current_dir();
+                continue;
+            }
+
+            match watcher.watch(path, RecursiveMode::NonRecursive) {
+                Ok(()) => {
+                    self.watched.insert(path.to_path_buf());
+                }
+                Err(e) if matches!(e.kind, notify::ErrorKind::MaxFilesWatch) => {
+                    self.limit_reached = true;
+                    unwatched.push(path.to_path_buf());
+                    walk.skip_current_dir();
+                }
+                // Removed while we were walking
+                Err(e) => tracing::debug!("Could not watch {}: {}", path.display(), e),
+            }
+        }
+
+        if !unwatched.is_empty() {
+            tracing::warn!(
+                "inotify watch limit reached ({} watches, limit {}): polling {} unwatched subtree(s) instead. \
+                 Raise it with: sudo sysctl fs.inotify.max_user_watches=524288",
+                self.watched.len(),
+                read_watch_limit().map(|l| l.to_string()).unwrap_or_else(|| "unknown".to_string()),
+                unwatched.len()
+            );
+        }
+        for subtree in unwatched {
+            self.fallback.add(&self.root, subtree, &self.rules, !report_existing);
+        }
+
+        self.update_watch_diagnostics();
+    }
+
+    /// Update watches for directories created, removed or renamed by `event`
+    ///
+    /// A directory moved into the tree arrives as a rename, not a create, so
+    /// rename targets are walked like new directories. Removals and renames
+    /// away drop every watch at or below the old path.
+    fn track_directories(&mut self, event: &Event) {
+        let (gone, arrived) = match event.kind {
+            NotifyEventKind::Create(_) => (None, event.paths.first()),
+            NotifyEventKind::Remove(_) => (event.paths.first(), None),
+            NotifyEventKind::Modify(notify::event::ModifyKind::Name(mode)) => match mode {
+                RenameMode::From => (event.paths.first(), None),
+                RenameMode::To => (None, event.paths.first()),
+                RenameMode::Both => (event.paths.first(), event.paths.get(1)),
+                _ => (None, None),
+            },
+            _ => (None, None),
+        };
+
+        if let Some(path) = gone {
+            // Removed directories lose their watches with the inode; moved ones keep them
+            let unwatch = !matches!(event.kind, NotifyEventKind::Remove(_));
+            self.forget_tree(path, unwatch);
+        }
+        if let Some(path) = arrived.filter(|p| p.is_dir()) {
+            let path = path.clone();
+            self.register_tree(&path, true);
+        }
+    }
+
+    /// Drop `dir` and every watched directory below it
+    fn forget_tree(&mut self, dir: &Path, unwatch: bool) {
+        let stale: Vec<PathBuf> = self.watched.iter()
+            .filter(|p| p.starts_with(dir))
+            .cloned()
+            .collect();
+        if stale.is_empty() {
+            return;
+        }
+
+        for path in stale {
+            if unwatch {
+                if let Some(watcher) = self.watcher.as_mut() {
+                    let _ = watcher.unwatch(&path);
+                }
+            }
+            self.watched.remove(&path);
+        }
+        self.update_watch_diagnostics();
+    }
+
+    /// Refresh watch counts in the diagnostics
+    fn update_watch_diagnostics(&self) {
+        let mut diag = self.diagnostics.write();
+        diag.watches = self.watched.len() as u64;
+        diag.watch_limit = read_watch_limit();
+        diag.unwatched_dirs = self.fallback.subtrees().len() as u64;
+        diag.poll_interval = (!self.fallback.subtrees().is_empty()).then(|| self.fallback.interval());
+    }
+
     /// Convert notify::Event to WatchEvent
     fn convert_event(&mut self, event: Event) -> Option<WatchEvent> {
         // Update diagnostics
@@ -129,7 +300,7 @@ impl LinuxWatcher {
         }
 
         // Detect atomic save patterns (Vim, Emacs, etc.)
-        if self.config.detect_atomic_saves && self.is_atomic_save_temp(path) {
+        if self.config.detect_atomic_saves && is_atomic_save_temp(path) {
             let mut diag = self.diagnostics.write();
             diag.atomic_saves_detected += 1;
             return true;
@@ -137,33 +308,6 @@ impl LinuxWatcher {
 
         false
     }
-
-    /// Check if path is an atomic save temporary file
-    fn is_atomic_save_temp(&self, path: &Path) -> bool {
-        let filename = path.file_name().and_then(|s| s.to_str()).unwrap_or("");
-
-        // Vim swap files (.swp, .swo, .swn)
-        if filename.starts_with('.') && filename.contains(".sw") {
-            return true;
-        }
-
-        // Generic temporary files
-        if filename.ends_with(".tmp") || filename.contains(".tmp.") {
-            return true;
-        }
-
-        // Emacs backup and autosave files
-        if filename.ends_with('~') || filename.starts_with('#') && filename.ends_with('#') {
-            return true;
-        }
-
-        // Kate/KWrite backup files
-        if filename.ends_with('~') || filename.ends_with(".bak") {
-            return true;
-        }
-
-        false
-    }
 }
 
 #[async_trait]
@@ -190,12 +334,15 @@ impl PlatformWatcher for LinuxWatcher {
 
         self.watcher = Some(watcher);
 
-        // Start watching the root path recursively
+        // Watch the root, then every non-ignored directory below it
         self.watcher
             .as_mut()
             .unwrap()
-            .watch(&self.root, RecursiveMode::Recursive)
+            .watch(&self.root, RecursiveMode::NonRecursive)
             .context("Failed to start watching directory")?;
+        self.watched.insert(self.root.clone());
+        let root = self.root.clone();
+        self.register_tree(&root, false);
 
         self.is_running = true;
 
@@ -212,17 +359,28 @@ impl PlatformWatcher for LinuxWatcher {
             return Ok(());
         }
 
-        if let Some(mut watcher) = self.watcher.take() {
-            watcher
-                .unwatch(&self.root)
-                .context("Failed to unwatch directory")?;
-        }
+        // Dropping the watcher closes the inotify instance and all its watches
+        self.watcher = None;
+        self.watched.clear();
 
         self.is_running = false;
         Ok(())
     }
 
     async fn poll_event(&mut self) -> Result<Option<WatchEvent>> {
+        if let Some(event) = self.pending.pop_front() {
+            return Ok(Some(event));
+        }
+
+        // Reconcile subtrees left unwatched by the watch limit
+        let fallback_events = self.fallback.poll(&self.root, &self.rules);
+        if !fallback_events.is_empty() {
+            self.diagnostics.write().events_received += fallback_events.len() as u64;
+            self.pending.extend(fallback_events);
+            self.update_watch_diagnostics();
+            return Ok(self.pending.pop_front());
+        }
+
         // Non-blocking receive
         match self.event_rx.try_recv() {
             Ok(Ok(event)) => {
@@ -230,6 +388,10 @@ impl PlatformWatcher for LinuxWatcher {
                 if matches!(event.kind, NotifyEventKind::Other) {
                     self.overflow_detected = true;
                 }
+
+                // Watches are per directory: follow directories as they come and go
+                self.track_directories(&event);
+
                 Ok(self.convert_event(event))
             }
             Ok(Err(e)) => {
@@ -263,6 +425,38 @@ impl PlatformWatcher for LinuxWatcher {
     }
 }
 
+/// Check if path is an atomic save temporary file (shared with fanotify)
+pub(super) fn is_atomic_save_temp(path: &Path) -> bool {
+    let filename = path.file_name().and_then(|s| s.to_str()).unwrap_or("");
+
+    // Vim swap files (.swp, .swo, .swn)
+    if filename.starts_with('.') && filename.contains(".sw") {
+        return true;
+    }
+
+    // Generic temporary files
+    if filename.ends_with(".tmp") || filename.contains(".tmp.") {
+        return true;
+    }
+
+    // Emacs backup and autosave files
+    if filename.ends_with('~') || filename.starts_with('#') && filename.ends_with('#') {
+        return true;
+    }
+
+    // Kate/KWrite backup files
+    if filename.ends_with('~') || filename.ends_with(".bak") {
+        return true;
+    }
+
+    false
+}
+
+/// Current `fs.inotify.max_user_watches`, if readable
+fn read_watch_limit() -> Option<u64> {
+    std::fs::read_to_string(MAX_USER_WATCHES).ok()?.trim().parse().ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -326,6 +520,86 @@ mod tests {
         watcher.stop().await.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_linux_watcher_skips_ignored_dirs_and_follows_new_ones() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("src/nested")).unwrap();
+        fs::create_dir_all(temp_dir.path().join("node_modules/pkg/lib")).unwrap();
+        fs::write(temp_dir.path().join(".gitignore"), "node_modules/\n").unwrap();
+
+        let mut watcher = LinuxWatcher::new(temp_dir.path(), &WatcherConfig::default()).unwrap();
+        watcher.start().await.unwrap();
+
+        // Root, src, src/nested - nothing under node_modules
+        assert_eq!(watcher.diagnostics().watches, 3);
+        assert_eq!(watcher.diagnostics().unwatched_dirs, 0);
+        assert!(!watcher.watched.iter().any(|p| p.to_string_lossy().contains("node_modules")));
+
+        // A new directory is watched, and files written into it are reported
+        fs::create_dir_all(temp_dir.path().join("src/fresh")).unwrap();
+        fs::write(temp_dir.path().join("src/fresh/new.txt"), b"hi").unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let mut found = false;
+        for _ in 0..50 {
+            match watcher.poll_event().await.unwrap() {
+                Some(event) if event.path.as_ref() == Path::new("src/fresh/new.txt") => {
+                    found = true;
+                    break;
+                }
+                Some(_) => {}
+                None => tokio::time::sleep(Duration::from_millis(10)).await,
+            }
+        }
+        assert!(found, "Should have seen the file in the new directory");
+        assert!(watcher.watched.contains(&temp_dir.path().join("src/fresh")));
+
+        watcher.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_linux_watcher_follows_renamed_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        let outside = TempDir::new_in(temp_dir.path().parent().unwrap()).unwrap();
+        fs::create_dir_all(outside.path().join("moved/deep")).unwrap();
+
+        let mut watcher = LinuxWatcher::new(temp_dir.path(), &WatcherConfig::default()).unwrap();
+        watcher.start().await.unwrap();
+
+        // Moved in: the whole subtree is watched
+        let moved = temp_dir.path().join("moved");
+        fs::rename(outside.path().join("moved"), &moved).unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        while watcher.poll_event().await.unwrap().is_some() {}
+        assert!(watcher.watched.contains(&moved));
+        assert!(watcher.watched.contains(&moved.join("deep")));
+
+        fs::write(moved.join("deep/new.txt"), b"hi").unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let mut found = false;
+        for _ in 0..50 {
+            match watcher.poll_event().await.unwrap() {
+                Some(event) if event.path.as_ref() == Path::new("moved/deep/new.txt") => {
+                    found = true;
+                    break;
+                }
+                Some(_) => {}
+                None => tokio::time::sleep(Duration::from_millis(10)).await,
+            }
+        }
+        assert!(found, "Should have seen the file in the moved-in directory");
+
+        // Moved out: watches below the old path are dropped
+        fs::rename(&moved, outside.path().join("moved")).unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        while watcher.poll_event().await.unwrap().is_some() {}
+        assert!(!watcher.watched.iter().any(|p| p.starts_with(&moved)));
+        assert_eq!(watcher.diagnostics().watches, 1);
+
+        watcher.stop().await.unwrap();
+    }
+
     #[test]
     fn test_should_filter_system_files() {
         let temp_dir = TempDir::new().unwrap();
@@ -339,23 +613,19 @@ mod tests {
 
     #[test]
     fn test_atomic_save_detection() {
-        let temp_dir = TempDir::new().unwrap();
-        let config = WatcherConfig::default();
-        let watcher = LinuxWatcher::new(temp_dir.path(), &config).unwrap();
-
         // Vim swap files
-        assert!(watcher.is_atomic_save_temp(Path::new(".file.swp")));
-        assert!(watcher.is_atomic_save_temp(Path::new(".file.swo")));
+        assert!(is_atomic_save_temp(Path::new(".file.swp")));
+        assert!(is_atomic_save_temp(Path::new(".file.swo")));
 
         // Emacs backup files
-        assert!(watcher.is_atomic_save_temp(Path::new("file~")));
-        assert!(watcher.is_atomic_save_temp(Path::new("#file#")));
+        assert!(is_atomic_save_temp(Path::new("file~")));
+        assert!(is_atomic_save_temp(Path::new("#file#")));
 
         // Generic temp files
-        assert!(watcher.is_atomic_save_temp(Path::new("file.tmp")));
+        assert!(is_atomic_save_temp(Path::new("file.tmp")));
 
         // Normal files
-        assert!(!watcher.is_atomic_save_temp(Path::new("file.txt")));
-        assert!(!watcher.is_atomic_save_temp(Path::new("file.rs")));
+        assert!(!is_atomic_save_temp(Path::new("file.txt")));
+        assert!(!is_atomic_save_temp(Path::new("file.rs")));
     }
 }
@@ -52,7 +52,10 @@ impl MacOSWatcher {
             watcher: None,
             event_rx: rx,
             event_tx: tx,
-            diagnostics: Arc::new(RwLock::new(WatcherDiagnostics::default())),
+            diagnostics: Arc::new(RwLock::new(WatcherDiagnostics {
+                backend: "fsevents".to_string(),
+                ..WatcherDiagnostics::default()
+            })),
             overflow_detected: false,
             is_running: false,
             config: config.clone(),
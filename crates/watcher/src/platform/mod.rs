@@ -1,7 +1,9 @@
 //! Platform-specific file watching implementations
 //!
 //! Provides a unified interface (PlatformWatcher trait) with platform-specific
-//! implementations for macOS (FSEvents) and Linux (inotify).
+//! implementations for macOS (FSEvents) and Linux (inotify, or fanotify when
+//! the daemon has CAP_SYS_ADMIN), plus a portable polling backend for
+//! filesystems without reliable change notifications.
 
 #[cfg(target_os = "macos")]
 pub mod macos;
@@ -9,10 +11,18 @@ pub mod macos;
 #[cfg(target_os = "linux")]
 pub mod linux;
 
-use crate::WatchEvent;
+#[cfg(target_os = "linux")]
+pub mod fanotify;
+
+pub mod poll;
+pub mod replay;
+
+use crate::overflow::SharedStatCache;
+use crate::{WatchEvent, WatcherBackend};
 use anyhow::Result;
 use async_trait::async_trait;
 use std::path::Path;
+use std::time::Duration;
 
 #[cfg(target_os = "macos")]
 pub use macos::MacOSWatcher;
@@ -20,6 +30,12 @@ pub use macos::MacOSWatcher;
 #[cfg(target_os = "linux")]
 pub use linux::LinuxWatcher;
 
+#[cfg(target_os = "linux")]
+pub use fanotify::FanotifyWatcher;
+
+pub use poll::PollingWatcher;
+pub use replay::ReplayWatcher;
+
 /// Platform-specific watcher diagnostics
 #[derive(Debug, Clone, Default)]
 pub struct WatcherDiagnostics {
@@ -37,6 +53,28 @@ pub struct WatcherDiagnostics {
 
     /// Platform-specific info (e.g., "FSEvents queue depth: 42")
     pub platform_info: String,
+
+    /// Backend in use ("inotify", "fanotify", "fsevents", "poll" or "replay")
+    pub backend: String,
+
+    /// Directory watches registered (inotify)
+    pub watches: u64,
+
+    /// Per-user watch limit, if known (`fs.inotify.max_user_watches`)
+    pub watch_limit: Option<u64>,
+
+    /// Subtrees left unwatched by the watch limit, reconciled by polling
+    pub unwatched_dirs: u64,
+
+    /// Current interval between scans (polling backend, or the
+    /// reconciliation of unwatched subtrees)
+    pub poll_interval: Option<Duration>,
+
+    /// Completed scans (polling backend)
+    pub poll_scans: u64,
+
+    /// Duration of the most recent scan (polling backend)
+    pub last_scan_duration: Option<Duration>,
 }
 
 /// Platform-agnostic file system watcher interface
@@ -83,22 +121,88 @@ pub trait PlatformWatcher: Send + Sync {
 
     /// Check if the watcher is currently active
     fn is_running(&self) -> bool;
+
+    /// Provide stat data from the last checkpoint before `start()`
+    ///
+    /// Backends that compare snapshots use it as their starting point;
+    /// event-driven backends ignore it.
+    fn set_baseline(&mut self, _cache: SharedStatCache) {}
 }
 
-/// Create a platform-specific watcher instance
+/// Create the watcher selected by `config.backend`
 ///
-/// This is a factory function that returns the appropriate watcher type
-/// for the current platform.
-#[cfg(target_os = "macos")]
+/// `Auto` polls if the path is on a network or FUSE filesystem, where kernel
+/// notifications are missing or unreliable; otherwise it tries fanotify on
+/// Linux and uses the native backend when that is unavailable. `Fanotify`
+/// falls back to the native backend the same way.
 pub fn create_platform_watcher(
     path: &Path,
     config: &crate::WatcherConfig,
+) -> Result<Box<dyn PlatformWatcher>> {
+    let poll_reason = match config.backend {
+        WatcherBackend::Native | WatcherBackend::Fanotify => None,
+        WatcherBackend::Poll => Some("configured".to_string()),
+        WatcherBackend::Auto => poll::network_filesystem(path)
+            .map(|fstype| format!("{} filesystem", fstype)),
+    };
+
+    match poll_reason {
+        Some(reason) => {
+            tracing::info!(
+                "Using polling watcher ({}), interval {:?}-{:?}",
+                reason,
+                config.poll_interval,
+                config.max_poll_interval
+            );
+            Ok(Box::new(PollingWatcher::new(path, config)?))
+        }
+        None if config.backend == WatcherBackend::Native => create_native_watcher(path, config),
+        None => create_fanotify_watcher(path, config),
+    }
+}
+
+/// Create a fanotify watcher, or the native one if that fails
+#[cfg(target_os = "linux")]
+fn create_fanotify_watcher(
+    path: &Path,
+    config: &crate::WatcherConfig,
+) -> Result<Box<dyn PlatformWatcher>> {
+    match FanotifyWatcher::new(path, config) {
+        Ok(watcher) => Ok(Box::new(watcher)),
+        // Without CAP_SYS_ADMIN this is expected under `Auto`
+        Err(e) if config.backend == WatcherBackend::Auto => {
+            tracing::debug!("fanotify unavailable ({:#}), using inotify", e);
+            create_native_watcher(path, config)
+        }
+        Err(e) => {
+            tracing::warn!("fanotify unavailable ({:#}), using inotify", e);
+            create_native_watcher(path, config)
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn create_fanotify_watcher(
+    path: &Path,
+    config: &crate::WatcherConfig,
+) -> Result<Box<dyn PlatformWatcher>> {
+    if config.backend == WatcherBackend::Fanotify {
+        tracing::warn!("fanotify is Linux-only, using the native watcher");
+    }
+    create_native_watcher(path, config)
+}
+
+/// Create the native watcher for the current platform
+#[cfg(target_os = "macos")]
+fn create_native_watcher(
+    path: &Path,
+    config: &crate::WatcherConfig,
 ) -> Result<Box<dyn PlatformWatcher>> {
     Ok(Box::new(MacOSWatcher::new(path, config)?))
 }
 
 #[cfg(target_os = "linux")]
-pub fn create_platform_watcher(
+fn create_native_watcher(
     path: &Path,
     config: &crate::WatcherConfig,
 ) -> Result<Box<dyn PlatformWatcher>> {
@@ -106,9 +210,10 @@ pub fn create_platform_watcher(
 }
 
 #[cfg(not(any(target_os = "macos", target_os = "linux")))]
-pub fn create_platform_watcher(
-    _path: &Path,
-    _config: &crate::WatcherConfig,
+fn create_native_watcher(
+    path: &Path,
+    config: &crate::WatcherConfig,
 ) -> Result<Box<dyn PlatformWatcher>> {
-    anyhow::bail!("Unsupported platform - only macOS and Linux are supported")
+    tracing::info!("No native watcher on this platform, polling instead");
+    Ok(Box::new(PollingWatcher::new(path, config)?))
 }
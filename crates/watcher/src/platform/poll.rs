@@ -0,0 +1,533 @@
+//! Polling implementation using stat snapshots
+//!
+//! For filesystems where kernel notifications are missing or unreliable
+//! (NFS, SMB, SSHFS, 9p, virtiofs and the FUSE mounts behind Docker bind
+//! mounts), the repository is walked periodically and each file's stat data
+//! (size, mtime, ctime, inode) is compared with the previous walk. Nothing
+//! is hashed.
+//!
+//! The first walk is compared against the daemon's PathMap stat data when
+//! it is available, so changes made while the daemon was down are reported.
+//! The interval adapts: it drops to the minimum after a walk that found
+//! changes, doubles up to the maximum while the tree is idle, and never
+//! falls below twice the duration of the last walk.
+
+use super::{PlatformWatcher, WatcherDiagnostics};
+use crate::overflow::{load_ignore_rules, SharedStatCache};
+use crate::{EventKind, ModifyKind, WatchEvent, WatcherConfig};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use crossbeam_channel::{Receiver, Sender};
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use timelapse_core::{walk_worktree, FileStat, IgnoreRules};
+
+/// Stat data of every file seen by the last walk
+type StatSnapshot = HashMap<PathBuf, FileStat>;
+
+/// Watcher that detects changes by periodically re-walking the tree
+pub struct PollingWatcher {
+    /// Root path being watched
+    root: PathBuf,
+
+    /// Poll interval bounds
+    min_interval: Duration,
+    max_interval: Duration,
+
+    /// Stat data to compare the first walk against
+    baseline: Option<SharedStatCache>,
+
+    /// Events produced by the polling thread
+    event_rx: Receiver<WatchEvent>,
+    event_tx: Sender<WatchEvent>,
+
+    /// Signals the polling thread to exit
+    stop_tx: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+
+    /// Diagnostics (shared with the polling thread)
+    diagnostics: Arc<RwLock<WatcherDiagnostics>>,
+}
+
+impl PollingWatcher {
+    /// Create a new polling watcher
+    pub fn new(path: &Path, config: &WatcherConfig) -> Result<Self> {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let min_interval = config.poll_interval.max(Duration::from_millis(10));
+
+        Ok(Self {
+            root: path.to_path_buf(),
+            min_interval,
+            max_interval: config.max_poll_interval.max(min_interval),
+            baseline: None,
+            event_rx: rx,
+            event_tx: tx,
+            stop_tx: None,
+            thread: None,
+            diagnostics: Arc::new(RwLock::new(WatcherDiagnostics {
+                backend: "poll".to_string(),
+                ..WatcherDiagnostics::default()
+            })),
+        })
+    }
+}
+
+#[async_trait]
+impl PlatformWatcher for PollingWatcher {
+    async fn start(&mut self) -> Result<()> {
+        if self.thread.is_some() {
+            return Ok(());
+        }
+
+        let rules = load_ignore_rules(&self.root);
+
+        // Establish the baseline before returning, so no change is missed
+        let snapshot = match &self.baseline {
+            Some(cache) => cache.cached_paths()
+                .into_iter()
+                .filter_map(|path| cache.cached_stat(&path).map(|stat| (path, stat)))
+                .collect(),
+            None => scan(&self.root, &rules)?,
+        };
+
+        let (stop_tx, stop_rx) = crossbeam_channel::bounded(1);
+        let poller = Poller {
+            root: self.root.clone(),
+            rules,
+            snapshot,
+            min_interval: self.min_interval,
+            max_interval: self.max_interval,
+            interval: self.min_interval,
+            events: self.event_tx.clone(),
+            diagnostics: Arc::clone(&self.diagnostics),
+        };
+
+        let thread = std::thread::Builder::new()
+            .name("tl-poll-watcher".to_string())
+            .spawn(move || poller.run(stop_rx))
+            .context("Failed to start polling thread")?;
+
+        self.stop_tx = Some(stop_tx);
+        self.thread = Some(thread);
+
+        {
+            let mut diag = self.diagnostics.write();
+            diag.platform_info = format!("polling: {}", self.root.display());
+            diag.poll_interval = Some(self.min_interval);
+        }
+
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> Result<()> {
+        if let Some(stop_tx) = self.stop_tx.take() {
+            let _ = stop_tx.send(());
+        }
+        if let Some(thread) = self.thread.take() {
+            thread.join().map_err(|_| anyhow::anyhow!("Polling thread panicked"))?;
+        }
+        Ok(())
+    }
+
+    async fn poll_event(&mut self) -> Result<Option<WatchEvent>> {
+        match self.event_rx.try_recv() {
+            Ok(event) => Ok(Some(event)),
+            Err(crossbeam_channel::TryRecvError::Empty) => Ok(None),
+            Err(crossbeam_channel::TryRecvError::Disconnected) => {
+                anyhow::bail!("Event channel disconnected")
+            }
+        }
+    }
+
+    fn has_overflow(&self) -> bool {
+        // Every walk is complete; nothing can be dropped
+        false
+    }
+
+    fn reset_overflow(&mut self) {}
+
+    fn diagnostics(&self) -> WatcherDiagnostics {
+        self.diagnostics.read().clone()
+    }
+
+    fn is_running(&self) -> bool {
+        self.thread.is_some()
+    }
+
+    fn set_baseline(&mut self, cache: SharedStatCache) {
+        self.baseline = Some(cache);
+    }
+}
+
+/// State owned by the polling thread
+struct Poller {
+    root: PathBuf,
+    rules: IgnoreRules,
+    snapshot: StatSnapshot,
+    min_interval: Duration,
+    max_interval: Duration,
+    interval: Duration,
+    events: Sender<WatchEvent>,
+    diagnostics: Arc<RwLock<WatcherDiagnostics>>,
+}
+
+impl Poller {
+    fn run(mut self, stop_rx: Receiver<()>) {
+        loop {
+            match stop_rx.recv_timeout(self.interval) {
+                Err(crossbeam_channel::RecvTimeoutError::Timeout) => {}
+                // Stopped, or the watcher was dropped
+                _ => return,
+            }
+
+            let started = Instant::now();
+            let current = match scan(&self.root, &self.rules) {
+                Ok(current) => current,
+                Err(e) => {
+                    tracing::warn!("Polling scan of {} failed: {}", self.root.display(), e);
+                    continue;
+                }
+            };
+            let elapsed = started.elapsed();
+
+            let events = diff_snapshots(&self.snapshot, &current);
+            self.snapshot = current;
+            self.interval = next_interval(self.interval, !events.is_empty(), elapsed, self.min_interval, self.max_interval);
+
+            {
+                let mut diag = self.diagnostics.write();
+                diag.events_received += events.len() as u64;
+                diag.poll_scans += 1;
+                diag.last_scan_duration = Some(elapsed);
+                diag.poll_interval = Some(self.interval);
+            }
+
+            for event in events {
+                if self.events.send(event).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Walk the tree and record each file's stat data
+fn scan(root: &Path, rules: &IgnoreRules) -> Result<StatSnapshot> {
+    Ok(walk_worktree(root, rules)?
+        .into_iter()
+        .map(|file| {
+            let stat = FileStat::from_metadata(&file.metadata);
+            (file.rel_path, stat)
+        })
+        .collect())
+}
+
+/// Walk only the given subtrees (absolute paths) of `root`
+fn scan_subtrees(root: &Path, subtrees: &[PathBuf], rules: &IgnoreRules) -> StatSnapshot {
+    let mut snapshot = StatSnapshot::new();
+
+    for subtree in subtrees {
+        let walk = walkdir::WalkDir::new(subtree)
+            .follow_links(false)
+            .into_iter()
+            .filter_entry(|e| match e.path().strip_prefix(root) {
+                Ok(rel) => rel.as_os_str().is_empty() || !rules.should_ignore(rel),
+                Err(_) => false,
+            });
+
+        // Files can vanish mid-walk; skip rather than abort the scan
+        for entry in walk.filter_map(|e| e.ok()) {
+            let file_type = entry.file_type();
+            if !file_type.is_file() && !file_type.is_symlink() {
+                continue;
+            }
+            let (Ok(metadata), Ok(rel)) = (entry.metadata(), entry.path().strip_prefix(root)) else {
+                continue;
+            };
+            snapshot.insert(rel.to_path_buf(), FileStat::from_metadata(&metadata));
+        }
+    }
+
+    snapshot
+}
+
+/// Periodic stat reconciliation for subtrees a native watcher can't cover
+///
+/// Used by the inotify backend for directories left unwatched when the
+/// watch limit is reached.
+pub(crate) struct SubtreePoller {
+    subtrees: Vec<PathBuf>,
+    snapshot: StatSnapshot,
+    min_interval: Duration,
+    max_interval: Duration,
+    interval: Duration,
+    last_scan: Instant,
+}
+
+impl SubtreePoller {
+    pub(crate) fn new(config: &WatcherConfig) -> Self {
+        let min_interval = config.poll_interval.max(Duration::from_millis(10));
+        Self {
+            subtrees: Vec::new(),
+            snapshot: StatSnapshot::new(),
+            min_interval,
+            max_interval: config.max_poll_interval.max(min_interval),
+            interval: min_interval,
+            last_scan: Instant::now(),
+        }
+    }
+
+    /// Start reconciling `subtree` (absolute)
+    ///
+    /// With `baseline`, its current files are recorded so only later changes
+    /// are reported; otherwise they all show up as created on the next scan.
+    pub(crate) fn add(&mut self, root: &Path, subtree: PathBuf, rules: &IgnoreRules, baseline: bool) {
+        if self.subtrees.iter().any(|s| subtree.starts_with(s)) {
+            return;
+        }
+        if baseline {
+            self.snapshot.extend(scan_subtrees(root, std::slice::from_ref(&subtree), rules));
+        }
+        self.subtrees.push(subtree);
+    }
+
+    /// Subtree roots being reconciled
+    pub(crate) fn subtrees(&self) -> &[PathBuf] {
+        &self.subtrees
+    }
+
+    /// Current interval between scans
+    pub(crate) fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Rescan if the interval has elapsed, returning what changed
+    pub(crate) fn poll(&mut self, root: &Path, rules: &IgnoreRules) -> Vec<WatchEvent> {
+        if self.subtrees.is_empty() || self.last_scan.elapsed() < self.interval {
+            return Vec::new();
+        }
+
+        let started = Instant::now();
+        let current = scan_subtrees(root, &self.subtrees, rules);
+        let events = diff_snapshots(&self.snapshot, &current);
+        self.snapshot = current;
+        self.interval = next_interval(self.interval, !events.is_empty(), started.elapsed(), self.min_interval, self.max_interval);
+        self.last_scan = Instant::now();
+
+        events
+    }
+}
+
+/// Events turning `old` into `new`
+fn diff_snapshots(old: &StatSnapshot, new: &StatSnapshot) -> Vec<WatchEvent> {
+    let mut events = Vec::new();
+
+    for (path, stat) in new {
+        let kind = match old.get(path) {
+            None => EventKind::Create,
+            Some(previous) if previous == stat => continue,
+            // Only ctime moved: permissions or ownership
+            Some(previous) if previous.size == stat.size && previous.mtime_ns == stat.mtime_ns => {
+                EventKind::Modify(ModifyKind::Metadata)
+            }
+            Some(_) => EventKind::Modify(ModifyKind::Data),
+        };
+        events.push(WatchEvent::new(Arc::from(path.as_path()), kind));
+    }
+
+    for path in old.keys() {
+        if !new.contains_key(path) {
+            events.push(WatchEvent::new(Arc::from(path.as_path()), EventKind::Delete));
+        }
+    }
+
+    events
+}
+
+/// Interval before the next walk
+fn next_interval(current: Duration, changed: bool, scan_time: Duration, min: Duration, max: Duration) -> Duration {
+    let interval = if changed { min } else { (current * 2).min(max) };
+    // Keep polling from eating more than about half a core
+    interval.max(scan_time * 2)
+}
+
+/// Filesystem type of `path` if it is one where inotify is unreliable
+///
+/// Reads `/proc/self/mounts` and picks the longest mount point containing
+/// `path`. Returns None on other platforms.
+pub fn network_filesystem(path: &Path) -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let mounts = std::fs::read_to_string("/proc/self/mounts").ok()?;
+        let fstype = mount_fstype(&path, &mounts)?;
+        is_network_fstype(&fstype).then_some(fstype)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = path;
+        None
+    }
+}
+
+/// Filesystem type of the mount containing `path`, from mounts(5) text
+fn mount_fstype(path: &Path, mounts: &str) -> Option<String> {
+    mounts.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let _device = fields.next()?;
+            let mount_point = unescape_mount_field(fields.next()?);
+            let fstype = fields.next()?;
+            path.starts_with(&mount_point).then(|| (mount_point, fstype.to_string()))
+        })
+        .max_by_key(|(mount_point, _)| mount_point.as_os_str().len())
+        .map(|(_, fstype)| fstype)
+}
+
+/// Filesystems whose change notifications can't be trusted
+fn is_network_fstype(fstype: &str) -> bool {
+    const NETWORK: &[&str] = &[
+        "nfs", "nfs4", "cifs", "smb3", "smbfs", "9p", "virtiofs", "vboxsf",
+        "afs", "ceph", "glusterfs", "lustre", "davfs", "fakeowner",
+    ];
+    NETWORK.contains(&fstype) || fstype == "fuse" || fstype.starts_with("fuse.")
+}
+
+/// Undo the octal escapes (`\040` for space) used in mounts(5)
+fn unescape_mount_field(field: &str) -> PathBuf {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let octal = bytes.get(i + 1..i + 4)
+            .filter(|digits| bytes[i] == b'\\' && digits.iter().all(|b| (b'0'..=b'7').contains(b)));
+        match octal {
+            Some(digits) => {
+                out.push(digits.iter().fold(0u8, |code, d| code.wrapping_mul(8).wrapping_add(d - b'0')));
+                i += 4;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    PathBuf::from(String::from_utf8_lossy(&out).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn fast_config() -> WatcherConfig {
+        WatcherConfig {
+            poll_interval: Duration::from_millis(20),
+            max_poll_interval: Duration::from_millis(40),
+            ..WatcherConfig::default()
+        }
+    }
+
+    async fn collect_events(watcher: &mut PollingWatcher, wait: Duration) -> Vec<WatchEvent> {
+        tokio::time::sleep(wait).await;
+        let mut events = Vec::new();
+        while let Some(event) = watcher.poll_event().await.unwrap() {
+            events.push(event);
+        }
+        events
+    }
+
+    #[tokio::test]
+    async fn test_polling_watcher_detects_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("existing.txt"), b"old").unwrap();
+        fs::write(temp_dir.path().join("doomed.txt"), b"bye").unwrap();
+
+        let mut watcher = PollingWatcher::new(temp_dir.path(), &fast_config()).unwrap();
+        watcher.start().await.unwrap();
+        assert!(watcher.is_running());
+
+        fs::write(temp_dir.path().join("new.txt"), b"hello").unwrap();
+        fs::write(temp_dir.path().join("existing.txt"), b"new content").unwrap();
+        fs::remove_file(temp_dir.path().join("doomed.txt")).unwrap();
+
+        let events = collect_events(&mut watcher, Duration::from_millis(300)).await;
+        let kind_of = |name: &str| events.iter()
+            .find(|e| e.path.as_ref() == Path::new(name))
+            .map(|e| e.kind);
+        assert_eq!(kind_of("new.txt"), Some(EventKind::Create));
+        assert_eq!(kind_of("existing.txt"), Some(EventKind::Modify(ModifyKind::Data)));
+        assert_eq!(kind_of("doomed.txt"), Some(EventKind::Delete));
+
+        let diag = watcher.diagnostics();
+        assert_eq!(diag.backend, "poll");
+        assert!(diag.poll_scans > 0);
+        assert!(diag.poll_interval.is_some());
+
+        watcher.stop().await.unwrap();
+        assert!(!watcher.is_running());
+    }
+
+    #[test]
+    fn test_subtree_poller_reports_changes_in_subtrees_only() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("deep/nested")).unwrap();
+        fs::write(root.join("deep/nested/a.txt"), b"a").unwrap();
+        fs::write(root.join("top.txt"), b"top").unwrap();
+
+        let config = WatcherConfig { poll_interval: Duration::from_millis(10), ..fast_config() };
+        let rules = load_ignore_rules(root);
+        let mut poller = SubtreePoller::new(&config);
+        poller.add(root, root.join("deep"), &rules, true);
+        poller.add(root, root.join("deep/nested"), &rules, true);
+        assert_eq!(poller.subtrees().len(), 1);
+
+        fs::write(root.join("deep/nested/b.txt"), b"b").unwrap();
+        fs::write(root.join("top.txt"), b"changed").unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+
+        let events = poller.poll(root, &rules);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].path.as_ref(), Path::new("deep/nested/b.txt"));
+        assert_eq!(events[0].kind, EventKind::Create);
+    }
+
+    #[test]
+    fn test_next_interval_adapts() {
+        let min = Duration::from_millis(100);
+        let max = Duration::from_millis(1000);
+        let quick = Duration::from_millis(1);
+
+        assert_eq!(next_interval(min, false, quick, min, max), Duration::from_millis(200));
+        assert_eq!(next_interval(Duration::from_millis(800), false, quick, min, max), max);
+        assert_eq!(next_interval(max, true, quick, min, max), min);
+        // Slow walks stretch the interval
+        assert_eq!(next_interval(min, true, Duration::from_millis(300), min, max), Duration::from_millis(600));
+    }
+
+    #[test]
+    fn test_mount_fstype_detection() {
+        let mounts = "\
+/dev/sda1 / ext4 rw,relatime 0 0
+server:/export /mnt/nfs nfs4 rw,vers=4.2 0 0
+user@host:/src /home/me/remote\\040dir fuse.sshfs rw 0 0
+tmpfs /mnt/nfs/tmp tmpfs rw 0 0
+";
+        assert_eq!(mount_fstype(Path::new("/home/me/project"), mounts).as_deref(), Some("ext4"));
+        assert_eq!(mount_fstype(Path::new("/mnt/nfs/repo"), mounts).as_deref(), Some("nfs4"));
+        assert_eq!(mount_fstype(Path::new("/mnt/nfs/tmp/x"), mounts).as_deref(), Some("tmpfs"));
+        assert_eq!(mount_fstype(Path::new("/home/me/remote dir/repo"), mounts).as_deref(), Some("fuse.sshfs"));
+
+        assert!(is_network_fstype("nfs4"));
+        assert!(is_network_fstype("fuse.sshfs"));
+        assert!(is_network_fstype("9p"));
+        assert!(!is_network_fstype("ext4"));
+        assert!(!is_network_fstype("tmpfs"));
+    }
+}
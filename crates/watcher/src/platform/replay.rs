@@ -0,0 +1,101 @@
+//! Replay of a recorded event trace
+//!
+//! Feeds the events of an `EventTrace` back at their recorded offsets,
+//! measured on tokio's clock from `start()`. Under a paused clock
+//! (`tokio::time::pause()`, or `#[tokio::test(start_paused = true)]`) time
+//! only moves when every task is waiting, so the coalescer and debouncer see
+//! exactly the recorded timing on every run, however slow the machine.
+
+use super::{PlatformWatcher, WatcherDiagnostics};
+use crate::record::RecordedEvent;
+use crate::{EventKind, WatchEvent};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::VecDeque;
+use tokio::time::Instant;
+
+/// Watcher that replays recorded events under tokio's (virtual) clock
+pub struct ReplayWatcher {
+    /// Events not yet delivered
+    events: VecDeque<RecordedEvent>,
+
+    /// When replay started
+    started: Option<Instant>,
+
+    /// Overflow detected flag
+    overflow_detected: bool,
+
+    /// Diagnostics
+    diagnostics: WatcherDiagnostics,
+}
+
+impl ReplayWatcher {
+    /// Create a watcher replaying `events` (in recording order)
+    pub fn new(events: Vec<RecordedEvent>) -> Self {
+        Self {
+            events: events.into(),
+            started: None,
+            overflow_detected: false,
+            diagnostics: WatcherDiagnostics {
+                backend: "replay".to_string(),
+                ..WatcherDiagnostics::default()
+            },
+        }
+    }
+
+    /// True once every event has been delivered
+    pub fn is_finished(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+#[async_trait]
+impl PlatformWatcher for ReplayWatcher {
+    async fn start(&mut self) -> Result<()> {
+        if self.started.is_none() {
+            self.started = Some(Instant::now());
+            self.diagnostics.platform_info = format!("replaying {} events", self.events.len());
+        }
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> Result<()> {
+        self.started = None;
+        Ok(())
+    }
+
+    async fn poll_event(&mut self) -> Result<Option<WatchEvent>> {
+        let Some(started) = self.started else {
+            return Ok(None);
+        };
+
+        let due = self.events.front().is_some_and(|next| started.elapsed() >= next.offset);
+        if !due {
+            return Ok(None);
+        }
+
+        let RecordedEvent { event, .. } = self.events.pop_front().expect("checked above");
+        self.diagnostics.events_received += 1;
+        if event.kind == EventKind::Overflow {
+            self.overflow_detected = true;
+            self.diagnostics.overflow_count += 1;
+        }
+        Ok(Some(event))
+    }
+
+    fn has_overflow(&self) -> bool {
+        self.overflow_detected
+    }
+
+    fn reset_overflow(&mut self) {
+        self.overflow_detected = false;
+    }
+
+    fn diagnostics(&self) -> WatcherDiagnostics {
+        self.diagnostics.clone()
+    }
+
+    fn is_running(&self) -> bool {
+        self.started.is_some()
+    }
+}
@@ -7,9 +7,10 @@ use std::path::{Path, PathBuf};
 use std::time::{Duration, SystemTime};
 use tokio::sync::mpsc;
 use tokio::time::interval;
-use walkdir::WalkDir;
 use tracing::{info, debug, warn};
 use anyhow::Result;
+use crate::overflow::load_ignore_rules;
+use timelapse_core::{walk_worktree, IgnoreRules};
 
 /// Periodic reconciliation scanner
 ///
@@ -27,6 +28,9 @@ pub struct PeriodicReconciler {
 
     /// Sender for detected changes
     change_tx: mpsc::Sender<Vec<PathBuf>>,
+
+    /// Repository ignore rules (.gitignore, .tlignore, built-ins)
+    rules: IgnoreRules,
 }
 
 impl PeriodicReconciler {
@@ -36,11 +40,13 @@ impl PeriodicReconciler {
         interval: Duration,
         change_tx: mpsc::Sender<Vec<PathBuf>>,
     ) -> Self {
+        let rules = load_ignore_rules(&repo_root);
         Self {
             repo_root,
             interval,
             last_checkpoint: SystemTime::now(),
             change_tx,
+            rules,
         }
     }
 
@@ -83,26 +89,18 @@ impl PeriodicReconciler {
         let checkpoint_time = self.last_checkpoint;
         let mut changed = Vec::new();
 
-        // Walk repository
-        for entry in WalkDir::new(&self.repo_root)
-            .follow_links(false)
-            .into_iter()
-            .filter_entry(|e| !self.should_ignore(e.path()))
-        {
-            let entry = entry?;
-
-            // Only check files
-            if !entry.file_type().is_file() {
+        // Walk repository (shared walker honors .gitignore/.tlignore)
+        for file in walk_worktree(&self.repo_root, &self.rules)? {
+            // Only check regular files
+            if !file.metadata.is_file() || self.should_ignore(&file.rel_path) {
                 continue;
             }
 
             // Check mtime
-            let metadata = entry.metadata()?;
-            let mtime = metadata.modified()?;
+            let mtime = file.metadata.modified()?;
 
             if mtime > checkpoint_time {
-                let rel_path = entry.path().strip_prefix(&self.repo_root)?;
-                changed.push(rel_path.to_path_buf());
+                changed.push(file.rel_path);
             }
         }
 
@@ -0,0 +1,262 @@
+//! Recording and loading raw watcher event traces
+//!
+//! Coalescing, debouncing and overflow handling depend on event timing,
+//! which makes their bugs hard to reproduce. With `WatcherConfig::record_events`
+//! set, every event the platform backend delivers is appended to a trace
+//! file with its offset from the start of recording. The trace can be fed
+//! back through the pipeline with `platform::ReplayWatcher`.
+//!
+//! The format is line-based text, so traces can be read and trimmed by hand
+//! before being attached to a bug report:
+//!
+//! ```text
+//! # timelapse watcher trace v1
+//! # backend: inotify
+//! # debounce_ms: 300
+//! 0 create src/main.rs
+//! 1250 modify-data src/main.rs
+//! 4100 overflow <overflow>
+//! ```
+//!
+//! Offsets are microseconds. Header lines (`# key: value`) record the
+//! settings that shape the pipeline's timing.
+
+use crate::{EventKind, ModifyKind, WatchEvent, WatcherConfig};
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// First line of every trace
+const TRACE_MAGIC: &str = "# timelapse watcher trace v1";
+
+/// An event with its offset from the start of recording
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedEvent {
+    pub offset: Duration,
+    pub event: WatchEvent,
+}
+
+/// Appends raw events to a trace file
+pub struct EventRecorder {
+    writer: BufWriter<File>,
+    started: Instant,
+    recorded: u64,
+}
+
+impl EventRecorder {
+    /// Create (or truncate) a trace file, writing the header
+    pub fn create(path: &Path, config: &WatcherConfig, backend: &str) -> Result<Self> {
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create event trace {}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+
+        writeln!(writer, "{}", TRACE_MAGIC)?;
+        writeln!(writer, "# backend: {}", backend)?;
+        writeln!(writer, "# debounce_ms: {}", config.debounce_duration.as_millis())?;
+        writeln!(writer, "# batch_size: {}", config.batch_size)?;
+        writeln!(writer, "# batch_timeout_ms: {}", config.batch_timeout.as_millis())?;
+        writeln!(writer, "# detect_atomic_saves: {}", config.detect_atomic_saves)?;
+        writer.flush()?;
+
+        Ok(Self { writer, started: Instant::now(), recorded: 0 })
+    }
+
+    /// Append an event, timestamped now
+    pub fn record(&mut self, event: &WatchEvent) -> Result<()> {
+        let offset = self.started.elapsed();
+        writeln!(
+            self.writer,
+            "{} {} {}",
+            offset.as_micros(),
+            kind_name(event.kind),
+            escape_path(&event.path.to_string_lossy())
+        )?;
+        self.recorded += 1;
+        Ok(())
+    }
+
+    /// Write buffered events to disk
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush().context("Failed to write event trace")
+    }
+
+    /// Number of events recorded so far
+    pub fn recorded(&self) -> u64 {
+        self.recorded
+    }
+}
+
+/// A trace loaded from disk
+#[derive(Debug, Clone, Default)]
+pub struct EventTrace {
+    /// Header values (`# key: value`)
+    pub header: BTreeMap<String, String>,
+
+    /// Events in recording order
+    pub events: Vec<RecordedEvent>,
+}
+
+impl EventTrace {
+    /// Load a trace written by `EventRecorder`
+    pub fn load(path: &Path) -> Result<Self> {
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open event trace {}", path.display()))?;
+        let mut lines = BufReader::new(file).lines();
+
+        match lines.next() {
+            Some(Ok(line)) if line.trim_end() == TRACE_MAGIC => {}
+            _ => anyhow::bail!("{} is not a watcher event trace", path.display()),
+        }
+
+        let mut trace = Self::default();
+        for (number, line) in lines.enumerate() {
+            let line = line?;
+            if let Some(comment) = line.strip_prefix('#') {
+                if let Some((key, value)) = comment.split_once(':') {
+                    trace.header.insert(key.trim().to_string(), value.trim().to_string());
+                }
+                continue;
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+            let event = parse_line(&line)
+                .with_context(|| format!("Invalid trace line {}: {}", number + 2, line))?;
+            trace.events.push(event);
+        }
+
+        Ok(trace)
+    }
+
+    /// Apply the recorded timing settings to `config`
+    pub fn apply_to(&self, config: &mut WatcherConfig) {
+        let get = |key: &str| self.header.get(key).and_then(|v| v.parse::<u64>().ok());
+
+        if let Some(ms) = get("debounce_ms") {
+            config.debounce_duration = Duration::from_millis(ms);
+        }
+        if let Some(size) = get("batch_size") {
+            config.batch_size = size as usize;
+        }
+        if let Some(ms) = get("batch_timeout_ms") {
+            config.batch_timeout = Duration::from_millis(ms);
+        }
+        if let Some(detect) = self.header.get("detect_atomic_saves").and_then(|v| v.parse().ok()) {
+            config.detect_atomic_saves = detect;
+        }
+    }
+}
+
+fn parse_line(line: &str) -> Option<RecordedEvent> {
+    let mut fields = line.splitn(3, ' ');
+    let offset = Duration::from_micros(fields.next()?.parse().ok()?);
+    let kind = parse_kind(fields.next()?)?;
+    let path = unescape_path(fields.next()?);
+
+    Some(RecordedEvent {
+        offset,
+        event: WatchEvent::new(Arc::from(Path::new(&path)), kind),
+    })
+}
+
+fn kind_name(kind: EventKind) -> &'static str {
+    match kind {
+        EventKind::Create => "create",
+        EventKind::Modify(ModifyKind::Data) => "modify-data",
+        EventKind::Modify(ModifyKind::Metadata) => "modify-metadata",
+        EventKind::Modify(ModifyKind::Any) => "modify",
+        EventKind::Delete => "delete",
+        EventKind::Rename => "rename",
+        EventKind::Overflow => "overflow",
+    }
+}
+
+fn parse_kind(name: &str) -> Option<EventKind> {
+    Some(match name {
+        "create" => EventKind::Create,
+        "modify-data" => EventKind::Modify(ModifyKind::Data),
+        "modify-metadata" => EventKind::Modify(ModifyKind::Metadata),
+        "modify" => EventKind::Modify(ModifyKind::Any),
+        "delete" => EventKind::Delete,
+        "rename" => EventKind::Rename,
+        "overflow" => EventKind::Overflow,
+        _ => return None,
+    })
+}
+
+/// Keep one event per line: escape backslashes and newlines
+fn escape_path(path: &str) -> String {
+    path.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape_path(field: &str) -> String {
+    let mut path = String::with_capacity(field.len());
+    let mut chars = field.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('\\')) => {
+                path.push('\\');
+                chars.next();
+            }
+            ('\\', Some('n')) => {
+                path.push('\n');
+                chars.next();
+            }
+            _ => path.push(c),
+        }
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_record_and_load_roundtrip() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let trace_path = temp_dir.path().join("events.trace");
+        let config = WatcherConfig {
+            debounce_duration: Duration::from_millis(50),
+            detect_atomic_saves: false,
+            ..WatcherConfig::default()
+        };
+
+        let events = [
+            WatchEvent::new(Arc::from(Path::new("src/main.rs")), EventKind::Create),
+            WatchEvent::new(Arc::from(Path::new("dir with space/a.txt")), EventKind::Modify(ModifyKind::Data)),
+            WatchEvent::new(Arc::from(Path::new("odd\\name\nx")), EventKind::Delete),
+            WatchEvent::new(Arc::from(Path::new("<overflow>")), EventKind::Overflow),
+        ];
+        let mut recorder = EventRecorder::create(&trace_path, &config, "inotify")?;
+        for event in &events {
+            recorder.record(event)?;
+        }
+        recorder.flush()?;
+        assert_eq!(recorder.recorded(), 4);
+
+        let trace = EventTrace::load(&trace_path)?;
+        assert_eq!(trace.header.get("backend").map(String::as_str), Some("inotify"));
+        let loaded: Vec<WatchEvent> = trace.events.iter().map(|e| e.event.clone()).collect();
+        assert_eq!(loaded, events);
+        assert!(trace.events.windows(2).all(|w| w[0].offset <= w[1].offset));
+
+        let mut replay_config = WatcherConfig::default();
+        trace.apply_to(&mut replay_config);
+        assert_eq!(replay_config.debounce_duration, Duration::from_millis(50));
+        assert!(!replay_config.detect_atomic_saves);
+
+        // Not a trace, and a corrupt line
+        std::fs::write(&trace_path, "hello\n")?;
+        assert!(EventTrace::load(&trace_path).is_err());
+        std::fs::write(&trace_path, format!("{}\n12 explode a.txt\n", TRACE_MAGIC))?;
+        assert!(EventTrace::load(&trace_path).is_err());
+
+        Ok(())
+    }
+}